@@ -1,10 +1,57 @@
 use criterion::{criterion_group, criterion_main};
 
-mod benchmark_poseidon;
-mod benchmark_sha;
+mod benchmark_chunk_size;
+mod benchmark_message_hash;
+mod benchmark_node_cache;
+mod benchmark_ots_gen;
+#[cfg(feature = "parallel")]
+mod benchmark_parallelism;
+// mod benchmark_poseidon;
+mod benchmark_schemes;
+mod benchmark_serialization;
+mod benchmark_sign_many;
+mod benchmark_tree;
+mod benchmark_tweak_hash;
 
-use benchmark_poseidon::bench_function_poseidon;
-use benchmark_sha::bench_function_sha;
+use benchmark_chunk_size::bench_function_chunk_size;
+use benchmark_message_hash::bench_function_message_hash;
+use benchmark_node_cache::bench_function_node_cache;
+use benchmark_ots_gen::bench_function_ots_gen;
+#[cfg(feature = "parallel")]
+use benchmark_parallelism::bench_function_parallelism;
+// use benchmark_poseidon::bench_function_poseidon;
+use benchmark_schemes::bench_function_schemes;
+use benchmark_serialization::bench_function_serialization;
+use benchmark_sign_many::bench_function_sign_many;
+use benchmark_tree::bench_function_tree;
+use benchmark_tweak_hash::bench_function_tweak_hash;
 
-criterion_group!(benches, bench_function_sha, bench_function_poseidon);
+// criterion_group!(benches, bench_function_schemes, bench_function_poseidon);
+#[cfg(feature = "parallel")]
+criterion_group!(
+    benches,
+    bench_function_schemes,
+    bench_function_tree,
+    bench_function_parallelism,
+    bench_function_message_hash,
+    bench_function_tweak_hash,
+    bench_function_serialization,
+    bench_function_chunk_size,
+    bench_function_sign_many,
+    bench_function_node_cache,
+    bench_function_ots_gen
+);
+#[cfg(not(feature = "parallel"))]
+criterion_group!(
+    benches,
+    bench_function_schemes,
+    bench_function_tree,
+    bench_function_message_hash,
+    bench_function_tweak_hash,
+    bench_function_serialization,
+    bench_function_chunk_size,
+    bench_function_sign_many,
+    bench_function_node_cache,
+    bench_function_ots_gen
+);
 criterion_main!(benches);
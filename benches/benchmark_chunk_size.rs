@@ -0,0 +1,221 @@
+use std::marker::PhantomData;
+
+use criterion::{
+    black_box, measurement::WallTime, BatchSize, BenchmarkGroup, BenchmarkId, Criterion,
+    SamplingMode,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use hashsig::{
+    declare_instantiation,
+    inc_encoding::IncomparableEncoding,
+    signature::{generalized_xmss::GeneralizedXMSSSignatureScheme, sizes::report, SignatureScheme},
+    symmetric::{prf::Pseudorandom, tweak_hash::TweakableHash},
+    MESSAGE_LENGTH,
+};
+
+/// Base 2 log of the lifetime of the benchmarked instantiations
+const LOG_LIFETIME: usize = 8;
+
+/// Number of signing seeds among which the worst cases are chosen
+const NUM_CANDIDATES: u64 = 1000;
+
+// Note: the parameters are those of the Winternitz instantiations with
+// lifetime 2^18, with a shorter lifetime so that key generation is fast enough.
+declare_instantiation! {
+    type SIGWinternitzW1 = Sha3 {
+        log_lifetime: LOG_LIFETIME,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 25,
+        chunk_size: 1,
+        encoding: Winternitz,
+    };
+
+    type SIGWinternitzW2 = Sha3 {
+        log_lifetime: LOG_LIFETIME,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 25,
+        chunk_size: 2,
+        encoding: Winternitz,
+    };
+
+    type SIGWinternitzW4 = Sha3 {
+        log_lifetime: LOG_LIFETIME,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+
+    type SIGWinternitzW8 = Sha3 {
+        log_lifetime: LOG_LIFETIME,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 28,
+        chunk_size: 8,
+        encoding: Winternitz,
+    };
+}
+
+/// Generic version of `GeneralizedXMSSSignatureScheme`, to name it in the templates
+type Sig<PRF, IE, TH, const LOG_LIFETIME: usize> =
+    GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+/// A template for benchmarking key generation of an instantiation with a given chunk
+/// size. The benchmark is added to the given group with the chunk size as parameter,
+/// so that criterion plots it against the chunk size.
+fn benchmark_chunk_size_gen<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+>(
+    group: &mut BenchmarkGroup<WallTime>,
+    _scheme: PhantomData<Sig<PRF, IE, TH, LOG_LIFETIME>>,
+) where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    let w = IE::CHUNK_SIZE;
+    group.bench_with_input(BenchmarkId::new("gen", w), &w, |b, _| {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        b.iter(|| Sig::<PRF, IE, TH, LOG_LIFETIME>::gen(black_box(&mut rng)));
+    });
+}
+
+/// A template for benchmarking signing and verification of an instantiation with
+/// a given chunk size. As for key generation, the chunk size is the parameter.
+///
+/// Signing walks each chain as far as the codeword says, and verification walks
+/// the rest. As the codeword depends on the randomness sampled during signing,
+/// the worst cases are given by a fixed message and an RNG seed: among a number
+/// of seeds, the one with the largest sum of the codeword is the worst case for
+/// signing, and the one with the smallest sum is the worst case for verification.
+/// The average cases use random seeds.
+fn benchmark_chunk_size<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+>(
+    group: &mut BenchmarkGroup<WallTime>,
+    _scheme: PhantomData<Sig<PRF, IE, TH, LOG_LIFETIME>>,
+) where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    let w = IE::CHUNK_SIZE;
+    let sizes = report::<Sig<PRF, IE, TH, LOG_LIFETIME>>();
+    println!(
+        "Chunk size w = {}: {} chains of length {}, signature {} bytes, public key {} bytes",
+        w,
+        IE::NUM_CHUNKS,
+        IE::chain_length(),
+        sizes.signature_bytes,
+        sizes.public_key_bytes
+    );
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let (pk, sk) = Sig::<PRF, IE, TH, LOG_LIFETIME>::gen(&mut rng);
+    let epoch = 13;
+    let message: [u8; MESSAGE_LENGTH] = rng.gen();
+
+    // the sum of the codeword that signing produces with the RNG seeded with `seed`.
+    // Signing samples the randomness first, and the Winternitz encoding never fails.
+    let codeword_sum = |seed: u64| -> u64 {
+        let rho = IE::rand(&mut ChaCha20Rng::seed_from_u64(seed));
+        let x = IE::encode(&pk.parameter.into(), &message, &rho, epoch).unwrap();
+        x.iter().map(|&xi| xi as u64).sum()
+    };
+    let worst_sign_seed = (0..NUM_CANDIDATES).max_by_key(|&seed| codeword_sum(seed));
+    let worst_verify_seed = (0..NUM_CANDIDATES).min_by_key(|&seed| codeword_sum(seed));
+    let sign_seeded = |seed: u64| {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        Sig::<PRF, IE, TH, LOG_LIFETIME>::sign(&mut rng, &sk, epoch, &message).unwrap()
+    };
+
+    group.bench_with_input(BenchmarkId::new("sign, worst case", w), &w, |b, _| {
+        let seed = worst_sign_seed.unwrap();
+        b.iter_batched(
+            || ChaCha20Rng::seed_from_u64(seed),
+            |mut rng| {
+                Sig::<PRF, IE, TH, LOG_LIFETIME>::sign(
+                    &mut rng,
+                    black_box(&sk),
+                    epoch,
+                    black_box(&message),
+                )
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.bench_with_input(BenchmarkId::new("sign, average", w), &w, |b, _| {
+        b.iter(|| {
+            Sig::<PRF, IE, TH, LOG_LIFETIME>::sign(
+                black_box(&mut rng),
+                black_box(&sk),
+                epoch,
+                black_box(&message),
+            )
+        });
+    });
+
+    group.bench_with_input(BenchmarkId::new("verify, worst case", w), &w, |b, _| {
+        let signature = sign_seeded(worst_verify_seed.unwrap());
+        b.iter(|| {
+            Sig::<PRF, IE, TH, LOG_LIFETIME>::verify(
+                black_box(&pk),
+                epoch,
+                black_box(&message),
+                black_box(&signature),
+            )
+        });
+    });
+
+    group.bench_with_input(BenchmarkId::new("verify, average", w), &w, |b, _| {
+        let signatures: Vec<_> = (NUM_CANDIDATES..2 * NUM_CANDIDATES)
+            .map(sign_seeded)
+            .collect();
+        let mut signatures = signatures.iter().cycle();
+        b.iter(|| {
+            Sig::<PRF, IE, TH, LOG_LIFETIME>::verify(
+                black_box(&pk),
+                epoch,
+                black_box(&message),
+                black_box(signatures.next().unwrap()),
+            )
+        });
+    });
+}
+
+/// Benchmarking the Winternitz encoding with chunk sizes w = 1, 2, 4, 8
+pub fn bench_function_chunk_size(c: &mut Criterion) {
+    let mut group = c.benchmark_group("SHA - Chunk Size: Signing and Verification");
+    benchmark_chunk_size(&mut group, PhantomData::<SIGWinternitzW1>);
+    benchmark_chunk_size(&mut group, PhantomData::<SIGWinternitzW2>);
+    benchmark_chunk_size(&mut group, PhantomData::<SIGWinternitzW4>);
+    benchmark_chunk_size(&mut group, PhantomData::<SIGWinternitzW8>);
+    group.finish();
+
+    let mut group = c.benchmark_group(format!(
+        "SHA - Chunk Size: Key Generation, Lifetime 2^{}",
+        LOG_LIFETIME
+    ));
+    // key gen takes long, so don't do that many repetitions
+    group.sampling_mode(SamplingMode::Flat);
+    group.sample_size(10);
+    benchmark_chunk_size_gen(&mut group, PhantomData::<SIGWinternitzW1>);
+    benchmark_chunk_size_gen(&mut group, PhantomData::<SIGWinternitzW2>);
+    benchmark_chunk_size_gen(&mut group, PhantomData::<SIGWinternitzW4>);
+    benchmark_chunk_size_gen(&mut group, PhantomData::<SIGWinternitzW8>);
+    group.finish();
+}
@@ -0,0 +1,124 @@
+use criterion::{black_box, Criterion, Throughput};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use hashsig::{
+    symmetric::message_hash::{
+        sha::ShaMessageHash128x3,
+        sha256::{Sha256MessageHash128x3, Sha256MessageHash192x3},
+        MessageHash,
+    },
+    MESSAGE_LENGTH,
+};
+
+/// Number of messages hashed in the batch benchmark
+const BATCH_SIZE: usize = 1000;
+
+/// A template for benchmarking the throughput of message hashes. It benchmarks
+/// single applications, and a batch of messages to expose per-call setup costs.
+/// Inputs are derived from a fixed seed, so that runs are comparable.
+pub fn benchmark_message_hash<MH: MessageHash>(
+    c: &mut Criterion,
+    description: &str,
+    parameter: MH::Parameter,
+) {
+    let mut group = c.benchmark_group(format!("Message Hash: {}", description));
+
+    // report throughput in messages per second
+    group.throughput(Throughput::Elements(1));
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let mut message = [0u8; MESSAGE_LENGTH];
+    rng.fill(&mut message);
+    let randomness = MH::rand(&mut rng);
+
+    group.bench_function("- apply", |b| {
+        b.iter(|| {
+            let _ = MH::apply(
+                black_box(&parameter),
+                black_box(13),
+                black_box(&randomness),
+                black_box(&message),
+            );
+        });
+    });
+
+    let batch: Vec<_> = (0..BATCH_SIZE)
+        .map(|epoch| {
+            let mut message = [0u8; MESSAGE_LENGTH];
+            rng.fill(&mut message);
+            (epoch as u32, MH::rand(&mut rng), message)
+        })
+        .collect();
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+
+    group.bench_function(format!("- apply, batch of {}", BATCH_SIZE), |b| {
+        b.iter(|| {
+            for (epoch, randomness, message) in &batch {
+                let _ = MH::apply(
+                    black_box(&parameter),
+                    black_box(*epoch),
+                    black_box(randomness),
+                    black_box(message),
+                );
+            }
+        });
+    });
+
+    group.finish();
+}
+
+/// Benchmarks the SHA-256 message hash with a prepared parameter, see
+/// `Sha256MessageHash::prepare`, against the unprepared message hash.
+fn benchmark_sha256_prepared(c: &mut Criterion, parameter: [u8; 24]) {
+    type MH = Sha256MessageHash192x3;
+
+    let mut group = c.benchmark_group("Message Hash: SHA-256 192x3, prepared");
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let batch: Vec<_> = (0..BATCH_SIZE)
+        .map(|epoch| {
+            let mut message = [0u8; MESSAGE_LENGTH];
+            rng.fill(&mut message);
+            (epoch as u32, MH::rand(&mut rng), message)
+        })
+        .collect();
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+
+    group.bench_function(format!("- unprepared, batch of {}", BATCH_SIZE), |b| {
+        b.iter(|| {
+            for (epoch, randomness, message) in &batch {
+                let _ = MH::apply(
+                    black_box(&parameter),
+                    black_box(*epoch),
+                    black_box(randomness),
+                    black_box(message),
+                );
+            }
+        });
+    });
+
+    group.bench_function(format!("- prepared, batch of {}", BATCH_SIZE), |b| {
+        b.iter(|| {
+            // preparing is part of the batch
+            let prepared = MH::prepare(black_box(&parameter));
+            for (epoch, randomness, message) in &batch {
+                let _ =
+                    prepared.apply(black_box(*epoch), black_box(randomness), black_box(message));
+            }
+        });
+    });
+
+    group.finish();
+}
+
+/// Benchmarking message hashes
+// Note: the Poseidon message hash is not available in this crate at the moment,
+// so only the SHA based message hashes are compared.
+pub fn bench_function_message_hash(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+
+    benchmark_message_hash::<Sha256MessageHash128x3>(c, "SHA-256 128x3", rng.gen());
+    benchmark_message_hash::<ShaMessageHash128x3>(c, "SHA3-256 128x3", rng.gen());
+    benchmark_sha256_prepared(c, rng.gen());
+}
@@ -0,0 +1,100 @@
+use criterion::{black_box, Criterion, Throughput};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use hashsig::{
+    declare_instantiation, signature::SignatureScheme, symmetric::tweak_hash_tree::NodeCache,
+    MESSAGE_LENGTH,
+};
+
+/// Number of signatures that are verified under one key
+const NUM_SIGNATURES: usize = 1000;
+
+/// Maximum number of inner nodes held by the cache
+const CACHE_CAPACITY: usize = 1 << 12;
+
+// Note: the parameters are those of `SIGWinternitzLifetime18W4`, with a
+// shorter lifetime so that key generation is fast enough.
+declare_instantiation! {
+    type SIGWinternitzLifetime8W4 = Sha3 {
+        log_lifetime: 8,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+}
+
+/// Benchmarking verification of many signatures under one key, without and with
+/// a `NodeCache` that shares the upper nodes of the Merkle paths between them.
+/// The throughput is the number of signatures, so that criterion reports the
+/// amortized time per verification. The hit rate of the cache is printed.
+pub fn bench_function_node_cache(c: &mut Criterion) {
+    type Sig = SIGWinternitzLifetime8W4;
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let (pk, sk) = Sig::gen(&mut rng);
+    let signed: Vec<_> = (0..NUM_SIGNATURES)
+        .map(|_| {
+            let epoch = rng.gen_range(0..Sig::LIFETIME as u32);
+            let message: [u8; MESSAGE_LENGTH] = rng.gen();
+            let signature = Sig::sign(&mut rng, &sk, epoch, &message).unwrap();
+            (epoch, message, signature)
+        })
+        .collect();
+
+    let mut cache = NodeCache::new(pk.parameter, CACHE_CAPACITY);
+    for (epoch, message, signature) in &signed {
+        assert!(Sig::verify_with_cache(
+            &pk, *epoch, message, signature, &mut cache
+        ));
+    }
+    println!(
+        "Node cache after {} verifications: {} nodes, {} hits, {} misses, hit rate {:.1}%",
+        NUM_SIGNATURES,
+        cache.len(),
+        cache.hits(),
+        cache.misses(),
+        100.0 * cache.hits() as f64 / (cache.hits() + cache.misses()) as f64
+    );
+
+    let mut group = c.benchmark_group(format!(
+        "SHA - Verifying {} Signatures under one Key",
+        NUM_SIGNATURES
+    ));
+    group.throughput(Throughput::Elements(NUM_SIGNATURES as u64));
+    // each iteration verifies all signatures, so don't do that many repetitions
+    group.sample_size(10);
+
+    group.bench_function("- verify", |b| {
+        b.iter(|| {
+            for (epoch, message, signature) in &signed {
+                black_box(Sig::verify(
+                    black_box(&pk),
+                    *epoch,
+                    black_box(message),
+                    black_box(signature),
+                ));
+            }
+        });
+    });
+
+    group.bench_function("- verify_with_cache", |b| {
+        b.iter(|| {
+            let mut cache = NodeCache::new(pk.parameter, CACHE_CAPACITY);
+            for (epoch, message, signature) in &signed {
+                black_box(Sig::verify_with_cache(
+                    black_box(&pk),
+                    *epoch,
+                    black_box(message),
+                    black_box(signature),
+                    &mut cache,
+                ));
+            }
+        });
+    });
+
+    group.finish();
+}
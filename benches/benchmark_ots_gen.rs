@@ -0,0 +1,56 @@
+use criterion::{black_box, Criterion, Throughput};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use hashsig::{
+    declare_instantiation,
+    symmetric::{
+        prf::{sha::ShaPRF, Pseudorandom},
+        tweak_hash::{sha::ShaTweakHash, TweakableHash},
+    },
+};
+
+/// Number of epochs for which one-time key pairs are generated
+const NUM_EPOCHS: u32 = 256;
+
+// Note: the parameters are those of `SIGWinternitzLifetime18W4`, with a
+// shorter lifetime so that all epochs fit into one batch.
+declare_instantiation! {
+    type SIGWinternitzLifetime8W4 = Sha3 {
+        log_lifetime: 8,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+}
+
+/// Benchmarking generation of the one-time key pairs of many epochs, one by one
+/// with `ots_gen`, and at once with `ots_gen_batch`, which is parallel with the
+/// feature `parallel`. The throughput is the number of key pairs.
+pub fn bench_function_ots_gen(c: &mut Criterion) {
+    type Sig = SIGWinternitzLifetime8W4;
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let prf_key = ShaPRF::<26>::gen(&mut rng);
+    let parameter = ShaTweakHash::<18, 26>::rand_parameter(&mut rng);
+
+    let mut group = c.benchmark_group(format!("SHA - One-Time Key Pairs of {} Epochs", NUM_EPOCHS));
+    group.throughput(Throughput::Elements(NUM_EPOCHS as u64));
+
+    group.bench_function("- ots_gen, one by one", |b| {
+        b.iter(|| {
+            (0..NUM_EPOCHS)
+                .map(|epoch| Sig::ots_gen(black_box(&prf_key), black_box(&parameter), epoch))
+                .collect::<Vec<_>>()
+        });
+    });
+
+    group.bench_function("- ots_gen_batch", |b| {
+        b.iter(|| Sig::ots_gen_batch(black_box(&prf_key), black_box(&parameter), 0..NUM_EPOCHS));
+    });
+
+    group.finish();
+}
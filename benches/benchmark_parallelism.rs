@@ -0,0 +1,67 @@
+use criterion::{black_box, Criterion, SamplingMode};
+
+use hashsig::{
+    inc_encoding::basic_winternitz::WinternitzEncoding,
+    parallelism::Parallelism,
+    signature::{generalized_xmss::GeneralizedXMSSSignatureScheme, SignatureScheme},
+    symmetric::{
+        message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, seed_expander::SEED_LENGTH,
+        tweak_hash::sha::ShaTweakHash,
+    },
+};
+
+/// Base 2 log of the lifetime of the benchmarked scheme
+const LOG_LIFETIME: usize = 10;
+
+// Note: the components are those of `SIGWinternitzLifetime18W4`,
+// with a shorter lifetime so that key generation is fast enough.
+type Sig = GeneralizedXMSSSignatureScheme<
+    ShaPRF<26>,
+    WinternitzEncoding<ShaMessageHash<18, 20, 36, 4>, 3>,
+    ShaTweakHash<18, 26>,
+    LOG_LIFETIME,
+>;
+
+/// Benchmarking key generation with the different choices of `Parallelism`.
+/// `gen` is included to show the overhead of choosing the parallelism.
+pub fn bench_function_parallelism(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!(
+        "SHA - Key Generation Parallelism: Lifetime 2^{}",
+        LOG_LIFETIME
+    ));
+
+    // key gen takes long, so don't do that many repetitions
+    group.sampling_mode(SamplingMode::Flat);
+    group.sample_size(10);
+
+    let seed = [0x42; SEED_LENGTH];
+    let num_threads = rayon::current_num_threads();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .unwrap();
+
+    group.bench_function("- key_gen_from_seed", |b| {
+        b.iter(|| Sig::key_gen_from_seed(black_box(seed)));
+    });
+
+    let configurations = [
+        ("sequential".to_string(), Parallelism::Sequential),
+        ("global pool".to_string(), Parallelism::Global),
+        (
+            format!("threads = {}", num_threads),
+            Parallelism::Threads(num_threads),
+        ),
+        (
+            format!("pool with threads = {}", num_threads),
+            Parallelism::Pool(&pool),
+        ),
+    ];
+    for (description, parallelism) in configurations {
+        group.bench_function(format!("- with parallelism, {}", description), |b| {
+            b.iter(|| Sig::key_gen_from_seed_with_parallelism(black_box(seed), parallelism));
+        });
+    }
+
+    group.finish();
+}
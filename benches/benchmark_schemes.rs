@@ -0,0 +1,125 @@
+use std::cell::OnceCell;
+
+use criterion::{black_box, Criterion, SamplingMode};
+use rand::{thread_rng, Rng};
+
+use hashsig::{
+    signature::{
+        param_set::{HashFamily, ParamSetId, ParamSetVisitor},
+        sizes::{report, ReportSizes},
+    },
+    MESSAGE_LENGTH,
+};
+
+/// A template for benchmarking signature schemes (key gen, signing, verification)
+/// It also prints the sizes of keys and signatures of the scheme.
+///
+/// The benchmarks are grouped by operation and named after the parameter set,
+/// e.g., `sign/ShaWinternitzLifetime18W4`, so that criterion compares all
+/// parameter sets for each operation. A single parameter set can be run using
+/// its name as a filter. Keys are only generated for benchmarks that are run.
+pub struct BenchmarkSignatureScheme<'a> {
+    c: &'a mut Criterion,
+}
+
+impl ParamSetVisitor for BenchmarkSignatureScheme<'_> {
+    type Output = ();
+
+    fn visit<S: ReportSizes>(self, param_set: ParamSetId) {
+        let c = self.c;
+        let name = param_set.name();
+        println!("Sizes for {}:\n{}", name, report::<S>());
+
+        let mut rng = thread_rng();
+
+        // Note: benchmarking key generation takes long, so it is
+        // disabled by default, see the features `with-gen-benches-*`.
+        //
+        // To compare key generation with and without multi-lane SHA3, run
+        // `cargo bench --features with-gen-benches-sha -- --save-baseline scalar`, and then
+        // `cargo bench --features with-gen-benches-sha,simd -- --baseline scalar`.
+        let gen_benches = match param_set.describe().hash_family {
+            HashFamily::Sha3 => cfg!(feature = "with-gen-benches-sha"),
+        };
+        if gen_benches {
+            let mut group = c.benchmark_group("gen");
+            // key gen takes long, so don't do that many repetitions
+            group.sampling_mode(SamplingMode::Flat);
+            group.sample_size(10);
+            group.bench_function(name, |b| {
+                b.iter(|| {
+                    // Benchmark key generation
+                    let _ = S::gen(black_box(&mut rng));
+                });
+            });
+            group.finish();
+        }
+
+        let keys = OnceCell::new();
+        let gen_keys = || S::gen(&mut thread_rng());
+
+        let mut group = c.benchmark_group("sign");
+        group.bench_function(name, |b| {
+            let (_, sk) = keys.get_or_init(gen_keys);
+            b.iter(|| {
+                // Sample random test message
+                let mut message = [0u8; MESSAGE_LENGTH];
+                rng.fill(&mut message);
+
+                // Sample random epoch
+                let epoch = rng.gen_range(0..S::LIFETIME) as u32;
+
+                // Benchmark signing
+                let _ = S::sign(
+                    black_box(&mut rng),
+                    black_box(sk),
+                    black_box(epoch),
+                    black_box(&message),
+                );
+            });
+        });
+        group.finish();
+
+        // Pre-generate messages, epochs, and signatures for verification
+        let precomputed = OnceCell::new();
+        let gen_signatures = |sk: &S::SecretKey| {
+            let mut rng = thread_rng();
+            (0..2000)
+                .map(|_| {
+                    let mut message = [0u8; MESSAGE_LENGTH];
+                    rng.fill(&mut message);
+                    let epoch = rng.gen_range(0..S::LIFETIME) as u32;
+                    let signature =
+                        S::sign(&mut rng, sk, epoch, &message).expect("Signing should succeed");
+                    (epoch, message, signature)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Verification benchmark
+        let mut group = c.benchmark_group("verify");
+        group.bench_function(name, |b| {
+            let (pk, sk) = keys.get_or_init(gen_keys);
+            let precomputed = precomputed.get_or_init(|| gen_signatures(sk));
+            b.iter(|| {
+                // Randomly pick a precomputed signature to verify
+                let (epoch, message, signature) =
+                    black_box(&precomputed[rng.gen_range(0..precomputed.len())]);
+                let _ = S::verify(
+                    black_box(pk),
+                    *epoch,
+                    black_box(message),
+                    black_box(signature),
+                );
+            });
+        });
+        group.finish();
+    }
+}
+
+/// Benchmarking all registered parameter sets, see `ParamSetId`
+pub fn bench_function_schemes(c: &mut Criterion) {
+    for param_set in ParamSetId::ALL {
+        param_set.visit(BenchmarkSignatureScheme { c });
+    }
+}
@@ -0,0 +1,174 @@
+use std::marker::PhantomData;
+
+use criterion::{black_box, Criterion, Throughput};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use hashsig::{
+    inc_encoding::IncomparableEncoding,
+    signature::{
+        codec::{self, ArtifactKind},
+        generalized_xmss::{
+            instantiations_sha::{
+                lifetime_2_to_the_18::{
+                    target_sum::SIGTargetSumLifetime18W4Off10,
+                    winternitz::SIGWinternitzLifetime18W4,
+                },
+                lifetime_2_to_the_20::{
+                    target_sum::SIGTargetSumLifetime20W4Off10,
+                    winternitz::SIGWinternitzLifetime20W4,
+                },
+            },
+            GeneralizedXMSSPublicKey, GeneralizedXMSSSignature, GeneralizedXMSSSignatureScheme,
+        },
+        param_set::ParamSetId,
+    },
+    symmetric::{prf::Pseudorandom, tweak_hash::TweakableHash},
+};
+
+/// Serialization formats of public keys and signatures
+const FORMATS: [&str; 3] = ["bincode", "codec", "codec minimal"];
+
+/// A template for benchmarking the serialization of public keys and signatures
+/// of an instantiation, in all supported formats: plain bincode, the encoding of
+/// `codec` with header, and for signatures the minimal encoding. It prints the
+/// encoded sizes, and benchmarks encoding and decoding. Decoding of signatures
+/// includes the structural checks of `is_structurally_valid`, which also check
+/// that all hashes are canonical, as on the verification path.
+///
+/// Note: the public key and the signature are decoded from random bytes derived
+/// from a fixed seed, so that runs are comparable, and key generation is not needed.
+/// Their encodings have the same sizes and structure as real ones.
+pub fn benchmark_serialization<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+>(
+    c: &mut Criterion,
+    param_set: ParamSetId,
+    _scheme: PhantomData<GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>>,
+) where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+    GeneralizedXMSSPublicKey<TH>: serde::Serialize + serde::de::DeserializeOwned,
+    GeneralizedXMSSSignature<IE, TH>: serde::Serialize + serde::de::DeserializeOwned,
+    TH::Domain: serde::Serialize + serde::de::DeserializeOwned,
+    IE::Randomness: serde::Serialize + serde::de::DeserializeOwned,
+{
+    let encode_minimal =
+        GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::encode_signature_minimal;
+    let decode_minimal =
+        GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::decode_signature_minimal;
+    let is_structurally_valid =
+        GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::is_structurally_valid;
+    let id = param_set.as_id();
+    let hash_len = std::mem::size_of::<TH::Domain>();
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let mut random_bytes = |len: usize| (0..len).map(|_| rng.gen()).collect::<Vec<u8>>();
+
+    // a public key consists of a root and a parameter, and a minimal
+    // signature of a Merkle path, randomness, and one hash per chain
+    let mut pk_bytes = codec::encode(ArtifactKind::PublicKey, id, &());
+    pk_bytes.extend(random_bytes(
+        hash_len + std::mem::size_of::<TH::Parameter>(),
+    ));
+    let pk: GeneralizedXMSSPublicKey<TH> =
+        codec::decode(ArtifactKind::PublicKey, id, &pk_bytes).unwrap();
+    let mut sig_bytes = codec::encode(ArtifactKind::MinimalSignature, id, &());
+    sig_bytes.extend(random_bytes(
+        (LOG_LIFETIME + IE::NUM_CHUNKS) * hash_len + std::mem::size_of::<IE::Randomness>(),
+    ));
+    let sig = decode_minimal(id, &sig_bytes).unwrap();
+
+    let encode_pk = |format: &str| match format {
+        "bincode" => bincode::serialize(&pk).unwrap(),
+        _ => codec::encode(ArtifactKind::PublicKey, id, &pk),
+    };
+    let encode_sig = |format: &str| match format {
+        "bincode" => bincode::serialize(&sig).unwrap(),
+        "codec" => codec::encode(ArtifactKind::Signature, id, &sig),
+        _ => encode_minimal(id, &sig),
+    };
+    let decode_sig = |format: &str, bytes: &[u8]| {
+        let sig: GeneralizedXMSSSignature<IE, TH> = match format {
+            "bincode" => bincode::deserialize(bytes).unwrap(),
+            "codec" => codec::decode(ArtifactKind::Signature, id, bytes).unwrap(),
+            _ => decode_minimal(id, bytes).unwrap(),
+        };
+        assert!(is_structurally_valid(&sig));
+        sig
+    };
+
+    // comparison table of the encoded sizes
+    println!("Encoded sizes in bytes: {}", param_set.name());
+    println!("{:<16}{:>12}{:>12}", "format", "public key", "signature");
+    for format in FORMATS {
+        // there is no minimal form of public keys, as they have no lengths
+        let pk_len = match format {
+            "codec minimal" => "-".to_string(),
+            _ => encode_pk(format).len().to_string(),
+        };
+        println!(
+            "{:<16}{:>12}{:>12}",
+            format,
+            pk_len,
+            encode_sig(format).len()
+        );
+    }
+
+    let mut group = c.benchmark_group(format!("Serialization: {}", param_set.name()));
+
+    for format in FORMATS {
+        if format != "codec minimal" {
+            let bytes = encode_pk(format);
+            group.throughput(Throughput::Bytes(bytes.len() as u64));
+            group.bench_function(format!("- public key, encode, {}", format), |b| {
+                b.iter(|| encode_pk(black_box(format)));
+            });
+            group.bench_function(format!("- public key, decode, {}", format), |b| {
+                b.iter(|| -> GeneralizedXMSSPublicKey<TH> {
+                    match format {
+                        "bincode" => bincode::deserialize(black_box(&bytes)).unwrap(),
+                        _ => codec::decode(ArtifactKind::PublicKey, id, black_box(&bytes)).unwrap(),
+                    }
+                });
+            });
+        }
+
+        let bytes = encode_sig(format);
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_function(format!("- signature, encode, {}", format), |b| {
+            b.iter(|| encode_sig(black_box(format)));
+        });
+        group.bench_function(format!("- signature, decode and check, {}", format), |b| {
+            b.iter(|| decode_sig(black_box(format), black_box(&bytes)));
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmarking the serialization of the recommended instantiations
+pub fn bench_function_serialization(c: &mut Criterion) {
+    benchmark_serialization(
+        c,
+        ParamSetId::ShaWinternitzLifetime18W4,
+        PhantomData::<SIGWinternitzLifetime18W4>,
+    );
+    benchmark_serialization(
+        c,
+        ParamSetId::ShaTargetSumLifetime18W4Off10,
+        PhantomData::<SIGTargetSumLifetime18W4Off10>,
+    );
+    benchmark_serialization(
+        c,
+        ParamSetId::ShaWinternitzLifetime20W4,
+        PhantomData::<SIGWinternitzLifetime20W4>,
+    );
+    benchmark_serialization(
+        c,
+        ParamSetId::ShaTargetSumLifetime20W4Off10,
+        PhantomData::<SIGTargetSumLifetime20W4Off10>,
+    );
+}
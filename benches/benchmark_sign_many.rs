@@ -0,0 +1,61 @@
+use criterion::{black_box, BatchSize, Criterion, Throughput};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use hashsig::{declare_instantiation, signature::SignatureScheme, MESSAGE_LENGTH};
+
+/// Number of messages that are signed at consecutive epochs
+const NUM_MESSAGES: usize = 64;
+
+// Note: the parameters are those of `SIGWinternitzLifetime18W4`, with a
+// shorter lifetime so that key generation is fast enough.
+declare_instantiation! {
+    type SIGWinternitzLifetime8W4 = Sha3 {
+        log_lifetime: 8,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+}
+
+/// Benchmarking signing of a burst of messages at consecutive epochs, one by one
+/// with `sign`, and at once with `sign_many`. The throughput is the number of
+/// messages, so that criterion reports the amortized time per signature.
+pub fn bench_function_sign_many(c: &mut Criterion) {
+    type Sig = SIGWinternitzLifetime8W4;
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let (_, sk) = Sig::gen(&mut rng);
+    let messages: Vec<[u8; MESSAGE_LENGTH]> = (0..NUM_MESSAGES).map(|_| rng.gen()).collect();
+    let start_epoch = 13;
+
+    let mut group = c.benchmark_group(format!("SHA - Signing {} Messages", NUM_MESSAGES));
+    group.throughput(Throughput::Elements(NUM_MESSAGES as u64));
+
+    group.bench_function("- sign, one by one", |b| {
+        b.iter_batched(
+            || ChaCha20Rng::seed_from_u64(1),
+            |mut rng| {
+                for (epoch, message) in (start_epoch..).zip(&messages) {
+                    Sig::sign(&mut rng, black_box(&sk), epoch, black_box(message)).unwrap();
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.bench_function("- sign_many", |b| {
+        b.iter_batched(
+            || ChaCha20Rng::seed_from_u64(1),
+            |mut rng| {
+                Sig::sign_many(&mut rng, black_box(&sk), start_epoch, black_box(&messages)).unwrap()
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
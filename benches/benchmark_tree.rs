@@ -0,0 +1,40 @@
+use criterion::{black_box, Criterion, SamplingMode};
+use rand::thread_rng;
+
+#[cfg(feature = "parallel")]
+use hashsig::symmetric::tweak_hash_tree::build_tree_parallel;
+use hashsig::symmetric::{
+    tweak_hash::{sha::ShaTweak128192, TweakableHash},
+    tweak_hash_tree::build_tree,
+};
+
+type TH = ShaTweak128192;
+
+/// Benchmarking sequential and parallel Merkle tree construction
+pub fn bench_function_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("SHA - Merkle Tree: 2^18 leafs");
+
+    // building a large tree takes long, so don't do that many repetitions
+    group.sampling_mode(SamplingMode::Flat);
+    group.sample_size(10);
+
+    let mut rng = thread_rng();
+    let parameter = TH::rand_parameter(&mut rng);
+    let leafs_hashes: Vec<_> = (0..(1 << 18)).map(|_| TH::rand_domain(&mut rng)).collect();
+
+    group.bench_function("- sequential", |b| {
+        b.iter(|| {
+            let _ = build_tree::<TH>(black_box(&parameter), black_box(leafs_hashes.clone()));
+        });
+    });
+
+    #[cfg(feature = "parallel")]
+    group.bench_function("- parallel", |b| {
+        b.iter(|| {
+            let _ =
+                build_tree_parallel::<TH>(black_box(&parameter), black_box(leafs_hashes.clone()));
+        });
+    });
+
+    group.finish();
+}
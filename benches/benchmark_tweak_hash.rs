@@ -0,0 +1,115 @@
+use criterion::{black_box, Criterion, Throughput};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use hashsig::symmetric::{
+    tweak_hash::{
+        sha::{ShaTweak128128, ShaTweak192192},
+        TweakableHash,
+    },
+    tweak_hash_tree::build_tree,
+};
+
+/// Number of steps of the benchmarked hash chains
+const CHAIN_STEPS: usize = 1000;
+
+/// Number of hashes in the benchmarked batches, see `TweakableHash::apply_batch`
+const BATCH_SIZE: usize = 8;
+
+/// Base 2 log of the number of leafs of the benchmarked Merkle trees
+const LOG_TREE_LEAFS: usize = 10;
+
+/// A template for benchmarking the throughput of tweakable hashes. It benchmarks
+/// single applications, hash chains, and Merkle trees. Inputs are derived from a
+/// fixed seed, so that runs are comparable.
+pub fn benchmark_tweak_hash<TH: TweakableHash>(c: &mut Criterion, description: &str) {
+    let mut group = c.benchmark_group(format!("Tweak Hash: {}", description));
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let parameter = TH::rand_parameter(&mut rng);
+    let left = TH::rand_domain(&mut rng);
+    let right = TH::rand_domain(&mut rng);
+
+    // report throughput in hashes per second
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("- apply, 1 domain element", |b| {
+        let tweak = TH::chain_tweak(13, 2, 5);
+        b.iter(|| {
+            let _ = TH::apply(black_box(&parameter), black_box(&tweak), black_box(&[left]));
+        });
+    });
+
+    group.bench_function("- apply, 2 domain elements", |b| {
+        let tweak = TH::tree_tweak(1, 13);
+        b.iter(|| {
+            let _ = TH::apply(
+                black_box(&parameter),
+                black_box(&tweak),
+                black_box(&[left, right]),
+            );
+        });
+    });
+
+    group.throughput(Throughput::Elements(CHAIN_STEPS as u64));
+
+    group.bench_function(format!("- chain, {} steps", CHAIN_STEPS), |b| {
+        b.iter(|| {
+            // walk the chain as in key generation, with one tweak per position
+            let mut current = black_box(left);
+            for pos_in_chain in 1..=CHAIN_STEPS {
+                let tweak = TH::chain_tweak(13, 2, pos_in_chain as u16);
+                current = TH::apply(&parameter, &tweak, &[current]);
+            }
+            current
+        });
+    });
+
+    // hashes of several chains in the same step, which can be processed at once
+    // with the feature `simd`. Run with and without it to compare.
+    let tweaks: Vec<_> = (0..BATCH_SIZE)
+        .map(|chain_index| TH::chain_tweak(13, chain_index as u16, 5))
+        .collect();
+    let batch: Vec<_> = (0..BATCH_SIZE).map(|_| TH::rand_domain(&mut rng)).collect();
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+
+    group.bench_function(format!("- apply, {} chains one by one", BATCH_SIZE), |b| {
+        b.iter(|| {
+            let mut messages = black_box(batch.clone());
+            for (tweak, message) in tweaks.iter().zip(messages.iter_mut()) {
+                *message = TH::apply(&parameter, tweak, &[*message]);
+            }
+            messages
+        });
+    });
+
+    group.bench_function(format!("- apply_batch, {} chains", BATCH_SIZE), |b| {
+        b.iter(|| {
+            let mut messages = black_box(batch.clone());
+            TH::apply_batch(&parameter, &tweaks, &mut messages);
+            messages
+        });
+    });
+
+    // a tree with n leafs has n - 1 inner nodes
+    let leafs: Vec<_> = (0..(1 << LOG_TREE_LEAFS))
+        .map(|_| TH::rand_domain(&mut rng))
+        .collect();
+    group.throughput(Throughput::Elements(leafs.len() as u64 - 1));
+
+    group.bench_function(format!("- tree, 2^{} leafs", LOG_TREE_LEAFS), |b| {
+        b.iter(|| {
+            let _ = build_tree::<TH>(black_box(&parameter), black_box(leafs.clone()));
+        });
+    });
+
+    group.finish();
+}
+
+/// Benchmarking tweakable hashes
+// Note: the Poseidon tweak hash is not available in this crate at the moment,
+// so only the SHA based tweak hashes are compared.
+pub fn bench_function_tweak_hash(c: &mut Criterion) {
+    benchmark_tweak_hash::<ShaTweak128128>(c, "SHA3 128-128");
+    benchmark_tweak_hash::<ShaTweak192192>(c, "SHA3 192-192");
+}
@@ -0,0 +1,123 @@
+//! Measures the memory used by key generation, for a selection of
+//! instantiations and lifetimes. Allocations are tracked with a counting
+//! global allocator, and the results are printed to stdout as CSV.
+//!
+//! Run with `cargo bench --bench keygen_memory --features profiling`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use hashsig::{
+    inc_encoding::{basic_winternitz::WinternitzEncoding, target_sum::TargetSumEncoding},
+    signature::{
+        generalized_xmss::GeneralizedXMSSSignatureScheme, hash_sig::HashSigSha3W4, SignatureScheme,
+    },
+    symmetric::{
+        message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, seed_expander::SEED_LENGTH,
+        tweak_hash::sha::ShaTweakHash,
+    },
+};
+
+/// Allocator that tracks the number of allocations, and the current and
+/// peak number of allocated bytes. Key generation is parallel, so this
+/// counts the allocations of all threads.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        let current = CURRENT_BYTES.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Modes of key generation that can be profiled
+#[derive(Clone, Copy)]
+enum KeyGenMode {
+    /// all leafs and the full Merkle tree are kept in memory
+    FullTree,
+}
+
+impl KeyGenMode {
+    fn name(&self) -> &'static str {
+        match self {
+            KeyGenMode::FullTree => "full-tree",
+        }
+    }
+}
+
+/// Generates a key pair in the given mode, and prints one CSV row with
+/// the peak memory, the number of allocations, and the peak bytes per leaf.
+fn profile_key_gen<S: SignatureScheme>(description: &str, log_lifetime: usize, mode: KeyGenMode) {
+    // the peak is measured relative to what is allocated before
+    let base_bytes = CURRENT_BYTES.load(Ordering::Relaxed);
+    PEAK_BYTES.store(base_bytes, Ordering::Relaxed);
+    let base_allocations = ALLOCATIONS.load(Ordering::Relaxed);
+
+    let keys = match mode {
+        KeyGenMode::FullTree => S::key_gen_from_seed([0x42; SEED_LENGTH]),
+    };
+
+    let peak_bytes = PEAK_BYTES.load(Ordering::Relaxed) - base_bytes;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - base_allocations;
+    drop(keys);
+
+    println!(
+        "{},{},{},{},{},{}",
+        description,
+        log_lifetime,
+        mode.name(),
+        peak_bytes,
+        allocations,
+        peak_bytes as u64 / S::LIFETIME
+    );
+}
+
+// Note: the components of the following schemes are those of the
+// instantiations with lifetime 2^18, but for any lifetime.
+type WinternitzW2<const LOG_LIFETIME: usize> = GeneralizedXMSSSignatureScheme<
+    ShaPRF<25>,
+    WinternitzEncoding<ShaMessageHash<18, 20, 72, 2>, 4>,
+    ShaTweakHash<18, 25>,
+    LOG_LIFETIME,
+>;
+type TargetSumW4<const LOG_LIFETIME: usize> = GeneralizedXMSSSignatureScheme<
+    ShaPRF<26>,
+    TargetSumEncoding<ShaMessageHash<18, 23, 36, 4>, 297>,
+    ShaTweakHash<18, 26>,
+    LOG_LIFETIME,
+>;
+
+/// Profiles one scheme for several lifetimes and all key generation modes
+macro_rules! profile_lifetimes {
+    ($description:expr, $scheme:ident, [$($log_lifetime:literal),*]) => {
+        $(
+            for mode in [KeyGenMode::FullTree] {
+                profile_key_gen::<$scheme<$log_lifetime>>($description, $log_lifetime, mode);
+            }
+        )*
+    };
+}
+
+fn main() {
+    println!("scheme,log_lifetime,mode,peak_bytes,allocations,peak_bytes_per_leaf");
+
+    profile_lifetimes!("Winternitz w = 4", HashSigSha3W4, [8, 10, 12, 14, 16, 18]);
+    profile_lifetimes!("Winternitz w = 2", WinternitzW2, [8, 10, 12, 14]);
+    profile_lifetimes!("Target Sum w = 4", TargetSumW4, [8, 10, 12, 14]);
+}
@@ -0,0 +1,64 @@
+//! Converts arbitrary bytes to chunks and back, with arbitrary chunk sizes.
+//! The conversions must not panic, must reject unsupported chunk sizes and
+//! chunks that are out of range, and must round-trip otherwise.
+//!
+//! The first byte of the input selects the chunk size, and the number of
+//! chunks that are written into a buffer, and the rest is the data.
+
+#![no_main]
+
+use hashsig::symmetric::message_hash::{
+    try_bytes_to_chunks, try_bytes_to_chunks_into, try_chunks_to_bytes, ChunkError,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, bytes)) = data.split_first() else {
+        return;
+    };
+    // chunk sizes 0 to 9, to also cover unsupported ones
+    let chunk_size = (selector % 10) as usize;
+    let supported = matches!(chunk_size, 1 | 2 | 4 | 8);
+
+    // the bytes as data
+    let chunks = match try_bytes_to_chunks(bytes, chunk_size) {
+        Ok(chunks) => chunks,
+        Err(error) => {
+            assert!(!supported);
+            assert_eq!(error, ChunkError::UnsupportedChunkSize(chunk_size));
+            return;
+        }
+    };
+    assert!(supported);
+    assert_eq!(chunks.len(), bytes.len() * 8 / chunk_size);
+    assert!(chunks.iter().all(|&chunk| (chunk as u16) < 1 << chunk_size));
+    assert_eq!(try_chunks_to_bytes(&chunks, chunk_size).unwrap(), bytes);
+
+    // a buffer of arbitrary length gets a prefix of the chunks, if it is not too long
+    let len = (selector / 10) as usize;
+    let mut out = vec![0xff; len];
+    match try_bytes_to_chunks_into(bytes, chunk_size, &mut out) {
+        Ok(()) => assert_eq!(out, chunks[..len]),
+        Err(error) => {
+            assert!(len > chunks.len());
+            assert_eq!(error, ChunkError::NotEnoughBytes);
+        }
+    }
+
+    // the bytes as chunks, which may be out of range
+    match try_chunks_to_bytes(bytes, chunk_size) {
+        Ok(packed) => {
+            assert_eq!(packed.len(), (bytes.len() * chunk_size).div_ceil(8));
+            let unpacked = try_bytes_to_chunks(&packed, chunk_size).unwrap();
+            assert_eq!(unpacked[..bytes.len()], *bytes);
+            assert!(unpacked[bytes.len()..].iter().all(|&chunk| chunk == 0));
+        }
+        Err(ChunkError::ChunkOutOfRange(index)) => {
+            assert!((bytes[index] as u16) >= 1 << chunk_size);
+            assert!(bytes[..index]
+                .iter()
+                .all(|&chunk| (chunk as u16) < 1 << chunk_size));
+        }
+        Err(error) => panic!("unexpected error {:?}", error),
+    }
+});
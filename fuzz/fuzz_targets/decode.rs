@@ -0,0 +1,125 @@
+//! Feeds arbitrary bytes to all decoders of keys and signatures. Decoding
+//! must fail gracefully, and whatever decodes must be usable without panics:
+//! signatures are checked and verified, and secret keys are used for signing.
+//!
+//! The first byte of the input selects the instantiation, the rest is given
+//! to the decoders, with and without the header of `codec`.
+
+#![no_main]
+
+use std::marker::PhantomData;
+
+use hashsig::{
+    declare_instantiation,
+    inc_encoding::IncomparableEncoding,
+    signature::{
+        codec,
+        generalized_xmss::{
+            GeneralizedXMSSPublicKey, GeneralizedXMSSSecretKey, GeneralizedXMSSSignature,
+            GeneralizedXMSSSignatureScheme,
+        },
+        SignatureScheme,
+    },
+    symmetric::{prf::Pseudorandom, tweak_hash::TweakableHash},
+    MESSAGE_LENGTH,
+};
+use libfuzzer_sys::fuzz_target;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde::{de::DeserializeOwned, Serialize};
+
+// Note: the parameters are those of the recommended instantiations, with a
+// short lifetime, so that decoded secret keys can have a tree of the right depth.
+declare_instantiation! {
+    type SIGWinternitzLifetime2W4 = Sha3 {
+        log_lifetime: 2,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+
+    type SIGTargetSumLifetime2W4Off10 = Sha3 {
+        log_lifetime: 2,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 23,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: TargetSum(297),
+    };
+}
+
+/// Identifier of the parameter set in the headers of the encodings
+const ID: u16 = codec::UNREGISTERED_PARAM_SET_ID;
+
+fn fuzz_decode<PRF, IE, TH, const LOG_LIFETIME: usize>(
+    data: &[u8],
+    _scheme: PhantomData<GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>>,
+) where
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+    GeneralizedXMSSPublicKey<TH>: Serialize + DeserializeOwned,
+    GeneralizedXMSSSecretKey<PRF, TH>: Serialize + DeserializeOwned,
+    GeneralizedXMSSSignature<IE, TH>: Serialize + DeserializeOwned,
+    TH::Domain: Serialize + DeserializeOwned,
+    IE::Randomness: Serialize + DeserializeOwned,
+{
+    type Sig<PRF, IE, TH, const LOG_LIFETIME: usize> =
+        GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+    let message = [0x42; MESSAGE_LENGTH];
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let use_pk = |pk: GeneralizedXMSSPublicKey<TH>| {
+        let _ = codec::encode_public_key::<Sig<PRF, IE, TH, LOG_LIFETIME>>(ID, &pk);
+    };
+    let use_sk = |sk: GeneralizedXMSSSecretKey<PRF, TH>, rng: &mut ChaCha20Rng| {
+        for epoch in 0..Sig::<PRF, IE, TH, LOG_LIFETIME>::LIFETIME as u32 {
+            let _ = Sig::<PRF, IE, TH, LOG_LIFETIME>::sign(rng, &sk, epoch, &message);
+        }
+    };
+    let use_sig = |sig: GeneralizedXMSSSignature<IE, TH>| {
+        Sig::<PRF, IE, TH, LOG_LIFETIME>::is_structurally_valid(&sig);
+        let _ = Sig::<PRF, IE, TH, LOG_LIFETIME>::encode_signature_minimal(ID, &sig);
+    };
+
+    // with the header of `codec`
+    if let Ok(pk) = codec::decode_public_key::<Sig<PRF, IE, TH, LOG_LIFETIME>>(ID, data) {
+        use_pk(pk);
+    }
+    if let Ok(sk) = codec::decode_secret_key::<Sig<PRF, IE, TH, LOG_LIFETIME>>(ID, data) {
+        use_sk(sk, &mut rng);
+    }
+    if let Ok(sig) = codec::decode_signature::<Sig<PRF, IE, TH, LOG_LIFETIME>>(ID, data) {
+        use_sig(sig);
+    }
+    if let Ok(sig) = Sig::<PRF, IE, TH, LOG_LIFETIME>::decode_signature_minimal(ID, data) {
+        use_sig(sig);
+    }
+
+    // plain serde, as used by applications that embed keys and signatures
+    if let Ok(pk) = bincode::deserialize(data) {
+        use_pk(pk);
+    }
+    if let Ok(sk) = bincode::deserialize(data) {
+        use_sk(sk, &mut rng);
+    }
+    if let Ok(sig) = bincode::deserialize(data) {
+        use_sig(sig);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, data)) = data.split_first() else {
+        return;
+    };
+    match selector % 2 {
+        0 => fuzz_decode(data, PhantomData::<SIGWinternitzLifetime2W4>),
+        _ => fuzz_decode(data, PhantomData::<SIGTargetSumLifetime2W4Off10>),
+    }
+});
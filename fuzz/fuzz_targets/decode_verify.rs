@@ -0,0 +1,64 @@
+//! Decodes arbitrary bytes as a signature, and verifies it against a fixed
+//! public key. Verification must not panic, whatever the signature is, and
+//! must only accept signatures that are structurally valid.
+//!
+//! The first byte of the input selects the epoch and the message, and the
+//! rest is decoded as a signature, in full and in minimal form.
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use hashsig::{
+    declare_instantiation,
+    signature::{
+        codec::{self, UNREGISTERED_PARAM_SET_ID},
+        generalized_xmss::GeneralizedXMSSPublicKey,
+        SignatureScheme,
+    },
+    symmetric::tweak_hash::sha::ShaTweakHash,
+    MESSAGE_LENGTH,
+};
+use libfuzzer_sys::fuzz_target;
+
+// Note: the parameters are those of `SIGWinternitzLifetime18W4`, with a
+// short lifetime, so that key generation is fast enough.
+declare_instantiation! {
+    type SIGWinternitzLifetime8W4 = Sha3 {
+        log_lifetime: 8,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+}
+
+type Sig = SIGWinternitzLifetime8W4;
+
+/// The public key that all signatures are verified against
+fn public_key() -> &'static GeneralizedXMSSPublicKey<ShaTweakHash<18, 26>> {
+    static PUBLIC_KEY: OnceLock<GeneralizedXMSSPublicKey<ShaTweakHash<18, 26>>> = OnceLock::new();
+    PUBLIC_KEY.get_or_init(|| Sig::key_gen_from_seed([0x42; 32]).0)
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, data)) = data.split_first() else {
+        return;
+    };
+    let pk = public_key();
+    let epoch = selector as u32 % Sig::LIFETIME as u32;
+    let message = [selector; MESSAGE_LENGTH];
+
+    let signatures = [
+        codec::decode_signature::<Sig>(UNREGISTERED_PARAM_SET_ID, data).ok(),
+        Sig::decode_signature_minimal(UNREGISTERED_PARAM_SET_ID, data).ok(),
+        bincode::deserialize(data).ok(),
+    ];
+    for sig in signatures.into_iter().flatten() {
+        if Sig::verify(pk, epoch, &message, &sig) {
+            assert!(Sig::is_structurally_valid(&sig));
+        }
+    }
+});
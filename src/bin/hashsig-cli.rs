@@ -0,0 +1,457 @@
+//! Command line tool to generate keys, sign, and verify with the registered
+//! parameter sets (see `ParamSetId`), using the encoding of `codec`.
+//!
+//! ```text
+//! hashsig-cli keygen --scheme sha-128 --lifetime 1024 --out key.bin [--pub pub.bin]
+//!     [--master master.bin [--path "m/1'/7'"]]
+//! hashsig-cli sign --key key.bin --epoch 7 --msg file [--sig file.sig]
+//! hashsig-cli verify --pub pub.bin --epoch 7 --msg file --sig file.sig
+//! ```
+//!
+//! The scheme is either `sha-128`, i.e., the 128-bit preset based on SHA3 with
+//! the Winternitz encoding and the given lifetime, or the name of a registered
+//! parameter set, e.g., `ShaTargetSumLifetime18W4Off10`. Files are signed by
+//! signing their SHA3-256 digest.
+//!
+//! Keys are generated from the randomness of the operating system, or, with
+//! `--master`, from the 32 bytes of the given file, i.e., a master seed. With
+//! `--path`, the seed at that path below the master seed is used instead, see
+//! `derive_child_seed`, so that many keys can be derived from one backed-up
+//! master seed. The same master seed and path give unrelated keys for different
+//! schemes. Only hardened indices are supported, i.e., all indices of the path
+//! end with `'` (or `h`), e.g., `m/1'/7'`. Note that deriving a key again
+//! starts at epoch 0, so the key file must not be replaced by a derived one.
+//!
+//! The key file contains, in this order, the next unused epoch (8 bytes, little
+//! endian), the length of the encoded public key (4 bytes, little endian), the
+//! encoded public key, and the encoded secret key. Signing refuses epochs below
+//! the next unused epoch, and updates the key file before the signature is
+//! written, so that an epoch is never used twice, even if signing is aborted.
+//!
+//! The exit code is 0 on success, 1 if a signature is invalid, and 2 on errors.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use hashsig::{
+    signature::{
+        derivation::derive_child_seed,
+        dyn_scheme::{DynScheme, DynSchemeError},
+        param_set::{EncodingKind, HashFamily, ParamSetId},
+    },
+    symmetric::seed_expander::SEED_LENGTH,
+    MESSAGE_LENGTH,
+};
+use rand_core::OsRng;
+use tiny_keccak::{Hasher, Sha3};
+use zeroize::Zeroize;
+
+const USAGE: &str = "usage:
+  hashsig-cli keygen --scheme <sha-128|NAME> [--lifetime <EPOCHS>] --out <KEY> [--pub <PUB>]
+                     [--master <SEED> [--path <m/INDEX'/...>]]
+  hashsig-cli sign --key <KEY> --epoch <EPOCH> --msg <FILE> [--sig <SIG>]
+  hashsig-cli verify --pub <PUB> --epoch <EPOCH> --msg <FILE> --sig <SIG>";
+
+/// Length of the header of the key file, i.e., the next unused
+/// epoch and the length of the encoded public key
+const KEY_FILE_HEADER_LENGTH: usize = 8 + 4;
+
+/// Number of bytes of the SHA3-256 digest of a public key that are printed
+const FINGERPRINT_LENGTH: usize = 8;
+
+/// Indices of derivation paths are below 2^31, as in the usual notation
+const MAX_PATH_INDEX: u32 = (1 << 31) - 1;
+
+/// Error enum of the command line tool
+#[derive(Debug)]
+enum CliError {
+    /// The arguments are invalid, with an explanation
+    Usage(String),
+    /// A file could not be read or written
+    Io(PathBuf, io::Error),
+    /// The key file is malformed
+    MalformedKeyFile,
+    /// The file of the master seed does not contain exactly the seed
+    MalformedMasterSeed,
+    /// The epoch was already used, or skipped, with the next unused epoch
+    EpochUsed(u64),
+    /// All epochs of the key are used
+    KeyExhausted,
+    /// The scheme failed, e.g., because a key could not be decoded
+    Scheme(DynSchemeError),
+    /// The signature is invalid
+    InvalidSignature,
+}
+
+impl From<DynSchemeError> for CliError {
+    fn from(error: DynSchemeError) -> Self {
+        CliError::Scheme(error)
+    }
+}
+
+impl CliError {
+    /// Returns the message that is printed for the error
+    fn message(&self) -> String {
+        match self {
+            CliError::Usage(reason) => format!("{}\n{}", reason, USAGE),
+            CliError::Io(path, error) => format!("{}: {}", path.display(), error),
+            CliError::MalformedKeyFile => "malformed key file".to_string(),
+            CliError::MalformedMasterSeed => {
+                format!("the master seed must consist of {} bytes", SEED_LENGTH)
+            }
+            CliError::EpochUsed(next_epoch) => format!(
+                "refusing to reuse an epoch: the next unused epoch of the key is {}",
+                next_epoch
+            ),
+            CliError::KeyExhausted => "all epochs of the key are used".to_string(),
+            CliError::Scheme(DynSchemeError::UnknownParamSet) => {
+                "unknown parameter set".to_string()
+            }
+            CliError::Scheme(DynSchemeError::InvalidEpoch) => {
+                "the epoch is not within the lifetime of the key".to_string()
+            }
+            CliError::Scheme(error) => format!("{:?}", error),
+            CliError::InvalidSignature => "signature is invalid".to_string(),
+        }
+    }
+
+    /// Returns the exit code for the error
+    fn exit_code(&self) -> u8 {
+        match self {
+            CliError::InvalidSignature => 1,
+            _ => 2,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("keygen") => keygen(&args[1..]),
+        Some("sign") => sign(&args[1..]),
+        Some("verify") => verify(&args[1..]),
+        Some(command) => Err(CliError::Usage(format!("unknown command {}", command))),
+        None => Err(CliError::Usage("missing command".to_string())),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error.message());
+            ExitCode::from(error.exit_code())
+        }
+    }
+}
+
+/// Parses options of the form `--name value`. Options that are not in
+/// `allowed` and options that are given twice are rejected.
+fn parse_options<'a>(
+    args: &'a [String],
+    allowed: &[&str],
+) -> Result<HashMap<&'a str, &'a str>, CliError> {
+    let mut options = HashMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let name = arg
+            .strip_prefix("--")
+            .filter(|name| allowed.contains(name))
+            .ok_or_else(|| CliError::Usage(format!("unexpected argument {}", arg)))?;
+        let value = args
+            .next()
+            .ok_or_else(|| CliError::Usage(format!("missing value of --{}", name)))?;
+        if options.insert(name, value.as_str()).is_some() {
+            return Err(CliError::Usage(format!("--{} is given twice", name)));
+        }
+    }
+    Ok(options)
+}
+
+/// Returns the value of a required option
+fn required<'a>(options: &HashMap<&str, &'a str>, name: &str) -> Result<&'a str, CliError> {
+    options
+        .get(name)
+        .copied()
+        .ok_or_else(|| CliError::Usage(format!("missing --{}", name)))
+}
+
+/// Parses a number given as the value of an option
+fn parse_number<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError::Usage(format!("invalid value of --{}: {}", name, value)))
+}
+
+/// Parses a derivation path of the form `m/1'/7'`, see the module docs
+fn parse_path(value: &str) -> Result<Vec<u32>, CliError> {
+    let invalid = || {
+        CliError::Usage(format!(
+            "invalid value of --path: {}, expected hardened indices like m/1'/7'",
+            value
+        ))
+    };
+    let mut components = value.split('/');
+    if components.next() != Some("m") {
+        return Err(invalid());
+    }
+    components
+        .map(|component| {
+            component
+                .strip_suffix(['\'', 'h'])
+                .and_then(|index| index.parse().ok())
+                .filter(|&index| index <= MAX_PATH_INDEX)
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+/// Returns the parameter set for the given scheme and lifetime, see the module docs
+fn select_param_set(scheme: &str, lifetime: Option<u64>) -> Result<ParamSetId, CliError> {
+    let lifetime_of = |param_set: &ParamSetId| 1u64 << param_set.describe().log_lifetime;
+
+    if scheme == "sha-128" {
+        let mut presets: Vec<ParamSetId> = ParamSetId::ALL
+            .iter()
+            .copied()
+            .filter(|param_set| {
+                let description = param_set.describe();
+                description.hash_family == HashFamily::Sha3
+                    && description.encoding == EncodingKind::Winternitz
+                    && description.chunk_size == 4
+            })
+            .collect();
+        presets.sort_by_key(lifetime_of);
+        let lifetimes: Vec<String> = presets.iter().map(|p| lifetime_of(p).to_string()).collect();
+        let lifetime = lifetime.ok_or_else(|| {
+            CliError::Usage(format!(
+                "sha-128 needs --lifetime, one of {}",
+                lifetimes.join(", ")
+            ))
+        })?;
+        return presets
+            .into_iter()
+            .find(|param_set| lifetime_of(param_set) == lifetime)
+            .ok_or_else(|| {
+                CliError::Usage(format!(
+                    "unsupported lifetime {} of sha-128, must be one of {}",
+                    lifetime,
+                    lifetimes.join(", ")
+                ))
+            });
+    }
+
+    let param_set = ParamSetId::from_name(scheme)
+        .ok_or_else(|| CliError::Usage(format!("unknown scheme {}", scheme)))?;
+    match lifetime {
+        Some(lifetime) if lifetime != lifetime_of(&param_set) => Err(CliError::Usage(format!(
+            "{} has lifetime {}, not {}",
+            scheme,
+            lifetime_of(&param_set),
+            lifetime
+        ))),
+        _ => Ok(param_set),
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, CliError> {
+    fs::read(path).map_err(|e| CliError::Io(path.to_path_buf(), e))
+}
+
+/// Writes a file by writing a temporary file first, which is then
+/// renamed, so that the file is never left partially written. On Unix,
+/// secret files are only readable by the owner.
+fn write(path: &Path, contents: &[u8], secret: bool) -> Result<(), CliError> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if secret {
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = secret;
+
+    let result = options
+        .open(&temporary)
+        .and_then(|mut file| {
+            io::Write::write_all(&mut file, contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temporary, path));
+    result.map_err(|e| CliError::Io(path.to_path_buf(), e))
+}
+
+/// Returns the SHA3-256 digest of the given bytes
+fn sha3_256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3::v256();
+    hasher.update(bytes);
+    let mut digest = [0u8; 32];
+    hasher.finalize(&mut digest);
+    digest
+}
+
+/// Returns the fingerprint of an encoded public key, i.e., the first
+/// bytes of its SHA3-256 digest, in hexadecimal
+fn fingerprint(pk: &[u8]) -> String {
+    sha3_256(pk)[..FINGERPRINT_LENGTH]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns the message that is signed for the given file, i.e., its SHA3-256 digest
+fn message_of_file(path: &Path) -> Result<[u8; MESSAGE_LENGTH], CliError> {
+    const _: () = assert!(MESSAGE_LENGTH == 32);
+    Ok(sha3_256(&read(path)?))
+}
+
+/// Contents of a key file, see the module docs
+struct KeyFile {
+    next_epoch: u64,
+    pk: Vec<u8>,
+    sk: Vec<u8>,
+}
+
+impl KeyFile {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(KEY_FILE_HEADER_LENGTH + self.pk.len() + self.sk.len());
+        bytes.extend_from_slice(&self.next_epoch.to_le_bytes());
+        bytes.extend_from_slice(&(self.pk.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.pk);
+        bytes.extend_from_slice(&self.sk);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, CliError> {
+        if bytes.len() < KEY_FILE_HEADER_LENGTH {
+            return Err(CliError::MalformedKeyFile);
+        }
+        let next_epoch = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let pk_len = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let rest = &bytes[KEY_FILE_HEADER_LENGTH..];
+        if rest.len() < pk_len {
+            return Err(CliError::MalformedKeyFile);
+        }
+        let (pk, sk) = rest.split_at(pk_len);
+        Ok(Self {
+            next_epoch,
+            pk: pk.to_vec(),
+            sk: sk.to_vec(),
+        })
+    }
+}
+
+fn keygen(args: &[String]) -> Result<(), CliError> {
+    let options = parse_options(
+        args,
+        &["scheme", "lifetime", "out", "pub", "master", "path"],
+    )?;
+    let lifetime = options
+        .get("lifetime")
+        .map(|value| parse_number(value, "lifetime"))
+        .transpose()?;
+    let param_set = select_param_set(required(&options, "scheme")?, lifetime)?;
+    let key_path = Path::new(required(&options, "out")?);
+    let pub_path = match options.get("pub") {
+        Some(path) => PathBuf::from(path),
+        None => key_path.with_extension("pub"),
+    };
+
+    let path = options
+        .get("path")
+        .map(|path| parse_path(path))
+        .transpose()?;
+
+    let scheme = DynScheme::new(param_set);
+    let (pk, sk) = match options.get("master") {
+        Some(master_path) => {
+            let mut bytes = read(Path::new(master_path))?;
+            let master: Result<[u8; SEED_LENGTH], _> = bytes.as_slice().try_into();
+            bytes.zeroize();
+            let mut master = master.map_err(|_| CliError::MalformedMasterSeed)?;
+            let mut seed = derive_child_seed(&master, path.as_deref().unwrap_or_default());
+            master.zeroize();
+            let key_pair = scheme.key_gen_from_seed(seed);
+            seed.zeroize();
+            key_pair
+        }
+        None if path.is_some() => {
+            return Err(CliError::Usage("--path needs --master".to_string()));
+        }
+        None => scheme.key_gen(&mut OsRng),
+    };
+    let key_file = KeyFile {
+        next_epoch: 0,
+        pk,
+        sk,
+    };
+    write(&pub_path, &key_file.pk, false)?;
+    write(key_path, &key_file.to_bytes(), true)?;
+
+    println!("scheme: {}", param_set.name());
+    println!("lifetime: {}", scheme.lifetime());
+    if let Some(path) = options.get("path") {
+        println!("path: {}", path);
+    }
+    println!("fingerprint: {}", fingerprint(&key_file.pk));
+    Ok(())
+}
+
+fn sign(args: &[String]) -> Result<(), CliError> {
+    let options = parse_options(args, &["key", "epoch", "msg", "sig"])?;
+    let key_path = Path::new(required(&options, "key")?);
+    let epoch: u32 = parse_number(required(&options, "epoch")?, "epoch")?;
+    let msg_path = Path::new(required(&options, "msg")?);
+    let sig_path = match options.get("sig") {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let mut path = msg_path.as_os_str().to_owned();
+            path.push(".sig");
+            PathBuf::from(path)
+        }
+    };
+
+    let mut key_file = KeyFile::from_bytes(&read(key_path)?)?;
+    let scheme = DynScheme::from_encoded(&key_file.sk)?;
+    if key_file.next_epoch >= scheme.lifetime() {
+        return Err(CliError::KeyExhausted);
+    }
+    if (epoch as u64) < key_file.next_epoch {
+        return Err(CliError::EpochUsed(key_file.next_epoch));
+    }
+
+    let message = message_of_file(msg_path)?;
+    let sig = scheme.sign(&mut OsRng, &key_file.sk, epoch, &message)?;
+
+    // the epoch is marked as used before the signature is written
+    key_file.next_epoch = epoch as u64 + 1;
+    write(key_path, &key_file.to_bytes(), true)?;
+    write(&sig_path, &sig, false)?;
+
+    println!("fingerprint: {}", fingerprint(&key_file.pk));
+    println!("epoch: {}", epoch);
+    println!("signature: {}", sig_path.display());
+    Ok(())
+}
+
+fn verify(args: &[String]) -> Result<(), CliError> {
+    let options = parse_options(args, &["pub", "epoch", "msg", "sig"])?;
+    let pk = read(Path::new(required(&options, "pub")?))?;
+    let epoch: u32 = parse_number(required(&options, "epoch")?, "epoch")?;
+    let message = message_of_file(Path::new(required(&options, "msg")?))?;
+    let sig = read(Path::new(required(&options, "sig")?))?;
+
+    let scheme = DynScheme::from_encoded(&pk)?;
+    println!("fingerprint: {}", fingerprint(&pk));
+    if scheme.verify(&pk, epoch, &message, &sig)? {
+        println!("signature is valid");
+        Ok(())
+    } else {
+        Err(CliError::InvalidSignature)
+    }
+}
@@ -1,10 +1,17 @@
-use rand::Rng;
+use std::hash::Hash;
+
+use rand_core::{CryptoRng, RngCore};
 
 use crate::MESSAGE_LENGTH;
 
 /// Error during encoding
 pub type EncodingError = ();
 
+/// Maximum number of chunks of a codeword for which `encode_into` of the
+/// encodings in this crate works without allocation. Buffers of this
+/// size are used on the stack.
+pub const MAX_NUM_CHUNKS: usize = 1 << 10;
+
 /// Trait to model incomparable encoding schemes.
 /// These schemes allow to encode a message into a codeword.
 /// A codeword consists of a number of chunks, and each chunk has
@@ -17,7 +24,7 @@ pub type EncodingError = ();
 /// x_i > x_i' for all i = 1,...,k.
 pub trait IncomparableEncoding {
     type Parameter;
-    type Randomness;
+    type Randomness: Clone + Eq + Hash;
 
     /// number of chunks of a codeword
     const NUM_CHUNKS: usize;
@@ -29,13 +36,35 @@ pub trait IncomparableEncoding {
     /// number of bits per chunks.
     const CHUNK_SIZE: usize;
 
+    /// Identifies the encoding, and everything that determines codewords
+    /// besides the constants above, e.g., a target sum and the message hash.
+    /// Different encodings must have different identifiers, as they are part
+    /// of the identifier of an instantiation.
+    fn identifier() -> Vec<u8>;
+
+    /// Length of the hash chains, i.e., the number of hash
+    /// steps from the start of a chain to its end. For chunks
+    /// of CHUNK_SIZE bits, this is 2^CHUNK_SIZE - 1. Encodings
+    /// with chunks that take fewer values override this.
+    fn chain_length() -> usize {
+        (1 << Self::CHUNK_SIZE) - 1
+    }
+
+    /// Number of bits of the message hash that determine a codeword.
+    /// By default, all chunks of a codeword are taken from the message
+    /// hash. Encodings that add chunks, e.g., a checksum, override this.
+    fn message_hash_bits() -> usize {
+        Self::NUM_CHUNKS * Self::CHUNK_SIZE
+    }
+
     /// Samples a randomness to be used for the encoding.
-    fn rand<R: Rng>(rng: &mut R) -> Self::Randomness;
+    fn rand<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Randomness;
 
     /// Apply the incomparable encoding to a message.
     /// It could happen that this fails. Otherwise,
     /// implementations must guarantee that the
     /// result is indeed a valid codeword.
+    #[allow(clippy::result_unit_err)]
     fn encode(
         parameter: &Self::Parameter,
         message: &[u8; MESSAGE_LENGTH],
@@ -43,11 +72,29 @@ pub trait IncomparableEncoding {
         epoch: u32,
     ) -> Result<Vec<u16>, EncodingError>;
 
+    /// Same as `encode`, but writes the codeword into `out` instead of
+    /// allocating a vector. The output buffer must have length NUM_CHUNKS.
+    /// If encoding fails, the content of `out` is unspecified.
+    ///
+    /// Note: the default implementation uses `encode`, and therefore allocates.
+    #[allow(clippy::result_unit_err)]
+    fn encode_into(
+        parameter: &Self::Parameter,
+        message: &[u8; MESSAGE_LENGTH],
+        randomness: &Self::Randomness,
+        epoch: u32,
+        out: &mut [u16],
+    ) -> Result<(), EncodingError> {
+        out.copy_from_slice(&Self::encode(parameter, message, randomness, epoch)?);
+        Ok(())
+    }
+
     /// Function to check internal consistency of any given parameters
     /// For testing only, and expected to panic if something is wrong.
     #[cfg(test)]
     fn internal_consistency_check();
 }
 
+pub mod base_w_winternitz;
 pub mod basic_winternitz;
 pub mod target_sum;
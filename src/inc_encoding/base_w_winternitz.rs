@@ -0,0 +1,320 @@
+use crate::{symmetric::message_hash::MessageHash, MESSAGE_LENGTH};
+
+use super::{IncomparableEncoding, MAX_NUM_CHUNKS};
+
+/// Incomparable Encoding Scheme based on the basic Winternitz scheme,
+/// with a Winternitz parameter `W` that is independent of the chunk size
+/// of the message hash. Chains have length `W - 1`, so a larger `W` gives
+/// fewer but longer chains, i.e., shorter signatures that take more hashes.
+///
+/// The chunks of the message hash are read as one little-endian integer,
+/// i.e., chunk `i` is the digit of `2^(i * MH::CHUNK_SIZE)`. This integer
+/// is decomposed into `NUM_CHUNKS_MESSAGE` digits in base `W`, least
+/// significant digit first, which is the largest number of digits that
+/// the message hash determines completely, i.e., `W^NUM_CHUNKS_MESSAGE <=
+/// 2^(MH::NUM_CHUNKS * MH::CHUNK_SIZE)`. The checksum is the sum of
+/// `W - 1 - d` over all digits `d`, in base `W` as well.
+///
+/// If `W = 2^MH::CHUNK_SIZE`, this gives the same codewords as
+/// `WinternitzEncoding`. `W` must be between 2 and 2^16.
+pub struct WinternitzBaseWEncoding<MH: MessageHash, const W: usize> {
+    _marker_mh: std::marker::PhantomData<MH>,
+}
+
+/// Number of limbs of the integers in `digits_of_bits`, enough for
+/// `w^n` with `w^(n - 1)` of at most `8 * MAX_NUM_CHUNKS` bits
+const NUM_LIMBS: usize = 8 * MAX_NUM_CHUNKS / 64 + 2;
+
+/// Returns the largest `n` with `w^n <= 2^bits`, together with floor(log2(w^n)),
+/// i.e., the number of bits that determine the `n` digits.
+const fn digits_of_bits(bits: usize, w: usize) -> (usize, usize) {
+    assert!(
+        w >= 2,
+        "Winternitz Base-W Encoding: Base must be at least 2"
+    );
+    if w.is_power_of_two() {
+        let log_w = w.trailing_zeros() as usize;
+        return (bits / log_w, bits / log_w * log_w);
+    }
+
+    // otherwise, w^n is never a power of two, so w^n <= 2^bits if and only if
+    // w^n has at most `bits` bits. We compute w^n in limbs until it has more.
+    let mut limbs = [0u64; NUM_LIMBS];
+    limbs[0] = 1;
+    let mut num_limbs = 1;
+    let mut n = 0;
+    let mut bit_length = 1;
+    loop {
+        let mut carry = 0u128;
+        let mut i = 0;
+        while i < num_limbs {
+            let product = limbs[i] as u128 * w as u128 + carry;
+            limbs[i] = product as u64;
+            carry = product >> 64;
+            i += 1;
+        }
+        if carry > 0 {
+            limbs[num_limbs] = carry as u64;
+            num_limbs += 1;
+        }
+        let next_bit_length = 64 * num_limbs - limbs[num_limbs - 1].leading_zeros() as usize;
+        if next_bit_length > bits {
+            return (n, bit_length - 1);
+        }
+        n += 1;
+        bit_length = next_bit_length;
+    }
+}
+
+/// Computes the number of chunks of the Winternitz encoding in base `w`
+/// that a message hash of `bits` bits determines completely, i.e., the
+/// largest `n` with `w^n <= 2^bits`.
+pub const fn num_chunks_base_w(bits: usize, w: usize) -> usize {
+    digits_of_bits(bits, w).0
+}
+
+/// Computes the number of checksum chunks for the Winternitz encoding
+/// in base `w`, given the number of chunks of the message. This is the
+/// number of digits in base `w` of the maximum checksum.
+pub const fn num_chunks_checksum_base_w(num_chunks_message: usize, w: usize) -> usize {
+    let max_checksum = num_chunks_message * (w - 1);
+
+    // compute 1 + floor(log_w(max_checksum))
+    let mut num_chunks = 1;
+    let mut remaining = max_checksum;
+    while remaining >= w {
+        remaining /= w;
+        num_chunks += 1;
+    }
+    num_chunks
+}
+
+impl<MH: MessageHash, const W: usize> WinternitzBaseWEncoding<MH, W> {
+    const NUM_CHUNKS_MESSAGE: usize = num_chunks_base_w(MH::NUM_CHUNKS * MH::CHUNK_SIZE, W);
+    const NUM_CHUNKS_CHECKSUM: usize = num_chunks_checksum_base_w(Self::NUM_CHUNKS_MESSAGE, W);
+    const NUM_CHUNKS: usize = Self::NUM_CHUNKS_MESSAGE + Self::NUM_CHUNKS_CHECKSUM;
+
+    /// Fails to compile if `W` is not between 2 and 2^16, as chain positions
+    /// and chunks are `u16`, or if a codeword or the chunks of the message
+    /// hash do not fit into the buffers of `encode_into`, see `MAX_NUM_CHUNKS`.
+    /// It is evaluated whenever the encoding is applied.
+    pub const PARAMETERS_VALID: () = {
+        assert!(
+            W >= 2 && W <= 1 << 16,
+            "Winternitz Base-W Encoding: W must be between 2 and 2^16"
+        );
+        assert!(
+            MH::NUM_CHUNKS <= MAX_NUM_CHUNKS && Self::NUM_CHUNKS <= MAX_NUM_CHUNKS,
+            "Winternitz Base-W Encoding: Number of chunks must be at most MAX_NUM_CHUNKS"
+        );
+    };
+}
+
+impl<MH: MessageHash, const W: usize> IncomparableEncoding for WinternitzBaseWEncoding<MH, W> {
+    type Parameter = MH::Parameter;
+
+    type Randomness = MH::Randomness;
+
+    const NUM_CHUNKS: usize = Self::NUM_CHUNKS;
+
+    const MAX_TRIES: usize = 1;
+
+    // number of bits that are needed for a chunk, i.e., ceil(log2(W))
+    const CHUNK_SIZE: usize = (usize::BITS - (W - 1).leading_zeros()) as usize;
+
+    fn chain_length() -> usize {
+        W - 1
+    }
+
+    fn identifier() -> Vec<u8> {
+        let mut identifier = b"WinternitzBaseW".to_vec();
+        identifier.extend(&(W as u64).to_be_bytes());
+        identifier.extend(MH::identifier());
+        identifier
+    }
+
+    fn message_hash_bits() -> usize {
+        // the digits only depend on the message hash modulo W^NUM_CHUNKS_MESSAGE
+        digits_of_bits(MH::NUM_CHUNKS * MH::CHUNK_SIZE, W).1
+    }
+
+    fn rand<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R) -> Self::Randomness {
+        MH::rand(rng)
+    }
+
+    fn encode(
+        parameter: &Self::Parameter,
+        message: &[u8; MESSAGE_LENGTH],
+        randomness: &Self::Randomness,
+        epoch: u32,
+    ) -> Result<Vec<u16>, super::EncodingError> {
+        let mut codeword = vec![0; Self::NUM_CHUNKS];
+        Self::encode_into(parameter, message, randomness, epoch, &mut codeword)?;
+        Ok(codeword)
+    }
+
+    fn encode_into(
+        parameter: &Self::Parameter,
+        message: &[u8; MESSAGE_LENGTH],
+        randomness: &Self::Randomness,
+        epoch: u32,
+        out: &mut [u16],
+    ) -> Result<(), super::EncodingError> {
+        let () = Self::PARAMETERS_VALID;
+        assert!(
+            out.len() == Self::NUM_CHUNKS,
+            "Winternitz Base-W Encoding: Output buffer must have length NUM_CHUNKS"
+        );
+
+        // apply the message hash to get chunks
+        let mut buffer = [0u8; MAX_NUM_CHUNKS];
+        let chunks = &mut buffer[..MH::NUM_CHUNKS];
+        MH::apply_into(parameter, epoch, randomness, message, chunks);
+
+        // we decompose the chunks into digits in base W, by repeated long division
+        // of the integer in base 2^CHUNK_SIZE. The quotient replaces the chunks,
+        // and leading zero chunks are skipped, as they do not change the result.
+        let chunk_base = 1u32 << MH::CHUNK_SIZE;
+        let (out_message, out_checksum) = out.split_at_mut(Self::NUM_CHUNKS_MESSAGE);
+        let mut len = chunks.len();
+        for x in out_message.iter_mut() {
+            let mut remainder = 0u32;
+            for chunk in chunks[..len].iter_mut().rev() {
+                let current = remainder * chunk_base + *chunk as u32;
+                *chunk = (current / W as u32) as u8;
+                remainder = current % W as u32;
+            }
+            while len > 0 && chunks[len - 1] == 0 {
+                len -= 1;
+            }
+            *x = remainder as u16;
+        }
+
+        // now, we compute the checksum, and split it into digits in base W, in
+        // little-endian. The remaining digits are zero, see NUM_CHUNKS_CHECKSUM.
+        let mut checksum: u64 = out_message
+            .iter()
+            .map(|&x| (W - 1 - x as usize) as u64)
+            .sum();
+        for x in out_checksum.iter_mut() {
+            *x = (checksum % W as u64) as u16;
+            checksum /= W as u64;
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        assert!(
+            (2..=1 << 16).contains(&W),
+            "Winternitz Base-W Encoding: W must be between 2 and 2^16"
+        );
+        assert!(
+            Self::NUM_CHUNKS_MESSAGE > 0,
+            "Winternitz Base-W Encoding: Message hash must determine at least one digit"
+        );
+        // also check internal consistency of message hash
+        MH::internal_consistency_check();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        inc_encoding::basic_winternitz::WinternitzEncoding,
+        symmetric::message_hash::{bytes_to_chunks, sha::ShaMessageHash},
+    };
+
+    use super::*;
+
+    /// A message hash with 144 bits in chunks of 8 bits
+    type MH = ShaMessageHash<18, 20, 18, 8>;
+
+    #[test]
+    fn test_num_chunks_base_w() {
+        // powers of two divide the bits
+        assert_eq!(num_chunks_base_w(144, 4), 72);
+        assert_eq!(num_chunks_base_w(144, 16), 36);
+        assert_eq!(num_chunks_base_w(144, 256), 18);
+        assert_eq!(num_chunks_base_w(146, 16), 36);
+
+        // otherwise, the largest n with w^n <= 2^bits
+        assert_eq!(num_chunks_base_w(8, 3), 5); // 3^5 = 243
+        assert_eq!(num_chunks_base_w(7, 3), 4); // 3^5 > 128
+        assert_eq!(num_chunks_base_w(144, 10), 43); // 10^43 < 2^144 < 10^44
+        assert_eq!(num_chunks_base_w(256, 255), 32); // 255^32 < 2^256
+        assert_eq!(num_chunks_base_w(3, 10), 0);
+
+        // the checksum has the digits of the maximum checksum
+        assert_eq!(num_chunks_checksum_base_w(72, 4), 4); // 72 * 3 = 216
+        assert_eq!(num_chunks_checksum_base_w(36, 16), 3); // 36 * 15 = 540
+        assert_eq!(num_chunks_checksum_base_w(18, 256), 2); // 18 * 255 = 4590
+        assert_eq!(num_chunks_checksum_base_w(43, 10), 3); // 43 * 9 = 387
+    }
+
+    fn _test_codewords<const W: usize>(num_chunks: usize, message_hash_bits: usize) {
+        type IE<const W: usize> = WinternitzBaseWEncoding<MH, W>;
+        IE::<W>::internal_consistency_check();
+        assert_eq!(IE::<W>::NUM_CHUNKS, num_chunks);
+        assert_eq!(IE::<W>::chain_length(), W - 1);
+        assert_eq!(IE::<W>::message_hash_bits(), message_hash_bits);
+
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let parameter = rng.gen();
+            let message = rng.gen();
+            let randomness = IE::<W>::rand(&mut rng);
+            let epoch = rng.gen();
+            let codeword = IE::<W>::encode(&parameter, &message, &randomness, epoch).unwrap();
+            assert_eq!(codeword.len(), num_chunks);
+
+            // for W a power of two, the message digits are groups of bits of the
+            // message hash, otherwise, we only check that they are digits
+            let chunks = MH::apply(&parameter, epoch, &randomness, &message);
+            let n = IE::<W>::NUM_CHUNKS_MESSAGE;
+            let (digits, checksum) = codeword.split_at(n);
+            assert!(digits.iter().all(|&digit| (digit as usize) < W));
+            if W.is_power_of_two() {
+                let groups = bytes_to_chunks(&chunks, W.trailing_zeros() as usize);
+                assert!(digits.iter().zip(groups).all(|(&x, y)| x == y as u16));
+            }
+
+            // and the checksum digits are the sum in base W
+            let sum: usize = digits.iter().map(|&x| W - 1 - x as usize).sum();
+            let checksum = checksum
+                .iter()
+                .rev()
+                .fold(0, |acc, &digit| acc * W + digit as usize);
+            assert_eq!(checksum, sum);
+        }
+    }
+
+    #[test]
+    fn test_codewords() {
+        // with chunks of 8 bits, a chunk is a digit in base 256
+        _test_codewords::<4>(72 + 4, 144);
+        _test_codewords::<16>(36 + 3, 144);
+        _test_codewords::<256>(18 + 2, 144);
+        _test_codewords::<10>(43 + 3, 142);
+    }
+
+    #[test]
+    fn test_same_as_winternitz_for_chunk_size() {
+        type Base256 = WinternitzBaseWEncoding<MH, 256>;
+        type Chunks = WinternitzEncoding<MH, 2>;
+
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let parameter = rng.gen();
+            let message = rng.gen();
+            let randomness = Base256::rand(&mut rng);
+            let epoch = rng.gen();
+            assert_eq!(
+                Base256::encode(&parameter, &message, &randomness, epoch),
+                Chunks::encode(&parameter, &message, &randomness, epoch)
+            );
+        }
+    }
+}
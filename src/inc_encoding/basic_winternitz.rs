@@ -1,9 +1,9 @@
 use crate::{
-    symmetric::message_hash::{bytes_to_chunks, MessageHash},
+    symmetric::message_hash::{bytes_to_chunks_into, MessageHash},
     MESSAGE_LENGTH,
 };
 
-use super::IncomparableEncoding;
+use super::{base_w_winternitz::num_chunks_checksum_base_w, IncomparableEncoding, MAX_NUM_CHUNKS};
 
 /// Incomparable Encoding Scheme based on the basic
 /// Winternitz scheme, implemented from a given message hash.
@@ -11,7 +11,8 @@ use super::IncomparableEncoding;
 ///
 /// Unfortunately, Rust cannot deal with logarithms and ceils in constants.
 /// Therefore, the user needs to supply NUM_CHUNKS_CHECKSUM. This value can
-/// be computed before compilation with the following steps:
+/// be computed before compilation with the following steps, or with the
+/// function `num_chunks_checksum`:
 /// ```ignore
 ///     base = 2 ** MH::CHUNK_SIZE
 ///     num_chunks_message = MH::NUM_CHUNKS
@@ -22,6 +23,13 @@ pub struct WinternitzEncoding<MH: MessageHash, const NUM_CHUNKS_CHECKSUM: usize>
     _marker_mh: std::marker::PhantomData<MH>,
 }
 
+/// Computes the number of checksum chunks for the Winternitz encoding,
+/// given the number of chunks of the message hash and the chunk size.
+/// This is the number of chunks needed to represent the maximum checksum.
+pub const fn num_chunks_checksum(num_chunks_message: usize, chunk_size: usize) -> usize {
+    num_chunks_checksum_base_w(num_chunks_message, 1 << chunk_size)
+}
+
 impl<MH: MessageHash, const NUM_CHUNKS_CHECKSUM: usize>
     WinternitzEncoding<MH, NUM_CHUNKS_CHECKSUM>
 {
@@ -43,7 +51,19 @@ impl<MH: MessageHash, const NUM_CHUNKS_CHECKSUM: usize> IncomparableEncoding
 
     const CHUNK_SIZE: usize = MH::CHUNK_SIZE;
 
-    fn rand<R: rand::Rng>(rng: &mut R) -> Self::Randomness {
+    fn identifier() -> Vec<u8> {
+        let mut identifier = b"Winternitz".to_vec();
+        identifier.extend(&(NUM_CHUNKS_CHECKSUM as u64).to_be_bytes());
+        identifier.extend(MH::identifier());
+        identifier
+    }
+
+    fn message_hash_bits() -> usize {
+        // the checksum chunks are derived from the message chunks
+        Self::NUM_CHUNKS_MESSAGE * MH::CHUNK_SIZE
+    }
+
+    fn rand<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R) -> Self::Randomness {
         MH::rand(rng)
     }
 
@@ -53,8 +73,31 @@ impl<MH: MessageHash, const NUM_CHUNKS_CHECKSUM: usize> IncomparableEncoding
         randomness: &Self::Randomness,
         epoch: u32,
     ) -> Result<Vec<u16>, super::EncodingError> {
+        let mut codeword = vec![0; Self::NUM_CHUNKS];
+        Self::encode_into(parameter, message, randomness, epoch, &mut codeword)?;
+        Ok(codeword)
+    }
+
+    fn encode_into(
+        parameter: &Self::Parameter,
+        message: &[u8; MESSAGE_LENGTH],
+        randomness: &Self::Randomness,
+        epoch: u32,
+        out: &mut [u16],
+    ) -> Result<(), super::EncodingError> {
+        assert!(
+            out.len() == Self::NUM_CHUNKS,
+            "Winternitz Encoding: Output buffer must have length NUM_CHUNKS"
+        );
+        assert!(
+            Self::NUM_CHUNKS_MESSAGE <= MAX_NUM_CHUNKS,
+            "Winternitz Encoding: Number of chunks of the message hash is too large"
+        );
+
         // apply the message hash to get chunks
-        let chunks_message = MH::apply(parameter, epoch, randomness, message);
+        let mut buffer = [0u8; MAX_NUM_CHUNKS];
+        let chunks_message = &mut buffer[..Self::NUM_CHUNKS_MESSAGE];
+        MH::apply_into(parameter, epoch, randomness, message, chunks_message);
 
         // now, we compute the checksum
         let checksum: u64 = chunks_message
@@ -62,19 +105,25 @@ impl<MH: MessageHash, const NUM_CHUNKS_CHECKSUM: usize> IncomparableEncoding
             .map(|&x| Self::BASE as u64 - 1 - x as u64)
             .sum();
 
-        // we split the checksum into chunks, in little-endian
+        // we split the checksum into chunks, in little-endian.
+        // Note that we only want to take the first NUM_CHUNKS_CHECKSUM chunks.
+        // The remaining ones must be zero anyways.
+        // A checksum of 64 bits has at most 64 chunks.
         let checksum_bytes = checksum.to_le_bytes();
-        let chunks_checksum = bytes_to_chunks(&checksum_bytes, Self::CHUNK_SIZE);
+        let mut chunks_checksum = [0u8; 64];
+        let chunks_checksum = &mut chunks_checksum[..NUM_CHUNKS_CHECKSUM];
+        bytes_to_chunks_into(&checksum_bytes, Self::CHUNK_SIZE, chunks_checksum);
 
-        // Assemble the resulting vector
+        // Assemble the resulting codeword
         // we take all message chunks, followed by the checksum chunks.
-        // Note that we only want to take the first NUM_CHUNKS_CHECKSUM chunks.
-        // The remaining ones must be zero anyways.
-        Ok(chunks_message
-            .iter()
-            .chain(chunks_checksum.iter().take(NUM_CHUNKS_CHECKSUM))
-            .map(|&x| x as u16)
-            .collect())
+        let (out_message, out_checksum) = out.split_at_mut(Self::NUM_CHUNKS_MESSAGE);
+        for (x, &chunk) in out_message.iter_mut().zip(chunks_message.iter()) {
+            *x = chunk as u16;
+        }
+        for (x, &chunk) in out_checksum.iter_mut().zip(chunks_checksum.iter()) {
+            *x = chunk as u16;
+        }
+        Ok(())
     }
 
     #[cfg(test)]
@@ -88,3 +137,24 @@ impl<MH: MessageHash, const NUM_CHUNKS_CHECKSUM: usize> IncomparableEncoding
         MH::internal_consistency_check();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::num_chunks_checksum;
+
+    #[test]
+    fn test_num_chunks_checksum() {
+        // values used for the instantiations, with a message hash of 144 bits
+        assert_eq!(num_chunks_checksum(144, 1), 8);
+        assert_eq!(num_chunks_checksum(72, 2), 4);
+        assert_eq!(num_chunks_checksum(36, 4), 3);
+        assert_eq!(num_chunks_checksum(18, 8), 2);
+
+        // boundaries: a maximum checksum of base^k - 1 needs k chunks
+        assert_eq!(num_chunks_checksum(1, 4), 1);
+        assert_eq!(num_chunks_checksum(17, 4), 2);
+        assert_eq!(num_chunks_checksum(18, 4), 3);
+        assert_eq!(num_chunks_checksum(255, 1), 8);
+        assert_eq!(num_chunks_checksum(256, 1), 9);
+    }
+}
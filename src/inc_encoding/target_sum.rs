@@ -1,6 +1,6 @@
 use crate::{symmetric::message_hash::MessageHash, MESSAGE_LENGTH};
 
-use super::IncomparableEncoding;
+use super::{IncomparableEncoding, MAX_NUM_CHUNKS};
 
 /// Incomparable Encoding Scheme based on Target Sums,
 /// implemented from a given message hash.
@@ -39,7 +39,14 @@ impl<MH: MessageHash, const TARGET_SUM: usize> IncomparableEncoding
 
     const CHUNK_SIZE: usize = MH::CHUNK_SIZE;
 
-    fn rand<R: rand::Rng>(rng: &mut R) -> Self::Randomness {
+    fn identifier() -> Vec<u8> {
+        let mut identifier = b"TargetSum".to_vec();
+        identifier.extend(&(TARGET_SUM as u64).to_be_bytes());
+        identifier.extend(MH::identifier());
+        identifier
+    }
+
+    fn rand<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R) -> Self::Randomness {
         MH::rand(rng)
     }
 
@@ -49,18 +56,41 @@ impl<MH: MessageHash, const TARGET_SUM: usize> IncomparableEncoding
         randomness: &Self::Randomness,
         epoch: u32,
     ) -> Result<Vec<u16>, super::EncodingError> {
+        let mut codeword = vec![0; Self::NUM_CHUNKS];
+        Self::encode_into(parameter, message, randomness, epoch, &mut codeword)?;
+        Ok(codeword)
+    }
+
+    fn encode_into(
+        parameter: &Self::Parameter,
+        message: &[u8; MESSAGE_LENGTH],
+        randomness: &Self::Randomness,
+        epoch: u32,
+        out: &mut [u16],
+    ) -> Result<(), super::EncodingError> {
+        assert!(
+            out.len() == Self::NUM_CHUNKS,
+            "Target Sum Encoding: Output buffer must have length NUM_CHUNKS"
+        );
+        assert!(
+            Self::NUM_CHUNKS <= MAX_NUM_CHUNKS,
+            "Target Sum Encoding: Number of chunks of the message hash is too large"
+        );
+
         // apply the message hash first to get chunks
-        let chunks = MH::apply(parameter, epoch, randomness, message);
-        let chunks_u16: Vec<u16> = chunks.iter().map(|&x| x as u16).collect();
-        let chunks_u32: Vec<u32> = chunks.iter().map(|&x| x as u32).collect();
+        let mut buffer = [0u8; MAX_NUM_CHUNKS];
+        let chunks = &mut buffer[..Self::NUM_CHUNKS];
+        MH::apply_into(parameter, epoch, randomness, message, chunks);
 
-        let sum: u32 = chunks_u32.iter().sum();
+        let sum: u32 = chunks.iter().map(|&x| x as u32).sum();
         // only output something if the chunks sum to the target sum
-        if sum as usize == Self::TARGET_SUM {
-            Ok(chunks_u16)
-        } else {
-            Err(())
+        if sum as usize != Self::TARGET_SUM {
+            return Err(());
+        }
+        for (x, &chunk) in out.iter_mut().zip(chunks.iter()) {
+            *x = chunk as u16;
         }
+        Ok(())
     }
 
     #[cfg(test)]
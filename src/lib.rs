@@ -1,10 +1,61 @@
 /// Message length in bytes, for messages that we want to sign.
+///
+/// Typically, the message is a digest of the actual data. Then, collisions
+/// of that digest are forgeries, and finding one takes about `2^(4 * MESSAGE_LENGTH)`
+/// work due to the birthday bound. Hence, the message length must be at
+/// least `2 * SECURITY_BITS` bits, which is checked at compile time.
 pub const MESSAGE_LENGTH: usize = 32;
 
+/// Targeted security level in bits against classical attackers
+pub const SECURITY_BITS: usize = 128;
+
+/// Returns whether a message length in bytes is sufficient for the given
+/// security level, i.e., whether a digest of that length is collision
+/// resistant at that level, see `MESSAGE_LENGTH`.
+pub const fn message_length_is_secure(message_length: usize, security_bits: usize) -> bool {
+    message_length * 8 >= 2 * security_bits
+}
+
+const _: () = assert!(
+    message_length_is_secure(MESSAGE_LENGTH, SECURITY_BITS),
+    "MESSAGE_LENGTH is too small for SECURITY_BITS: digests of messages are not collision resistant"
+);
+
 pub const TWEAK_SEPARATOR_FOR_MESSAGE_HASH: u8 = 0x02;
 pub const TWEAK_SEPARATOR_FOR_TREE_HASH: u8 = 0x01;
 pub const TWEAK_SEPARATOR_FOR_CHAIN_HASH: u8 = 0x00;
 
+#[cfg(not(any(feature = "sha", feature = "poseidon")))]
+compile_error!(
+    "No hash backend selected: enable at least one of the features `sha` or `poseidon`."
+);
+
+#[cfg(feature = "poseidon")]
+compile_error!("The Poseidon backend is currently not available: use the feature `sha` instead.");
+
 pub mod inc_encoding;
+pub mod parallelism;
+/// Re-exports of the recommended API surface, use via `use hashsig::prelude::*;`
+pub mod prelude;
+#[cfg(feature = "rand_core_09")]
+pub mod rand_compat;
+#[cfg(feature = "sha")]
+pub mod self_test;
 pub mod signature;
 pub mod symmetric;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_length_is_secure() {
+        assert!(message_length_is_secure(MESSAGE_LENGTH, SECURITY_BITS));
+        assert!(message_length_is_secure(32, 128));
+
+        // a digest of 16 bytes has collisions after about 2^64 messages
+        assert!(!message_length_is_secure(16, 128));
+        assert!(!message_length_is_secure(31, 128));
+        assert!(!message_length_is_secure(32, 192));
+    }
+}
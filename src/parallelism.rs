@@ -0,0 +1,88 @@
+//! Parallelism of key generation.
+//!
+//! With the feature `parallel` (default), key generation uses rayon, by default
+//! in the current thread pool of rayon. Using `Parallelism`, it can instead run
+//! sequentially, with a given number of threads, or in a given thread pool, e.g.,
+//! to bound the CPU usage of a service. Without the feature, key generation is
+//! sequential and rayon is not a dependency.
+//!
+//! All choices result in exactly the same keys.
+
+use std::ops::Range;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// How key generation is parallelized
+#[cfg(feature = "parallel")]
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Parallelism<'a> {
+    /// Everything runs on the calling thread
+    Sequential,
+    /// The current thread pool of rayon, which is the global
+    /// thread pool unless called from within another pool
+    #[default]
+    Global,
+    /// A new thread pool with the given number of threads.
+    /// If it is zero, rayon chooses the number of threads.
+    Threads(usize),
+    /// The given thread pool
+    Pool(&'a rayon::ThreadPool),
+}
+
+#[cfg(feature = "parallel")]
+impl Parallelism<'_> {
+    /// Runs `op` according to this parallelism. The argument of `op` is whether
+    /// it must run sequentially. Otherwise, it runs in the chosen thread pool.
+    pub(crate) fn run<T: Send>(self, op: impl FnOnce(bool) -> T + Send) -> T {
+        match self {
+            Parallelism::Sequential => op(true),
+            Parallelism::Global => op(false),
+            Parallelism::Threads(num_threads) => rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .expect("Parallelism: Failed to build thread pool")
+                .install(|| op(false)),
+            Parallelism::Pool(pool) => pool.install(|| op(false)),
+        }
+    }
+}
+
+/// Maps `f` over the given range and collects the results in order.
+/// This is parallel unless `sequential` is set or the feature `parallel`
+/// is disabled.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+pub(crate) fn map_range<T, F>(sequential: bool, range: Range<u64>, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(u64) -> T + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    if !sequential {
+        return range.into_par_iter().map(f).collect();
+    }
+    range.map(f).collect()
+}
+
+/// Calls `f` with the index and the content of each chunk of `slice`.
+/// This is parallel unless `sequential` is set or the feature `parallel`
+/// is disabled.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+pub(crate) fn for_each_chunk_mut<T, F>(sequential: bool, slice: &mut [T], chunk_size: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    if !sequential {
+        slice
+            .par_chunks_mut(chunk_size)
+            .enumerate()
+            .for_each(|(index, chunk)| f(index, chunk));
+        return;
+    }
+    slice
+        .chunks_mut(chunk_size)
+        .enumerate()
+        .for_each(|(index, chunk)| f(index, chunk));
+}
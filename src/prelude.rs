@@ -0,0 +1,40 @@
+pub use crate::{
+    inc_encoding::{
+        basic_winternitz::WinternitzEncoding, target_sum::TargetSumEncoding, IncomparableEncoding,
+    },
+    signature::{
+        generalized_xmss::{
+            GeneralizedXMSSPublicKey, GeneralizedXMSSSecretKey, GeneralizedXMSSSignature,
+            GeneralizedXMSSSignatureScheme, KeyGenProgress,
+        },
+        KeyGenError, SignatureScheme, SigningError,
+    },
+    symmetric::{
+        message_hash::MessageHash, prf::Pseudorandom, seed_expander::SEED_LENGTH,
+        tweak_hash::TweakableHash,
+    },
+    MESSAGE_LENGTH,
+};
+
+/// Control over the parallelism of key generation
+#[cfg(feature = "parallel")]
+pub use crate::parallelism::Parallelism;
+
+/// Building blocks based on SHA
+#[cfg(feature = "sha")]
+pub use crate::symmetric::{
+    message_hash::{sha::ShaMessageHash, sha256::Sha256MessageHash},
+    prf::sha::ShaPRF,
+    tweak_hash::sha::ShaTweakHash,
+};
+
+/// Recommended instantiations, all based on SHA3 and using chunk size w = 4.
+#[cfg(feature = "sha")]
+pub use crate::signature::generalized_xmss::instantiations_sha::{
+    lifetime_2_to_the_18::{
+        target_sum::SIGTargetSumLifetime18W4Off10, winternitz::SIGWinternitzLifetime18W4,
+    },
+    lifetime_2_to_the_20::{
+        target_sum::SIGTargetSumLifetime20W4Off10, winternitz::SIGWinternitzLifetime20W4,
+    },
+};
@@ -0,0 +1,34 @@
+use rand_core::{CryptoRng, RngCore};
+
+/// Wrapper to use an RNG from the `rand_core` 0.9 ecosystem
+/// (e.g., `rand` 0.9) wherever this crate expects an RNG.
+///
+/// The wrapper can hold the RNG itself or a mutable reference to it:
+///
+/// ```ignore
+///     let mut rng = Rng09(&mut rand::rng());
+///     let (pk, sk) = SIG::gen(&mut rng);
+/// ```
+pub struct Rng09<R>(pub R);
+
+impl<R: rand_core_09::RngCore> RngCore for Rng09<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        // RNGs in rand_core 0.9 are infallible
+        self.0.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<R: rand_core_09::CryptoRng> CryptoRng for Rng09<R> {}
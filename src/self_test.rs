@@ -0,0 +1,239 @@
+//! Power-on self-test, which checks known-answer vectors of all enabled hash
+//! backends and a sign/verify round-trip, see `self_test`.
+//!
+//! This is meant to be called once at startup, to detect a miscompiled binary
+//! or a broken backend before any keys are generated or signatures are issued.
+//! Enabling the feature `self-test-corrupt` corrupts the expected output of
+//! SHA3-256, so that deployments can check that a failing self-test is handled.
+
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "backend-rustcrypto")]
+use crate::symmetric::sha3_backend::RustCryptoSha3;
+#[cfg(feature = "backend-tiny-keccak")]
+use crate::symmetric::sha3_backend::TinyKeccakSha3;
+use crate::{
+    inc_encoding::basic_winternitz::WinternitzEncoding,
+    signature::{generalized_xmss::GeneralizedXMSSSignatureScheme, SignatureScheme, SigningError},
+    symmetric::{
+        message_hash::sha::ShaMessageHash192x3,
+        prf::sha::ShaPRF,
+        seed_expander::{SeedExpander, SEED_LENGTH},
+        sha3_backend::Sha3Backend,
+        tweak_hash::sha::ShaTweak192192,
+    },
+    MESSAGE_LENGTH,
+};
+
+/// Error enum for the self-test
+#[derive(Debug)]
+pub enum SelfTestError {
+    /// A hash function of the given backend did not compute
+    /// the expected output for a known-answer vector
+    KnownAnswer {
+        backend: &'static str,
+        function: &'static str,
+    },
+    /// Signing failed in the round-trip
+    Signing(SigningError),
+    /// A valid signature was rejected in the round-trip
+    SignatureRejected,
+    /// A signature was accepted for a different message or epoch
+    ForgeryAccepted,
+}
+
+impl From<SigningError> for SelfTestError {
+    fn from(error: SigningError) -> Self {
+        SelfTestError::Signing(error)
+    }
+}
+
+/// Input of all known-answer vectors
+const INPUT: &[u8] = b"abc";
+
+// expected outputs for the input "abc", see FIPS 202 and FIPS 180-4.
+// Keccak-256 is the variant with the original padding, as used by Ethereum.
+const SHA3_256_ABC: [u8; 32] = [
+    0x3a, 0x98, 0x5d, 0xa7, 0x4f, 0xe2, 0x25, 0xb2, 0x04, 0x5c, 0x17, 0x2d, 0x6b, 0xd3, 0x90, 0xbd,
+    0x85, 0x5f, 0x08, 0x6e, 0x3e, 0x9d, 0x52, 0x5b, 0x46, 0xbf, 0xe2, 0x45, 0x11, 0x43, 0x15, 0x32,
+];
+const SHA3_384_ABC: [u8; 48] = [
+    0xec, 0x01, 0x49, 0x82, 0x88, 0x51, 0x6f, 0xc9, 0x26, 0x45, 0x9f, 0x58, 0xe2, 0xc6, 0xad, 0x8d,
+    0xf9, 0xb4, 0x73, 0xcb, 0x0f, 0xc0, 0x8c, 0x25, 0x96, 0xda, 0x7c, 0xf0, 0xe4, 0x9b, 0xe4, 0xb2,
+    0x98, 0xd8, 0x8c, 0xea, 0x92, 0x7a, 0xc7, 0xf5, 0x39, 0xf1, 0xed, 0xf2, 0x28, 0x37, 0x6d, 0x25,
+];
+const SHA3_512_ABC: [u8; 64] = [
+    0xb7, 0x51, 0x85, 0x0b, 0x1a, 0x57, 0x16, 0x8a, 0x56, 0x93, 0xcd, 0x92, 0x4b, 0x6b, 0x09, 0x6e,
+    0x08, 0xf6, 0x21, 0x82, 0x74, 0x44, 0xf7, 0x0d, 0x88, 0x4f, 0x5d, 0x02, 0x40, 0xd2, 0x71, 0x2e,
+    0x10, 0xe1, 0x16, 0xe9, 0x19, 0x2a, 0xf3, 0xc9, 0x1a, 0x7e, 0xc5, 0x76, 0x47, 0xe3, 0x93, 0x40,
+    0x57, 0x34, 0x0b, 0x4c, 0xf4, 0x08, 0xd5, 0xa5, 0x65, 0x92, 0xf8, 0x27, 0x4e, 0xec, 0x53, 0xf0,
+];
+const KECCAK256_ABC: [u8; 32] = [
+    0x4e, 0x03, 0x65, 0x7a, 0xea, 0x45, 0xa9, 0x4f, 0xc7, 0xd4, 0x7b, 0xa8, 0x26, 0xc8, 0xd6, 0x67,
+    0xc0, 0xd1, 0xe6, 0xe3, 0x3a, 0x64, 0xa0, 0x36, 0xec, 0x44, 0xf5, 0x8f, 0xa1, 0x2d, 0x6c, 0x45,
+];
+const SHA256_ABC: [u8; 32] = [
+    0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23,
+    0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad,
+];
+
+/// Instantiation used for the sign/verify round-trip. It has a lifetime of 4,
+/// so that key generation is fast, but the same building blocks as the
+/// recommended instantiations.
+type SelfTestScheme = GeneralizedXMSSSignatureScheme<
+    ShaPRF<24>,
+    WinternitzEncoding<ShaMessageHash192x3, 3>,
+    ShaTweak192192,
+    2,
+>;
+
+/// Runs the self-test: known-answer vectors for SHA3 of each enabled backend
+/// (including multi-lane SHA3 if the feature `simd` is enabled and AVX2 is
+/// available), for SHA-256, and a sign/verify round-trip that also checks
+/// that signatures are rejected for a different message or epoch.
+///
+/// Returns the first check that failed. Key generation for the round-trip is
+/// deterministic and takes a few milliseconds.
+#[must_use = "the library must not be used if the self-test fails"]
+pub fn self_test() -> Result<(), SelfTestError> {
+    #[cfg(feature = "backend-tiny-keccak")]
+    check_sha3_backend::<TinyKeccakSha3>("tiny-keccak")?;
+    #[cfg(feature = "backend-rustcrypto")]
+    check_sha3_backend::<RustCryptoSha3>("rustcrypto")?;
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    check_sha3_multi()?;
+
+    let output = Sha256::digest(INPUT);
+    check_output("sha2", "SHA-256", &output, &SHA256_ABC)?;
+
+    check_sign_verify()
+}
+
+/// Compares an output with the expected one. If the feature `self-test-corrupt`
+/// is enabled, the expected output of SHA3-256 is corrupted, so that it fails.
+fn check_output(
+    backend: &'static str,
+    function: &'static str,
+    output: &[u8],
+    expected: &[u8],
+) -> Result<(), SelfTestError> {
+    let mut expected = expected.to_vec();
+    if cfg!(feature = "self-test-corrupt") && function == "SHA3-256" {
+        expected[0] ^= 0x01;
+    }
+
+    if output == expected {
+        Ok(())
+    } else {
+        Err(SelfTestError::KnownAnswer { backend, function })
+    }
+}
+
+/// Checks the known-answer vectors of all SHA3 variants for the given backend
+fn check_sha3_backend<B: Sha3Backend>(backend: &'static str) -> Result<(), SelfTestError> {
+    let vectors: [(&'static str, B, &[u8]); 4] = [
+        ("SHA3-256", B::v256(), &SHA3_256_ABC),
+        ("SHA3-384", B::v384(), &SHA3_384_ABC),
+        ("SHA3-512", B::v512(), &SHA3_512_ABC),
+        ("Keccak-256", B::keccak256(), &KECCAK256_ABC),
+    ];
+
+    for (function, mut hasher, expected) in vectors {
+        hasher.update(INPUT);
+        let mut output = vec![0u8; expected.len()];
+        hasher.finalize(&mut output);
+        check_output(backend, function, &output, expected)?;
+    }
+    Ok(())
+}
+
+/// Checks the known-answer vectors of SHA3 for multi-lane SHA3 in all lanes,
+/// if it is available on this CPU
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+fn check_sha3_multi() -> Result<(), SelfTestError> {
+    use crate::symmetric::sha3_multi::{self, LANES};
+
+    if !sha3_multi::is_available() {
+        return Ok(());
+    }
+
+    let vectors: [(&'static str, usize, &[u8]); 3] = [
+        ("SHA3-256", 256, &SHA3_256_ABC),
+        ("SHA3-384", 384, &SHA3_384_ABC),
+        ("SHA3-512", 512, &SHA3_512_ABC),
+    ];
+
+    for (function, bits, expected) in vectors {
+        let mut outputs = [[0u8; 64]; LANES];
+        sha3_multi::sha3(bits, [INPUT; LANES], &mut outputs);
+        for output in &outputs {
+            check_output("simd", function, &output[..bits / 8], expected)?;
+        }
+    }
+    Ok(())
+}
+
+/// Generates a key from a fixed seed, signs a fixed message, and checks that
+/// the signature verifies, but not for a different message or epoch.
+fn check_sign_verify() -> Result<(), SelfTestError> {
+    let seed = [0x5a; SEED_LENGTH];
+    let (pk, sk) = SelfTestScheme::key_gen_from_seed(seed);
+
+    // the randomness of signing is derived from the same seed, so
+    // that the self-test does not depend on a source of randomness
+    let mut rng = SeedExpander::new(b"hashsig self-test", &seed);
+    let message = [0x42; MESSAGE_LENGTH];
+    let epoch = 2;
+    let sig = SelfTestScheme::sign(&mut rng, &sk, epoch, &message)?;
+
+    if !SelfTestScheme::verify(&pk, epoch, &message, &sig) {
+        return Err(SelfTestError::SignatureRejected);
+    }
+
+    let mut other_message = message;
+    other_message[0] ^= 0x01;
+    if SelfTestScheme::verify(&pk, epoch, &other_message, &sig)
+        || SelfTestScheme::verify(&pk, epoch + 1, &message, &sig)
+    {
+        return Err(SelfTestError::ForgeryAccepted);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "self-test-corrupt"))]
+    #[test]
+    fn test_self_test_passes() {
+        self_test().unwrap();
+    }
+
+    #[cfg(feature = "self-test-corrupt")]
+    #[test]
+    fn test_self_test_detects_corrupted_vector() {
+        assert!(matches!(
+            self_test(),
+            Err(SelfTestError::KnownAnswer {
+                function: "SHA3-256",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_check_output() {
+        assert!(check_output("test", "SHA3-512", &SHA3_512_ABC, &SHA3_512_ABC).is_ok());
+
+        let mut output = SHA3_512_ABC;
+        output[63] ^= 0x80;
+        assert!(matches!(
+            check_output("test", "SHA3-512", &output, &SHA3_512_ABC),
+            Err(SelfTestError::KnownAnswer {
+                backend: "test",
+                function: "SHA3-512"
+            })
+        ));
+    }
+}
@@ -1,12 +1,24 @@
-use rand::Rng;
+use std::ops::RangeBounds;
 
-use crate::MESSAGE_LENGTH;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{symmetric::seed_expander::SEED_LENGTH, MESSAGE_LENGTH};
 
 /// Error enum for signatures
 #[derive(Debug)]
 pub enum SigningError {
     InvalidMessageLength,
     UnluckyFailure,
+    /// The secret key does not belong to this instantiation, e.g.,
+    /// its Merkle tree has a different depth than the lifetime
+    InvalidSecretKey,
+}
+
+/// Error enum for key generation
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeyGenError {
+    /// Key generation was cancelled by its progress callback
+    Cancelled,
 }
 
 /// Trait to model a synchronized signature scheme.
@@ -22,18 +34,45 @@ pub trait SignatureScheme {
     const LIFETIME: u64;
 
     /// Generates a new key pair, returning the public and private keys.
-    fn gen<R: Rng>(rng: &mut R) -> (Self::PublicKey, Self::SecretKey);
+    /// The given RNG must be cryptographically secure.
+    fn gen<R: RngCore + CryptoRng>(rng: &mut R) -> (Self::PublicKey, Self::SecretKey);
+
+    /// Deterministically generates a key pair from a seed.
+    /// No other randomness is used, so the same seed always results
+    /// in the same keys. Different instantiations of a scheme derive
+    /// unrelated keys from the same seed.
+    fn key_gen_from_seed(seed: [u8; SEED_LENGTH]) -> (Self::PublicKey, Self::SecretKey);
 
     /// Signs a message and returns the signature.
     /// The signature is with respect to a given epoch.
-    fn sign<R: Rng>(
+    /// The given RNG must be cryptographically secure.
+    fn sign<R: RngCore + CryptoRng>(
         rng: &mut R,
         sk: &Self::SecretKey,
         epoch: u32,
         message: &[u8; MESSAGE_LENGTH],
     ) -> Result<Self::Signature, SigningError>;
 
+    /// Generates a new key pair using the randomness of the operating system.
+    /// This is a convenience wrapper around `gen`.
+    #[cfg(feature = "getrandom")]
+    fn gen_os() -> (Self::PublicKey, Self::SecretKey) {
+        Self::gen(&mut rand_core::OsRng)
+    }
+
+    /// Signs a message using the randomness of the operating system.
+    /// This is a convenience wrapper around `sign`.
+    #[cfg(feature = "getrandom")]
+    fn sign_os(
+        sk: &Self::SecretKey,
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<Self::Signature, SigningError> {
+        Self::sign(&mut rand_core::OsRng, sk, epoch, message)
+    }
+
     /// Verifies a signature with respect to public key, epoch, and message digest.
+    #[must_use = "a signature must be rejected if verification fails"]
     fn verify(
         pk: &Self::PublicKey,
         epoch: u32,
@@ -41,17 +80,52 @@ pub trait SignatureScheme {
         sig: &Self::Signature,
     ) -> bool;
 
+    /// Same as `verify`, but additionally rejects signatures for epochs that are
+    /// not in `allowed_epochs`, e.g., epochs that have been retired in a deployment
+    /// that expires old epochs. The range is checked first, and epochs in the range
+    /// must be within the lifetime, as for `verify`.
+    #[must_use = "a signature must be rejected if verification fails"]
+    fn verify_in_epoch_range<E: RangeBounds<u32>>(
+        pk: &Self::PublicKey,
+        allowed_epochs: E,
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &Self::Signature,
+    ) -> bool {
+        allowed_epochs.contains(&epoch) && Self::verify(pk, epoch, message, sig)
+    }
+
     /// Function to check internal consistency of any given parameters
     /// For testing only, and expected to panic if something is wrong.
     #[cfg(test)]
     fn internal_consistency_check();
 }
 
+pub mod codec;
+pub mod derivation;
+#[cfg(feature = "sha")]
+pub mod dyn_scheme;
+pub mod embedded_epoch;
+#[cfg(feature = "sha")]
+pub mod evm;
 pub mod generalized_xmss;
+pub mod hash_sig;
+#[cfg(feature = "hybrid")]
+pub mod hybrid;
+#[cfg(feature = "mnemonic")]
+pub mod mnemonic;
+#[cfg(feature = "sha")]
+pub mod param_set;
+#[cfg(feature = "sha")]
+pub mod presets;
+#[cfg(feature = "signature-traits")]
+pub mod signature_traits;
+pub mod sizes;
 
 #[cfg(test)]
 mod test_templates {
-    use rand::thread_rng;
+    use rand::{thread_rng, Rng};
+    use serde::{de::DeserializeOwned, Serialize};
 
     use super::*;
 
@@ -88,4 +162,122 @@ mod test_templates {
             epoch
         );
     }
+
+    /// Generic test for any implementation of the `SignatureScheme` trait whose
+    /// signatures can be serialized. Takes a valid signature for the given public
+    /// key, epoch, and message, and checks that it does not verify after any of
+    /// the following manipulations: flipping any byte of its encoding, changing
+    /// the epoch by one, or changing any byte of the message. Encodings that
+    /// cannot be decoded anymore count as rejected.
+    pub fn _test_signature_scheme_rejects_manipulations<T: SignatureScheme>(
+        pk: &T::PublicKey,
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+        signature: &T::Signature,
+    ) where
+        T::Signature: Serialize + DeserializeOwned,
+    {
+        assert!(
+            T::verify(pk, epoch, message, signature),
+            "Signature to manipulate must be valid"
+        );
+
+        // flip each byte of the encoding of the signature
+        let bytes = bincode::serialize(signature).unwrap();
+        for i in 0..bytes.len() {
+            let mut manipulated = bytes.clone();
+            manipulated[i] ^= 0xff;
+            if let Ok(manipulated) = bincode::deserialize::<T::Signature>(&manipulated) {
+                assert!(
+                    !T::verify(pk, epoch, message, &manipulated),
+                    "Signature with flipped byte {} verifies",
+                    i
+                );
+            }
+        }
+
+        // change the epoch by one, staying within the lifetime
+        for other_epoch in [epoch.checked_sub(1), epoch.checked_add(1)]
+            .into_iter()
+            .flatten()
+            .filter(|&other_epoch| (other_epoch as u64) < T::LIFETIME)
+        {
+            assert!(
+                !T::verify(pk, other_epoch, message, signature),
+                "Signature verifies for epoch {} instead of {}",
+                other_epoch,
+                epoch
+            );
+        }
+
+        // change each byte of the message
+        for i in 0..MESSAGE_LENGTH {
+            let mut other_message = *message;
+            other_message[i] ^= 0x01;
+            assert!(
+                !T::verify(pk, epoch, &other_message, signature),
+                "Signature verifies for message with changed byte {}",
+                i
+            );
+        }
+    }
+
+    /// Generic test for `verify_in_epoch_range`. Signs a random message for
+    /// the given epoch, which must not be the first or last one, and checks that
+    /// the signature is accepted by ranges that contain the epoch, and rejected
+    /// by ranges that do not, i.e., as if the epoch had been retired.
+    pub fn _test_signature_scheme_epoch_range<T: SignatureScheme>(epoch: u32) {
+        let mut rng = thread_rng();
+        let (pk, sk) = T::gen(&mut rng);
+        let message = rng.gen();
+        let signature = T::sign(&mut rng, &sk, epoch, &message).expect("Signing failed");
+
+        // whether the signature verifies for a range of allowed epochs
+        macro_rules! accepts {
+            ($range:expr, $message:expr) => {
+                T::verify_in_epoch_range(&pk, $range, epoch, $message, &signature)
+            };
+        }
+
+        // ranges that contain the epoch
+        assert!(accepts!(.., &message));
+        assert!(accepts!(epoch.., &message));
+        assert!(accepts!(..=epoch, &message));
+        assert!(accepts!(epoch..epoch + 1, &message));
+        assert!(accepts!(epoch - 1..epoch + 1, &message));
+
+        // ranges that do not contain the epoch, in particular all
+        // later epochs, and empty ranges
+        assert!(!accepts!(epoch + 1.., &message));
+        assert!(!accepts!(..epoch, &message));
+        assert!(!accepts!(epoch - 1..epoch, &message));
+        assert!(!accepts!(epoch..epoch, &message));
+
+        // the signature must still be valid
+        let mut other_message = message;
+        other_message[0] ^= 0x01;
+        assert!(!accepts!(.., &other_message));
+    }
+
+    /// Generic test for the convenience functions using the OS randomness.
+    /// Tests correctness as in `_test_signature_scheme_correctness`.
+    #[cfg(feature = "getrandom")]
+    pub fn _test_signature_scheme_correctness_os<T: SignatureScheme>(epoch: u32) {
+        let mut rng = thread_rng();
+
+        // Generate a key pair
+        let (pk, sk) = T::gen_os();
+
+        // Sample random test message
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+
+        // Sign and verify the message
+        let signature = T::sign_os(&sk, epoch, &message).expect("Signing failed");
+        assert!(
+            T::verify(&pk, epoch, &message, &signature),
+            "Signature verification failed. Epoch was {:?}",
+            epoch
+        );
+    }
 }
@@ -0,0 +1,540 @@
+use std::io::{self, Read, Write};
+
+use bincode::Options;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::SignatureScheme;
+
+/// Version of the serialization format. It is the first byte of every
+/// encoded key or signature, and has to be incremented whenever the
+/// encoding of any of them changes.
+pub const FORMAT_VERSION: u8 = 2;
+
+/// Length of the header that precedes every encoded key or signature.
+pub const HEADER_LENGTH: usize = 4;
+
+/// Identifier of the parameter set for instantiations that are not
+/// registered (see `ParamSetId`). No registered parameter set uses it.
+pub const UNREGISTERED_PARAM_SET_ID: u16 = 0x0000;
+
+/// Kinds of objects that can be encoded. The kind is
+/// the second byte of every encoded key or signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    PublicKey = 0x01,
+    SecretKey = 0x02,
+    Signature = 0x03,
+    /// A signature without the fields that are determined by the
+    /// instantiation, see `GeneralizedXMSSSignatureScheme::encode_signature_minimal`
+    MinimalSignature = 0x04,
+}
+
+/// Error enum for decoding
+#[derive(Debug, PartialEq, Eq)]
+pub enum CodecError {
+    /// The input is too short to contain a header
+    MissingHeader,
+    /// The header has a version that is not supported
+    UnsupportedVersion(u8),
+    /// The header announces a different kind of object
+    WrongKind,
+    /// The header announces a different parameter set
+    WrongParamSet(u16),
+    /// The body is not a valid encoding
+    InvalidEncoding,
+}
+
+/// Error enum for reading from an `io::Read`
+#[derive(Debug)]
+pub enum ReadError {
+    /// Reading failed, e.g., because the stream ended too early
+    Io(io::Error),
+    /// The bytes that were read are not a valid encoding
+    Codec(CodecError),
+}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        ReadError::Io(error)
+    }
+}
+
+impl From<CodecError> for ReadError {
+    fn from(error: CodecError) -> Self {
+        ReadError::Codec(error)
+    }
+}
+
+/// Options for bincode. Integers use a fixed-size little-endian encoding,
+/// and trailing bytes are rejected, so that encodings are unique.
+fn bincode_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .reject_trailing_bytes()
+}
+
+/// Encodes a value of the given kind and parameter set, i.e., a header
+/// followed by the value. The header consists of the format version, the
+/// kind, and the identifier of the parameter set in little-endian.
+pub fn encode<T: Serialize>(kind: ArtifactKind, param_set_id: u16, value: &T) -> Vec<u8> {
+    let mut bytes = header(kind, param_set_id);
+    write_value(&mut bytes, value);
+    bytes
+}
+
+/// Returns the header for the given kind and parameter set
+pub(crate) fn header(kind: ArtifactKind, param_set_id: u16) -> Vec<u8> {
+    let mut bytes = vec![FORMAT_VERSION, kind as u8];
+    bytes.extend(param_set_id.to_le_bytes());
+    bytes
+}
+
+/// Appends the encoding of a value to `bytes`, without a header
+pub(crate) fn write_value<T: Serialize>(bytes: &mut Vec<u8>, value: &T) {
+    bincode_options()
+        .serialize_into(bytes, value)
+        .expect("Serializing into a vector cannot fail");
+}
+
+/// Decodes a value from the beginning of `bytes`, and advances `bytes`
+/// past it. In contrast to `decode`, bytes may remain afterwards.
+pub(crate) fn read_value<T: DeserializeOwned>(bytes: &mut &[u8]) -> Result<T, CodecError> {
+    bincode_options()
+        .allow_trailing_bytes()
+        .deserialize_from(bytes)
+        .map_err(|_| CodecError::InvalidEncoding)
+}
+
+/// Returns the identifier of the parameter set announced in the header
+/// of an encoded key or signature, without decoding the body.
+pub fn param_set_id(bytes: &[u8]) -> Result<u16, CodecError> {
+    if bytes.len() < HEADER_LENGTH {
+        return Err(CodecError::MissingHeader);
+    }
+    if bytes[0] != FORMAT_VERSION {
+        return Err(CodecError::UnsupportedVersion(bytes[0]));
+    }
+    Ok(u16::from_le_bytes([bytes[2], bytes[3]]))
+}
+
+/// Decodes a value of the given kind and parameter set, checking the header.
+pub fn decode<T: DeserializeOwned>(
+    kind: ArtifactKind,
+    param_set_id: u16,
+    bytes: &[u8],
+) -> Result<T, CodecError> {
+    bincode_options()
+        .deserialize(body(kind, param_set_id, bytes)?)
+        .map_err(|_| CodecError::InvalidEncoding)
+}
+
+/// Checks the header for the given kind and parameter set,
+/// and returns the bytes that follow it.
+pub(crate) fn body(
+    kind: ArtifactKind,
+    param_set_id: u16,
+    bytes: &[u8],
+) -> Result<&[u8], CodecError> {
+    let found_param_set_id = self::param_set_id(bytes)?;
+    if bytes[1] != kind as u8 {
+        return Err(CodecError::WrongKind);
+    }
+    if found_param_set_id != param_set_id {
+        return Err(CodecError::WrongParamSet(found_param_set_id));
+    }
+    Ok(&bytes[HEADER_LENGTH..])
+}
+
+/// Writes the encoding of a value of the given kind and parameter set, see
+/// `encode`, to `writer`. Nothing is buffered, so callers writing many small
+/// values may want to wrap `writer` in an `io::BufWriter`.
+pub fn write_to<T: Serialize, W: Write>(
+    kind: ArtifactKind,
+    param_set_id: u16,
+    value: &T,
+    mut writer: W,
+) -> io::Result<()> {
+    writer.write_all(&header(kind, param_set_id))?;
+    bincode_options()
+        .serialize_into(writer, value)
+        .map_err(|error| match *error {
+            bincode::ErrorKind::Io(error) => error,
+            error => panic!("Serializing a value cannot fail: {}", error),
+        })
+}
+
+/// Reads a value of the given kind and parameter set from `reader`, checking
+/// the header, see `decode`. Exactly the bytes of the encoding are consumed,
+/// so several encodings can be read from the same stream one after another.
+/// If the stream ends before the encoding does, an `io::Error` of kind
+/// `UnexpectedEof` is returned.
+pub fn read_from<T: DeserializeOwned, R: Read>(
+    kind: ArtifactKind,
+    param_set_id: u16,
+    mut reader: R,
+) -> Result<T, ReadError> {
+    let mut header = [0u8; HEADER_LENGTH];
+    reader.read_exact(&mut header)?;
+    body(kind, param_set_id, &header)?;
+    bincode_options()
+        .allow_trailing_bytes()
+        .deserialize_from(reader)
+        .map_err(|error| match *error {
+            bincode::ErrorKind::Io(error) => ReadError::Io(error),
+            _ => ReadError::Codec(CodecError::InvalidEncoding),
+        })
+}
+
+/// Encodes a public key of the given scheme.
+pub fn encode_public_key<S: SignatureScheme>(param_set_id: u16, pk: &S::PublicKey) -> Vec<u8>
+where
+    S::PublicKey: Serialize,
+{
+    encode(ArtifactKind::PublicKey, param_set_id, pk)
+}
+
+/// Decodes a public key of the given scheme.
+pub fn decode_public_key<S: SignatureScheme>(
+    param_set_id: u16,
+    bytes: &[u8],
+) -> Result<S::PublicKey, CodecError>
+where
+    S::PublicKey: DeserializeOwned,
+{
+    decode(ArtifactKind::PublicKey, param_set_id, bytes)
+}
+
+/// Encodes a secret key of the given scheme.
+pub fn encode_secret_key<S: SignatureScheme>(param_set_id: u16, sk: &S::SecretKey) -> Vec<u8>
+where
+    S::SecretKey: Serialize,
+{
+    encode(ArtifactKind::SecretKey, param_set_id, sk)
+}
+
+/// Decodes a secret key of the given scheme.
+pub fn decode_secret_key<S: SignatureScheme>(
+    param_set_id: u16,
+    bytes: &[u8],
+) -> Result<S::SecretKey, CodecError>
+where
+    S::SecretKey: DeserializeOwned,
+{
+    decode(ArtifactKind::SecretKey, param_set_id, bytes)
+}
+
+/// Encodes a signature of the given scheme.
+pub fn encode_signature<S: SignatureScheme>(param_set_id: u16, sig: &S::Signature) -> Vec<u8>
+where
+    S::Signature: Serialize,
+{
+    encode(ArtifactKind::Signature, param_set_id, sig)
+}
+
+/// Decodes a signature of the given scheme.
+pub fn decode_signature<S: SignatureScheme>(
+    param_set_id: u16,
+    bytes: &[u8],
+) -> Result<S::Signature, CodecError>
+where
+    S::Signature: DeserializeOwned,
+{
+    decode(ArtifactKind::Signature, param_set_id, bytes)
+}
+
+/// Writes a public key of the given scheme to `writer`.
+pub fn write_public_key_to<S: SignatureScheme, W: Write>(
+    param_set_id: u16,
+    pk: &S::PublicKey,
+    writer: W,
+) -> io::Result<()>
+where
+    S::PublicKey: Serialize,
+{
+    write_to(ArtifactKind::PublicKey, param_set_id, pk, writer)
+}
+
+/// Reads a public key of the given scheme from `reader`.
+pub fn read_public_key_from<S: SignatureScheme, R: Read>(
+    param_set_id: u16,
+    reader: R,
+) -> Result<S::PublicKey, ReadError>
+where
+    S::PublicKey: DeserializeOwned,
+{
+    read_from(ArtifactKind::PublicKey, param_set_id, reader)
+}
+
+/// Writes a secret key of the given scheme to `writer`.
+pub fn write_secret_key_to<S: SignatureScheme, W: Write>(
+    param_set_id: u16,
+    sk: &S::SecretKey,
+    writer: W,
+) -> io::Result<()>
+where
+    S::SecretKey: Serialize,
+{
+    write_to(ArtifactKind::SecretKey, param_set_id, sk, writer)
+}
+
+/// Reads a secret key of the given scheme from `reader`.
+pub fn read_secret_key_from<S: SignatureScheme, R: Read>(
+    param_set_id: u16,
+    reader: R,
+) -> Result<S::SecretKey, ReadError>
+where
+    S::SecretKey: DeserializeOwned,
+{
+    read_from(ArtifactKind::SecretKey, param_set_id, reader)
+}
+
+/// Writes a signature of the given scheme to `writer`.
+pub fn write_signature_to<S: SignatureScheme, W: Write>(
+    param_set_id: u16,
+    sig: &S::Signature,
+    writer: W,
+) -> io::Result<()>
+where
+    S::Signature: Serialize,
+{
+    write_to(ArtifactKind::Signature, param_set_id, sig, writer)
+}
+
+/// Reads a signature of the given scheme from `reader`.
+pub fn read_signature_from<S: SignatureScheme, R: Read>(
+    param_set_id: u16,
+    reader: R,
+) -> Result<S::Signature, ReadError>
+where
+    S::Signature: DeserializeOwned,
+{
+    read_from(ArtifactKind::Signature, param_set_id, reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        inc_encoding::basic_winternitz::WinternitzEncoding,
+        signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+        symmetric::{
+            message_hash::sha::ShaMessageHash192x3, prf::sha::ShaPRF,
+            tweak_hash::sha::ShaTweak192192,
+        },
+        MESSAGE_LENGTH,
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type Sig = GeneralizedXMSSSignatureScheme<
+        ShaPRF<24>,
+        WinternitzEncoding<ShaMessageHash192x3, 3>,
+        ShaTweak192192,
+        6,
+    >;
+    const ID: u16 = UNREGISTERED_PARAM_SET_ID;
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Sig::gen(&mut rng);
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+        let epoch = 11;
+        let sig = Sig::sign(&mut rng, &sk, epoch, &message).unwrap();
+
+        // decoded keys and signatures must be usable as the original ones
+        let pk_decoded = decode_public_key::<Sig>(ID, &encode_public_key::<Sig>(ID, &pk)).unwrap();
+        let sk_decoded = decode_secret_key::<Sig>(ID, &encode_secret_key::<Sig>(ID, &sk)).unwrap();
+        let sig_decoded = decode_signature::<Sig>(ID, &encode_signature::<Sig>(ID, &sig)).unwrap();
+        assert!(pk_decoded == pk);
+        assert!(sig_decoded == sig);
+        assert!(Sig::verify(&pk_decoded, epoch, &message, &sig_decoded));
+        let sig_from_decoded = Sig::sign(&mut rng, &sk_decoded, epoch, &message).unwrap();
+        assert!(Sig::verify(&pk, epoch, &message, &sig_from_decoded));
+    }
+
+    #[test]
+    fn test_header() {
+        let mut rng = thread_rng();
+        let (pk, _) = Sig::gen(&mut rng);
+        let bytes = encode_public_key::<Sig>(ID, &pk);
+        assert_eq!(bytes[0], FORMAT_VERSION);
+        assert_eq!(bytes[1], ArtifactKind::PublicKey as u8);
+        assert_eq!(param_set_id(&bytes), Ok(ID));
+
+        // too short
+        assert_eq!(
+            decode_public_key::<Sig>(ID, &bytes[..HEADER_LENGTH - 1]).err(),
+            Some(CodecError::MissingHeader)
+        );
+
+        // wrong version
+        let mut modified = bytes.clone();
+        modified[0] = FORMAT_VERSION + 1;
+        assert_eq!(
+            decode_public_key::<Sig>(ID, &modified).err(),
+            Some(CodecError::UnsupportedVersion(FORMAT_VERSION + 1))
+        );
+
+        // wrong kind
+        assert_eq!(
+            decode_signature::<Sig>(ID, &bytes).err(),
+            Some(CodecError::WrongKind)
+        );
+
+        // wrong parameter set
+        let other_bytes = encode_public_key::<Sig>(0x0102, &pk);
+        assert_eq!(param_set_id(&other_bytes), Ok(0x0102));
+        assert_eq!(&other_bytes[2..HEADER_LENGTH], &[0x02, 0x01]);
+        assert_eq!(
+            decode_public_key::<Sig>(ID, &other_bytes).err(),
+            Some(CodecError::WrongParamSet(0x0102))
+        );
+
+        // trailing or missing bytes
+        let mut modified = bytes.clone();
+        modified.push(0);
+        assert_eq!(
+            decode_public_key::<Sig>(ID, &modified).err(),
+            Some(CodecError::InvalidEncoding)
+        );
+        assert_eq!(
+            decode_public_key::<Sig>(ID, &bytes[..bytes.len() - 1]).err(),
+            Some(CodecError::InvalidEncoding)
+        );
+    }
+
+    /// A reader that returns at most one byte per call
+    struct OneByteReader<'a>(&'a [u8]);
+
+    impl Read for OneByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = (&self.0[..self.0.len().min(1)]).read(buf)?;
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    fn is_unexpected_eof<T>(result: Result<T, ReadError>) -> bool {
+        matches!(result, Err(ReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof)
+    }
+
+    #[test]
+    fn test_read_write() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Sig::gen(&mut rng);
+        let message = rng.gen();
+        let epoch = 11;
+        let sig = Sig::sign(&mut rng, &sk, epoch, &message).unwrap();
+
+        // a stream with several encodings, which are the same as those of `encode`
+        let mut stream = Cursor::new(Vec::new());
+        write_public_key_to::<Sig, _>(ID, &pk, &mut stream).unwrap();
+        write_secret_key_to::<Sig, _>(ID, &sk, &mut stream).unwrap();
+        write_signature_to::<Sig, _>(ID, &sig, &mut stream).unwrap();
+        let bytes = stream.into_inner();
+        let encodings = [
+            encode_public_key::<Sig>(ID, &pk),
+            encode_secret_key::<Sig>(ID, &sk),
+            encode_signature::<Sig>(ID, &sig),
+        ];
+        assert_eq!(bytes, encodings.concat());
+
+        // reading consumes exactly one encoding at a time
+        let mut stream = Cursor::new(&bytes);
+        let pk_read = read_public_key_from::<Sig, _>(ID, &mut stream).unwrap();
+        let sk_read = read_secret_key_from::<Sig, _>(ID, &mut stream).unwrap();
+        let sig_read = read_signature_from::<Sig, _>(ID, &mut stream).unwrap();
+        assert_eq!(stream.position() as usize, bytes.len());
+        assert!(pk_read == pk);
+        assert!(sig_read == sig);
+        let sig_from_read = Sig::sign(&mut rng, &sk_read, epoch, &message).unwrap();
+        assert!(Sig::verify(&pk, epoch, &message, &sig_from_read));
+
+        // partial reads
+        let mut reader = OneByteReader(&bytes);
+        assert!(read_public_key_from::<Sig, _>(ID, &mut reader).unwrap() == pk);
+        read_secret_key_from::<Sig, _>(ID, &mut reader).unwrap();
+        assert!(read_signature_from::<Sig, _>(ID, &mut reader).unwrap() == sig);
+
+        // truncated streams, within the header or the body
+        let [pk_bytes, sk_bytes, sig_bytes] = &encodings;
+        for length in [0, HEADER_LENGTH - 1, HEADER_LENGTH] {
+            assert!(is_unexpected_eof(read_public_key_from::<Sig, _>(
+                ID,
+                &pk_bytes[..length]
+            )));
+        }
+        let truncated = &pk_bytes[..pk_bytes.len() - 1];
+        assert!(is_unexpected_eof(read_public_key_from::<Sig, _>(
+            ID, truncated
+        )));
+        let truncated = &sk_bytes[..sk_bytes.len() - 1];
+        assert!(is_unexpected_eof(read_secret_key_from::<Sig, _>(
+            ID, truncated
+        )));
+        let truncated = &sig_bytes[..sig_bytes.len() - 1];
+        assert!(is_unexpected_eof(read_signature_from::<Sig, _>(
+            ID, truncated
+        )));
+
+        // invalid headers are reported as such
+        assert!(matches!(
+            read_signature_from::<Sig, _>(ID, &encodings[0][..]),
+            Err(ReadError::Codec(CodecError::WrongKind))
+        ));
+        assert!(matches!(
+            read_public_key_from::<Sig, _>(0x0102, &encodings[0][..]),
+            Err(ReadError::Codec(CodecError::WrongParamSet(ID)))
+        ));
+
+        // errors of the writer are passed on
+        let mut buffer = [0u8; 8];
+        let error = write_public_key_to::<Sig, _>(ID, &pk, &mut buffer[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn test_mutated_encodings_do_not_panic() {
+        // a lightweight version of the fuzz targets in `fuzz/`: randomly
+        // mutated encodings either fail to decode, or can be used without
+        // panics, i.e., signatures can be verified and secret keys used
+        let mut rng = thread_rng();
+        let (pk, sk) = Sig::gen(&mut rng);
+        let message = rng.gen();
+        let epoch = 11;
+        let sig = Sig::sign(&mut rng, &sk, epoch, &message).unwrap();
+        let encodings = [
+            encode_public_key::<Sig>(ID, &pk),
+            encode_secret_key::<Sig>(ID, &sk),
+            encode_signature::<Sig>(ID, &sig),
+        ];
+
+        for round in 0..3000 {
+            let mut bytes = encodings[round % 3].clone();
+            for _ in 0..rng.gen_range(1..4) {
+                let position = rng.gen_range(0..bytes.len());
+                match rng.gen_range(0..3) {
+                    0 => bytes[position] = rng.gen(),
+                    1 => bytes.truncate(position.max(1)),
+                    _ => bytes.insert(position, rng.gen()),
+                }
+            }
+            if let Ok(pk) = decode_public_key::<Sig>(ID, &bytes) {
+                let _ = Sig::verify(&pk, epoch, &message, &sig);
+            }
+            if let Ok(sk) = decode_secret_key::<Sig>(ID, &bytes) {
+                let _ = Sig::sign(&mut rng, &sk, epoch, &message);
+            }
+            if let Ok(sig) = decode_signature::<Sig>(ID, &bytes) {
+                let _ = Sig::verify(&pk, epoch, &message, &sig);
+            }
+        }
+    }
+}
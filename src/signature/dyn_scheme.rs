@@ -0,0 +1,415 @@
+use std::marker::PhantomData;
+
+use rand_core::CryptoRngCore;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{symmetric::seed_expander::SEED_LENGTH, MESSAGE_LENGTH};
+
+use super::{
+    codec::{
+        decode_public_key, decode_secret_key, decode_signature, encode_public_key,
+        encode_secret_key, encode_signature, param_set_id, CodecError,
+    },
+    param_set::ParamSetId,
+    SignatureScheme, SigningError,
+};
+
+/// Error enum for the runtime-parameterized scheme
+#[derive(Debug)]
+pub enum DynSchemeError {
+    /// There is no registered parameter set with this identifier
+    UnknownParamSet,
+    /// The epoch is not within the lifetime of the scheme
+    InvalidEpoch,
+    /// A key or signature could not be decoded
+    Codec(CodecError),
+    /// Signing failed
+    Signing(SigningError),
+}
+
+impl From<CodecError> for DynSchemeError {
+    fn from(error: CodecError) -> Self {
+        DynSchemeError::Codec(error)
+    }
+}
+
+/// Object-safe version of `SignatureScheme`, in which keys and
+/// signatures are given in their encoded form (see `codec`).
+pub(crate) trait ErasedScheme: Send + Sync {
+    fn lifetime(&self) -> u64;
+
+    fn key_gen(&self, rng: &mut dyn CryptoRngCore) -> (Vec<u8>, Vec<u8>);
+
+    fn key_gen_from_seed(&self, seed: [u8; SEED_LENGTH]) -> (Vec<u8>, Vec<u8>);
+
+    fn sign(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        sk: &[u8],
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<Vec<u8>, DynSchemeError>;
+
+    fn verify(
+        &self,
+        pk: &[u8],
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &[u8],
+    ) -> Result<bool, DynSchemeError>;
+}
+
+/// Implementation of `ErasedScheme` for any signature scheme whose keys and
+/// signatures can be serialized, by encoding and decoding them on the fly.
+/// Encodings are tagged with the given parameter set identifier.
+pub(crate) struct ErasedSchemeImpl<S: SignatureScheme> {
+    param_set_id: u16,
+    _marker: PhantomData<fn() -> S>,
+}
+
+impl<S: SignatureScheme> ErasedSchemeImpl<S> {
+    pub(crate) fn new(param_set_id: u16) -> Self {
+        Self {
+            param_set_id,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<S: SignatureScheme> ErasedScheme for ErasedSchemeImpl<S>
+where
+    S::PublicKey: Serialize + DeserializeOwned,
+    S::SecretKey: Serialize + DeserializeOwned,
+    S::Signature: Serialize + DeserializeOwned,
+{
+    fn lifetime(&self) -> u64 {
+        S::LIFETIME
+    }
+
+    fn key_gen(&self, mut rng: &mut dyn CryptoRngCore) -> (Vec<u8>, Vec<u8>) {
+        let (pk, sk) = S::gen(&mut rng);
+        (
+            encode_public_key::<S>(self.param_set_id, &pk),
+            encode_secret_key::<S>(self.param_set_id, &sk),
+        )
+    }
+
+    fn key_gen_from_seed(&self, seed: [u8; SEED_LENGTH]) -> (Vec<u8>, Vec<u8>) {
+        let (pk, sk) = S::key_gen_from_seed(seed);
+        (
+            encode_public_key::<S>(self.param_set_id, &pk),
+            encode_secret_key::<S>(self.param_set_id, &sk),
+        )
+    }
+
+    fn sign(
+        &self,
+        mut rng: &mut dyn CryptoRngCore,
+        sk: &[u8],
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<Vec<u8>, DynSchemeError> {
+        if epoch as u64 >= S::LIFETIME {
+            return Err(DynSchemeError::InvalidEpoch);
+        }
+        let sk = decode_secret_key::<S>(self.param_set_id, sk)?;
+        let sig = S::sign(&mut rng, &sk, epoch, message).map_err(DynSchemeError::Signing)?;
+        Ok(encode_signature::<S>(self.param_set_id, &sig))
+    }
+
+    fn verify(
+        &self,
+        pk: &[u8],
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &[u8],
+    ) -> Result<bool, DynSchemeError> {
+        if epoch as u64 >= S::LIFETIME {
+            return Err(DynSchemeError::InvalidEpoch);
+        }
+        let pk = decode_public_key::<S>(self.param_set_id, pk)?;
+        let sig = decode_signature::<S>(self.param_set_id, sig)?;
+        Ok(S::verify(&pk, epoch, message, &sig))
+    }
+}
+
+/// Handle to a signature scheme that is selected at runtime, e.g., from a
+/// config file or across an FFI boundary. Keys and signatures are passed as
+/// bytes, in the encoding of `codec`, and calls are dispatched to the
+/// const-generic instantiation of the given parameter set.
+///
+/// Keys and signatures are compatible with the static API: for example, a
+/// signature produced by `SignatureScheme::sign` and encoded with `codec`,
+/// using the identifier of the parameter set, verifies with the `DynScheme`
+/// of the same parameter set.
+pub struct DynScheme {
+    param_set: ParamSetId,
+    inner: Box<dyn ErasedScheme>,
+}
+
+impl DynScheme {
+    /// Creates a handle for the given parameter set.
+    pub fn new(param_set: ParamSetId) -> Self {
+        Self {
+            param_set,
+            inner: param_set.erased_scheme(),
+        }
+    }
+
+    /// Creates a handle for the parameter set with the given numeric identifier.
+    pub fn from_id(id: u16) -> Result<Self, DynSchemeError> {
+        ParamSetId::from_id(id)
+            .map(Self::new)
+            .ok_or(DynSchemeError::UnknownParamSet)
+    }
+
+    /// Creates a handle for the parameter set that an encoded key or
+    /// signature announces in its header.
+    pub fn from_encoded(bytes: &[u8]) -> Result<Self, DynSchemeError> {
+        Self::from_id(param_set_id(bytes)?)
+    }
+
+    /// Creates a handle for the parameter set with the given name.
+    pub fn from_name(name: &str) -> Result<Self, DynSchemeError> {
+        ParamSetId::from_name(name)
+            .map(Self::new)
+            .ok_or(DynSchemeError::UnknownParamSet)
+    }
+
+    /// Returns the parameter set of this handle.
+    pub fn param_set(&self) -> ParamSetId {
+        self.param_set
+    }
+
+    /// Returns the number of epochs that are supported with one key.
+    pub fn lifetime(&self) -> u64 {
+        self.inner.lifetime()
+    }
+
+    /// Generates a new key pair, returning the encoded public and secret keys.
+    /// The given RNG must be cryptographically secure.
+    pub fn key_gen(&self, rng: &mut dyn CryptoRngCore) -> (Vec<u8>, Vec<u8>) {
+        self.inner.key_gen(rng)
+    }
+
+    /// Deterministically generates a key pair from a seed, returning the
+    /// encoded public and secret keys. See `SignatureScheme::key_gen_from_seed`.
+    pub fn key_gen_from_seed(&self, seed: [u8; SEED_LENGTH]) -> (Vec<u8>, Vec<u8>) {
+        self.inner.key_gen_from_seed(seed)
+    }
+
+    /// Signs a message with an encoded secret key, and returns the encoded
+    /// signature. The given RNG must be cryptographically secure.
+    pub fn sign(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        sk: &[u8],
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<Vec<u8>, DynSchemeError> {
+        self.inner.sign(rng, sk, epoch, message)
+    }
+
+    /// Verifies an encoded signature with respect to an encoded public key,
+    /// an epoch, and a message. Returns an error if the inputs cannot be decoded.
+    #[must_use = "a signature must be rejected if verification fails"]
+    pub fn verify(
+        &self,
+        pk: &[u8],
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &[u8],
+    ) -> Result<bool, DynSchemeError> {
+        self.inner.verify(pk, epoch, message, sig)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        inc_encoding::basic_winternitz::WinternitzEncoding,
+        signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+        symmetric::{
+            message_hash::sha::ShaMessageHash192x3, prf::sha::ShaPRF,
+            tweak_hash::sha::ShaTweak192192,
+        },
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type Sig = GeneralizedXMSSSignatureScheme<
+        ShaPRF<24>,
+        WinternitzEncoding<ShaMessageHash192x3, 3>,
+        ShaTweak192192,
+        6,
+    >;
+    const ID: u16 = 0x0102;
+
+    /// Checks that signatures from the static API verify through the erased
+    /// scheme and vice versa, for keys generated through the erased scheme.
+    fn _test_static_dynamic_round_trip<S: SignatureScheme>(
+        scheme: &dyn ErasedScheme,
+        id: u16,
+        epoch: u32,
+    ) where
+        S::PublicKey: Serialize + DeserializeOwned,
+        S::SecretKey: Serialize + DeserializeOwned,
+        S::Signature: Serialize + DeserializeOwned,
+    {
+        let mut rng = thread_rng();
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+
+        // keys from the dynamic API
+        let (pk_bytes, sk_bytes) = scheme.key_gen(&mut rng);
+        let pk = decode_public_key::<S>(id, &pk_bytes).unwrap();
+        let sk = decode_secret_key::<S>(id, &sk_bytes).unwrap();
+
+        // static sign, dynamic verify
+        let sig = S::sign(&mut rng, &sk, epoch, &message).unwrap();
+        let sig_bytes = encode_signature::<S>(id, &sig);
+        assert!(scheme
+            .verify(&pk_bytes, epoch, &message, &sig_bytes)
+            .unwrap());
+
+        // dynamic sign, static verify
+        let sig_bytes = scheme.sign(&mut rng, &sk_bytes, epoch, &message).unwrap();
+        let sig = decode_signature::<S>(id, &sig_bytes).unwrap();
+        assert!(S::verify(&pk, epoch, &message, &sig));
+
+        // a different message does not verify
+        message[0] ^= 0x01;
+        assert!(!scheme
+            .verify(&pk_bytes, epoch, &message, &sig_bytes)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_erased_round_trip() {
+        let scheme = ErasedSchemeImpl::<Sig>::new(ID);
+        assert_eq!(scheme.lifetime(), Sig::LIFETIME);
+        _test_static_dynamic_round_trip::<Sig>(&scheme, ID, 0);
+        _test_static_dynamic_round_trip::<Sig>(&scheme, ID, 17);
+    }
+
+    #[test]
+    fn test_erased_rejects_invalid_inputs() {
+        let mut rng = thread_rng();
+        let scheme = ErasedSchemeImpl::<Sig>::new(ID);
+        let message = [0u8; MESSAGE_LENGTH];
+
+        let (pk, sk) = scheme.key_gen(&mut rng);
+        let sig = scheme.sign(&mut rng, &sk, 3, &message).unwrap();
+
+        // epochs beyond the lifetime
+        let epoch = Sig::LIFETIME as u32;
+        assert!(matches!(
+            scheme.sign(&mut rng, &sk, epoch, &message),
+            Err(DynSchemeError::InvalidEpoch)
+        ));
+        assert!(matches!(
+            scheme.verify(&pk, epoch, &message, &sig),
+            Err(DynSchemeError::InvalidEpoch)
+        ));
+
+        // keys and signatures mixed up
+        assert!(matches!(
+            scheme.verify(&sig, 3, &message, &pk),
+            Err(DynSchemeError::Codec(CodecError::WrongKind))
+        ));
+        assert!(matches!(
+            scheme.sign(&mut rng, &pk, 3, &message),
+            Err(DynSchemeError::Codec(CodecError::WrongKind))
+        ));
+
+        // keys and signatures of another parameter set
+        let other_scheme = ErasedSchemeImpl::<Sig>::new(ID + 1);
+        let (other_pk, _) = other_scheme.key_gen(&mut rng);
+        assert!(matches!(
+            scheme.verify(&other_pk, 3, &message, &sig),
+            Err(DynSchemeError::Codec(CodecError::WrongParamSet(id))) if id == ID + 1
+        ));
+
+        // truncated signature
+        assert!(matches!(
+            scheme.verify(&pk, 3, &message, &sig[..sig.len() - 1]),
+            Err(DynSchemeError::Codec(CodecError::InvalidEncoding))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_param_set() {
+        assert!(matches!(
+            DynScheme::from_name("NotAParameterSet"),
+            Err(DynSchemeError::UnknownParamSet)
+        ));
+
+        let name = ParamSetId::ShaWinternitzLifetime18W4.name();
+        let scheme = DynScheme::from_name(name).unwrap();
+        assert_eq!(scheme.param_set(), ParamSetId::ShaWinternitzLifetime18W4);
+        assert_eq!(scheme.lifetime(), 1 << 18);
+
+        assert!(matches!(
+            DynScheme::from_id(0xFFFF),
+            Err(DynSchemeError::UnknownParamSet)
+        ));
+        let id = ParamSetId::ShaWinternitzLifetime20W2.as_id();
+        let scheme = DynScheme::from_id(id).unwrap();
+        assert_eq!(scheme.param_set(), ParamSetId::ShaWinternitzLifetime20W2);
+        assert_eq!(scheme.lifetime(), 1 << 20);
+    }
+
+    #[test]
+    fn test_from_encoded() {
+        // the parameter set is taken from the header of the encoding
+        let id = ParamSetId::ShaTargetSumLifetime18W8Off10.as_id();
+        let bytes = encode_public_key::<Sig>(id, &Sig::gen(&mut thread_rng()).0);
+        let scheme = DynScheme::from_encoded(&bytes).unwrap();
+        assert_eq!(
+            scheme.param_set(),
+            ParamSetId::ShaTargetSumLifetime18W8Off10
+        );
+
+        assert!(matches!(
+            DynScheme::from_encoded(&encode_public_key::<Sig>(
+                ID,
+                &Sig::gen(&mut thread_rng()).0
+            )),
+            Err(DynSchemeError::UnknownParamSet)
+        ));
+        assert!(matches!(
+            DynScheme::from_encoded(&bytes[..1]),
+            Err(DynSchemeError::Codec(CodecError::MissingHeader))
+        ));
+    }
+
+    #[cfg(feature = "slow-tests")]
+    #[test]
+    fn test_registered_param_sets_round_trip() {
+        use crate::signature::generalized_xmss::instantiations_sha::{
+            lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W4,
+            lifetime_2_to_the_20::target_sum::SIGTargetSumLifetime20W2Off10,
+        };
+
+        // generating keys for registered parameter sets is expensive,
+        // so we test a representative subset here
+        let param_set = ParamSetId::ShaWinternitzLifetime18W4;
+        let scheme = DynScheme::new(param_set);
+        _test_static_dynamic_round_trip::<SIGWinternitzLifetime18W4>(
+            &*scheme.inner,
+            param_set.as_id(),
+            1234,
+        );
+
+        let param_set = ParamSetId::ShaTargetSumLifetime20W2Off10;
+        let scheme = DynScheme::new(param_set);
+        _test_static_dynamic_round_trip::<SIGTargetSumLifetime20W2Off10>(
+            &*scheme.inner,
+            param_set.as_id(),
+            9,
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::MESSAGE_LENGTH;
+
+use super::{SignatureScheme, SigningError};
+
+/// Signature that carries the epoch it has been produced for. The epoch
+/// is public anyways, and including it allows the verifier to verify
+/// without knowing the epoch out-of-band.
+///
+/// Note: the embedded epoch does not need to be authenticated separately.
+/// The signature is bound to its epoch, as the Merkle path opens the leaf at
+/// that epoch and all hashes are tweaked with it. Changing the embedded epoch
+/// therefore makes verification fail.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct SignatureWithEpoch<Sig> {
+    pub epoch: u32,
+    pub signature: Sig,
+}
+
+/// Determines how the verifier obtains the epoch of a `SignatureWithEpoch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpochMode {
+    /// Use the epoch embedded in the signature
+    TrustEmbedded,
+    /// Use the given epoch, and reject signatures embedding another one
+    Explicit(u32),
+}
+
+/// Signs a message with respect to an epoch, and embeds the epoch into the signature.
+/// The given RNG must be cryptographically secure.
+pub fn sign_with_epoch<S: SignatureScheme, R: RngCore + CryptoRng>(
+    rng: &mut R,
+    sk: &S::SecretKey,
+    epoch: u32,
+    message: &[u8; MESSAGE_LENGTH],
+) -> Result<SignatureWithEpoch<S::Signature>, SigningError> {
+    let signature = S::sign(rng, sk, epoch, message)?;
+    Ok(SignatureWithEpoch { epoch, signature })
+}
+
+/// Verifies a signature with embedded epoch with respect to public key and message.
+/// The epoch is determined by `mode`. Signatures with an epoch beyond the lifetime
+/// of the scheme are rejected.
+#[must_use = "a signature must be rejected if verification fails"]
+pub fn verify_with_epoch<S: SignatureScheme>(
+    pk: &S::PublicKey,
+    message: &[u8; MESSAGE_LENGTH],
+    sig: &SignatureWithEpoch<S::Signature>,
+    mode: EpochMode,
+) -> bool {
+    if let EpochMode::Explicit(epoch) = mode {
+        if epoch != sig.epoch {
+            return false;
+        }
+    }
+
+    // the embedded epoch may come from an attacker, so we cannot
+    // rely on verify to handle epochs that are out of range
+    if sig.epoch as u64 >= S::LIFETIME {
+        return false;
+    }
+
+    S::verify(pk, sig.epoch, message, &sig.signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        inc_encoding::basic_winternitz::WinternitzEncoding,
+        signature::{
+            codec::{decode, encode, ArtifactKind, UNREGISTERED_PARAM_SET_ID},
+            generalized_xmss::GeneralizedXMSSSignatureScheme,
+        },
+        symmetric::{
+            message_hash::sha::ShaMessageHash192x3, prf::sha::ShaPRF,
+            tweak_hash::sha::ShaTweak192192,
+        },
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type Sig = GeneralizedXMSSSignatureScheme<
+        ShaPRF<24>,
+        WinternitzEncoding<ShaMessageHash192x3, 3>,
+        ShaTweak192192,
+        6,
+    >;
+
+    #[test]
+    fn test_trust_embedded() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Sig::gen(&mut rng);
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+        let epoch = 23;
+
+        let sig = sign_with_epoch::<Sig, _>(&mut rng, &sk, epoch, &message).unwrap();
+        assert_eq!(sig.epoch, epoch);
+        assert!(verify_with_epoch::<Sig>(
+            &pk,
+            &message,
+            &sig,
+            EpochMode::TrustEmbedded
+        ));
+
+        // the epoch survives serialization
+        let bytes = encode(ArtifactKind::Signature, UNREGISTERED_PARAM_SET_ID, &sig);
+        let decoded: SignatureWithEpoch<_> =
+            decode(ArtifactKind::Signature, UNREGISTERED_PARAM_SET_ID, &bytes).unwrap();
+        assert!(decoded == sig);
+        assert!(verify_with_epoch::<Sig>(
+            &pk,
+            &message,
+            &decoded,
+            EpochMode::TrustEmbedded
+        ));
+
+        // an attacker changing the embedded epoch, including to epochs
+        // beyond the lifetime, must not make the signature verify
+        for other_epoch in [0, epoch - 1, epoch + 1, Sig::LIFETIME as u32, u32::MAX] {
+            let mut modified = sig.clone();
+            modified.epoch = other_epoch;
+            assert!(!verify_with_epoch::<Sig>(
+                &pk,
+                &message,
+                &modified,
+                EpochMode::TrustEmbedded
+            ));
+        }
+
+        // and a different message does not verify either
+        message[0] ^= 0x01;
+        assert!(!verify_with_epoch::<Sig>(
+            &pk,
+            &message,
+            &sig,
+            EpochMode::TrustEmbedded
+        ));
+    }
+
+    #[test]
+    fn test_explicit_epoch() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Sig::gen(&mut rng);
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+        let epoch = 41;
+
+        let sig = sign_with_epoch::<Sig, _>(&mut rng, &sk, epoch, &message).unwrap();
+        assert!(verify_with_epoch::<Sig>(
+            &pk,
+            &message,
+            &sig,
+            EpochMode::Explicit(epoch)
+        ));
+
+        // a signature for another epoch is rejected
+        assert!(!verify_with_epoch::<Sig>(
+            &pk,
+            &message,
+            &sig,
+            EpochMode::Explicit(epoch + 1)
+        ));
+
+        // changing the embedded epoch to the expected one does not help
+        let other_sig = sign_with_epoch::<Sig, _>(&mut rng, &sk, 7, &message).unwrap();
+        let mut modified = other_sig.clone();
+        modified.epoch = epoch;
+        assert!(!verify_with_epoch::<Sig>(
+            &pk,
+            &message,
+            &modified,
+            EpochMode::Explicit(epoch)
+        ));
+
+        // an embedded epoch that differs from the expected one is
+        // rejected, even if the signature is valid for it
+        assert!(!verify_with_epoch::<Sig>(
+            &pk,
+            &message,
+            &other_sig,
+            EpochMode::Explicit(epoch)
+        ));
+        assert!(verify_with_epoch::<Sig>(
+            &pk,
+            &message,
+            &other_sig,
+            EpochMode::Explicit(7)
+        ));
+    }
+}
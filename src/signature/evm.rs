@@ -0,0 +1,334 @@
+//! Encoding of public keys and signatures for EVM contracts.
+//!
+//! Every value, e.g., a root, a parameter, a randomness, or a chain value, is
+//! one 32-byte word. Values shorter than 32 bytes are left-aligned and padded
+//! with zero bytes, as Solidity does when converting a `bytesN` into `bytes32`,
+//! so that `bytesN(word)` gives back the value. The words are packed without
+//! lengths or offsets, as `abi.encodePacked` does for `bytes32` values and
+//! `bytes32[]` arrays:
+//!
+//! - a public key is `abi.encodePacked(root, parameter)`, i.e., two words
+//! - a signature is `abi.encodePacked(randomness, chainValues, authPath)`, i.e.,
+//!   one word of randomness, one word per chain in the order of the chunks of
+//!   the codeword, and one word per sibling of the Merkle authentication path,
+//!   from the leaf level upwards
+//!
+//! The epoch is not part of the signature, as it is passed to `verify`.
+//!
+//! `SIGEvmWinternitzLifetime18W4` pairs this encoding with the message hash
+//! `KeccakMessageHash`, so that the contract can recompute the message hash
+//! with the native `keccak256`. The known-answer file `testdata/evm/kat.json`
+//! contains encodings that EVM test suites can check against, regenerated with
+//! `cargo test --release --lib evm -- --ignored`.
+
+use crate::{
+    inc_encoding::IncomparableEncoding,
+    symmetric::{tweak_hash::TweakableHash, tweak_hash_tree::HashTreeOpening},
+};
+
+use super::{
+    codec::CodecError,
+    generalized_xmss::{GeneralizedXMSSPublicKey, GeneralizedXMSSSignature},
+};
+
+/// Length of a word of the EVM in bytes
+pub const WORD_LENGTH: usize = 32;
+
+crate::declare_instantiation! {
+    /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 4,
+    /// and the message hash `KeccakMessageHash`. The other parameters are
+    /// those of `SIGWinternitzLifetime18W4`.
+    ///
+    /// Note: the seed identifier includes the message hash, so
+    /// `key_gen_from_seed` derives other keys than the SHA3 variant from the
+    /// same seed.
+    pub type SIGEvmWinternitzLifetime18W4 = Keccak {
+        log_lifetime: 18,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+}
+
+/// Values that fit into one word of the EVM
+pub trait EvmWord: Sized {
+    /// Returns the value left-aligned in a word, padded with zero bytes
+    fn to_word(&self) -> [u8; WORD_LENGTH];
+
+    /// Returns the value in a word, or `None` if the padding is not zero
+    fn from_word(word: &[u8; WORD_LENGTH]) -> Option<Self>;
+}
+
+/// Fails to compile if arrays of length `N` do not fit into a word
+struct FitsIntoWord<const N: usize>;
+
+impl<const N: usize> FitsIntoWord<N> {
+    const CHECK: () = assert!(N <= WORD_LENGTH, "EVM: Values must have at most 32 bytes");
+}
+
+impl<const N: usize> EvmWord for [u8; N] {
+    fn to_word(&self) -> [u8; WORD_LENGTH] {
+        let () = FitsIntoWord::<N>::CHECK;
+        let mut word = [0u8; WORD_LENGTH];
+        word[..N].copy_from_slice(self);
+        word
+    }
+
+    fn from_word(word: &[u8; WORD_LENGTH]) -> Option<Self> {
+        let () = FitsIntoWord::<N>::CHECK;
+        let (value, padding) = word.split_at(N);
+        padding
+            .iter()
+            .all(|&b| b == 0)
+            .then(|| value.try_into().unwrap())
+    }
+}
+
+/// Keys and signatures with an encoding for EVM contracts, see the module docs
+pub trait EvmEncoding: Sized {
+    /// Encodes the value as packed 32-byte words
+    fn encode_for_evm(&self) -> Vec<u8>;
+
+    /// Decodes a value from packed 32-byte words, see `encode_for_evm`. Fails
+    /// if the number of words does not match, or if any padding is not zero.
+    fn decode_from_evm(bytes: &[u8]) -> Result<Self, CodecError>;
+}
+
+/// Splits `bytes` into words, failing if there are bytes left over
+fn words(bytes: &[u8]) -> Result<Vec<&[u8; WORD_LENGTH]>, CodecError> {
+    let (words, rest) = bytes.as_chunks::<WORD_LENGTH>();
+    if !rest.is_empty() {
+        return Err(CodecError::InvalidEncoding);
+    }
+    Ok(words.iter().collect())
+}
+
+fn from_word<T: EvmWord>(word: &[u8; WORD_LENGTH]) -> Result<T, CodecError> {
+    T::from_word(word).ok_or(CodecError::InvalidEncoding)
+}
+
+impl<TH: TweakableHash> EvmEncoding for GeneralizedXMSSPublicKey<TH>
+where
+    TH::Domain: EvmWord,
+    TH::Parameter: EvmWord,
+{
+    fn encode_for_evm(&self) -> Vec<u8> {
+        [self.root.to_word(), self.parameter.to_word()].concat()
+    }
+
+    fn decode_from_evm(bytes: &[u8]) -> Result<Self, CodecError> {
+        let [root, parameter] = words(bytes)?[..] else {
+            return Err(CodecError::InvalidEncoding);
+        };
+        Ok(GeneralizedXMSSPublicKey {
+            root: from_word(root)?,
+            parameter: from_word(parameter)?,
+        })
+    }
+}
+
+/// Note: the depth of the Merkle tree is given by the number of words. It is
+/// not checked when decoding, but `verify` rejects paths of the wrong depth.
+impl<IE: IncomparableEncoding, TH: TweakableHash> EvmEncoding for GeneralizedXMSSSignature<IE, TH>
+where
+    IE::Randomness: EvmWord,
+    TH::Domain: EvmWord,
+{
+    fn encode_for_evm(&self) -> Vec<u8> {
+        let mut bytes = self.rho().to_word().to_vec();
+        for value in self.chain_values().iter().chain(self.path().co_path()) {
+            bytes.extend(value.to_word());
+        }
+        bytes
+    }
+
+    fn decode_from_evm(bytes: &[u8]) -> Result<Self, CodecError> {
+        let words = words(bytes)?;
+        let Some((rho, rest)) = words.split_first() else {
+            return Err(CodecError::InvalidEncoding);
+        };
+        if rest.len() < IE::NUM_CHUNKS {
+            return Err(CodecError::InvalidEncoding);
+        }
+        let (chain_values, co_path) = rest.split_at(IE::NUM_CHUNKS);
+        let chain_values = chain_values
+            .iter()
+            .map(|word| from_word(word))
+            .collect::<Result<_, _>>()?;
+        let co_path = co_path
+            .iter()
+            .map(|word| from_word(word))
+            .collect::<Result<_, _>>()?;
+        GeneralizedXMSSSignature::from_chain_values(
+            HashTreeOpening::from_co_path(co_path),
+            from_word(rho)?,
+            chain_values,
+        )
+        .ok_or(CodecError::InvalidEncoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use rand::{thread_rng, Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        signature::SignatureScheme,
+        symmetric::{message_hash::keccak::KeccakMessageHash, seed_expander::SEED_LENGTH},
+        MESSAGE_LENGTH,
+    };
+
+    use super::*;
+
+    crate::declare_instantiation! {
+        /// The building blocks of `SIGEvmWinternitzLifetime18W4`,
+        /// with a lifetime that allows to generate keys in tests
+        type SIGEvmWinternitzLifetime6W4 = Keccak {
+            log_lifetime: 6,
+            parameter_len: 18,
+            message_hash_len: 18,
+            rand_len: 20,
+            hash_len: 26,
+            chunk_size: 4,
+            encoding: Winternitz,
+        };
+    }
+
+    type Sig = SIGEvmWinternitzLifetime6W4;
+    type PublicKey = <Sig as SignatureScheme>::PublicKey;
+    type Signature = <Sig as SignatureScheme>::Signature;
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Sig::gen(&mut rng);
+        let message = rng.gen();
+        let epoch = 13;
+        let sig = Sig::sign(&mut rng, &sk, epoch, &message).unwrap();
+
+        let pk_bytes = pk.encode_for_evm();
+        assert_eq!(pk_bytes.len(), 2 * WORD_LENGTH);
+        assert_eq!(&pk_bytes[..26], &pk.root[..]);
+        assert_eq!(&pk_bytes[32..50], &pk.parameter[..]);
+        assert!(PublicKey::decode_from_evm(&pk_bytes).unwrap() == pk);
+
+        // randomness, 36 + 3 chain values, and 6 siblings
+        let sig_bytes = sig.encode_for_evm();
+        assert_eq!(sig_bytes.len(), (1 + 39 + 6) * WORD_LENGTH);
+        assert_eq!(&sig_bytes[..20], &sig.rho()[..]);
+        assert_eq!(&sig_bytes[32..58], &sig.chain_values()[0][..]);
+        assert_eq!(
+            &sig_bytes[40 * 32..40 * 32 + 26],
+            &sig.path().co_path()[0][..]
+        );
+        let decoded = Signature::decode_from_evm(&sig_bytes).unwrap();
+        assert!(decoded == sig);
+        assert!(Sig::verify(&pk, epoch, &message, &decoded));
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_encodings() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Sig::gen(&mut rng);
+        let message = rng.gen();
+        let sig = Sig::sign(&mut rng, &sk, 13, &message).unwrap();
+        let pk_bytes = pk.encode_for_evm();
+        let sig_bytes = sig.encode_for_evm();
+
+        // lengths that are not a number of words, or the wrong number of words
+        for bytes in [&pk_bytes[..63], &pk_bytes[..32], &[]] {
+            assert!(PublicKey::decode_from_evm(bytes).is_err());
+        }
+        assert!(PublicKey::decode_from_evm(&[&pk_bytes[..], &[0; 32]].concat()).is_err());
+        for length in [0, 32, 39 * 32, sig_bytes.len() - 1] {
+            assert!(Signature::decode_from_evm(&sig_bytes[..length]).is_err());
+        }
+
+        // non-zero padding, of the root, the randomness, and the last sibling
+        let mut modified = pk_bytes.clone();
+        modified[26] = 1;
+        assert!(PublicKey::decode_from_evm(&modified).is_err());
+        for position in [20, 45 * 32 + 31] {
+            let mut modified = sig_bytes.clone();
+            modified[position] = 1;
+            assert!(Signature::decode_from_evm(&modified).is_err());
+        }
+    }
+
+    /// Contents of the known-answer file, with all bytes in hex with prefix `0x`,
+    /// as expected by `vm.parseJsonBytes` of Foundry
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct KnownAnswer {
+        log_lifetime: usize,
+        seed: String,
+        epoch: u32,
+        message: String,
+        /// the first `NUM_CHUNKS * CHUNK_SIZE / 8` bytes of the Keccak-256 digest
+        message_hash: String,
+        public_key: String,
+        signature: String,
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("0x{}", hex)
+    }
+
+    fn path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/evm/kat.json")
+    }
+
+    fn derive() -> KnownAnswer {
+        let seed: [u8; SEED_LENGTH] = std::array::from_fn(|i| i as u8);
+        let epoch = 13;
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| (i as u8).wrapping_mul(7));
+
+        let (pk, sk) = Sig::key_gen_from_seed(seed);
+        let mut rng = ChaCha20Rng::seed_from_u64(epoch as u64);
+        let sig = Sig::sign(&mut rng, &sk, epoch, &message).unwrap();
+        let message_hash = KeccakMessageHash::<18, 20, 36, 4>::apply_raw(
+            &pk.parameter,
+            epoch,
+            sig.rho(),
+            &message,
+        );
+
+        KnownAnswer {
+            log_lifetime: 6,
+            seed: to_hex(&seed),
+            epoch,
+            message: to_hex(&message),
+            message_hash: to_hex(&message_hash),
+            public_key: to_hex(&pk.encode_for_evm()),
+            signature: to_hex(&sig.encode_for_evm()),
+        }
+    }
+
+    #[test]
+    fn test_known_answer() {
+        let file = std::fs::read_to_string(path()).unwrap();
+        let expected: KnownAnswer = serde_json::from_str(&file).unwrap();
+        assert_eq!(
+            derive(),
+            expected,
+            "The EVM encoding or the Keccak message hash changed. If this is deliberate, \
+             regenerate testdata/evm/kat.json with `cargo test --release --lib evm -- --ignored`"
+        );
+    }
+
+    #[test]
+    #[ignore = "regenerates the known-answer file"]
+    fn generate_known_answer() {
+        let path = path();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let json = serde_json::to_string_pretty(&derive()).unwrap();
+        std::fs::write(path, json + "\n").unwrap();
+    }
+}
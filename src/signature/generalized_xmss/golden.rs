@@ -0,0 +1,240 @@
+//! Golden-file tests that freeze the wire format, against the binary files in
+//! `testdata/golden`, three per registered parameter set: the encodings of
+//! `codec` of a public key (`.pk`), a secret key (`.sk`), and a signature
+//! (`.sig`), all derived from a fixed seed.
+//!
+//! For each file, the tests check that it still decodes, that the decoded keys
+//! and signature still work together, and that encoding the decoded value again
+//! gives back the file byte for byte.
+//!
+//! Note: the keys use the building blocks of the parameter set, but a lifetime
+//! of 2^`GOLDEN_LOG_LIFETIME`, as keys with a lifetime of 2^18 or 2^20 take far
+//! too long to generate and are far too large to commit. The lifetime only
+//! determines the number of hashes in the Merkle tree and path.
+//!
+//! After a deliberate change of the wire format, which also requires a new
+//! `codec::FORMAT_VERSION`, the files are regenerated with
+//! `cargo test --release --lib golden -- --ignored`.
+
+use std::{marker::PhantomData, path::PathBuf};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    inc_encoding::IncomparableEncoding,
+    signature::{codec, param_set::ParamSetId, SignatureScheme},
+    symmetric::{prf::Pseudorandom, seed_expander::SEED_LENGTH, tweak_hash::TweakableHash},
+    MESSAGE_LENGTH,
+};
+
+use super::{
+    instantiations_sha::{lifetime_2_to_the_10, lifetime_2_to_the_18, lifetime_2_to_the_20},
+    GeneralizedXMSSPublicKey, GeneralizedXMSSSecretKey, GeneralizedXMSSSignature,
+    GeneralizedXMSSSignatureScheme,
+};
+
+/// Base 2 log of the lifetime of the keys in the golden files
+const GOLDEN_LOG_LIFETIME: usize = 6;
+
+/// Extensions of the golden files of a parameter set
+const EXTENSIONS: [&str; 3] = ["pk", "sk", "sig"];
+
+fn path(param_set: ParamSetId, extension: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata/golden")
+        .join(format!("{}.{}", param_set.name(), extension))
+}
+
+/// Seed, epoch, and message of the golden files of a parameter set.
+/// They only depend on the identifier of the parameter set.
+fn inputs(param_set: ParamSetId) -> ([u8; SEED_LENGTH], u32, [u8; MESSAGE_LENGTH]) {
+    let id = param_set.as_id();
+    let seed = std::array::from_fn(|i| i as u8 ^ id as u8);
+    let epoch = (id as u32).wrapping_mul(0x9e37) % (1 << GOLDEN_LOG_LIFETIME);
+    let message = std::array::from_fn(|i| (i as u8).wrapping_mul(7));
+    (seed, epoch, message)
+}
+
+/// Generates the contents of the golden files of a parameter set, i.e., the
+/// encoded public key, secret key, and signature, in the order of `EXTENSIONS`.
+/// The scheme is only used to select the building blocks, see the module docs.
+fn generate<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+>(
+    _scheme: PhantomData<GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>>,
+    param_set: ParamSetId,
+) -> [Vec<u8>; 3]
+where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+    GeneralizedXMSSPublicKey<TH>: Serialize,
+    GeneralizedXMSSSecretKey<PRF, TH>: Serialize,
+    GeneralizedXMSSSignature<IE, TH>: Serialize,
+{
+    type Sig<PRF, IE, TH> = GeneralizedXMSSSignatureScheme<PRF, IE, TH, GOLDEN_LOG_LIFETIME>;
+
+    let id = param_set.as_id();
+    let (seed, epoch, message) = inputs(param_set);
+    let (pk, sk) = Sig::<PRF, IE, TH>::key_gen_from_seed(seed);
+    let mut rng = ChaCha20Rng::seed_from_u64(epoch as u64);
+    let sig = Sig::<PRF, IE, TH>::sign(&mut rng, &sk, epoch, &message).unwrap();
+    [
+        codec::encode_public_key::<Sig<PRF, IE, TH>>(id, &pk),
+        codec::encode_secret_key::<Sig<PRF, IE, TH>>(id, &sk),
+        codec::encode_signature::<Sig<PRF, IE, TH>>(id, &sig),
+    ]
+}
+
+/// Checks the golden files of a parameter set, given in the order of
+/// `EXTENSIONS`: they decode, the decoded signature verifies, the decoded
+/// secret key signs for the decoded public key, and encoding again gives
+/// back the same bytes.
+fn check<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+>(
+    _scheme: PhantomData<GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>>,
+    param_set: ParamSetId,
+    golden: &[Vec<u8>; 3],
+) where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+    GeneralizedXMSSPublicKey<TH>: Serialize + DeserializeOwned,
+    GeneralizedXMSSSecretKey<PRF, TH>: Serialize + DeserializeOwned,
+    GeneralizedXMSSSignature<IE, TH>: Serialize + DeserializeOwned,
+{
+    type Sig<PRF, IE, TH> = GeneralizedXMSSSignatureScheme<PRF, IE, TH, GOLDEN_LOG_LIFETIME>;
+
+    let name = param_set.name();
+    let id = param_set.as_id();
+    let (_, epoch, message) = inputs(param_set);
+    let [pk_bytes, sk_bytes, sig_bytes] = golden;
+    let breaking = |kind: &str| {
+        format!(
+            "{}: the {} in the golden file no longer decodes to the same value. This breaks \
+             the wire format! If this is deliberate, increment codec::FORMAT_VERSION and \
+             regenerate the golden files with `cargo test --release --lib golden -- --ignored`",
+            name, kind
+        )
+    };
+
+    // (a) the files still decode, and work together
+    let pk = codec::decode_public_key::<Sig<PRF, IE, TH>>(id, pk_bytes)
+        .unwrap_or_else(|e| panic!("{} ({:?})", breaking("public key"), e));
+    let sk = codec::decode_secret_key::<Sig<PRF, IE, TH>>(id, sk_bytes)
+        .unwrap_or_else(|e| panic!("{} ({:?})", breaking("secret key"), e));
+    let sig = codec::decode_signature::<Sig<PRF, IE, TH>>(id, sig_bytes)
+        .unwrap_or_else(|e| panic!("{} ({:?})", breaking("signature"), e));
+    assert!(
+        Sig::<PRF, IE, TH>::verify(&pk, epoch, &message, &sig),
+        "{}",
+        breaking("signature")
+    );
+    let other_epoch = (epoch + 1) % (1 << GOLDEN_LOG_LIFETIME);
+    let mut rng = ChaCha20Rng::seed_from_u64(other_epoch as u64);
+    let other_sig = Sig::<PRF, IE, TH>::sign(&mut rng, &sk, other_epoch, &message).unwrap();
+    assert!(
+        Sig::<PRF, IE, TH>::verify(&pk, other_epoch, &message, &other_sig),
+        "{}",
+        breaking("secret key")
+    );
+
+    // (b) encoding the decoded values gives back the files byte for byte
+    assert!(
+        codec::encode_public_key::<Sig<PRF, IE, TH>>(id, &pk) == *pk_bytes,
+        "{}",
+        breaking("public key")
+    );
+    assert!(
+        codec::encode_secret_key::<Sig<PRF, IE, TH>>(id, &sk) == *sk_bytes,
+        "{}",
+        breaking("secret key")
+    );
+    assert!(
+        codec::encode_signature::<Sig<PRF, IE, TH>>(id, &sig) == *sig_bytes,
+        "{}",
+        breaking("signature")
+    );
+}
+
+/// Calls `$f` with the scheme of the given parameter set, and the given arguments
+macro_rules! dispatch {
+    ($param_set:expr, $f:ident($($arg:expr),*)) => {
+        dispatch!(@schemes $param_set, $f, ($($arg),*),
+            ShaWinternitzLifetime18W1 => SIGWinternitzLifetime18W1,
+            ShaWinternitzLifetime18W2 => SIGWinternitzLifetime18W2,
+            ShaWinternitzLifetime18W4 => SIGWinternitzLifetime18W4,
+            ShaWinternitzLifetime18W8 => SIGWinternitzLifetime18W8,
+            ShaTargetSumLifetime18W1NoOff => SIGTargetSumLifetime18W1NoOff,
+            ShaTargetSumLifetime18W1Off10 => SIGTargetSumLifetime18W1Off10,
+            ShaTargetSumLifetime18W2NoOff => SIGTargetSumLifetime18W2NoOff,
+            ShaTargetSumLifetime18W2Off10 => SIGTargetSumLifetime18W2Off10,
+            ShaTargetSumLifetime18W4NoOff => SIGTargetSumLifetime18W4NoOff,
+            ShaTargetSumLifetime18W4Off10 => SIGTargetSumLifetime18W4Off10,
+            ShaTargetSumLifetime18W8NoOff => SIGTargetSumLifetime18W8NoOff,
+            ShaTargetSumLifetime18W8Off10 => SIGTargetSumLifetime18W8Off10,
+            ShaWinternitzLifetime20W1 => SIGWinternitzLifetime20W1,
+            ShaWinternitzLifetime20W2 => SIGWinternitzLifetime20W2,
+            ShaWinternitzLifetime20W4 => SIGWinternitzLifetime20W4,
+            ShaWinternitzLifetime20W8 => SIGWinternitzLifetime20W8,
+            ShaTargetSumLifetime20W1NoOff => SIGTargetSumLifetime20W1NoOff,
+            ShaTargetSumLifetime20W1Off10 => SIGTargetSumLifetime20W1Off10,
+            ShaTargetSumLifetime20W2NoOff => SIGTargetSumLifetime20W2NoOff,
+            ShaTargetSumLifetime20W2Off10 => SIGTargetSumLifetime20W2Off10,
+            ShaTargetSumLifetime20W4NoOff => SIGTargetSumLifetime20W4NoOff,
+            ShaTargetSumLifetime20W4Off10 => SIGTargetSumLifetime20W4Off10,
+            ShaTargetSumLifetime20W8NoOff => SIGTargetSumLifetime20W8NoOff,
+            ShaTargetSumLifetime20W8Off10 => SIGTargetSumLifetime20W8Off10,
+            ShaWinternitzLifetime10W4 => SIGWinternitzLifetime10W4,
+        )
+    };
+    (@schemes $param_set:expr, $f:ident, $args:tt, $($variant:ident => $scheme:ident,)*) => {{
+        use lifetime_2_to_the_10::winternitz::*;
+        use lifetime_2_to_the_18::{target_sum::*, winternitz::*};
+        use lifetime_2_to_the_20::{target_sum::*, winternitz::*};
+
+        match $param_set {
+            $(ParamSetId::$variant => dispatch!(@call $f, $scheme, $args),)*
+        }
+    }};
+    (@call $f:ident, $scheme:ident, ($($arg:expr),*)) => {
+        $f(PhantomData::<$scheme>, $($arg),*)
+    };
+}
+
+#[test]
+fn test_golden_files() {
+    for &param_set in ParamSetId::ALL {
+        let golden = EXTENSIONS.map(|extension| {
+            std::fs::read(path(param_set, extension)).unwrap_or_else(|e| {
+                panic!(
+                    "{}: cannot read golden file {}: {}",
+                    param_set.name(),
+                    extension,
+                    e
+                )
+            })
+        });
+        dispatch!(param_set, check(param_set, &golden));
+    }
+}
+
+#[test]
+#[ignore = "regenerates the golden files"]
+fn generate_golden_files() {
+    for &param_set in ParamSetId::ALL {
+        let golden = dispatch!(param_set, generate(param_set));
+        for (extension, bytes) in EXTENSIONS.iter().zip(golden) {
+            let path = path(param_set, extension);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, bytes).unwrap();
+        }
+    }
+}
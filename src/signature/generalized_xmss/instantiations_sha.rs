@@ -1,70 +1,263 @@
+/// Declares an instantiation of the generalized XMSS signature scheme based on SHA3.
+///
+/// The instantiation is given by named arguments, from which the derived constants
+/// are computed: the number of chunks of the message hash is
+/// `message_hash_len * 8 / chunk_size`, and for the Winternitz encoding the number
+/// of checksum chunks is computed with `num_chunks_checksum`. All lengths are given
+/// in bytes. The encoding is either `Winternitz` or `TargetSum(target_sum)`.
+///
+/// The family is either `Sha3`, or `Keccak` to use Keccak-256 for the message hash
+/// (see `KeccakMessageHash`), e.g., for verification in EVM contracts. In both
+/// families, the PRF and the tweakable hash are based on SHA3.
+///
+/// The macro emits the type alias, an assertion that fails compilation if the
+/// constants are inconsistent (see `check_instantiation`), and a test, named as
+/// the alias, that runs the consistency check of the instantiation.
+///
+/// ```
+/// hashsig::declare_instantiation! {
+///     /// Instantiation with Lifetime 2^10, Winternitz encoding, chunk size w = 4
+///     pub type MyInstantiation = Sha3 {
+///         log_lifetime: 10,
+///         parameter_len: 18,
+///         message_hash_len: 18,
+///         rand_len: 20,
+///         hash_len: 26,
+///         chunk_size: 4,
+///         encoding: Winternitz,
+///     };
+/// }
+/// ```
+#[macro_export]
+macro_rules! declare_instantiation {
+    () => {};
+    (
+        $(#[$attr:meta])*
+        $vis:vis type $name:ident = $family:ident {
+            log_lifetime: $log_lifetime:expr,
+            parameter_len: $parameter_len:expr,
+            message_hash_len: $message_hash_len:expr,
+            rand_len: $rand_len:expr,
+            hash_len: $hash_len:expr,
+            chunk_size: $chunk_size:expr,
+            encoding: $encoding:ident $(($target_sum:expr))?,
+        };
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        $vis type $name = $crate::signature::generalized_xmss::GeneralizedXMSSSignatureScheme<
+            $crate::symmetric::prf::sha::ShaPRF<{ $hash_len }>,
+            $crate::declare_instantiation!(
+                @encoding $encoding $(($target_sum))?,
+                $crate::declare_instantiation!(
+                    @message_hash $family,
+                    $parameter_len,
+                    $rand_len,
+                    $message_hash_len * 8 / $chunk_size,
+                    $chunk_size
+                ),
+                $message_hash_len * 8 / $chunk_size,
+                $chunk_size
+            ),
+            $crate::symmetric::tweak_hash::sha::ShaTweakHash<{ $parameter_len }, { $hash_len }>,
+            { $log_lifetime },
+        >;
+
+        const _: () = $crate::signature::generalized_xmss::instantiations_sha::check_instantiation(
+            $log_lifetime,
+            $parameter_len,
+            $message_hash_len,
+            $rand_len,
+            $hash_len,
+            $chunk_size,
+            $crate::declare_instantiation!(@target_sum $encoding $(($target_sum))?),
+        );
+
+        #[cfg(test)]
+        #[test]
+        #[allow(non_snake_case)]
+        fn $name() {
+            $name::consistency_check();
+        }
+
+        $crate::declare_instantiation!($($rest)*);
+    };
+    (@message_hash Sha3, $parameter_len:expr, $rand_len:expr, $num_chunks:expr, $chunk_size:expr) => {
+        $crate::symmetric::message_hash::sha::ShaMessageHash<
+            { $parameter_len },
+            { $rand_len },
+            { $num_chunks },
+            { $chunk_size },
+        >
+    };
+    (@message_hash Keccak, $parameter_len:expr, $rand_len:expr, $num_chunks:expr, $chunk_size:expr) => {
+        $crate::symmetric::message_hash::keccak::KeccakMessageHash<
+            { $parameter_len },
+            { $rand_len },
+            { $num_chunks },
+            { $chunk_size },
+        >
+    };
+    (@encoding Winternitz, $mh:ty, $num_chunks:expr, $chunk_size:expr) => {
+        $crate::inc_encoding::basic_winternitz::WinternitzEncoding<
+            $mh,
+            { $crate::inc_encoding::basic_winternitz::num_chunks_checksum($num_chunks, $chunk_size) },
+        >
+    };
+    (@encoding TargetSum($target_sum:expr), $mh:ty, $num_chunks:expr, $chunk_size:expr) => {
+        $crate::inc_encoding::target_sum::TargetSumEncoding<$mh, { $target_sum }>
+    };
+    (@target_sum Winternitz) => {
+        None
+    };
+    (@target_sum TargetSum($target_sum:expr)) => {
+        Some($target_sum)
+    };
+}
+
+/// Checks the constants of an instantiation declared with `declare_instantiation`,
+/// and panics if they are inconsistent. As it is evaluated in a constant, this
+/// results in an error at compile time. All lengths are given in bytes.
+pub const fn check_instantiation(
+    log_lifetime: usize,
+    parameter_len: usize,
+    message_hash_len: usize,
+    rand_len: usize,
+    hash_len: usize,
+    chunk_size: usize,
+    target_sum: Option<usize>,
+) {
+    assert!(
+        log_lifetime > 0 && log_lifetime <= 32,
+        "Instantiation: Lifetime must be between 2^1 and 2^32"
+    );
+    assert!(
+        parameter_len > 0 && parameter_len < 256 / 8,
+        "Instantiation: Parameter Length must be non-zero and less than 256 bit"
+    );
+    assert!(
+        message_hash_len > 0 && message_hash_len < 256 / 8,
+        "Instantiation: Message Hash Length must be non-zero and less than 256 bit"
+    );
+    assert!(
+        rand_len > 0 && rand_len < 256 / 8,
+        "Instantiation: Randomness Length must be non-zero and less than 256 bit"
+    );
+    assert!(
+        hash_len > 0 && hash_len < 256 / 8,
+        "Instantiation: Hash Length must be non-zero and less than 256 bit"
+    );
+    assert!(
+        chunk_size == 1 || chunk_size == 2 || chunk_size == 4 || chunk_size == 8,
+        "Instantiation: Chunk Size must be 1, 2, 4, or 8"
+    );
+    if let Some(target_sum) = target_sum {
+        let num_chunks = message_hash_len * 8 / chunk_size;
+        assert!(
+            target_sum <= num_chunks * ((1 << chunk_size) - 1),
+            "Instantiation: Target Sum must be at most the maximum sum of all chunks"
+        );
+    }
+}
+
+/// Instantiations with Lifetime 2^10, e.g., for short-lived keys and for testing
+pub mod lifetime_2_to_the_10 {
+    /// Instantiations based on the Winternitz encoding
+    pub mod winternitz {
+        crate::declare_instantiation! {
+            /// Instantiation with Lifetime 2^10, Winternitz encoding, chunk size w = 4
+            pub type SIGWinternitzLifetime10W4 = Sha3 {
+                log_lifetime: 10,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 20,
+                hash_len: 26,
+                chunk_size: 4,
+                encoding: Winternitz,
+            };
+        }
+
+        #[cfg(test)]
+        mod test {
+            use std::marker::PhantomData;
+
+            use crate::signature::{
+                generalized_xmss::test_templates::_test_rejects_manipulated_signatures,
+                test_templates::_test_signature_scheme_correctness,
+            };
+
+            use super::SIGWinternitzLifetime10W4;
+
+            #[test]
+            pub fn test_w4_correctness() {
+                _test_signature_scheme_correctness::<SIGWinternitzLifetime10W4>(732);
+            }
+            #[test]
+            pub fn test_w4_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(PhantomData::<SIGWinternitzLifetime10W4>, 732);
+            }
+        }
+    }
+}
+
 /// Instantiations with Lifetime 2^18
 pub mod lifetime_2_to_the_18 {
     /// Instantiations based on the Winternitz encoding
     pub mod winternitz {
-        use crate::{
-            inc_encoding::basic_winternitz::WinternitzEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
-            symmetric::{
-                message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
-            },
-        };
+        crate::declare_instantiation! {
+            /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 1
+            pub type SIGWinternitzLifetime18W1 = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 20,
+                hash_len: 25,
+                chunk_size: 1,
+                encoding: Winternitz,
+            };
 
-        const LOG_LIFETIME: usize = 18;
-        const PARAMETER_LEN: usize = 18;
-        const MESSAGE_HASH_LEN: usize = 18;
-        const RAND_LEN: usize = 20;
-
-        const CHUNK_SIZE_W1: usize = 1;
-        const NUM_CHUNKS_W1: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W1;
-        type MHw1 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W1, CHUNK_SIZE_W1>;
-        const HASH_LEN_W1: usize = 25;
-        type THw1 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W1>;
-        type PRFw1 = ShaPRF<HASH_LEN_W1>;
-        type IEw1 = WinternitzEncoding<MHw1, 8>;
-        /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 1
-        pub type SIGWinternitzLifetime18W1 =
-            GeneralizedXMSSSignatureScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W2: usize = 2;
-        const NUM_CHUNKS_W2: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W2;
-        type MHw2 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W2, CHUNK_SIZE_W2>;
-        const HASH_LEN_W2: usize = 25;
-        type THw2 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W2>;
-        type PRFw2 = ShaPRF<HASH_LEN_W2>;
-        type IEw2 = WinternitzEncoding<MHw2, 4>;
-        /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 2
-        pub type SIGWinternitzLifetime18W2 =
-            GeneralizedXMSSSignatureScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W4: usize = 4;
-        const NUM_CHUNKS_W4: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W4;
-        type MHw4 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W4, CHUNK_SIZE_W4>;
-        const HASH_LEN_W4: usize = 26;
-        type THw4 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W4>;
-        type PRFw4 = ShaPRF<HASH_LEN_W4>;
-        type IEw4 = WinternitzEncoding<MHw4, 3>;
-        /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 4
-        pub type SIGWinternitzLifetime18W4 =
-            GeneralizedXMSSSignatureScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W8: usize = 8;
-        const NUM_CHUNKS_W8: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W8;
-        type MHw8 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W8, CHUNK_SIZE_W8>;
-        const HASH_LEN_W8: usize = 28;
-        type THw8 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W8>;
-        type PRFw8 = ShaPRF<HASH_LEN_W8>;
-        type IEw8 = WinternitzEncoding<MHw8, 2>;
-        /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 8
-        pub type SIGWinternitzLifetime18W8 =
-            GeneralizedXMSSSignatureScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
+            /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 2
+            pub type SIGWinternitzLifetime18W2 = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 20,
+                hash_len: 25,
+                chunk_size: 2,
+                encoding: Winternitz,
+            };
 
-        #[cfg(test)]
+            /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 4
+            pub type SIGWinternitzLifetime18W4 = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 20,
+                hash_len: 26,
+                chunk_size: 4,
+                encoding: Winternitz,
+            };
+
+            /// Instantiation with Lifetime 2^18, Winternitz encoding, chunk size w = 8
+            pub type SIGWinternitzLifetime18W8 = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 20,
+                hash_len: 28,
+                chunk_size: 8,
+                encoding: Winternitz,
+            };
+        }
+
+        #[cfg(all(test, feature = "slow-tests"))]
         mod test {
-            use crate::signature::SignatureScheme;
+            use std::marker::PhantomData;
 
-            #[cfg(feature = "slow-tests")]
-            use crate::signature::test_templates::_test_signature_scheme_correctness;
+            use crate::signature::{
+                generalized_xmss::test_templates::_test_rejects_manipulated_signatures,
+                test_templates::_test_signature_scheme_correctness,
+            };
 
             use super::{
                 SIGWinternitzLifetime18W1, SIGWinternitzLifetime18W2, SIGWinternitzLifetime18W4,
@@ -72,133 +265,160 @@ pub mod lifetime_2_to_the_18 {
             };
 
             #[test]
-            pub fn test_w1_internal_consistency() {
-                SIGWinternitzLifetime18W1::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w2_internal_consistency() {
-                SIGWinternitzLifetime18W2::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w4_internal_consistency() {
-                SIGWinternitzLifetime18W4::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w8_internal_consistency() {
-                SIGWinternitzLifetime18W8::internal_consistency_check();
-            }
-
-            #[test]
-            #[cfg(feature = "slow-tests")]
             pub fn test_w1_correctness() {
                 _test_signature_scheme_correctness::<SIGWinternitzLifetime18W1>(1032);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w1_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGWinternitzLifetime18W1>,
+                    1032,
+                );
+            }
+            #[test]
             pub fn test_w2_correctness() {
                 _test_signature_scheme_correctness::<SIGWinternitzLifetime18W2>(32);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w2_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(PhantomData::<SIGWinternitzLifetime18W2>, 32);
+            }
+            #[test]
             pub fn test_w4_correctness() {
                 _test_signature_scheme_correctness::<SIGWinternitzLifetime18W4>(2032);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w4_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGWinternitzLifetime18W4>,
+                    2032,
+                );
+            }
+            #[test]
             pub fn test_w8_correctness() {
                 _test_signature_scheme_correctness::<SIGWinternitzLifetime18W8>(2142);
             }
+            #[test]
+            pub fn test_w8_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGWinternitzLifetime18W8>,
+                    2142,
+                );
+            }
         }
     }
     /// Instantiations based on the target sum encoding
     pub mod target_sum {
-        use crate::{
-            inc_encoding::target_sum::TargetSumEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
-            symmetric::{
-                message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
-            },
-        };
+        crate::declare_instantiation! {
+            /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 1,
+            /// and target sum set at expectation
+            pub type SIGTargetSumLifetime18W1NoOff = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 25,
+                chunk_size: 1,
+                encoding: TargetSum(72),
+            };
 
-        const LOG_LIFETIME: usize = 18;
-        const PARAMETER_LEN: usize = 18;
-        const MESSAGE_HASH_LEN: usize = 18;
-        const RAND_LEN: usize = 23;
-
-        const CHUNK_SIZE_W1: usize = 1;
-        const NUM_CHUNKS_W1: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W1;
-        type MHw1 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W1, CHUNK_SIZE_W1>;
-        const HASH_LEN_W1: usize = 25;
-        type THw1 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W1>;
-        type PRFw1 = ShaPRF<HASH_LEN_W1>;
-        type IEw1<const TARGET_SUM: usize> = TargetSumEncoding<MHw1, TARGET_SUM>;
-        /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 1,
-        /// and target sum set at expectation
-        pub type SIGTargetSumLifetime18W1NoOff =
-            GeneralizedXMSSSignatureScheme<PRFw1, IEw1<72>, THw1, LOG_LIFETIME>;
-        /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 1,
-        /// and target sum set at 1.1 * expectation (10% offset)
-        pub type SIGTargetSumLifetime18W1Off10 =
-            GeneralizedXMSSSignatureScheme<PRFw1, IEw1<80>, THw1, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W2: usize = 2;
-        const NUM_CHUNKS_W2: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W2;
-        type MHw2 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W2, CHUNK_SIZE_W2>;
-        const HASH_LEN_W2: usize = 25;
-        type THw2 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W2>;
-        type PRFw2 = ShaPRF<HASH_LEN_W2>;
-        type IEw2<const TARGET_SUM: usize> = TargetSumEncoding<MHw2, TARGET_SUM>;
-        /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 2,
-        /// and target sum set at expectation
-        pub type SIGTargetSumLifetime18W2NoOff =
-            GeneralizedXMSSSignatureScheme<PRFw2, IEw2<108>, THw2, LOG_LIFETIME>;
-        /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 2,
-        /// and target sum set at 1.1 * expectation (10% offset)
-        pub type SIGTargetSumLifetime18W2Off10 =
-            GeneralizedXMSSSignatureScheme<PRFw2, IEw2<119>, THw2, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W4: usize = 4;
-        const NUM_CHUNKS_W4: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W4;
-        type MHw4 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W4, CHUNK_SIZE_W4>;
-        const HASH_LEN_W4: usize = 26;
-        type THw4 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W4>;
-        type PRFw4 = ShaPRF<HASH_LEN_W4>;
-        type IEw4<const TARGET_SUM: usize> = TargetSumEncoding<MHw4, TARGET_SUM>;
-        /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 4,
-        /// and target sum set at expectation
-        pub type SIGTargetSumLifetime18W4NoOff =
-            GeneralizedXMSSSignatureScheme<PRFw4, IEw4<270>, THw4, LOG_LIFETIME>;
-        /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 4,
-        /// and target sum set at 1.1 * expectation (10% offset)
-        pub type SIGTargetSumLifetime18W4Off10 =
-            GeneralizedXMSSSignatureScheme<PRFw4, IEw4<297>, THw4, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W8: usize = 8;
-        const NUM_CHUNKS_W8: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W8;
-        type MHw8 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W8, CHUNK_SIZE_W8>;
-        const HASH_LEN_W8: usize = 28;
-        type THw8 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W8>;
-        type PRFw8 = ShaPRF<HASH_LEN_W8>;
-        type IEw8<const TARGET_SUM: usize> = TargetSumEncoding<MHw8, TARGET_SUM>;
-        /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 8,
-        /// and target sum set at expectation
-        /// Note: with chunk size w = 8, chains are very long. This leads to high variance
-        /// and so signing may fail from time to time. It is not recommended to use this.
-        pub type SIGTargetSumLifetime18W8NoOff =
-            GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2295>, THw8, LOG_LIFETIME>;
-        /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 8,
-        /// and target sum set at 1.1 * expectation (10% offset)
-        /// Note: with chunk size w = 8, chains are very long. This leads to high variance
-        /// and so signing may fail from time to time. It is not recommended to use this.
-        pub type SIGTargetSumLifetime18W8Off10 =
-            GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2525>, THw8, LOG_LIFETIME>;
+            /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 1,
+            /// and target sum set at 1.1 * expectation (10% offset)
+            pub type SIGTargetSumLifetime18W1Off10 = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 25,
+                chunk_size: 1,
+                encoding: TargetSum(80),
+            };
 
-        #[cfg(test)]
+            /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 2,
+            /// and target sum set at expectation
+            pub type SIGTargetSumLifetime18W2NoOff = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 25,
+                chunk_size: 2,
+                encoding: TargetSum(108),
+            };
+
+            /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 2,
+            /// and target sum set at 1.1 * expectation (10% offset)
+            pub type SIGTargetSumLifetime18W2Off10 = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 25,
+                chunk_size: 2,
+                encoding: TargetSum(119),
+            };
+
+            /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 4,
+            /// and target sum set at expectation
+            pub type SIGTargetSumLifetime18W4NoOff = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 26,
+                chunk_size: 4,
+                encoding: TargetSum(270),
+            };
+
+            /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 4,
+            /// and target sum set at 1.1 * expectation (10% offset)
+            pub type SIGTargetSumLifetime18W4Off10 = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 26,
+                chunk_size: 4,
+                encoding: TargetSum(297),
+            };
+
+            /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 8,
+            /// and target sum set at expectation
+            /// Note: with chunk size w = 8, chains are very long. This leads to high variance
+            /// and so signing may fail from time to time. It is not recommended to use this.
+            pub type SIGTargetSumLifetime18W8NoOff = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 28,
+                chunk_size: 8,
+                encoding: TargetSum(2295),
+            };
+
+            /// Instantiation with Lifetime 2^18, Target sum encoding, chunk size w = 8,
+            /// and target sum set at 1.1 * expectation (10% offset)
+            /// Note: with chunk size w = 8, chains are very long. This leads to high variance
+            /// and so signing may fail from time to time. It is not recommended to use this.
+            pub type SIGTargetSumLifetime18W8Off10 = Sha3 {
+                log_lifetime: 18,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 28,
+                chunk_size: 8,
+                encoding: TargetSum(2525),
+            };
+        }
+
+        #[cfg(all(test, feature = "slow-tests"))]
         mod test {
-            use crate::signature::SignatureScheme;
+            use std::marker::PhantomData;
 
-            #[cfg(feature = "slow-tests")]
-            use crate::signature::test_templates::_test_signature_scheme_correctness;
+            use crate::signature::{
+                generalized_xmss::test_templates::_test_rejects_manipulated_signatures,
+                test_templates::_test_signature_scheme_correctness,
+            };
 
             use super::{
                 SIGTargetSumLifetime18W1NoOff, SIGTargetSumLifetime18W1Off10,
@@ -208,122 +428,131 @@ pub mod lifetime_2_to_the_18 {
             };
 
             #[test]
-            pub fn test_w1_internal_consistency() {
-                SIGTargetSumLifetime18W1NoOff::internal_consistency_check();
-                SIGTargetSumLifetime18W1Off10::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w2_internal_consistency() {
-                SIGTargetSumLifetime18W2NoOff::internal_consistency_check();
-                SIGTargetSumLifetime18W2Off10::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w4_internal_consistency() {
-                SIGTargetSumLifetime18W4NoOff::internal_consistency_check();
-                SIGTargetSumLifetime18W4Off10::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w8_internal_consistency() {
-                SIGTargetSumLifetime18W8NoOff::internal_consistency_check();
-                SIGTargetSumLifetime18W8Off10::internal_consistency_check();
-            }
-
-            #[test]
-            #[cfg(feature = "slow-tests")]
             pub fn test_w1_correctness() {
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime18W1NoOff>(1032);
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime18W1Off10>(32);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w1_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime18W1NoOff>,
+                    1032,
+                );
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime18W1Off10>,
+                    32,
+                );
+            }
+            #[test]
             pub fn test_w2_correctness() {
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime18W2NoOff>(436);
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime18W2Off10>(312);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w2_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime18W2NoOff>,
+                    436,
+                );
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime18W2Off10>,
+                    312,
+                );
+            }
+            #[test]
             pub fn test_w4_correctness() {
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime18W4NoOff>(21);
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime18W4Off10>(3211);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w4_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime18W4NoOff>,
+                    21,
+                );
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime18W4Off10>,
+                    3211,
+                );
+            }
+            #[test]
             pub fn test_w8_correctness() {
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime18W8NoOff>(32);
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime18W8Off10>(768);
             }
+            #[test]
+            pub fn test_w8_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime18W8NoOff>,
+                    32,
+                );
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime18W8Off10>,
+                    768,
+                );
+            }
         }
     }
 }
 
 /// Instantiations with Lifetime 2^20
 pub mod lifetime_2_to_the_20 {
-
     /// Instantiations based on the Winternitz encoding
     pub mod winternitz {
-        use crate::{
-            inc_encoding::basic_winternitz::WinternitzEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
-            symmetric::{
-                message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
-            },
-        };
+        crate::declare_instantiation! {
+            /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 1
+            pub type SIGWinternitzLifetime20W1 = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 20,
+                hash_len: 25,
+                chunk_size: 1,
+                encoding: Winternitz,
+            };
 
-        const LOG_LIFETIME: usize = 20;
-        const PARAMETER_LEN: usize = 18;
-        const MESSAGE_HASH_LEN: usize = 18;
-        const RAND_LEN: usize = 20;
-
-        const CHUNK_SIZE_W1: usize = 1;
-        const NUM_CHUNKS_W1: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W1;
-        type MHw1 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W1, CHUNK_SIZE_W1>;
-        const HASH_LEN_W1: usize = 25;
-        type THw1 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W1>;
-        type PRFw1 = ShaPRF<HASH_LEN_W1>;
-        type IEw1 = WinternitzEncoding<MHw1, 8>;
-        /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 1
-        pub type SIGWinternitzLifetime20W1 =
-            GeneralizedXMSSSignatureScheme<PRFw1, IEw1, THw1, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W2: usize = 2;
-        const NUM_CHUNKS_W2: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W2;
-        type MHw2 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W2, CHUNK_SIZE_W2>;
-        const HASH_LEN_W2: usize = 26;
-        type THw2 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W2>;
-        type PRFw2 = ShaPRF<HASH_LEN_W2>;
-        type IEw2 = WinternitzEncoding<MHw2, 4>;
-        /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 2
-        pub type SIGWinternitzLifetime20W2 =
-            GeneralizedXMSSSignatureScheme<PRFw2, IEw2, THw2, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W4: usize = 4;
-        const NUM_CHUNKS_W4: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W4;
-        type MHw4 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W4, CHUNK_SIZE_W4>;
-        const HASH_LEN_W4: usize = 26;
-        type THw4 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W4>;
-        type PRFw4 = ShaPRF<HASH_LEN_W4>;
-        type IEw4 = WinternitzEncoding<MHw4, 3>;
-        /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 4
-        pub type SIGWinternitzLifetime20W4 =
-            GeneralizedXMSSSignatureScheme<PRFw4, IEw4, THw4, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W8: usize = 8;
-        const NUM_CHUNKS_W8: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W8;
-        type MHw8 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W8, CHUNK_SIZE_W8>;
-        const HASH_LEN_W8: usize = 28;
-        type THw8 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W8>;
-        type PRFw8 = ShaPRF<HASH_LEN_W8>;
-        type IEw8 = WinternitzEncoding<MHw8, 2>;
-        /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 8
-        pub type SIGWinternitzLifetime20W8 =
-            GeneralizedXMSSSignatureScheme<PRFw8, IEw8, THw8, LOG_LIFETIME>;
+            /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 2
+            pub type SIGWinternitzLifetime20W2 = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 20,
+                hash_len: 26,
+                chunk_size: 2,
+                encoding: Winternitz,
+            };
 
-        #[cfg(test)]
+            /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 4
+            pub type SIGWinternitzLifetime20W4 = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 20,
+                hash_len: 26,
+                chunk_size: 4,
+                encoding: Winternitz,
+            };
+
+            /// Instantiation with Lifetime 2^20, Winternitz encoding, chunk size w = 8
+            pub type SIGWinternitzLifetime20W8 = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 20,
+                hash_len: 28,
+                chunk_size: 8,
+                encoding: Winternitz,
+            };
+        }
+
+        #[cfg(all(test, feature = "slow-tests"))]
         mod test {
-            #[cfg(feature = "slow-tests")]
-            use crate::signature::test_templates::_test_signature_scheme_correctness;
+            use std::marker::PhantomData;
 
-            use crate::signature::SignatureScheme;
+            use crate::signature::{
+                generalized_xmss::test_templates::_test_rejects_manipulated_signatures,
+                test_templates::_test_signature_scheme_correctness,
+            };
 
             use super::{
                 SIGWinternitzLifetime20W1, SIGWinternitzLifetime20W2, SIGWinternitzLifetime20W4,
@@ -331,134 +560,161 @@ pub mod lifetime_2_to_the_20 {
             };
 
             #[test]
-            pub fn test_w1_internal_consistency() {
-                SIGWinternitzLifetime20W1::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w2_internal_consistency() {
-                SIGWinternitzLifetime20W2::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w4_internal_consistency() {
-                SIGWinternitzLifetime20W4::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w8_internal_consistency() {
-                SIGWinternitzLifetime20W8::internal_consistency_check();
-            }
-
-            #[test]
-            #[cfg(feature = "slow-tests")]
             pub fn test_w1_correctness() {
                 _test_signature_scheme_correctness::<SIGWinternitzLifetime20W1>(1032);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w1_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGWinternitzLifetime20W1>,
+                    1032,
+                );
+            }
+            #[test]
             pub fn test_w2_correctness() {
                 _test_signature_scheme_correctness::<SIGWinternitzLifetime20W2>(32);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w2_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(PhantomData::<SIGWinternitzLifetime20W2>, 32);
+            }
+            #[test]
             pub fn test_w4_correctness() {
                 _test_signature_scheme_correctness::<SIGWinternitzLifetime20W4>(2032);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w4_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGWinternitzLifetime20W4>,
+                    2032,
+                );
+            }
+            #[test]
             pub fn test_w8_correctness() {
                 _test_signature_scheme_correctness::<SIGWinternitzLifetime20W8>(2142);
             }
+            #[test]
+            pub fn test_w8_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGWinternitzLifetime20W8>,
+                    2142,
+                );
+            }
         }
     }
 
     /// Instantiations based on the target sum encoding
     pub mod target_sum {
-        use crate::{
-            inc_encoding::target_sum::TargetSumEncoding,
-            signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
-            symmetric::{
-                message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
-            },
-        };
+        crate::declare_instantiation! {
+            /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 1,
+            /// and target sum set at expectation
+            pub type SIGTargetSumLifetime20W1NoOff = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 25,
+                chunk_size: 1,
+                encoding: TargetSum(72),
+            };
 
-        const LOG_LIFETIME: usize = 20;
-        const PARAMETER_LEN: usize = 18;
-        const MESSAGE_HASH_LEN: usize = 18;
-        const RAND_LEN: usize = 23;
-
-        const CHUNK_SIZE_W1: usize = 1;
-        const NUM_CHUNKS_W1: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W1;
-        type MHw1 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W1, CHUNK_SIZE_W1>;
-        const HASH_LEN_W1: usize = 25;
-        type THw1 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W1>;
-        type PRFw1 = ShaPRF<HASH_LEN_W1>;
-        type IEw1<const TARGET_SUM: usize> = TargetSumEncoding<MHw1, TARGET_SUM>;
-        /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 1,
-        /// and target sum set at expectation
-        pub type SIGTargetSumLifetime20W1NoOff =
-            GeneralizedXMSSSignatureScheme<PRFw1, IEw1<72>, THw1, LOG_LIFETIME>;
-        /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 1,
-        /// and target sum set at 1.1 * expectation (10% offset)
-        pub type SIGTargetSumLifetime20W1Off10 =
-            GeneralizedXMSSSignatureScheme<PRFw1, IEw1<80>, THw1, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W2: usize = 2;
-        const NUM_CHUNKS_W2: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W2;
-        type MHw2 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W2, CHUNK_SIZE_W2>;
-        const HASH_LEN_W2: usize = 26;
-        type THw2 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W2>;
-        type PRFw2 = ShaPRF<HASH_LEN_W2>;
-        type IEw2<const TARGET_SUM: usize> = TargetSumEncoding<MHw2, TARGET_SUM>;
-        /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 2,
-        /// and target sum set at expectation
-        pub type SIGTargetSumLifetime20W2NoOff =
-            GeneralizedXMSSSignatureScheme<PRFw2, IEw2<108>, THw2, LOG_LIFETIME>;
-        /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 2,
-        /// and target sum set at 1.1 * expectation (10% offset)
-        pub type SIGTargetSumLifetime20W2Off10 =
-            GeneralizedXMSSSignatureScheme<PRFw2, IEw2<119>, THw2, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W4: usize = 4;
-        const NUM_CHUNKS_W4: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W4;
-        type MHw4 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W4, CHUNK_SIZE_W4>;
-        const HASH_LEN_W4: usize = 26;
-        type THw4 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W4>;
-        type PRFw4 = ShaPRF<HASH_LEN_W4>;
-        type IEw4<const TARGET_SUM: usize> = TargetSumEncoding<MHw4, TARGET_SUM>;
-        /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 4,
-        /// and target sum set at expectation
-        pub type SIGTargetSumLifetime20W4NoOff =
-            GeneralizedXMSSSignatureScheme<PRFw4, IEw4<270>, THw4, LOG_LIFETIME>;
-        /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 4,
-        /// and target sum set at 1.1 * expectation (10% offset)
-        pub type SIGTargetSumLifetime20W4Off10 =
-            GeneralizedXMSSSignatureScheme<PRFw4, IEw4<297>, THw4, LOG_LIFETIME>;
-
-        const CHUNK_SIZE_W8: usize = 8;
-        const NUM_CHUNKS_W8: usize = MESSAGE_HASH_LEN * 8 / CHUNK_SIZE_W8;
-        type MHw8 = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS_W8, CHUNK_SIZE_W8>;
-        const HASH_LEN_W8: usize = 28;
-        type THw8 = ShaTweakHash<PARAMETER_LEN, HASH_LEN_W8>;
-        type PRFw8 = ShaPRF<HASH_LEN_W8>;
-        type IEw8<const TARGET_SUM: usize> = TargetSumEncoding<MHw8, TARGET_SUM>;
-        /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 8,
-        /// and target sum set at expectation
-        /// Note: with chunk size w = 8, chains are very long. This leads to high variance
-        /// and so signing may fail from time to time. It is not recommended to use this.
-        pub type SIGTargetSumLifetime20W8NoOff =
-            GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2295>, THw8, LOG_LIFETIME>;
-        /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 8,
-        /// and target sum set at 1.1 * expectation (10% offset)
-        /// Note: with chunk size w = 8, chains are very long. This leads to high variance
-        /// and so signing may fail from time to time. It is not recommended to use this.
-        pub type SIGTargetSumLifetime20W8Off10 =
-            GeneralizedXMSSSignatureScheme<PRFw8, IEw8<2525>, THw8, LOG_LIFETIME>;
+            /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 1,
+            /// and target sum set at 1.1 * expectation (10% offset)
+            pub type SIGTargetSumLifetime20W1Off10 = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 25,
+                chunk_size: 1,
+                encoding: TargetSum(80),
+            };
 
-        #[cfg(test)]
+            /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 2,
+            /// and target sum set at expectation
+            pub type SIGTargetSumLifetime20W2NoOff = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 26,
+                chunk_size: 2,
+                encoding: TargetSum(108),
+            };
+
+            /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 2,
+            /// and target sum set at 1.1 * expectation (10% offset)
+            pub type SIGTargetSumLifetime20W2Off10 = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 26,
+                chunk_size: 2,
+                encoding: TargetSum(119),
+            };
+
+            /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 4,
+            /// and target sum set at expectation
+            pub type SIGTargetSumLifetime20W4NoOff = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 26,
+                chunk_size: 4,
+                encoding: TargetSum(270),
+            };
+
+            /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 4,
+            /// and target sum set at 1.1 * expectation (10% offset)
+            pub type SIGTargetSumLifetime20W4Off10 = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 26,
+                chunk_size: 4,
+                encoding: TargetSum(297),
+            };
+
+            /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 8,
+            /// and target sum set at expectation
+            /// Note: with chunk size w = 8, chains are very long. This leads to high variance
+            /// and so signing may fail from time to time. It is not recommended to use this.
+            pub type SIGTargetSumLifetime20W8NoOff = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 28,
+                chunk_size: 8,
+                encoding: TargetSum(2295),
+            };
+
+            /// Instantiation with Lifetime 2^20, Target sum encoding, chunk size w = 8,
+            /// and target sum set at 1.1 * expectation (10% offset)
+            /// Note: with chunk size w = 8, chains are very long. This leads to high variance
+            /// and so signing may fail from time to time. It is not recommended to use this.
+            pub type SIGTargetSumLifetime20W8Off10 = Sha3 {
+                log_lifetime: 20,
+                parameter_len: 18,
+                message_hash_len: 18,
+                rand_len: 23,
+                hash_len: 28,
+                chunk_size: 8,
+                encoding: TargetSum(2525),
+            };
+        }
+
+        #[cfg(all(test, feature = "slow-tests"))]
         mod test {
-            #[cfg(feature = "slow-tests")]
-            use crate::signature::test_templates::_test_signature_scheme_correctness;
+            use std::marker::PhantomData;
 
-            use crate::signature::SignatureScheme;
+            use crate::signature::{
+                generalized_xmss::test_templates::_test_rejects_manipulated_signatures,
+                test_templates::_test_signature_scheme_correctness,
+            };
 
             use super::{
                 SIGTargetSumLifetime20W1NoOff, SIGTargetSumLifetime20W1Off10,
@@ -468,50 +724,69 @@ pub mod lifetime_2_to_the_20 {
             };
 
             #[test]
-            pub fn test_w1_internal_consistency() {
-                SIGTargetSumLifetime20W1NoOff::internal_consistency_check();
-                SIGTargetSumLifetime20W1Off10::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w2_internal_consistency() {
-                SIGTargetSumLifetime20W2NoOff::internal_consistency_check();
-                SIGTargetSumLifetime20W2Off10::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w4_internal_consistency() {
-                SIGTargetSumLifetime20W4NoOff::internal_consistency_check();
-                SIGTargetSumLifetime20W4Off10::internal_consistency_check();
-            }
-            #[test]
-            pub fn test_w8_internal_consistency() {
-                SIGTargetSumLifetime20W8NoOff::internal_consistency_check();
-                SIGTargetSumLifetime20W8Off10::internal_consistency_check();
-            }
-
-            #[test]
-            #[cfg(feature = "slow-tests")]
             pub fn test_w1_correctness() {
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime20W1NoOff>(932);
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime20W1Off10>(321);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w1_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime20W1NoOff>,
+                    932,
+                );
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime20W1Off10>,
+                    321,
+                );
+            }
+            #[test]
             pub fn test_w2_correctness() {
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime20W2NoOff>(54);
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime20W2Off10>(435);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w2_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime20W2NoOff>,
+                    54,
+                );
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime20W2Off10>,
+                    435,
+                );
+            }
+            #[test]
             pub fn test_w4_correctness() {
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime20W4NoOff>(3435);
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime20W4Off10>(3424);
             }
             #[test]
-            #[cfg(feature = "slow-tests")]
+            pub fn test_w4_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime20W4NoOff>,
+                    3435,
+                );
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime20W4Off10>,
+                    3424,
+                );
+            }
+            #[test]
             pub fn test_w8_correctness() {
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime20W8NoOff>(3241);
                 _test_signature_scheme_correctness::<SIGTargetSumLifetime20W8Off10>(34);
             }
+            #[test]
+            pub fn test_w8_rejects_manipulations() {
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime20W8NoOff>,
+                    3241,
+                );
+                _test_rejects_manipulated_signatures(
+                    PhantomData::<SIGTargetSumLifetime20W8Off10>,
+                    34,
+                );
+            }
         }
     }
 }
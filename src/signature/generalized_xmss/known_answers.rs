@@ -0,0 +1,296 @@
+//! Known-answer tests for all registered parameter sets, against the
+//! JSON files in `testdata/kat`, one per parameter set.
+//!
+//! Each file fixes a seed, an epoch, a message, and the randomness of a
+//! signature, and pins everything that is derived from them: the parameter
+//! of the key pair generated from the seed, the codeword, the chain values
+//! of the signature, and the one-time public key of the epoch, i.e., the
+//! leaf of the Merkle tree. All bytes are in lowercase hex, in the encoding
+//! of `bincode`.
+//!
+//! Note: the root of the Merkle tree and the authentication path would
+//! require generating all 2^18 or 2^20 leafs, which takes far too long for
+//! a test. They are built from the leafs by the same functions for all
+//! instantiations, and pinned for small lifetimes by the other tests.
+//!
+//! After a deliberate change of the derivation or the encoding, the files
+//! are regenerated with
+//! `cargo test --release --lib known_answers -- --ignored`.
+
+use std::{marker::PhantomData, path::PathBuf};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    inc_encoding::IncomparableEncoding,
+    signature::param_set::ParamSetId,
+    symmetric::{
+        prf::Pseudorandom,
+        seed_expander::{SeedExpander, SEED_LENGTH},
+        tweak_hash::{chain, TweakableHash},
+        tweak_hash_tree::ots_public_key_to_leaf,
+    },
+    MESSAGE_LENGTH,
+};
+
+use super::{
+    instantiations_sha::{lifetime_2_to_the_10, lifetime_2_to_the_18, lifetime_2_to_the_20},
+    GeneralizedXMSSSignatureScheme,
+};
+
+/// Contents of a known-answer file
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct KnownAnswer {
+    param_set: String,
+    seed: String,
+    parameter: String,
+    epoch: u32,
+    message: String,
+    randomness: String,
+    chunks: Vec<u16>,
+    chain_values: Vec<String>,
+    one_time_public_key: String,
+}
+
+fn to_hex<T: Serialize>(value: &T) -> String {
+    bincode::serialize(value)
+        .unwrap()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn from_hex<T: DeserializeOwned>(hex: &str) -> T {
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect();
+    bincode::deserialize(&bytes).unwrap()
+}
+
+fn path(param_set: ParamSetId) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata/kat")
+        .join(format!("{}.json", param_set.name()))
+}
+
+/// Derives the known answer of an instantiation from the inputs in `inputs`,
+/// i.e., seed, epoch, message, and randomness. All other fields of `inputs`
+/// are ignored. If the randomness is empty, it is sampled from an RNG seeded
+/// with the epoch until the message can be encoded.
+fn derive<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+>(
+    _scheme: PhantomData<GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>>,
+    inputs: &KnownAnswer,
+) -> KnownAnswer
+where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter> + Serialize,
+    TH::Domain: Serialize,
+    IE::Randomness: Serialize + DeserializeOwned,
+{
+    type Sig<PRF, IE, TH, const LOG_LIFETIME: usize> =
+        GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+    // parameter and PRF key are drawn as in `key_gen_from_seed`
+    let seed: [u8; SEED_LENGTH] = from_hex(&inputs.seed);
+    let mut rng = SeedExpander::new(&Sig::<PRF, IE, TH, LOG_LIFETIME>::seed_identifier(), &seed);
+    let parameter = TH::rand_parameter(&mut rng);
+    let prf_key = PRF::gen(&mut rng);
+
+    let epoch = inputs.epoch;
+    let message: [u8; MESSAGE_LENGTH] = from_hex(&inputs.message);
+    let encode = |rho: &IE::Randomness| IE::encode(&parameter.into(), &message, rho, epoch);
+    let rho = if inputs.randomness.is_empty() {
+        let mut rng = ChaCha20Rng::seed_from_u64(epoch as u64);
+        std::iter::repeat_with(|| IE::rand(&mut rng))
+            .find(|rho| encode(rho).is_ok())
+            .unwrap()
+    } else {
+        from_hex(&inputs.randomness)
+    };
+    let x = encode(&rho).expect("Known answer: randomness does not encode the message");
+
+    // the signature walks each chain as far as the codeword says,
+    // and the chain ends are the rest of the way
+    let chain_length = IE::chain_length();
+    let walk = |chain_index: usize, start: &TH::Domain, from: u16, steps: usize| {
+        chain::<TH>(&parameter, epoch, chain_index as u16, from, steps, start)
+    };
+    let chain_values: Vec<TH::Domain> = x
+        .iter()
+        .enumerate()
+        .map(|(chain_index, &xi)| {
+            let start = PRF::apply(&prf_key, epoch, chain_index as u64).into();
+            walk(chain_index, &start, 0, xi as usize)
+        })
+        .collect();
+    let chain_ends = x
+        .iter()
+        .zip(&chain_values)
+        .enumerate()
+        .map(|(chain_index, (&xi, value))| {
+            walk(chain_index, value, xi, chain_length - xi as usize)
+        });
+    let leaf = ots_public_key_to_leaf::<TH, _>(&parameter, epoch, chain_ends);
+
+    KnownAnswer {
+        param_set: inputs.param_set.clone(),
+        seed: inputs.seed.clone(),
+        parameter: to_hex(&parameter),
+        epoch,
+        message: inputs.message.clone(),
+        randomness: to_hex(&rho),
+        chunks: x,
+        chain_values: chain_values.iter().map(to_hex).collect(),
+        one_time_public_key: to_hex(&leaf),
+    }
+}
+
+/// Derives the known answer of the given parameter set, see `derive`
+fn derive_for(param_set: ParamSetId, inputs: &KnownAnswer) -> KnownAnswer {
+    use lifetime_2_to_the_10::winternitz::*;
+    use lifetime_2_to_the_18::{target_sum::*, winternitz::*};
+    use lifetime_2_to_the_20::{target_sum::*, winternitz::*};
+
+    macro_rules! dispatch {
+        ($($variant:ident => $scheme:ident,)*) => {
+            match param_set {
+                $(ParamSetId::$variant => derive(PhantomData::<$scheme>, inputs),)*
+            }
+        };
+    }
+    dispatch! {
+        ShaWinternitzLifetime18W1 => SIGWinternitzLifetime18W1,
+        ShaWinternitzLifetime18W2 => SIGWinternitzLifetime18W2,
+        ShaWinternitzLifetime18W4 => SIGWinternitzLifetime18W4,
+        ShaWinternitzLifetime18W8 => SIGWinternitzLifetime18W8,
+        ShaTargetSumLifetime18W1NoOff => SIGTargetSumLifetime18W1NoOff,
+        ShaTargetSumLifetime18W1Off10 => SIGTargetSumLifetime18W1Off10,
+        ShaTargetSumLifetime18W2NoOff => SIGTargetSumLifetime18W2NoOff,
+        ShaTargetSumLifetime18W2Off10 => SIGTargetSumLifetime18W2Off10,
+        ShaTargetSumLifetime18W4NoOff => SIGTargetSumLifetime18W4NoOff,
+        ShaTargetSumLifetime18W4Off10 => SIGTargetSumLifetime18W4Off10,
+        ShaTargetSumLifetime18W8NoOff => SIGTargetSumLifetime18W8NoOff,
+        ShaTargetSumLifetime18W8Off10 => SIGTargetSumLifetime18W8Off10,
+        ShaWinternitzLifetime20W1 => SIGWinternitzLifetime20W1,
+        ShaWinternitzLifetime20W2 => SIGWinternitzLifetime20W2,
+        ShaWinternitzLifetime20W4 => SIGWinternitzLifetime20W4,
+        ShaWinternitzLifetime20W8 => SIGWinternitzLifetime20W8,
+        ShaTargetSumLifetime20W1NoOff => SIGTargetSumLifetime20W1NoOff,
+        ShaTargetSumLifetime20W1Off10 => SIGTargetSumLifetime20W1Off10,
+        ShaTargetSumLifetime20W2NoOff => SIGTargetSumLifetime20W2NoOff,
+        ShaTargetSumLifetime20W2Off10 => SIGTargetSumLifetime20W2Off10,
+        ShaTargetSumLifetime20W4NoOff => SIGTargetSumLifetime20W4NoOff,
+        ShaTargetSumLifetime20W4Off10 => SIGTargetSumLifetime20W4Off10,
+        ShaTargetSumLifetime20W8NoOff => SIGTargetSumLifetime20W8NoOff,
+        ShaTargetSumLifetime20W8Off10 => SIGTargetSumLifetime20W8Off10,
+        ShaWinternitzLifetime10W4 => SIGWinternitzLifetime10W4,
+    }
+}
+
+#[test]
+fn test_derive_matches_scheme() {
+    use crate::{
+        inc_encoding::basic_winternitz::WinternitzEncoding,
+        signature::SignatureScheme,
+        symmetric::{
+            message_hash::sha::ShaMessageHash192x3,
+            prf::sha::ShaPRF,
+            tweak_hash::sha::ShaTweak192192,
+            tweak_hash_tree::{hash_tree_path, hash_tree_verify_leaf_hash},
+        },
+    };
+
+    // Note: do not use these parameters, they are just for testing
+    type IE = WinternitzEncoding<ShaMessageHash192x3, 3>;
+    type TH = ShaTweak192192;
+    type Sig = GeneralizedXMSSSignatureScheme<ShaPRF<24>, IE, TH, 5>;
+
+    // the derived values are those of the key pair generated from the seed,
+    // i.e., they form a valid signature together with the Merkle path
+    let seed = [0x5a; SEED_LENGTH];
+    let message = [0x17; MESSAGE_LENGTH];
+    let inputs = KnownAnswer {
+        param_set: String::new(),
+        seed: to_hex(&seed),
+        parameter: String::new(),
+        epoch: 13,
+        message: to_hex(&message),
+        randomness: String::new(),
+        chunks: Vec::new(),
+        chain_values: Vec::new(),
+        one_time_public_key: String::new(),
+    };
+    let known_answer = derive(PhantomData::<Sig>, &inputs);
+    let (pk, sk) = Sig::key_gen_from_seed(seed);
+    assert_eq!(known_answer.parameter, to_hex(&pk.parameter));
+    let path = hash_tree_path(&sk.tree, 13);
+    assert!(hash_tree_verify_leaf_hash(
+        &pk.parameter,
+        &pk.root,
+        13,
+        &from_hex(&known_answer.one_time_public_key),
+        &path
+    ));
+
+    let chain_values = known_answer
+        .chain_values
+        .iter()
+        .map(|value| from_hex(value));
+    let signature = super::GeneralizedXMSSSignature::<IE, TH>::from_chain_values(
+        path,
+        from_hex(&known_answer.randomness),
+        chain_values.collect(),
+    )
+    .unwrap();
+    assert!(Sig::verify(&pk, 13, &message, &signature));
+}
+
+#[test]
+fn test_known_answers() {
+    for &param_set in ParamSetId::ALL {
+        let json = std::fs::read_to_string(path(param_set))
+            .unwrap_or_else(|e| panic!("{}: cannot read known answer: {}", param_set.name(), e));
+        let expected: KnownAnswer = serde_json::from_str(&json).unwrap();
+        assert_eq!(expected.param_set, param_set.name());
+        assert_eq!(
+            derive_for(param_set, &expected),
+            expected,
+            "{}: known answer does not match",
+            param_set.name()
+        );
+    }
+}
+
+#[test]
+#[ignore = "regenerates the known-answer files"]
+fn generate_known_answers() {
+    for &param_set in ParamSetId::ALL {
+        // the inputs only depend on the identifier of the parameter set
+        let id = param_set.as_id();
+        let seed: [u8; SEED_LENGTH] = std::array::from_fn(|i| i as u8 ^ id as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| (i as u8).wrapping_mul(7));
+        let inputs = KnownAnswer {
+            param_set: param_set.name().to_string(),
+            seed: to_hex(&seed),
+            parameter: String::new(),
+            epoch: (id as u32).wrapping_mul(0x9e37) % (1 << param_set.describe().log_lifetime),
+            message: to_hex(&message),
+            randomness: String::new(),
+            chunks: Vec::new(),
+            chain_values: Vec::new(),
+            one_time_public_key: String::new(),
+        };
+        let known_answer = derive_for(param_set, &inputs);
+        let json = serde_json::to_string_pretty(&known_answer).unwrap() + "\n";
+        std::fs::create_dir_all(path(param_set).parent().unwrap()).unwrap();
+        std::fs::write(path(param_set), json).unwrap();
+    }
+}
@@ -0,0 +1,357 @@
+use std::ops::ControlFlow;
+
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{
+    inc_encoding::IncomparableEncoding,
+    parallelism::map_range,
+    signature::SigningError,
+    symmetric::{
+        prf::Pseudorandom,
+        tweak_hash::{chain, TweakableHash},
+        tweak_hash_tree::{
+            build_tree_cancellable, hash_tree_path, hash_tree_root, hash_tree_verify_leaf_hash,
+            ots_public_key_to_leaf,
+        },
+    },
+    MESSAGE_LENGTH,
+};
+
+use super::{
+    public_key_hash, GeneralizedXMSSPublicKey, GeneralizedXMSSSecretKey, GeneralizedXMSSSignature,
+};
+
+/// Slotted mode of the generalized XMSS signature scheme, in which one key can
+/// sign `NUM_SLOTS` independent messages per epoch, one per slot. This allows,
+/// e.g., to sign two 128-bit values with one key at one epoch.
+///
+/// The one-time key of an epoch has `NUM_SLOTS * IE::NUM_CHUNKS` chains, which
+/// are partitioned into the slots: slot `slot` uses the chains with indices
+/// `slot * IE::NUM_CHUNKS + i`. Each slot encodes its message with its own
+/// index in the message hash, see `message_hash_index`, and is signed with its
+/// own chains. The leaf of an epoch commits to the chain ends of all slots, so
+/// a signature for a slot contains the values in the chains of that slot, and
+/// the chain ends of all other slots. Verification checks the slot it is asked
+/// about, so a signature for one slot does not verify for another.
+///
+/// Note: every slot is still one-time. Signing two messages for the same slot
+/// of the same epoch is as insecure as signing two messages for the same epoch
+/// with `GeneralizedXMSSSignatureScheme`. Keys of the slotted mode must only be
+/// used in slotted mode.
+pub struct SlottedSignatureScheme<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+    const NUM_SLOTS: usize,
+> {
+    _marker_prf: std::marker::PhantomData<PRF>,
+    _marker_ie: std::marker::PhantomData<IE>,
+    _marker_th: std::marker::PhantomData<TH>,
+}
+
+impl<
+        PRF: Pseudorandom,
+        IE: IncomparableEncoding,
+        TH: TweakableHash,
+        const LOG_LIFETIME: usize,
+        const NUM_SLOTS: usize,
+    > SlottedSignatureScheme<PRF, IE, TH, LOG_LIFETIME, NUM_SLOTS>
+where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    /// number of epochs that are supported, each with `NUM_SLOTS` slots
+    pub const LIFETIME: u64 = 1 << LOG_LIFETIME;
+
+    /// number of chains of the one-time key of an epoch
+    const NUM_CHAINS: usize = NUM_SLOTS * IE::NUM_CHUNKS;
+
+    /// Fails to compile if there are no slots, if the chains of an epoch cannot
+    /// be indexed in the chain tweak, or if the message hash indices of all slots
+    /// and epochs do not fit into 32 bits.
+    const SLOTS_VALID: () = {
+        assert!(
+            NUM_SLOTS > 0,
+            "Slotted Signature Scheme: There must be at least one slot"
+        );
+        assert!(
+            NUM_SLOTS * IE::NUM_CHUNKS <= 1 << 16,
+            "Slotted Signature Scheme: Chains of all slots must have 16-bit indices"
+        );
+        assert!(
+            LOG_LIFETIME <= 32 && (NUM_SLOTS as u64) << LOG_LIFETIME <= 1 << 32,
+            "Slotted Signature Scheme: Message hash indices of all slots must fit into 32 bits"
+        );
+    };
+
+    /// Returns the index with which the message of the given slot of the given
+    /// epoch is hashed, i.e., the value that the message hash uses as its epoch.
+    /// The slots of all epochs have distinct indices, so that the message hash
+    /// of each slot is domain-separated from that of all other slots.
+    pub fn message_hash_index(epoch: u32, slot: usize) -> u32 {
+        let () = Self::SLOTS_VALID;
+        assert!(
+            (epoch as u64) < Self::LIFETIME,
+            "Slotted Signature Scheme: Epoch too large."
+        );
+        assert!(
+            slot < NUM_SLOTS,
+            "Slotted Signature Scheme: Slot too large."
+        );
+        (slot as u64 * Self::LIFETIME + epoch as u64) as u32
+    }
+
+    /// Generates a new key pair, in which the one-time key of each epoch
+    /// has the chains of all slots. See `SignatureScheme::gen`.
+    pub fn gen<R: RngCore + CryptoRng>(
+        rng: &mut R,
+    ) -> (
+        GeneralizedXMSSPublicKey<TH>,
+        GeneralizedXMSSSecretKey<PRF, TH>,
+    ) {
+        let () = Self::SLOTS_VALID;
+        let parameter = TH::rand_parameter(rng);
+        let prf_key = PRF::gen(rng);
+
+        // the leaf of each epoch is the hash of the chain ends of all slots
+        let sequential = !cfg!(feature = "parallel");
+        let chain_length = IE::chain_length();
+        let leafs_hashes = map_range(sequential, 0..Self::LIFETIME, |epoch| {
+            let chain_ends: Vec<_> = (0..Self::NUM_CHAINS)
+                .map(|chain_index| {
+                    let start = PRF::apply(&prf_key, epoch as u32, chain_index as u64).into();
+                    chain::<TH>(
+                        &parameter,
+                        epoch as u32,
+                        chain_index as u16,
+                        0,
+                        chain_length,
+                        &start,
+                    )
+                })
+                .collect();
+            public_key_hash::<TH>(&parameter, epoch as u32, &chain_ends)
+        });
+        let tree = build_tree_cancellable(&parameter, leafs_hashes, sequential, |_| {
+            ControlFlow::Continue(())
+        })
+        .expect("Slotted Signature Scheme: Key generation is never cancelled");
+        let root = hash_tree_root(&tree);
+
+        let pk = GeneralizedXMSSPublicKey { root, parameter };
+        let sk = GeneralizedXMSSSecretKey {
+            prf_key,
+            tree,
+            parameter,
+        };
+        (pk, sk)
+    }
+
+    /// Signs a message with respect to the given slot of the given epoch.
+    /// Each slot of each epoch must be used at most once.
+    pub fn sign<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        sk: &GeneralizedXMSSSecretKey<PRF, TH>,
+        epoch: u32,
+        slot: usize,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<GeneralizedXMSSSignature<IE, TH>, SigningError> {
+        let index = Self::message_hash_index(epoch, slot);
+
+        // the secret key may be of another lifetime, see `SigningError::InvalidSecretKey`
+        if sk.tree.depth() != LOG_LIFETIME {
+            return Err(SigningError::InvalidSecretKey);
+        }
+        let path = hash_tree_path(&sk.tree, epoch);
+
+        // encode the message with the index of the slot, and
+        // retry until we get a valid codeword, or until we give up
+        let mut encoding = None;
+        for _ in 0..IE::MAX_TRIES {
+            let rho = IE::rand(rng);
+            if let Ok(x) = IE::encode(&sk.parameter.into(), message, &rho, index) {
+                encoding = Some((rho, x));
+                break;
+            }
+        }
+        let Some((rho, x)) = encoding else {
+            return Err(SigningError::UnluckyFailure);
+        };
+
+        // the signer walks the chains of the slot as far as the codeword
+        // says, and the chains of all other slots to their ends
+        let first_chain = slot * IE::NUM_CHUNKS;
+        let hashes = (0..Self::NUM_CHAINS)
+            .map(|chain_index| {
+                let steps = match chain_index.checked_sub(first_chain).and_then(|i| x.get(i)) {
+                    Some(&xi) => xi as usize,
+                    None => IE::chain_length(),
+                };
+                let start = PRF::apply(&sk.prf_key, epoch, chain_index as u64).into();
+                chain::<TH>(&sk.parameter, epoch, chain_index as u16, 0, steps, &start)
+            })
+            .collect();
+
+        Ok(GeneralizedXMSSSignature { path, rho, hashes })
+    }
+
+    /// Verifies a signature with respect to public key, epoch, slot, and message.
+    #[must_use = "a signature must be rejected if verification fails"]
+    pub fn verify(
+        pk: &GeneralizedXMSSPublicKey<TH>,
+        epoch: u32,
+        slot: usize,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &GeneralizedXMSSSignature<IE, TH>,
+    ) -> bool {
+        let index = Self::message_hash_index(epoch, slot);
+
+        // a signature has one value for each chain of each slot
+        if sig.hashes.len() != Self::NUM_CHAINS
+            || sig.path.depth() != LOG_LIFETIME
+            || !sig.hashes.iter().all(TH::is_canonical)
+            || !sig.path.co_path().iter().all(TH::is_canonical)
+        {
+            return false;
+        }
+
+        // get back the codeword of the slot, with the index of the slot
+        let Ok(x) = IE::encode(&pk.parameter.into(), message, &sig.rho, index) else {
+            return false;
+        };
+
+        // a chunk beyond the end of its chain cannot be valid, and the
+        // number of remaining steps below would underflow otherwise
+        let chain_length = IE::chain_length();
+        if x.iter().any(|&xi| xi as usize > chain_length) {
+            return false;
+        }
+
+        // the chains of the slot are walked to their ends, and the
+        // values for the chains of all other slots are their ends
+        let first_chain = slot * IE::NUM_CHUNKS;
+        let chain_ends =
+            sig.hashes.iter().enumerate().map(|(chain_index, value)| {
+                match chain_index.checked_sub(first_chain).and_then(|i| x.get(i)) {
+                    Some(&xi) => chain::<TH>(
+                        &pk.parameter,
+                        epoch,
+                        chain_index as u16,
+                        xi,
+                        chain_length - xi as usize,
+                        value,
+                    ),
+                    None => *value,
+                }
+            });
+
+        // the chain ends of all slots must be the leaf of the epoch
+        let leaf_hash = ots_public_key_to_leaf::<TH, _>(&pk.parameter, epoch, chain_ends);
+        hash_tree_verify_leaf_hash(&pk.parameter, &pk.root, epoch, &leaf_hash, &sig.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        inc_encoding::basic_winternitz::WinternitzEncoding,
+        signature::{generalized_xmss::GeneralizedXMSSSignatureScheme, SignatureScheme},
+        symmetric::{
+            message_hash::sha::ShaMessageHash192x3, prf::sha::ShaPRF,
+            tweak_hash::sha::ShaTweak192192,
+        },
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type Prf = ShaPRF<24>;
+    type IE = WinternitzEncoding<ShaMessageHash192x3, 3>;
+    type TH = ShaTweak192192;
+    type Slotted = SlottedSignatureScheme<Prf, IE, TH, 5, 2>;
+    type Sig = GeneralizedXMSSSignatureScheme<Prf, IE, TH, 5>;
+
+    #[test]
+    fn test_message_hash_indices_distinct() {
+        let mut indices = HashSet::new();
+        for epoch in 0..Slotted::LIFETIME as u32 {
+            for slot in 0..2 {
+                assert!(indices.insert(Slotted::message_hash_index(epoch, slot)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_slots_verify_independently() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Slotted::gen(&mut rng);
+        let epoch = 13;
+        let first: [u8; MESSAGE_LENGTH] = rng.gen();
+        let second: [u8; MESSAGE_LENGTH] = rng.gen();
+        let sig_first = Slotted::sign(&mut rng, &sk, epoch, 0, &first).unwrap();
+        let sig_second = Slotted::sign(&mut rng, &sk, epoch, 1, &second).unwrap();
+        assert_eq!(sig_first.chain_values().len(), 2 * IE::NUM_CHUNKS);
+
+        // each slot verifies its own message
+        assert!(Slotted::verify(&pk, epoch, 0, &first, &sig_first));
+        assert!(Slotted::verify(&pk, epoch, 1, &second, &sig_second));
+        assert!(!Slotted::verify(&pk, epoch, 0, &second, &sig_first));
+        assert!(!Slotted::verify(&pk, epoch, 1, &first, &sig_second));
+    }
+
+    #[test]
+    fn test_cross_slot_substitution_fails() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Slotted::gen(&mut rng);
+        let epoch = 7;
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let sig = Slotted::sign(&mut rng, &sk, epoch, 0, &message).unwrap();
+        assert!(Slotted::verify(&pk, epoch, 0, &message, &sig));
+
+        // a signature for one slot is not valid for another slot,
+        // for another epoch, or in the generalized XMSS scheme
+        assert!(!Slotted::verify(&pk, epoch, 1, &message, &sig));
+        assert!(!Slotted::verify(&pk, epoch + 1, 0, &message, &sig));
+        assert!(!Sig::verify(&pk, epoch, &message, &sig));
+
+        // moving the chain values of the slot into the chains of the
+        // other slot does not give a signature for the other slot
+        let values = sig.chain_values();
+        let (first, second) = values.split_at(IE::NUM_CHUNKS);
+        let swapped = GeneralizedXMSSSignature::<IE, TH> {
+            path: sig.path().clone(),
+            rho: *sig.rho(),
+            hashes: [second, first].concat(),
+        };
+        assert!(!Slotted::verify(&pk, epoch, 1, &message, &swapped));
+    }
+
+    #[test]
+    fn test_chunk_beyond_chain_rejected() {
+        use crate::signature::generalized_xmss::tests::ShortChainEncoding;
+
+        type Slotted = SlottedSignatureScheme<Prf, ShortChainEncoding, TH, 4, 2>;
+
+        let mut rng = thread_rng();
+        let (pk, sk) = Slotted::gen(&mut rng);
+        let (epoch, slot) = (11, 1);
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let sig = Slotted::sign(&mut rng, &sk, epoch, slot, &message).unwrap();
+
+        let index = Slotted::message_hash_index(epoch, slot);
+        let x = ShortChainEncoding::encode(&pk.parameter, &message, &sig.rho, index).unwrap();
+        assert!(x.iter().any(|&xi| xi > 1));
+        assert!(!Slotted::verify(&pk, epoch, slot, &message, &sig));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_slot_out_of_range() {
+        Slotted::message_hash_index(0, 2);
+    }
+}
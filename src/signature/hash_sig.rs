@@ -0,0 +1,207 @@
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{
+    inc_encoding::{
+        basic_winternitz::{num_chunks_checksum, WinternitzEncoding},
+        MAX_NUM_CHUNKS,
+    },
+    symmetric::{
+        message_hash::MessageHash, prf::Pseudorandom, seed_expander::SEED_LENGTH,
+        tweak_hash::TweakableHash,
+    },
+    MESSAGE_LENGTH,
+};
+
+use super::{generalized_xmss::GeneralizedXMSSSignatureScheme, SignatureScheme, SigningError};
+
+/// Signature scheme wiring a message hash, a tweakable hash, and a PRF together,
+/// using the generalized XMSS scheme with the Winternitz encoding as OTS.
+///
+/// In contrast to using `GeneralizedXMSSSignatureScheme` directly, the building
+/// blocks are checked to be compatible at compile time. That is, the message hash
+/// has a valid chunk size, NUM_CHUNKS_CHECKSUM matches its number of chunks (see
+/// `num_chunks_checksum`), the PRF outputs and parameters have the lengths that
+/// the tweakable hash expects, and the lifetime is supported. Also, the domain of
+/// the tweakable hash must have at least as many bits as the output of the message
+/// hash, as otherwise the hash chains would be the weakest part of the scheme:
+///
+/// ```compile_fail
+/// use hashsig::prelude::*;
+/// use hashsig::signature::hash_sig::HashSigScheme;
+///
+/// // the message hash has 36 chunks of 4 bits, which needs 3 checksum chunks
+/// type Sig = HashSigScheme<ShaPRF<26>, ShaMessageHash<18, 20, 36, 4>, ShaTweakHash<18, 26>, 2, 10>;
+/// let _ = Sig::key_gen_from_seed([0; SEED_LENGTH]);
+/// ```
+pub struct HashSigScheme<
+    PRF: Pseudorandom,
+    MH: MessageHash,
+    TH: TweakableHash,
+    const NUM_CHUNKS_CHECKSUM: usize,
+    const LOG_LIFETIME: usize,
+> {
+    _marker_prf: std::marker::PhantomData<PRF>,
+    _marker_mh: std::marker::PhantomData<MH>,
+    _marker_th: std::marker::PhantomData<TH>,
+}
+
+/// The generalized XMSS scheme that a `HashSigScheme` uses
+type Inner<PRF, MH, TH, const NUM_CHUNKS_CHECKSUM: usize, const LOG_LIFETIME: usize> =
+    GeneralizedXMSSSignatureScheme<
+        PRF,
+        WinternitzEncoding<MH, NUM_CHUNKS_CHECKSUM>,
+        TH,
+        LOG_LIFETIME,
+    >;
+
+impl<
+        PRF: Pseudorandom,
+        MH: MessageHash,
+        TH: TweakableHash,
+        const NUM_CHUNKS_CHECKSUM: usize,
+        const LOG_LIFETIME: usize,
+    > HashSigScheme<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME>
+{
+    /// Fails to compile if the building blocks are not compatible. It is
+    /// evaluated by all functions of the scheme, and can be evaluated in
+    /// a constant to check a combination without using it.
+    pub const CHECK: () = {
+        assert!(
+            MH::CHUNK_SIZE == 1
+                || MH::CHUNK_SIZE == 2
+                || MH::CHUNK_SIZE == 4
+                || MH::CHUNK_SIZE == 8,
+            "HashSig: Chunk Size of the message hash must be 1, 2, 4, or 8"
+        );
+        assert!(
+            NUM_CHUNKS_CHECKSUM == num_chunks_checksum(MH::NUM_CHUNKS, MH::CHUNK_SIZE),
+            "HashSig: Number of checksum chunks does not match the message hash"
+        );
+        assert!(
+            MH::NUM_CHUNKS + NUM_CHUNKS_CHECKSUM <= MAX_NUM_CHUNKS,
+            "HashSig: Number of chains must be at most MAX_NUM_CHUNKS"
+        );
+        assert!(
+            std::mem::size_of::<PRF::Output>() == std::mem::size_of::<TH::Domain>(),
+            "HashSig: PRF output length must match the hash length of the tweakable hash"
+        );
+        assert!(
+            8 * std::mem::size_of::<TH::Domain>() >= MH::NUM_CHUNKS * MH::CHUNK_SIZE,
+            "HashSig: Hash length of the tweakable hash must be at least the output length of the message hash"
+        );
+        assert!(
+            std::mem::size_of::<MH::Parameter>() == std::mem::size_of::<TH::Parameter>(),
+            "HashSig: Parameter lengths of message hash and tweakable hash must match"
+        );
+        assert!(
+            LOG_LIFETIME > 0 && LOG_LIFETIME <= 32,
+            "HashSig: Lifetime must be between 2^1 and 2^32"
+        );
+    };
+}
+
+impl<
+        PRF: Pseudorandom,
+        MH: MessageHash,
+        TH: TweakableHash,
+        const NUM_CHUNKS_CHECKSUM: usize,
+        const LOG_LIFETIME: usize,
+    > SignatureScheme for HashSigScheme<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME>
+where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<MH::Parameter>,
+{
+    type PublicKey =
+        <Inner<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME> as SignatureScheme>::PublicKey;
+
+    type SecretKey =
+        <Inner<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME> as SignatureScheme>::SecretKey;
+
+    type Signature =
+        <Inner<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME> as SignatureScheme>::Signature;
+
+    const LIFETIME: u64 = 1 << LOG_LIFETIME;
+
+    fn gen<R: RngCore + CryptoRng>(rng: &mut R) -> (Self::PublicKey, Self::SecretKey) {
+        let () = Self::CHECK;
+        Inner::<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME>::gen(rng)
+    }
+
+    fn key_gen_from_seed(seed: [u8; SEED_LENGTH]) -> (Self::PublicKey, Self::SecretKey) {
+        let () = Self::CHECK;
+        Inner::<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME>::key_gen_from_seed(seed)
+    }
+
+    fn sign<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        sk: &Self::SecretKey,
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<Self::Signature, SigningError> {
+        let () = Self::CHECK;
+        Inner::<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME>::sign(rng, sk, epoch, message)
+    }
+
+    fn verify(
+        pk: &Self::PublicKey,
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &Self::Signature,
+    ) -> bool {
+        let () = Self::CHECK;
+        Inner::<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME>::verify(pk, epoch, message, sig)
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        Inner::<PRF, MH, TH, NUM_CHUNKS_CHECKSUM, LOG_LIFETIME>::internal_consistency_check();
+    }
+}
+
+/// All-SHA3 scheme with chunk size w = 4, using the building blocks of
+/// `SIGWinternitzLifetime18W4` for any lifetime.
+#[cfg(feature = "sha")]
+pub type HashSigSha3W4<const LOG_LIFETIME: usize> = HashSigScheme<
+    crate::symmetric::prf::sha::ShaPRF<26>,
+    crate::symmetric::message_hash::sha::ShaMessageHash<18, 20, 36, 4>,
+    crate::symmetric::tweak_hash::sha::ShaTweakHash<18, 26>,
+    3,
+    LOG_LIFETIME,
+>;
+
+#[cfg(all(test, feature = "sha"))]
+mod tests {
+    use crate::{
+        signature::test_templates::_test_signature_scheme_correctness,
+        symmetric::{
+            message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    pub fn test_hash_sig_sha3() {
+        HashSigSha3W4::<6>::internal_consistency_check();
+
+        _test_signature_scheme_correctness::<HashSigSha3W4<6>>(0);
+        _test_signature_scheme_correctness::<HashSigSha3W4<6>>(13);
+        _test_signature_scheme_correctness::<HashSigSha3W4<6>>(63);
+    }
+
+    #[test]
+    pub fn test_same_keys_as_generalized_xmss() {
+        // the facade does not change the scheme, so keys derived from a seed
+        // are the same as for the generalized XMSS scheme with the same components
+        type Sig = GeneralizedXMSSSignatureScheme<
+            ShaPRF<26>,
+            WinternitzEncoding<ShaMessageHash<18, 20, 36, 4>, 3>,
+            ShaTweakHash<18, 26>,
+            6,
+        >;
+        let seed = [0x42; SEED_LENGTH];
+        let (pk_facade, _) = HashSigSha3W4::<6>::key_gen_from_seed(seed);
+        let (pk, _) = Sig::key_gen_from_seed(seed);
+        assert!(pk_facade == pk);
+    }
+}
@@ -0,0 +1,499 @@
+//! Hybrid signatures, which combine an Ed25519 signature and a hash-based
+//! signature of the same message, e.g., during a migration to hash-based
+//! signatures. A hybrid signature only verifies if both components verify,
+//! so that it stays secure as long as one of the two schemes is not broken.
+//!
+//! Both components sign the same message bytes, i.e., a digest of length
+//! `MESSAGE_LENGTH`. As for the hash-based scheme alone, each epoch must
+//! only be used once.
+//!
+//! Encoded hybrid keys and signatures are a concatenation: a header of
+//! `HYBRID_HEADER_LENGTH` bytes, i.e., the version of the hybrid format and
+//! the kind, then the Ed25519 component, and then the hash-based component
+//! in the encoding of `codec`, which is tagged with the parameter set identifier.
+
+use ed25519_dalek::{Signer, Verifier};
+use rand_core::{CryptoRng, RngCore};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::MESSAGE_LENGTH;
+
+use super::{
+    codec::{
+        self, decode_public_key, decode_signature, encode_public_key, encode_signature, CodecError,
+    },
+    sizes::{report, ReportSizes},
+    SignatureScheme, SigningError,
+};
+
+/// Version of the hybrid format. It is the first byte of every encoded
+/// hybrid key or signature, and has to be incremented whenever the
+/// concatenation changes. Changes of the hash-based component are covered
+/// by `codec::FORMAT_VERSION`.
+pub const HYBRID_FORMAT_VERSION: u8 = 1;
+
+/// Length of the header that precedes every encoded hybrid key or signature
+pub const HYBRID_HEADER_LENGTH: usize = 2;
+
+/// Length of an Ed25519 public key
+pub const ED25519_PUBLIC_KEY_LENGTH: usize = ed25519_dalek::PUBLIC_KEY_LENGTH;
+
+/// Length of an Ed25519 signature
+pub const ED25519_SIGNATURE_LENGTH: usize = ed25519_dalek::SIGNATURE_LENGTH;
+
+/// Number of bytes that an encoded hybrid public key has
+/// in addition to the encoded hash-based public key
+pub const HYBRID_PUBLIC_KEY_OVERHEAD: usize = HYBRID_HEADER_LENGTH + ED25519_PUBLIC_KEY_LENGTH;
+
+/// Number of bytes that an encoded hybrid signature has
+/// in addition to the encoded hash-based signature
+pub const HYBRID_SIGNATURE_OVERHEAD: usize = HYBRID_HEADER_LENGTH + ED25519_SIGNATURE_LENGTH;
+
+/// Returns the length of an encoded hybrid public key for the given scheme
+pub fn hybrid_public_key_bytes<S: ReportSizes>() -> usize {
+    HYBRID_PUBLIC_KEY_OVERHEAD + report::<S>().public_key_bytes
+}
+
+/// Returns the length of an encoded hybrid signature for the given scheme
+pub fn hybrid_signature_bytes<S: ReportSizes>() -> usize {
+    HYBRID_SIGNATURE_OVERHEAD + report::<S>().signature_bytes
+}
+
+/// Kinds of hybrid objects that can be encoded. The kind is
+/// the second byte of every encoded hybrid key or signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HybridKind {
+    PublicKey = 0x01,
+    Signature = 0x03,
+}
+
+/// Error enum for the verification of hybrid signatures,
+/// telling which of the components did not verify
+#[derive(Debug, PartialEq, Eq)]
+pub enum HybridVerifyError {
+    /// The Ed25519 signature is invalid
+    Ed25519,
+    /// The hash-based signature is invalid
+    HashSig,
+    /// Both signatures are invalid
+    Both,
+}
+
+/// Returns the header for the given kind
+fn header(kind: HybridKind) -> [u8; HYBRID_HEADER_LENGTH] {
+    [HYBRID_FORMAT_VERSION, kind as u8]
+}
+
+/// Checks the header for the given kind and returns the
+/// Ed25519 component of length `N` and the bytes that follow it
+fn split<const N: usize>(kind: HybridKind, bytes: &[u8]) -> Result<([u8; N], &[u8]), CodecError> {
+    if bytes.len() < HYBRID_HEADER_LENGTH + N {
+        return Err(CodecError::MissingHeader);
+    }
+    if bytes[0] != HYBRID_FORMAT_VERSION {
+        return Err(CodecError::UnsupportedVersion(bytes[0]));
+    }
+    if bytes[1] != kind as u8 {
+        return Err(CodecError::WrongKind);
+    }
+    let (ed25519, rest) = bytes[HYBRID_HEADER_LENGTH..].split_at(N);
+    Ok((ed25519.try_into().unwrap(), rest))
+}
+
+/// Hybrid public key, i.e., an Ed25519 public key and a public key
+/// of the hash-based scheme, tagged with its parameter set identifier
+#[derive(Serialize, Deserialize)]
+pub struct HybridPublicKey<S: SignatureScheme> {
+    param_set_id: u16,
+    ed25519: ed25519_dalek::VerifyingKey,
+    #[serde(bound(
+        serialize = "S::PublicKey: Serialize",
+        deserialize = "S::PublicKey: Deserialize<'de>"
+    ))]
+    hash_sig: S::PublicKey,
+}
+
+impl<S: SignatureScheme> Clone for HybridPublicKey<S>
+where
+    S::PublicKey: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            param_set_id: self.param_set_id,
+            ed25519: self.ed25519,
+            hash_sig: self.hash_sig.clone(),
+        }
+    }
+}
+
+impl<S: SignatureScheme> HybridPublicKey<S> {
+    /// Returns the identifier of the parameter set of the hash-based public key
+    pub fn param_set_id(&self) -> u16 {
+        self.param_set_id
+    }
+
+    /// Returns the Ed25519 public key
+    pub fn ed25519(&self) -> &ed25519_dalek::VerifyingKey {
+        &self.ed25519
+    }
+
+    /// Returns the hash-based public key
+    pub fn hash_sig(&self) -> &S::PublicKey {
+        &self.hash_sig
+    }
+
+    /// Verifies a hybrid signature of a message with respect to an epoch.
+    /// Both components are always checked, and if any of them is invalid,
+    /// the error tells which. A signature for a different parameter set
+    /// has an invalid hash-based component.
+    #[must_use = "a signature must be rejected if verification fails"]
+    pub fn verify(
+        &self,
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+        sig: &HybridSignature<S>,
+    ) -> Result<(), HybridVerifyError> {
+        let ed25519_valid = self.ed25519.verify(message, &sig.ed25519).is_ok();
+        let hash_sig_valid = sig.param_set_id == self.param_set_id
+            && S::verify(&self.hash_sig, epoch, message, &sig.hash_sig);
+
+        match (ed25519_valid, hash_sig_valid) {
+            (true, true) => Ok(()),
+            (false, true) => Err(HybridVerifyError::Ed25519),
+            (true, false) => Err(HybridVerifyError::HashSig),
+            (false, false) => Err(HybridVerifyError::Both),
+        }
+    }
+
+    /// Encodes the public key, see the module documentation
+    pub fn to_bytes(&self) -> Vec<u8>
+    where
+        S::PublicKey: Serialize,
+    {
+        let mut bytes = header(HybridKind::PublicKey).to_vec();
+        bytes.extend_from_slice(self.ed25519.as_bytes());
+        bytes.extend(encode_public_key::<S>(self.param_set_id, &self.hash_sig));
+        bytes
+    }
+
+    /// Decodes a public key, taking the parameter set identifier
+    /// from the header of the hash-based component
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError>
+    where
+        S::PublicKey: DeserializeOwned,
+    {
+        let (ed25519, rest) = split::<ED25519_PUBLIC_KEY_LENGTH>(HybridKind::PublicKey, bytes)?;
+        let ed25519 = ed25519_dalek::VerifyingKey::from_bytes(&ed25519)
+            .map_err(|_| CodecError::InvalidEncoding)?;
+        let param_set_id = codec::param_set_id(rest)?;
+        let hash_sig = decode_public_key::<S>(param_set_id, rest)?;
+        Ok(Self {
+            param_set_id,
+            ed25519,
+            hash_sig,
+        })
+    }
+}
+
+/// Hybrid key pair, i.e., an Ed25519 signing key and a key pair
+/// of the hash-based scheme, tagged with its parameter set identifier
+#[derive(Serialize, Deserialize)]
+pub struct HybridKeyPair<S: SignatureScheme> {
+    ed25519: ed25519_dalek::SigningKey,
+    #[serde(bound(
+        serialize = "S::PublicKey: Serialize",
+        deserialize = "S::PublicKey: Deserialize<'de>"
+    ))]
+    pk: HybridPublicKey<S>,
+    #[serde(bound(
+        serialize = "S::SecretKey: Serialize",
+        deserialize = "S::SecretKey: Deserialize<'de>"
+    ))]
+    sk: S::SecretKey,
+}
+
+impl<S: SignatureScheme> HybridKeyPair<S> {
+    /// Generates a new Ed25519 key and a new key pair of the
+    /// hash-based scheme of the parameter set with the given identifier
+    pub fn gen<R: RngCore + CryptoRng>(rng: &mut R, param_set_id: u16) -> Self {
+        let ed25519 = ed25519_dalek::SigningKey::generate(rng);
+        let (pk, sk) = S::gen(rng);
+        Self::from_parts(param_set_id, ed25519, pk, sk)
+    }
+
+    /// Combines an existing Ed25519 key and an existing key pair of the
+    /// hash-based scheme of the parameter set with the given identifier
+    pub fn from_parts(
+        param_set_id: u16,
+        ed25519: ed25519_dalek::SigningKey,
+        pk: S::PublicKey,
+        sk: S::SecretKey,
+    ) -> Self {
+        Self {
+            pk: HybridPublicKey {
+                param_set_id,
+                ed25519: ed25519.verifying_key(),
+                hash_sig: pk,
+            },
+            ed25519,
+            sk,
+        }
+    }
+
+    /// Returns the hybrid public key
+    pub fn public_key(&self) -> &HybridPublicKey<S> {
+        &self.pk
+    }
+
+    /// Signs a message for the given epoch with both schemes. The epoch must not
+    /// have been used before. Fails if the hash-based scheme fails to sign.
+    pub fn sign<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+        epoch: u32,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<HybridSignature<S>, SigningError> {
+        let hash_sig = S::sign(rng, &self.sk, epoch, message)?;
+        Ok(HybridSignature {
+            param_set_id: self.pk.param_set_id,
+            ed25519: self.ed25519.sign(message),
+            hash_sig,
+        })
+    }
+}
+
+/// Hybrid signature, i.e., an Ed25519 signature and a signature of the
+/// hash-based scheme, tagged with its parameter set identifier
+#[derive(Serialize, Deserialize)]
+pub struct HybridSignature<S: SignatureScheme> {
+    param_set_id: u16,
+    ed25519: ed25519_dalek::Signature,
+    #[serde(bound(
+        serialize = "S::Signature: Serialize",
+        deserialize = "S::Signature: Deserialize<'de>"
+    ))]
+    hash_sig: S::Signature,
+}
+
+impl<S: SignatureScheme> Clone for HybridSignature<S>
+where
+    S::Signature: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            param_set_id: self.param_set_id,
+            ed25519: self.ed25519,
+            hash_sig: self.hash_sig.clone(),
+        }
+    }
+}
+
+impl<S: SignatureScheme> HybridSignature<S> {
+    /// Returns the identifier of the parameter set of the hash-based signature
+    pub fn param_set_id(&self) -> u16 {
+        self.param_set_id
+    }
+
+    /// Returns the Ed25519 signature
+    pub fn ed25519(&self) -> &ed25519_dalek::Signature {
+        &self.ed25519
+    }
+
+    /// Returns the hash-based signature
+    pub fn hash_sig(&self) -> &S::Signature {
+        &self.hash_sig
+    }
+
+    /// Encodes the signature, see the module documentation
+    pub fn to_bytes(&self) -> Vec<u8>
+    where
+        S::Signature: Serialize,
+    {
+        let mut bytes = header(HybridKind::Signature).to_vec();
+        bytes.extend_from_slice(&self.ed25519.to_bytes());
+        bytes.extend(encode_signature::<S>(self.param_set_id, &self.hash_sig));
+        bytes
+    }
+
+    /// Decodes a signature, taking the parameter set identifier
+    /// from the header of the hash-based component
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError>
+    where
+        S::Signature: DeserializeOwned,
+    {
+        let (ed25519, rest) = split::<ED25519_SIGNATURE_LENGTH>(HybridKind::Signature, bytes)?;
+        let param_set_id = codec::param_set_id(rest)?;
+        let hash_sig = decode_signature::<S>(param_set_id, rest)?;
+        Ok(Self {
+            param_set_id,
+            ed25519: ed25519_dalek::Signature::from_bytes(&ed25519),
+            hash_sig,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        inc_encoding::basic_winternitz::WinternitzEncoding,
+        signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+        symmetric::{
+            message_hash::sha::ShaMessageHash192x3, prf::sha::ShaPRF,
+            tweak_hash::sha::ShaTweak192192,
+        },
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type Sig = GeneralizedXMSSSignatureScheme<
+        ShaPRF<24>,
+        WinternitzEncoding<ShaMessageHash192x3, 3>,
+        ShaTweak192192,
+        3,
+    >;
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = thread_rng();
+        let key_pair = HybridKeyPair::<Sig>::gen(&mut rng, 0x1234);
+        let pk = key_pair.public_key();
+
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let epoch = 5;
+        let sig = key_pair.sign(&mut rng, epoch, &message).unwrap();
+        assert_eq!(pk.verify(epoch, &message, &sig), Ok(()));
+
+        // both components reject a different message
+        let mut other_message = message;
+        other_message[0] ^= 0x01;
+        assert_eq!(
+            pk.verify(epoch, &other_message, &sig),
+            Err(HybridVerifyError::Both)
+        );
+
+        // the epoch is only bound by the hash-based component
+        assert_eq!(
+            pk.verify(epoch + 1, &message, &sig),
+            Err(HybridVerifyError::HashSig)
+        );
+    }
+
+    #[test]
+    fn test_each_component_fails_independently() {
+        let mut rng = thread_rng();
+        let key_pair = HybridKeyPair::<Sig>::gen(&mut rng, 0x1234);
+        let pk = key_pair.public_key();
+
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let other_message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let sig = key_pair.sign(&mut rng, 2, &message).unwrap();
+        let other_sig = key_pair.sign(&mut rng, 2, &other_message).unwrap();
+
+        // only the Ed25519 component is for another message
+        let mut mixed = sig.clone();
+        mixed.ed25519 = other_sig.ed25519;
+        assert_eq!(
+            pk.verify(2, &message, &mixed),
+            Err(HybridVerifyError::Ed25519)
+        );
+
+        // only the hash-based component is for another message
+        let mut mixed = sig.clone();
+        mixed.hash_sig = other_sig.hash_sig.clone();
+        assert_eq!(
+            pk.verify(2, &message, &mixed),
+            Err(HybridVerifyError::HashSig)
+        );
+
+        // a key with a different Ed25519 key rejects only the Ed25519 component
+        let other_ed25519 = ed25519_dalek::SigningKey::generate(&mut rng);
+        let mut other_pk = pk.clone();
+        other_pk.ed25519 = other_ed25519.verifying_key();
+        assert_eq!(
+            other_pk.verify(2, &message, &sig),
+            Err(HybridVerifyError::Ed25519)
+        );
+
+        // a key of another parameter set rejects only the hash-based component
+        let mut other_pk = pk.clone();
+        other_pk.param_set_id = 0x4321;
+        assert_eq!(
+            other_pk.verify(2, &message, &sig),
+            Err(HybridVerifyError::HashSig)
+        );
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut rng = thread_rng();
+        let key_pair = HybridKeyPair::<Sig>::gen(&mut rng, 0x1234);
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let sig = key_pair.sign(&mut rng, 7, &message).unwrap();
+
+        // encoding round-trips, and has the announced size
+        let pk_bytes = key_pair.public_key().to_bytes();
+        let sig_bytes = sig.to_bytes();
+        assert_eq!(pk_bytes.len(), hybrid_public_key_bytes::<Sig>());
+        assert_eq!(sig_bytes.len(), hybrid_signature_bytes::<Sig>());
+        assert_eq!(
+            &pk_bytes[..HYBRID_HEADER_LENGTH],
+            &[HYBRID_FORMAT_VERSION, 0x01]
+        );
+        assert_eq!(
+            &sig_bytes[..HYBRID_HEADER_LENGTH],
+            &[HYBRID_FORMAT_VERSION, 0x03]
+        );
+
+        let pk = HybridPublicKey::<Sig>::from_bytes(&pk_bytes).unwrap();
+        let decoded = HybridSignature::<Sig>::from_bytes(&sig_bytes).unwrap();
+        assert_eq!(pk.param_set_id(), 0x1234);
+        assert_eq!(decoded.param_set_id(), 0x1234);
+        assert_eq!(pk.verify(7, &message, &decoded), Ok(()));
+        assert_eq!(decoded.to_bytes(), sig_bytes);
+
+        // the header is checked
+        let mut bytes = sig_bytes.clone();
+        bytes[0] = HYBRID_FORMAT_VERSION + 1;
+        assert!(matches!(
+            HybridSignature::<Sig>::from_bytes(&bytes),
+            Err(CodecError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            HybridSignature::<Sig>::from_bytes(&pk_bytes),
+            Err(CodecError::WrongKind)
+        ));
+        assert!(matches!(
+            HybridSignature::<Sig>::from_bytes(&sig_bytes[..HYBRID_SIGNATURE_OVERHEAD - 1]),
+            Err(CodecError::MissingHeader)
+        ));
+
+        // and so is the hash-based component
+        let mut bytes = sig_bytes.clone();
+        bytes.push(0);
+        assert!(matches!(
+            HybridSignature::<Sig>::from_bytes(&bytes),
+            Err(CodecError::InvalidEncoding)
+        ));
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut rng = thread_rng();
+        let key_pair = HybridKeyPair::<Sig>::gen(&mut rng, 0x1234);
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+
+        // the key pair survives serialization, and can sign afterwards
+        let json = serde_json::to_string(&key_pair).unwrap();
+        let key_pair: HybridKeyPair<Sig> = serde_json::from_str(&json).unwrap();
+        let sig = key_pair.sign(&mut rng, 3, &message).unwrap();
+
+        let json = serde_json::to_string(key_pair.public_key()).unwrap();
+        let pk: HybridPublicKey<Sig> = serde_json::from_str(&json).unwrap();
+        let json = serde_json::to_string(&sig).unwrap();
+        let sig: HybridSignature<Sig> = serde_json::from_str(&json).unwrap();
+        assert_eq!(pk.verify(3, &message, &sig), Ok(()));
+    }
+}
@@ -0,0 +1,287 @@
+//! Key generation from BIP39 mnemonics, so that keys can be backed up as a
+//! phrase of English words, as for wallets.
+//!
+//! The seed of BIP39 (64 bytes, derived from the phrase and a passphrase with
+//! PBKDF2) is compressed into a seed of length `SEED_LENGTH` with SHA3-256,
+//! domain-separated from other uses of the BIP39 seed, and then passed to
+//! `SignatureScheme::key_gen_from_seed`. Hence, the same phrase gives the same
+//! keys, and different instantiations give unrelated keys for the same phrase.
+//!
+//! Note: the keys do not contain the next unused epoch. Restoring keys from
+//! a phrase does not restore which epochs have already been used.
+
+use bip39::{Language, Mnemonic};
+use rand_core::{CryptoRng, RngCore};
+use tiny_keccak::{Hasher, Sha3};
+use zeroize::Zeroizing;
+
+use crate::symmetric::seed_expander::SEED_LENGTH;
+
+use super::SignatureScheme;
+
+/// Number of words of mnemonics generated by `generate_mnemonic`,
+/// which encode 256 bits of entropy.
+pub const MNEMONIC_WORDS: usize = 24;
+
+const MNEMONIC_DOMAIN_SEP: [u8; 16] = *b"hashsig-bip39-v1";
+
+/// Error enum for parsing mnemonics
+#[derive(Debug, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// The number of words is not 12, 15, 18, 21, or 24
+    WrongNumberOfWords(usize),
+    /// The word at the given index is not in the English word list
+    UnknownWord(usize),
+    /// The checksum encoded in the last word does not match
+    InvalidChecksum,
+}
+
+impl From<bip39::Error> for MnemonicError {
+    fn from(error: bip39::Error) -> Self {
+        match error {
+            bip39::Error::BadWordCount(count) => MnemonicError::WrongNumberOfWords(count),
+            bip39::Error::UnknownWord(index) => MnemonicError::UnknownWord(index),
+            bip39::Error::InvalidChecksum => MnemonicError::InvalidChecksum,
+            // only occur when creating mnemonics from entropy, or
+            // when parsing without knowing the language
+            bip39::Error::BadEntropyBitCount(_) | bip39::Error::AmbiguousLanguages(_) => {
+                unreachable!("Mnemonic: unexpected error when parsing: {:?}", error)
+            }
+        }
+    }
+}
+
+/// Derives the seed of BIP39, i.e., PBKDF2 with HMAC-SHA512 of the
+/// normalized phrase, salted with the normalized passphrase
+fn bip39_seed(phrase: &str, passphrase: &str) -> Result<Zeroizing<[u8; 64]>, MnemonicError> {
+    let mnemonic = Mnemonic::parse_in(Language::English, phrase)?;
+    Ok(Zeroizing::new(mnemonic.to_seed(passphrase)))
+}
+
+/// Compresses a seed of BIP39 into a seed for `key_gen_from_seed`
+fn compress(bip39_seed: &[u8; 64]) -> [u8; SEED_LENGTH] {
+    let mut hasher = Sha3::v256();
+    hasher.update(&MNEMONIC_DOMAIN_SEP);
+    hasher.update(bip39_seed);
+    let mut seed = [0u8; SEED_LENGTH];
+    hasher.finalize(&mut seed);
+    seed
+}
+
+/// Derives the seed for `key_gen_from_seed` from a mnemonic and a passphrase,
+/// which may be empty. Returns an error if the mnemonic is not valid, i.e.,
+/// if a word is not in the English word list or the checksum does not match.
+/// The phrase and passphrase are normalized, as specified by BIP39.
+pub fn mnemonic_to_seed(
+    phrase: &str,
+    passphrase: &str,
+) -> Result<[u8; SEED_LENGTH], MnemonicError> {
+    Ok(compress(&*bip39_seed(phrase, passphrase)?))
+}
+
+/// Generates a key pair from a mnemonic and a passphrase, see `mnemonic_to_seed`.
+pub fn key_gen_from_mnemonic<S: SignatureScheme>(
+    phrase: &str,
+    passphrase: &str,
+) -> Result<(S::PublicKey, S::SecretKey), MnemonicError> {
+    let mut seed = mnemonic_to_seed(phrase, passphrase)?;
+    let key_pair = S::key_gen_from_seed(seed);
+    zeroize::Zeroize::zeroize(&mut seed);
+    Ok(key_pair)
+}
+
+/// Generates a fresh mnemonic of `MNEMONIC_WORDS` words, and returns it
+/// together with the public key derived from it with an empty passphrase.
+/// The secret key is derived again with `key_gen_from_mnemonic`.
+pub fn generate_mnemonic<S: SignatureScheme, R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> (String, S::PublicKey) {
+    let mnemonic = Mnemonic::generate_in_with(rng, Language::English, MNEMONIC_WORDS)
+        .expect("Mnemonic: the number of words is valid");
+    let phrase = mnemonic.to_string();
+    let (pk, _) =
+        key_gen_from_mnemonic::<S>(&phrase, "").expect("Mnemonic: a generated mnemonic is valid");
+    (phrase, pk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vectors of BIP39 for English, as (phrase, seed) with passphrase "TREZOR"
+    const VECTORS: [(&str, &str); 6] = [
+        (
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        ),
+        (
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        ),
+        (
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo when",
+            "0cd6e5d827bb62eb8fc1e262254223817fd068a74b5b449cc2f667c3f1f985a76379b43348d952e2265b4cd129090758b3e3c2c49103b5051aac2eaeb890a528",
+        ),
+        (
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+            "bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd3097170af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8",
+        ),
+        (
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless",
+            "c0c519bd0e91a2ed54357d9d1ebef6f5af218a153624cf4f2da911a0ed8f7a09e2ef61af0aca007096df430022f7a2b6fb91661a9589097069720d015e4e982f",
+        ),
+        (
+            "panda eyebrow bullet gorilla call smoke muffin taste mesh discover soft ostrich alcohol speed nation flash devote level hobby quick inner drive ghost inside",
+            "72be8e052fc4919d2adf28d5306b5474b0069df35b02303de8c1729c9538dbb6fc2d731d5f832193cd9fb6aeecbc469594a70e3dd50811b5067f3b88b28c3e8d",
+        ),
+    ];
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_bip39_vectors() {
+        for (phrase, seed) in VECTORS {
+            assert_eq!(to_hex(&*bip39_seed(phrase, "TREZOR").unwrap()), seed);
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_to_seed() {
+        let (phrase, _) = VECTORS[3];
+
+        // we pin the seed derived from a fixed phrase, so that
+        // accidental changes to the derivation are caught
+        assert_eq!(
+            to_hex(&mnemonic_to_seed(phrase, "TREZOR").unwrap()),
+            "42e90bb2416d28ed67fda0d643cf8389e158c51c8d2fe89e2127c180cac43b3a"
+        );
+
+        // the passphrase and the normalization of the phrase matter
+        assert_ne!(
+            mnemonic_to_seed(phrase, "TREZOR").unwrap(),
+            mnemonic_to_seed(phrase, "").unwrap()
+        );
+        assert_eq!(
+            mnemonic_to_seed(phrase, "").unwrap(),
+            mnemonic_to_seed(&format!("  {}  ", phrase.replace(' ', "  ")), "").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_mnemonics() {
+        let (phrase, _) = VECTORS[3];
+        let words: Vec<&str> = phrase.split(' ').collect();
+
+        assert_eq!(
+            mnemonic_to_seed(&words[..23].join(" "), ""),
+            Err(MnemonicError::WrongNumberOfWords(23))
+        );
+
+        let mut unknown = words.clone();
+        unknown[5] = "hashsig";
+        assert_eq!(
+            mnemonic_to_seed(&unknown.join(" "), ""),
+            Err(MnemonicError::UnknownWord(5))
+        );
+
+        // "art" encodes the checksum of the all-zero entropy, "zoo" does not
+        let mut checksum = words.clone();
+        checksum[23] = "zoo";
+        assert_eq!(
+            mnemonic_to_seed(&checksum.join(" "), ""),
+            Err(MnemonicError::InvalidChecksum)
+        );
+    }
+
+    #[cfg(feature = "sha")]
+    mod schemes {
+        use rand::{thread_rng, Rng};
+
+        use crate::{
+            inc_encoding::{basic_winternitz::WinternitzEncoding, target_sum::TargetSumEncoding},
+            signature::{generalized_xmss::GeneralizedXMSSSignatureScheme, param_set::ParamSetId},
+            symmetric::{
+                message_hash::sha::ShaMessageHash192x3, prf::sha::ShaPRF,
+                tweak_hash::sha::ShaTweak192192,
+            },
+            MESSAGE_LENGTH,
+        };
+
+        use super::*;
+
+        // Note: do not use these parameters, they are just for testing
+        type Sig = GeneralizedXMSSSignatureScheme<
+            ShaPRF<24>,
+            WinternitzEncoding<ShaMessageHash192x3, 3>,
+            ShaTweak192192,
+            3,
+        >;
+
+        #[test]
+        fn test_key_gen_from_mnemonic_pinned() {
+            let (phrase, _) = VECTORS[5];
+
+            // we pin the public key derived from a fixed phrase, so that
+            // accidental changes to the derivation are caught
+            let (pk, _) = key_gen_from_mnemonic::<Sig>(phrase, "").unwrap();
+            assert_eq!(
+                to_hex(&bincode::serialize(&pk).unwrap()),
+                "dea1d263cd7a68acb8e6e9b1059d5e7cff0f80fcc784af67d50db23c1dbc4102\
+                 35066fe6eacfe2bce74d979e3bd99f72"
+            );
+
+            // the same as key generation from the derived seed
+            let (pk_seed, _) = Sig::key_gen_from_seed(mnemonic_to_seed(phrase, "").unwrap());
+            assert!(pk == pk_seed);
+        }
+
+        #[test]
+        fn test_key_gen_from_mnemonic_separated() {
+            let (phrase, _) = VECTORS[5];
+
+            // instantiations that only differ in the target sum give different keys
+            type SigTargetSum<const TARGET_SUM: usize> = GeneralizedXMSSSignatureScheme<
+                ShaPRF<24>,
+                TargetSumEncoding<ShaMessageHash192x3, TARGET_SUM>,
+                ShaTweak192192,
+                3,
+            >;
+            let (pk, _) = key_gen_from_mnemonic::<SigTargetSum<360>>(phrase, "").unwrap();
+            let (pk_offset, _) = key_gen_from_mnemonic::<SigTargetSum<370>>(phrase, "").unwrap();
+            assert!(pk != pk_offset);
+
+            // and so do all registered parameter sets, see `ParamSetId::key_material`
+            let seed = mnemonic_to_seed(phrase, "").unwrap();
+            let key_materials: Vec<_> = ParamSetId::ALL
+                .iter()
+                .map(|param_set| param_set.key_material(seed))
+                .collect();
+            for i in 0..key_materials.len() {
+                for j in 0..i {
+                    assert_ne!(key_materials[i], key_materials[j]);
+                }
+            }
+        }
+
+        #[test]
+        fn test_generate_mnemonic() {
+            let mut rng = thread_rng();
+            let (phrase, pk) = generate_mnemonic::<Sig, _>(&mut rng);
+            assert_eq!(phrase.split(' ').count(), MNEMONIC_WORDS);
+
+            // the phrase restores a key pair that can sign for the public key
+            let (pk_restored, sk) = key_gen_from_mnemonic::<Sig>(&phrase, "").unwrap();
+            assert!(pk == pk_restored);
+
+            let message: [u8; MESSAGE_LENGTH] = rng.gen();
+            let sig = Sig::sign(&mut rng, &sk, 4, &message).unwrap();
+            assert!(Sig::verify(&pk, 4, &message, &sig));
+
+            // fresh mnemonics are different
+            let (other_phrase, _) = generate_mnemonic::<Sig, _>(&mut rng);
+            assert_ne!(phrase, other_phrase);
+        }
+    }
+}
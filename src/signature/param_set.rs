@@ -0,0 +1,414 @@
+use crate::signature::generalized_xmss::instantiations_sha::{
+    lifetime_2_to_the_10::winternitz::SIGWinternitzLifetime10W4,
+    lifetime_2_to_the_18::{
+        target_sum::{
+            SIGTargetSumLifetime18W1NoOff, SIGTargetSumLifetime18W1Off10,
+            SIGTargetSumLifetime18W2NoOff, SIGTargetSumLifetime18W2Off10,
+            SIGTargetSumLifetime18W4NoOff, SIGTargetSumLifetime18W4Off10,
+            SIGTargetSumLifetime18W8NoOff, SIGTargetSumLifetime18W8Off10,
+        },
+        winternitz::{
+            SIGWinternitzLifetime18W1, SIGWinternitzLifetime18W2, SIGWinternitzLifetime18W4,
+            SIGWinternitzLifetime18W8,
+        },
+    },
+    lifetime_2_to_the_20::{
+        target_sum::{
+            SIGTargetSumLifetime20W1NoOff, SIGTargetSumLifetime20W1Off10,
+            SIGTargetSumLifetime20W2NoOff, SIGTargetSumLifetime20W2Off10,
+            SIGTargetSumLifetime20W4NoOff, SIGTargetSumLifetime20W4Off10,
+            SIGTargetSumLifetime20W8NoOff, SIGTargetSumLifetime20W8Off10,
+        },
+        winternitz::{
+            SIGWinternitzLifetime20W1, SIGWinternitzLifetime20W2, SIGWinternitzLifetime20W4,
+            SIGWinternitzLifetime20W8,
+        },
+    },
+};
+
+use std::marker::PhantomData;
+
+use crate::{
+    inc_encoding::IncomparableEncoding,
+    symmetric::{prf::Pseudorandom, tweak_hash::TweakableHash},
+};
+
+use super::{
+    dyn_scheme::{ErasedScheme, ErasedSchemeImpl},
+    generalized_xmss::GeneralizedXMSSSignatureScheme,
+    sizes::ReportSizes,
+};
+
+/// Hash function families that parameter sets are based on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashFamily {
+    Sha3,
+}
+
+/// Incomparable encodings that parameter sets are based on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EncodingKind {
+    Winternitz,
+    TargetSum,
+}
+
+/// Structural constants of a parameter set. All lengths are in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParamSetDescription {
+    pub hash_family: HashFamily,
+    pub encoding: EncodingKind,
+    /// base 2 log of the number of epochs
+    pub log_lifetime: usize,
+    /// number of chunks of a codeword, including checksum chunks
+    pub num_chunks: usize,
+    /// number of bits per chunk
+    pub chunk_size: usize,
+    pub parameter_len: usize,
+    pub hash_len: usize,
+    pub rand_len: usize,
+}
+
+impl ParamSetDescription {
+    /// Reads the structural constants off the given instantiation.
+    fn of<
+        PRF: Pseudorandom,
+        IE: IncomparableEncoding,
+        TH: TweakableHash,
+        const LOG_LIFETIME: usize,
+    >(
+        hash_family: HashFamily,
+        encoding: EncodingKind,
+        _scheme: PhantomData<GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>>,
+    ) -> Self {
+        Self {
+            hash_family,
+            encoding,
+            log_lifetime: LOG_LIFETIME,
+            num_chunks: IE::NUM_CHUNKS,
+            chunk_size: IE::CHUNK_SIZE,
+            parameter_len: std::mem::size_of::<TH::Parameter>(),
+            hash_len: std::mem::size_of::<TH::Domain>(),
+            rand_len: std::mem::size_of::<IE::Randomness>(),
+        }
+    }
+}
+
+/// An operation that is generic over the instantiation, e.g., a benchmark.
+/// It can be run for a parameter set with `ParamSetId::visit`.
+pub trait ParamSetVisitor {
+    type Output;
+
+    /// Runs the operation with the instantiation `S` of the given parameter set
+    fn visit<S: ReportSizes>(self, param_set: ParamSetId) -> Self::Output;
+}
+
+/// Macro to define the registry of parameter sets. For each parameter set,
+/// it takes the name of the enum variant, its identifier, the corresponding
+/// instantiation, and its hash family and encoding.
+macro_rules! param_sets {
+    ($($variant:ident = $id:literal => $scheme:ty, $family:ident, $encoding:ident;)*) => {
+        /// Identifier of a registered parameter set, i.e., one of the
+        /// exported instantiations. It allows to select an instantiation
+        /// at runtime, see `DynScheme`.
+        ///
+        /// Every parameter set has a stable numeric identifier, which is part
+        /// of the header of encoded keys and signatures. Identifiers must never
+        /// be changed or reused. The high byte is the hash family: 0x00 for SHA3,
+        /// with 0x01 being reserved for Poseidon.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[repr(u16)]
+        pub enum ParamSetId {
+            $($variant = $id,)*
+        }
+
+        impl ParamSetId {
+            /// All registered parameter sets
+            pub const ALL: &'static [ParamSetId] = &[$(ParamSetId::$variant,)*];
+
+            /// Returns the numeric identifier of the parameter set
+            pub fn as_id(&self) -> u16 {
+                *self as u16
+            }
+
+            /// Returns the parameter set with the given numeric identifier, if it exists
+            pub fn from_id(id: u16) -> Option<Self> {
+                Self::ALL.iter().copied().find(|param_set| param_set.as_id() == id)
+            }
+
+            /// Returns the name of the parameter set
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(ParamSetId::$variant => stringify!($variant),)*
+                }
+            }
+
+            /// Returns the parameter set with the given name, if it exists
+            pub fn from_name(name: &str) -> Option<Self> {
+                Self::ALL.iter().copied().find(|id| id.name() == name)
+            }
+
+            /// Returns the structural constants of the parameter set
+            pub fn describe(&self) -> ParamSetDescription {
+                match self {
+                    $(ParamSetId::$variant => ParamSetDescription::of(
+                        HashFamily::$family,
+                        EncodingKind::$encoding,
+                        PhantomData::<$scheme>,
+                    ),)*
+                }
+            }
+
+            /// Returns the parameter and PRF key that key generation from
+            /// the seed draws, which determine the keys, see `tests::key_material`
+            #[cfg(test)]
+            pub(crate) fn key_material(&self, seed: [u8; crate::symmetric::seed_expander::SEED_LENGTH]) -> Vec<u8> {
+                match self {
+                    $(ParamSetId::$variant => tests::key_material(PhantomData::<$scheme>, seed),)*
+                }
+            }
+
+            /// Runs the visitor with the instantiation of the parameter set. In contrast
+            /// to `DynScheme`, this runs generic code with the concrete types, e.g., to
+            /// benchmark all registered parameter sets.
+            pub fn visit<V: ParamSetVisitor>(&self, visitor: V) -> V::Output {
+                match self {
+                    $(ParamSetId::$variant => visitor.visit::<$scheme>(*self),)*
+                }
+            }
+
+            /// Returns a type-erased handle to the instantiation
+            pub(crate) fn erased_scheme(&self) -> Box<dyn ErasedScheme> {
+                match self {
+                    $(ParamSetId::$variant => {
+                        Box::new(ErasedSchemeImpl::<$scheme>::new(self.as_id()))
+                    })*
+                }
+            }
+        }
+    };
+}
+
+param_sets! {
+    ShaWinternitzLifetime18W1 = 0x0001 => SIGWinternitzLifetime18W1, Sha3, Winternitz;
+    ShaWinternitzLifetime18W2 = 0x0002 => SIGWinternitzLifetime18W2, Sha3, Winternitz;
+    ShaWinternitzLifetime18W4 = 0x0003 => SIGWinternitzLifetime18W4, Sha3, Winternitz;
+    ShaWinternitzLifetime18W8 = 0x0004 => SIGWinternitzLifetime18W8, Sha3, Winternitz;
+    ShaTargetSumLifetime18W1NoOff = 0x0005 => SIGTargetSumLifetime18W1NoOff, Sha3, TargetSum;
+    ShaTargetSumLifetime18W1Off10 = 0x0006 => SIGTargetSumLifetime18W1Off10, Sha3, TargetSum;
+    ShaTargetSumLifetime18W2NoOff = 0x0007 => SIGTargetSumLifetime18W2NoOff, Sha3, TargetSum;
+    ShaTargetSumLifetime18W2Off10 = 0x0008 => SIGTargetSumLifetime18W2Off10, Sha3, TargetSum;
+    ShaTargetSumLifetime18W4NoOff = 0x0009 => SIGTargetSumLifetime18W4NoOff, Sha3, TargetSum;
+    ShaTargetSumLifetime18W4Off10 = 0x000A => SIGTargetSumLifetime18W4Off10, Sha3, TargetSum;
+    ShaTargetSumLifetime18W8NoOff = 0x000B => SIGTargetSumLifetime18W8NoOff, Sha3, TargetSum;
+    ShaTargetSumLifetime18W8Off10 = 0x000C => SIGTargetSumLifetime18W8Off10, Sha3, TargetSum;
+    ShaWinternitzLifetime20W1 = 0x0011 => SIGWinternitzLifetime20W1, Sha3, Winternitz;
+    ShaWinternitzLifetime20W2 = 0x0012 => SIGWinternitzLifetime20W2, Sha3, Winternitz;
+    ShaWinternitzLifetime20W4 = 0x0013 => SIGWinternitzLifetime20W4, Sha3, Winternitz;
+    ShaWinternitzLifetime20W8 = 0x0014 => SIGWinternitzLifetime20W8, Sha3, Winternitz;
+    ShaTargetSumLifetime20W1NoOff = 0x0015 => SIGTargetSumLifetime20W1NoOff, Sha3, TargetSum;
+    ShaTargetSumLifetime20W1Off10 = 0x0016 => SIGTargetSumLifetime20W1Off10, Sha3, TargetSum;
+    ShaTargetSumLifetime20W2NoOff = 0x0017 => SIGTargetSumLifetime20W2NoOff, Sha3, TargetSum;
+    ShaTargetSumLifetime20W2Off10 = 0x0018 => SIGTargetSumLifetime20W2Off10, Sha3, TargetSum;
+    ShaTargetSumLifetime20W4NoOff = 0x0019 => SIGTargetSumLifetime20W4NoOff, Sha3, TargetSum;
+    ShaTargetSumLifetime20W4Off10 = 0x001A => SIGTargetSumLifetime20W4Off10, Sha3, TargetSum;
+    ShaTargetSumLifetime20W8NoOff = 0x001B => SIGTargetSumLifetime20W8NoOff, Sha3, TargetSum;
+    ShaTargetSumLifetime20W8Off10 = 0x001C => SIGTargetSumLifetime20W8Off10, Sha3, TargetSum;
+    ShaWinternitzLifetime10W4 = 0x0021 => SIGWinternitzLifetime10W4, Sha3, Winternitz;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use serde::Serialize;
+
+    use crate::{
+        signature::{
+            codec::{HEADER_LENGTH, UNREGISTERED_PARAM_SET_ID},
+            sizes::report,
+        },
+        symmetric::seed_expander::{SeedExpander, SEED_LENGTH},
+    };
+
+    use super::*;
+
+    /// Draws parameter and PRF key from the seed as `key_gen_from_seed` does.
+    /// Key generation itself takes far too long for lifetimes of 2^18 and 2^20.
+    pub(super) fn key_material<
+        PRF: Pseudorandom,
+        IE: IncomparableEncoding,
+        TH: TweakableHash,
+        const LOG_LIFETIME: usize,
+    >(
+        _scheme: PhantomData<GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>>,
+        seed: [u8; SEED_LENGTH],
+    ) -> Vec<u8>
+    where
+        TH::Parameter: Serialize,
+        PRF::Key: Serialize,
+    {
+        let identifier =
+            GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::seed_identifier();
+        let mut rng = SeedExpander::new(&identifier, &seed);
+        let parameter = TH::rand_parameter(&mut rng);
+        let prf_key = PRF::gen(&mut rng);
+        bincode::serialize(&(parameter, prf_key)).unwrap()
+    }
+
+    #[test]
+    fn test_names_unique_and_round_trip() {
+        let names: HashSet<_> = ParamSetId::ALL.iter().map(|id| id.name()).collect();
+        assert_eq!(names.len(), ParamSetId::ALL.len());
+
+        for id in ParamSetId::ALL {
+            assert_eq!(ParamSetId::from_name(id.name()), Some(*id));
+        }
+        assert_eq!(ParamSetId::from_name("NotAParameterSet"), None);
+    }
+
+    #[test]
+    fn test_ids_unique_and_round_trip() {
+        let ids: HashSet<_> = ParamSetId::ALL.iter().map(|id| id.as_id()).collect();
+        assert_eq!(ids.len(), ParamSetId::ALL.len());
+        assert!(!ids.contains(&UNREGISTERED_PARAM_SET_ID));
+
+        for id in ParamSetId::ALL {
+            assert_eq!(ParamSetId::from_id(id.as_id()), Some(*id));
+        }
+        assert_eq!(ParamSetId::from_id(0xFFFF), None);
+    }
+
+    #[test]
+    fn test_ids_pinned() {
+        // identifiers are part of the encoding of keys and signatures,
+        // so they must never change
+        let expected: [(ParamSetId, u16); 25] = [
+            (ParamSetId::ShaWinternitzLifetime18W1, 0x0001),
+            (ParamSetId::ShaWinternitzLifetime18W2, 0x0002),
+            (ParamSetId::ShaWinternitzLifetime18W4, 0x0003),
+            (ParamSetId::ShaWinternitzLifetime18W8, 0x0004),
+            (ParamSetId::ShaTargetSumLifetime18W1NoOff, 0x0005),
+            (ParamSetId::ShaTargetSumLifetime18W1Off10, 0x0006),
+            (ParamSetId::ShaTargetSumLifetime18W2NoOff, 0x0007),
+            (ParamSetId::ShaTargetSumLifetime18W2Off10, 0x0008),
+            (ParamSetId::ShaTargetSumLifetime18W4NoOff, 0x0009),
+            (ParamSetId::ShaTargetSumLifetime18W4Off10, 0x000A),
+            (ParamSetId::ShaTargetSumLifetime18W8NoOff, 0x000B),
+            (ParamSetId::ShaTargetSumLifetime18W8Off10, 0x000C),
+            (ParamSetId::ShaWinternitzLifetime20W1, 0x0011),
+            (ParamSetId::ShaWinternitzLifetime20W2, 0x0012),
+            (ParamSetId::ShaWinternitzLifetime20W4, 0x0013),
+            (ParamSetId::ShaWinternitzLifetime20W8, 0x0014),
+            (ParamSetId::ShaTargetSumLifetime20W1NoOff, 0x0015),
+            (ParamSetId::ShaTargetSumLifetime20W1Off10, 0x0016),
+            (ParamSetId::ShaTargetSumLifetime20W2NoOff, 0x0017),
+            (ParamSetId::ShaTargetSumLifetime20W2Off10, 0x0018),
+            (ParamSetId::ShaTargetSumLifetime20W4NoOff, 0x0019),
+            (ParamSetId::ShaTargetSumLifetime20W4Off10, 0x001A),
+            (ParamSetId::ShaTargetSumLifetime20W8NoOff, 0x001B),
+            (ParamSetId::ShaTargetSumLifetime20W8Off10, 0x001C),
+            (ParamSetId::ShaWinternitzLifetime10W4, 0x0021),
+        ];
+        assert_eq!(expected.len(), ParamSetId::ALL.len());
+        for (param_set, id) in expected {
+            assert_eq!(param_set.as_id(), id);
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        // we compare against the constants in `instantiations_sha`, where
+        // the number of chunks includes the checksum chunks for Winternitz
+        for param_set in ParamSetId::ALL {
+            let description = param_set.describe();
+            let name = param_set.name();
+
+            assert_eq!(description.hash_family, HashFamily::Sha3);
+            assert_eq!(description.parameter_len, 18);
+
+            let log_lifetime = [10, 18, 20]
+                .into_iter()
+                .find(|l| name.contains(&format!("Lifetime{}", l)))
+                .unwrap();
+            assert_eq!(description.log_lifetime, log_lifetime);
+
+            let chunk_size = [1, 2, 4, 8]
+                .into_iter()
+                .find(|w| name.contains(&format!("W{}", w)))
+                .unwrap();
+            assert_eq!(description.chunk_size, chunk_size);
+
+            let num_chunks_message = 18 * 8 / chunk_size;
+            let (encoding, num_chunks, rand_len) = if name.contains("Winternitz") {
+                let num_chunks_checksum = match chunk_size {
+                    1 => 8,
+                    2 => 4,
+                    4 => 3,
+                    _ => 2,
+                };
+                (
+                    EncodingKind::Winternitz,
+                    num_chunks_message + num_chunks_checksum,
+                    20,
+                )
+            } else {
+                (EncodingKind::TargetSum, num_chunks_message, 23)
+            };
+            assert_eq!(description.encoding, encoding);
+            assert_eq!(description.num_chunks, num_chunks);
+            assert_eq!(description.rand_len, rand_len);
+
+            let hash_len = match (log_lifetime, chunk_size) {
+                (18, 1) | (18, 2) | (20, 1) => 25,
+                (_, 8) => 28,
+                _ => 26,
+            };
+            assert_eq!(description.hash_len, hash_len);
+        }
+    }
+
+    #[test]
+    fn test_key_gen_from_seed_unique() {
+        // no two parameter sets derive the same keys from a seed, as that
+        // would allow to combine signatures of different parameter sets
+        let seed = [0x42; SEED_LENGTH];
+        let key_materials: Vec<_> = ParamSetId::ALL
+            .iter()
+            .map(|param_set| param_set.key_material(seed))
+            .collect();
+        for i in 0..ParamSetId::ALL.len() {
+            for j in 0..i {
+                assert_ne!(
+                    key_materials[i],
+                    key_materials[j],
+                    "{} and {}",
+                    ParamSetId::ALL[i].name(),
+                    ParamSetId::ALL[j].name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_visit() {
+        // the visitor is run with the instantiation of the parameter set
+        struct Lifetime;
+        impl ParamSetVisitor for Lifetime {
+            type Output = (ParamSetId, u64, usize);
+            fn visit<S: ReportSizes>(self, param_set: ParamSetId) -> Self::Output {
+                (param_set, S::LIFETIME, report::<S>().public_key_bytes)
+            }
+        }
+
+        for param_set in ParamSetId::ALL {
+            let (visited, lifetime, public_key_bytes) = param_set.visit(Lifetime);
+            let description = param_set.describe();
+            assert_eq!(visited, *param_set);
+            assert_eq!(lifetime, 1 << description.log_lifetime);
+            assert_eq!(
+                public_key_bytes,
+                HEADER_LENGTH + description.hash_len + description.parameter_len
+            );
+        }
+    }
+}
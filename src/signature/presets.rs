@@ -0,0 +1,88 @@
+//! Named presets of the signature scheme, by security level.
+//!
+//! Each preset is one of the instantiations in `instantiations_sha`, which are
+//! declared with `declare_instantiation` and hence checked for consistency at
+//! compile time. The presets use chunk size w = 4, which is a good trade-off
+//! between signature size and signing and verification time.
+//!
+//! Note: only presets for 128-bit security are provided. The SHA3 based building
+//! blocks are limited to lengths below 256 bit, which is not enough for 192-bit
+//! security with the lifetimes below. Poseidon based presets will be added once
+//! the Poseidon backend is available again.
+
+use super::generalized_xmss::instantiations_sha::{
+    lifetime_2_to_the_18::{
+        target_sum::SIGTargetSumLifetime18W4Off10, winternitz::SIGWinternitzLifetime18W4,
+    },
+    lifetime_2_to_the_20::{
+        target_sum::SIGTargetSumLifetime20W4Off10, winternitz::SIGWinternitzLifetime20W4,
+    },
+};
+
+/// 128-bit security, based on SHA3, with Lifetime 2^18 and the Winternitz encoding
+pub type Sha3Security128Lifetime18Winternitz = SIGWinternitzLifetime18W4;
+
+/// 128-bit security, based on SHA3, with Lifetime 2^18 and the target sum encoding.
+/// Compared to the Winternitz encoding, verification is faster, but signing is
+/// randomized and may take several attempts.
+pub type Sha3Security128Lifetime18TargetSum = SIGTargetSumLifetime18W4Off10;
+
+/// 128-bit security, based on SHA3, with Lifetime 2^20 and the Winternitz encoding
+pub type Sha3Security128Lifetime20Winternitz = SIGWinternitzLifetime20W4;
+
+/// 128-bit security, based on SHA3, with Lifetime 2^20 and the target sum encoding.
+/// Compared to the Winternitz encoding, verification is faster, but signing is
+/// randomized and may take several attempts.
+pub type Sha3Security128Lifetime20TargetSum = SIGTargetSumLifetime20W4Off10;
+
+#[cfg(test)]
+mod tests {
+    use crate::signature::SignatureScheme;
+
+    use super::*;
+
+    #[test]
+    pub fn test_presets_consistency() {
+        Sha3Security128Lifetime18Winternitz::internal_consistency_check();
+        Sha3Security128Lifetime18TargetSum::internal_consistency_check();
+        Sha3Security128Lifetime20Winternitz::internal_consistency_check();
+        Sha3Security128Lifetime20TargetSum::internal_consistency_check();
+    }
+
+    #[test]
+    pub fn test_presets_effective_security_bits() {
+        // the message hash has 144 bits, the tweakable hash 208 bits, and the
+        // randomness 160 bits (Winternitz) or 184 bits (target sum)
+        assert_eq!(
+            Sha3Security128Lifetime18Winternitz::effective_security_bits(),
+            144
+        );
+        assert_eq!(
+            Sha3Security128Lifetime18TargetSum::effective_security_bits(),
+            144
+        );
+        assert_eq!(
+            Sha3Security128Lifetime20Winternitz::effective_security_bits(),
+            144
+        );
+        assert_eq!(
+            Sha3Security128Lifetime20TargetSum::effective_security_bits(),
+            144
+        );
+    }
+
+    #[cfg(feature = "slow-tests")]
+    mod slow {
+        use crate::signature::test_templates::_test_signature_scheme_correctness;
+
+        use super::*;
+
+        #[test]
+        pub fn test_presets_correctness() {
+            _test_signature_scheme_correctness::<Sha3Security128Lifetime18Winternitz>(17);
+            _test_signature_scheme_correctness::<Sha3Security128Lifetime18TargetSum>(17);
+            _test_signature_scheme_correctness::<Sha3Security128Lifetime20Winternitz>(1017);
+            _test_signature_scheme_correctness::<Sha3Security128Lifetime20TargetSum>(1017);
+        }
+    }
+}
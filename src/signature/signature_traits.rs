@@ -0,0 +1,373 @@
+//! Implementations of the traits of the RustCrypto `signature` crate, so that
+//! code written against `Signer`, `Verifier`, and `Keypair` can use this scheme,
+//! e.g., in place of Ed25519.
+//!
+//! The scheme is synchronized, i.e., each epoch must only be used once, while
+//! the traits have no notion of epochs. Hence, `HashSigSigningKey` manages the
+//! epochs: each call to `try_sign` consumes the next unused epoch, which is
+//! embedded into the signature and returned by `HashSigSignature::epoch`.
+//! The state is only kept in memory. Callers that restart must persist
+//! `HashSigSigningKey::next_epoch` before releasing a signature, and create
+//! the signing key with it again.
+//!
+//! Messages are digests, see `MESSAGE_LENGTH`: signing and verification fail
+//! for messages of any other length.
+//!
+//! Keys and signatures are encoded with the canonical codec (see `codec`), and
+//! tagged with the parameter set identifier given when creating the key.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ::signature::{Error, Keypair, RandomizedSigner, SignatureEncoding, Verifier};
+use rand_core::CryptoRngCore;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::MESSAGE_LENGTH;
+
+use super::{
+    codec::{self, decode_public_key, encode_public_key, ArtifactKind, CodecError},
+    embedded_epoch::{sign_with_epoch, verify_with_epoch, EpochMode, SignatureWithEpoch},
+    SignatureScheme,
+};
+
+/// Verifying key, i.e., a public key tagged with its parameter set identifier
+pub struct HashSigVerifyingKey<S: SignatureScheme> {
+    param_set_id: u16,
+    pk: S::PublicKey,
+}
+
+impl<S: SignatureScheme> Clone for HashSigVerifyingKey<S>
+where
+    S::PublicKey: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            param_set_id: self.param_set_id,
+            pk: self.pk.clone(),
+        }
+    }
+}
+
+impl<S: SignatureScheme> HashSigVerifyingKey<S> {
+    /// Wraps a public key of the parameter set with the given identifier
+    pub fn new(param_set_id: u16, pk: S::PublicKey) -> Self {
+        Self { param_set_id, pk }
+    }
+
+    /// Returns the identifier of the parameter set
+    pub fn param_set_id(&self) -> u16 {
+        self.param_set_id
+    }
+
+    /// Returns the wrapped public key
+    pub fn public_key(&self) -> &S::PublicKey {
+        &self.pk
+    }
+
+    /// Encodes the public key, see `codec::encode_public_key`
+    pub fn to_bytes(&self) -> Vec<u8>
+    where
+        S::PublicKey: Serialize,
+    {
+        encode_public_key::<S>(self.param_set_id, &self.pk)
+    }
+
+    /// Decodes a public key, taking the parameter set identifier from its header
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CodecError>
+    where
+        S::PublicKey: DeserializeOwned,
+    {
+        let param_set_id = codec::param_set_id(bytes)?;
+        let pk = decode_public_key::<S>(param_set_id, bytes)?;
+        Ok(Self { param_set_id, pk })
+    }
+}
+
+impl<S: SignatureScheme> Verifier<HashSigSignature<S>> for HashSigVerifyingKey<S> {
+    /// Verifies a signature for the epoch embedded in it. Fails if the message
+    /// is not a digest of length `MESSAGE_LENGTH`, or if the signature is for
+    /// a different parameter set.
+    fn verify(&self, msg: &[u8], signature: &HashSigSignature<S>) -> Result<(), Error> {
+        let message: &[u8; MESSAGE_LENGTH] = msg.try_into().map_err(|_| Error::new())?;
+        if signature.param_set_id != self.param_set_id {
+            return Err(Error::new());
+        }
+
+        if verify_with_epoch::<S>(
+            &self.pk,
+            message,
+            &signature.inner,
+            EpochMode::TrustEmbedded,
+        ) {
+            Ok(())
+        } else {
+            Err(Error::new())
+        }
+    }
+}
+
+/// Signing key that manages the epochs, see the module documentation
+pub struct HashSigSigningKey<S: SignatureScheme> {
+    sk: S::SecretKey,
+    verifying_key: HashSigVerifyingKey<S>,
+    next_epoch: AtomicU64,
+}
+
+impl<S: SignatureScheme> HashSigSigningKey<S> {
+    /// Creates a signing key from a key pair of the parameter set with the given
+    /// identifier. The first signature uses `next_epoch`, which must be larger
+    /// than all epochs that have been used with this key before.
+    pub fn new(param_set_id: u16, pk: S::PublicKey, sk: S::SecretKey, next_epoch: u32) -> Self {
+        Self {
+            sk,
+            verifying_key: HashSigVerifyingKey::new(param_set_id, pk),
+            next_epoch: AtomicU64::new(next_epoch as u64),
+        }
+    }
+
+    /// Returns the epoch that the next signature will use. If it is equal to
+    /// the lifetime of the scheme, all epochs are used and signing fails.
+    pub fn next_epoch(&self) -> u64 {
+        self.next_epoch.load(Ordering::SeqCst).min(S::LIFETIME)
+    }
+
+    /// Returns the wrapped secret key
+    pub fn secret_key(&self) -> &S::SecretKey {
+        &self.sk
+    }
+
+    /// Reserves the next unused epoch. Epochs are never handed out twice,
+    /// also if signatures are produced concurrently.
+    fn reserve_epoch(&self) -> Result<u32, Error> {
+        self.next_epoch
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |epoch| {
+                (epoch < S::LIFETIME).then_some(epoch + 1)
+            })
+            .map(|epoch| epoch as u32)
+            .map_err(|_| Error::new())
+    }
+}
+
+impl<S: SignatureScheme> Keypair for HashSigSigningKey<S>
+where
+    S::PublicKey: Clone,
+{
+    type VerifyingKey = HashSigVerifyingKey<S>;
+
+    fn verifying_key(&self) -> Self::VerifyingKey {
+        self.verifying_key.clone()
+    }
+}
+
+impl<S: SignatureScheme> RandomizedSigner<HashSigSignature<S>> for HashSigSigningKey<S> {
+    /// Signs a digest of length `MESSAGE_LENGTH` for the next unused epoch.
+    /// The epoch is consumed even if signing fails afterwards, so that it is
+    /// never used twice.
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> Result<HashSigSignature<S>, Error> {
+        let message: &[u8; MESSAGE_LENGTH] = msg.try_into().map_err(|_| Error::new())?;
+        let epoch = self.reserve_epoch()?;
+        let inner =
+            sign_with_epoch::<S, _>(rng, &self.sk, epoch, message).map_err(|_| Error::new())?;
+        Ok(HashSigSignature {
+            param_set_id: self.verifying_key.param_set_id,
+            inner,
+        })
+    }
+}
+
+#[cfg(feature = "getrandom")]
+impl<S: SignatureScheme> ::signature::Signer<HashSigSignature<S>> for HashSigSigningKey<S> {
+    /// Signs a digest of length `MESSAGE_LENGTH` for the next unused epoch,
+    /// using the randomness of the operating system, see `try_sign_with_rng`.
+    fn try_sign(&self, msg: &[u8]) -> Result<HashSigSignature<S>, Error> {
+        self.try_sign_with_rng(&mut rand_core::OsRng, msg)
+    }
+}
+
+/// Signature together with the epoch it has been produced for,
+/// tagged with the parameter set identifier of the signing key
+pub struct HashSigSignature<S: SignatureScheme> {
+    param_set_id: u16,
+    inner: SignatureWithEpoch<S::Signature>,
+}
+
+impl<S: SignatureScheme> Clone for HashSigSignature<S>
+where
+    S::Signature: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            param_set_id: self.param_set_id,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S: SignatureScheme> HashSigSignature<S> {
+    /// Returns the epoch that this signature consumed
+    pub fn epoch(&self) -> u32 {
+        self.inner.epoch
+    }
+
+    /// Returns the identifier of the parameter set
+    pub fn param_set_id(&self) -> u16 {
+        self.param_set_id
+    }
+
+    /// Returns the wrapped signature of the scheme
+    pub fn signature(&self) -> &S::Signature {
+        &self.inner.signature
+    }
+}
+
+impl<S: SignatureScheme> TryFrom<&[u8]> for HashSigSignature<S>
+where
+    S::Signature: DeserializeOwned,
+{
+    type Error = CodecError;
+
+    /// Decodes a signature with embedded epoch, taking the
+    /// parameter set identifier from its header
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let param_set_id = codec::param_set_id(bytes)?;
+        let inner = codec::decode(ArtifactKind::Signature, param_set_id, bytes)?;
+        Ok(Self {
+            param_set_id,
+            inner,
+        })
+    }
+}
+
+impl<S: SignatureScheme> From<HashSigSignature<S>> for Vec<u8>
+where
+    S::Signature: Serialize,
+{
+    fn from(signature: HashSigSignature<S>) -> Self {
+        codec::encode(
+            ArtifactKind::Signature,
+            signature.param_set_id,
+            &signature.inner,
+        )
+    }
+}
+
+impl<S: SignatureScheme> SignatureEncoding for HashSigSignature<S>
+where
+    S::Signature: Clone + Serialize + DeserializeOwned,
+{
+    type Repr = Vec<u8>;
+}
+
+#[cfg(test)]
+mod tests {
+    use ::signature::{Keypair, RandomizedSigner, SignatureEncoding, Verifier};
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        inc_encoding::basic_winternitz::WinternitzEncoding,
+        signature::generalized_xmss::GeneralizedXMSSSignatureScheme,
+        symmetric::{
+            message_hash::sha::ShaMessageHash192x3, prf::sha::ShaPRF,
+            tweak_hash::sha::ShaTweak192192,
+        },
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type Sig = GeneralizedXMSSSignatureScheme<
+        ShaPRF<24>,
+        WinternitzEncoding<ShaMessageHash192x3, 3>,
+        ShaTweak192192,
+        2,
+    >;
+
+    /// Signs and verifies only through the generic traits, as generic
+    /// code that can also use other signature schemes would do.
+    /// Returns the encoded signature.
+    fn sign_and_verify_generic<K, Signature>(signer: &K, msg: &[u8]) -> Vec<u8>
+    where
+        K: RandomizedSigner<Signature> + Keypair,
+        K::VerifyingKey: Verifier<Signature>,
+        Signature: SignatureEncoding,
+    {
+        let signature = signer.sign_with_rng(&mut thread_rng(), msg);
+        let bytes = signature.to_vec();
+
+        let decoded = Signature::try_from(bytes.as_slice()).ok().unwrap();
+        let verifying_key = signer.verifying_key();
+        assert!(verifying_key.verify(msg, &decoded).is_ok());
+
+        let mut other_msg = msg.to_vec();
+        other_msg[0] ^= 0x01;
+        assert!(verifying_key.verify(&other_msg, &decoded).is_err());
+        bytes
+    }
+
+    #[test]
+    fn test_sign_verify_generic() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Sig::gen(&mut rng);
+        let signer = HashSigSigningKey::<Sig>::new(0x1234, pk, sk, 1);
+
+        // each signature consumes the next epoch
+        for expected_epoch in 1..Sig::LIFETIME as u32 {
+            let msg: [u8; MESSAGE_LENGTH] = rng.gen();
+            let bytes = sign_and_verify_generic(&signer, &msg);
+
+            let signature = HashSigSignature::<Sig>::try_from(bytes.as_slice()).unwrap();
+            assert_eq!(signature.epoch(), expected_epoch);
+            assert_eq!(signature.param_set_id(), 0x1234);
+            assert_eq!(signer.next_epoch(), expected_epoch as u64 + 1);
+        }
+
+        // all epochs are used
+        let msg: [u8; MESSAGE_LENGTH] = rng.gen();
+        assert!(signer.try_sign_with_rng(&mut rng, &msg).is_err());
+        assert_eq!(signer.next_epoch(), Sig::LIFETIME);
+    }
+
+    #[test]
+    fn test_rejects_invalid_inputs() {
+        let mut rng = thread_rng();
+        let (pk, sk) = Sig::gen(&mut rng);
+        let signer = HashSigSigningKey::<Sig>::new(0x1234, pk, sk, 0);
+
+        // messages must be digests, and no epoch is consumed otherwise
+        assert!(signer.try_sign_with_rng(&mut rng, &[0u8; 31]).is_err());
+        assert_eq!(signer.next_epoch(), 0);
+
+        let msg: [u8; MESSAGE_LENGTH] = rng.gen();
+        let signature = signer.try_sign_with_rng(&mut rng, &msg).unwrap();
+        let verifying_key = signer.verifying_key();
+        assert!(verifying_key.verify(&msg, &signature).is_ok());
+        assert!(verifying_key.verify(&msg[1..], &signature).is_err());
+
+        // the verifying key survives encoding
+        let decoded = HashSigVerifyingKey::<Sig>::from_bytes(&verifying_key.to_bytes()).unwrap();
+        assert_eq!(decoded.param_set_id(), 0x1234);
+        assert!(decoded.verify(&msg, &signature).is_ok());
+
+        // a key of another parameter set rejects the signature
+        let other_key = HashSigVerifyingKey::<Sig>::new(0x4321, *verifying_key.public_key());
+        assert!(other_key.verify(&msg, &signature).is_err());
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn test_signer_os() {
+        use ::signature::Signer;
+
+        let (pk, sk) = Sig::gen_os();
+        let signer = HashSigSigningKey::<Sig>::new(0x1234, pk, sk, 0);
+        let msg = [0x42; MESSAGE_LENGTH];
+
+        let signature: HashSigSignature<Sig> = signer.try_sign(&msg).unwrap();
+        assert_eq!(signature.epoch(), 0);
+        assert!(signer.verifying_key().verify(&msg, &signature).is_ok());
+    }
+}
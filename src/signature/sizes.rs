@@ -0,0 +1,207 @@
+use std::fmt;
+
+use crate::{
+    inc_encoding::IncomparableEncoding,
+    symmetric::{prf::Pseudorandom, seed_expander::SEED_LENGTH, tweak_hash::TweakableHash},
+};
+
+use super::{
+    codec::HEADER_LENGTH, generalized_xmss::GeneralizedXMSSSignatureScheme, SignatureScheme,
+};
+
+/// Length in bytes of the length prefix of a vector in the encoding of `codec`
+const VEC_LENGTH_PREFIX: usize = 8;
+
+/// Sizes and costs of an instantiation of a signature scheme.
+///
+/// Sizes are in bytes, and refer to the encoding of `codec`, including the header.
+/// Costs are given as the number of calls to the underlying hash functions, i.e.,
+/// the PRF, the message hash, and the tweakable hash, in the worst case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeReport {
+    pub public_key_bytes: usize,
+    /// secret key as it is stored, i.e., including the Merkle tree
+    pub secret_key_bytes: usize,
+    /// secret key in seed form, see `SignatureScheme::key_gen_from_seed`
+    pub secret_key_seed_bytes: usize,
+    pub signature_bytes: usize,
+    pub key_gen_hash_calls: u64,
+    pub sign_hash_calls: u64,
+    pub verify_hash_calls: u64,
+}
+
+/// Signature schemes for which a `SizeReport` can be computed
+pub trait ReportSizes: SignatureScheme {
+    fn size_report() -> SizeReport;
+}
+
+/// Computes the `SizeReport` of the given signature scheme.
+pub fn report<S: ReportSizes>() -> SizeReport {
+    S::size_report()
+}
+
+/// Note: sizes are computed assuming that parameters, randomness, keys, and
+/// domain elements are encoded using their in-memory size, as for byte arrays.
+impl<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash, const LOG_LIFETIME: usize>
+    ReportSizes for GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>
+where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+{
+    fn size_report() -> SizeReport {
+        let parameter_len = std::mem::size_of::<TH::Parameter>();
+        let hash_len = std::mem::size_of::<TH::Domain>();
+        let rand_len = std::mem::size_of::<IE::Randomness>();
+        let prf_key_len = std::mem::size_of::<PRF::Key>();
+
+        let lifetime = Self::LIFETIME;
+        let num_chains = IE::NUM_CHUNKS as u64;
+        let chain_length = IE::chain_length() as u64;
+
+        // the public key is a root and a parameter
+        let public_key_bytes = HEADER_LENGTH + hash_len + parameter_len;
+
+        // the secret key is a PRF key, all layers of the Merkle tree, and a parameter
+        let num_layers = LOG_LIFETIME + 1;
+        let num_nodes = (2 << LOG_LIFETIME) - 1;
+        let secret_key_bytes = HEADER_LENGTH
+            + prf_key_len
+            + VEC_LENGTH_PREFIX
+            + num_layers * VEC_LENGTH_PREFIX
+            + num_nodes * hash_len
+            + parameter_len;
+
+        // the signature is a Merkle path, a randomness, and one hash per chain
+        let signature_bytes = HEADER_LENGTH
+            + VEC_LENGTH_PREFIX
+            + LOG_LIFETIME * hash_len
+            + rand_len
+            + VEC_LENGTH_PREFIX
+            + IE::NUM_CHUNKS * hash_len;
+
+        // key generation: per epoch, one PRF call and a full walk per chain,
+        // and one hash of all chain ends. Then, all inner nodes of the tree.
+        let key_gen_hash_calls = lifetime * (num_chains * (1 + chain_length) + 1) + (lifetime - 1);
+
+        // signing: at most MAX_TRIES message hashes, and then one PRF
+        // call per chain and at most a full walk per chain
+        let sign_hash_calls = IE::MAX_TRIES as u64 + num_chains * (1 + chain_length);
+
+        // verification: one message hash, at most a full walk per chain,
+        // one hash of all chain ends, and one hash per level of the tree
+        let verify_hash_calls = 1 + num_chains * chain_length + 1 + LOG_LIFETIME as u64;
+
+        SizeReport {
+            public_key_bytes,
+            secret_key_bytes,
+            secret_key_seed_bytes: SEED_LENGTH,
+            signature_bytes,
+            key_gen_hash_calls,
+            sign_hash_calls,
+            verify_hash_calls,
+        }
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: [(&str, u64); 7] = [
+            ("Public key (bytes)", self.public_key_bytes as u64),
+            ("Secret key (bytes)", self.secret_key_bytes as u64),
+            (
+                "Secret key, seed (bytes)",
+                self.secret_key_seed_bytes as u64,
+            ),
+            ("Signature (bytes)", self.signature_bytes as u64),
+            ("Key gen (hash calls)", self.key_gen_hash_calls),
+            ("Sign, worst case (hash calls)", self.sign_hash_calls),
+            ("Verify, worst case (hash calls)", self.verify_hash_calls),
+        ];
+        for (name, value) in rows {
+            writeln!(f, "| {:<31} | {:>12} |", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        inc_encoding::{basic_winternitz::WinternitzEncoding, target_sum::TargetSumEncoding},
+        signature::codec::{
+            encode_public_key, encode_secret_key, encode_signature, UNREGISTERED_PARAM_SET_ID,
+        },
+        symmetric::{
+            message_hash::sha::ShaMessageHash192x3, prf::sha::ShaPRF,
+            tweak_hash::sha::ShaTweak192192,
+        },
+        MESSAGE_LENGTH,
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type SigWinternitz = GeneralizedXMSSSignatureScheme<
+        ShaPRF<24>,
+        WinternitzEncoding<ShaMessageHash192x3, 3>,
+        ShaTweak192192,
+        6,
+    >;
+    type SigTargetSum = GeneralizedXMSSSignatureScheme<
+        ShaPRF<24>,
+        TargetSumEncoding<ShaMessageHash192x3, 360>,
+        ShaTweak192192,
+        5,
+    >;
+
+    /// Checks that the reported sizes match the lengths of actual encodings.
+    fn _test_sizes_match_encodings<S: ReportSizes>()
+    where
+        S::PublicKey: serde::Serialize,
+        S::SecretKey: serde::Serialize,
+        S::Signature: serde::Serialize,
+    {
+        let mut rng = thread_rng();
+        let report = report::<S>();
+
+        let (pk, sk) = S::gen(&mut rng);
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+        let sig = S::sign(&mut rng, &sk, 3, &message).unwrap();
+
+        let id = UNREGISTERED_PARAM_SET_ID;
+        assert_eq!(
+            report.public_key_bytes,
+            encode_public_key::<S>(id, &pk).len()
+        );
+        assert_eq!(
+            report.secret_key_bytes,
+            encode_secret_key::<S>(id, &sk).len()
+        );
+        assert_eq!(
+            report.signature_bytes,
+            encode_signature::<S>(id, &sig).len()
+        );
+        assert_eq!(report.secret_key_seed_bytes, SEED_LENGTH);
+    }
+
+    #[test]
+    fn test_sizes_match_encodings() {
+        _test_sizes_match_encodings::<SigWinternitz>();
+        _test_sizes_match_encodings::<SigTargetSum>();
+    }
+
+    #[test]
+    fn test_hash_calls() {
+        let report = report::<SigWinternitz>();
+
+        // 51 chains of length 15, and a tree of depth 6
+        let lifetime = 64;
+        let per_epoch = 51 * 16 + 1;
+        assert_eq!(report.key_gen_hash_calls, lifetime * per_epoch + 63);
+        assert_eq!(report.sign_hash_calls, 1 + 51 * 16);
+        assert_eq!(report.verify_hash_calls, 1 + 51 * 15 + 1 + 6);
+    }
+}
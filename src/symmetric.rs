@@ -1,4 +1,60 @@
+pub mod constant_time;
+#[cfg(feature = "profiling")]
+pub mod counters;
+pub mod fixed_bytes;
 pub mod message_hash;
 pub mod prf;
+pub mod seed_expander;
+#[cfg(feature = "sha")]
+pub(crate) mod sha3_backend;
+#[cfg(all(feature = "sha", feature = "simd", target_arch = "x86_64"))]
+pub(crate) mod sha3_multi;
+pub mod trace;
 pub mod tweak_hash;
 pub mod tweak_hash_tree;
+
+#[cfg(all(test, feature = "sha"))]
+mod tests {
+    use super::{
+        message_hash::{sha::ShaMessageHash, sha256::Sha256MessageHash, MessageHash},
+        prf::{sha::ShaPRF, Pseudorandom},
+        tweak_hash::{sha::ShaTweakHash, TweakableHash},
+    };
+
+    /// The hash markers are zero-sized, and can be stored in structs
+    #[derive(Clone, Copy, Debug, Default)]
+    struct Components {
+        prf: ShaPRF<24>,
+        message_hash: ShaMessageHash<16, 16, 16, 8>,
+        message_hash_sha256: Sha256MessageHash<16, 16, 16, 8>,
+        tweak_hash: ShaTweakHash<16, 24>,
+    }
+
+    fn prf_output_len<PRF: Pseudorandom>(_prf: PRF) -> usize {
+        std::mem::size_of::<PRF::Output>()
+    }
+
+    fn num_chunks<MH: MessageHash>(_message_hash: MH) -> usize {
+        MH::NUM_CHUNKS
+    }
+
+    fn hash_len<TH: TweakableHash>(_tweak_hash: TH) -> usize {
+        std::mem::size_of::<TH::Domain>()
+    }
+
+    #[test]
+    fn test_markers_default_and_copy() {
+        let components = Components::default();
+        assert_eq!(std::mem::size_of::<Components>(), 0);
+
+        // markers are passed by value, and can still be used afterwards
+        let copy = components;
+        assert_eq!(prf_output_len(copy.prf), 24);
+        assert_eq!(num_chunks(copy.message_hash), 16);
+        assert_eq!(num_chunks(copy.message_hash_sha256), 16);
+        assert_eq!(hash_len(copy.tweak_hash), 24);
+        assert_eq!(hash_len(components.tweak_hash), 24);
+
+        assert_eq!(format!("{:?}", components.prf), "ShaPRF");
+    }
+}
@@ -0,0 +1,55 @@
+/// Equality that does not branch on the compared values, and whose running
+/// time only depends on their length. It is used for the comparisons that
+/// decide whether a signature verifies, e.g., of a recomputed Merkle root
+/// with the root in the public key.
+///
+/// Note: for comparisons of public data, e.g., of two public keys, the usual
+/// `PartialEq` is fine, and the types implement both.
+pub trait ConstantTimeEq {
+    /// Returns true if and only if `self` equals `other`, in constant time.
+    fn ct_eq(&self, other: &Self) -> bool;
+}
+
+/// Compares two byte slices in constant time. Slices of different lengths are
+/// not equal. Their lengths are considered public and may leak.
+pub fn ct_eq_bytes(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    // keep the compiler from turning the fold into an early exit
+    std::hint::black_box(difference) == 0
+}
+
+impl<const N: usize> ConstantTimeEq for [u8; N] {
+    fn ct_eq(&self, other: &Self) -> bool {
+        ct_eq_bytes(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ct_eq_bytes() {
+        let a = [0x42u8; 24];
+        assert!(a.ct_eq(&a));
+        assert!(ct_eq_bytes(&a, &a));
+        assert!(ct_eq_bytes(&[], &[]));
+
+        // every single differing bit is detected
+        for i in 0..a.len() {
+            for bit in 0..8 {
+                let mut b = a;
+                b[i] ^= 1 << bit;
+                assert!(!a.ct_eq(&b));
+                assert!(!ct_eq_bytes(&a, &b));
+            }
+        }
+
+        // slices of different lengths are not equal
+        assert!(!ct_eq_bytes(&a, &a[..23]));
+        assert!(!ct_eq_bytes(&a[..1], &[]));
+    }
+}
@@ -0,0 +1,335 @@
+//! Counting hash invocations, e.g., for cost accounting.
+//!
+//! `CountingTweakHash` and `CountingMessageHash` wrap a tweakable hash and a
+//! message hash, respectively. They compute exactly the same hashes as the
+//! wrapped functions, but count each invocation in global atomic counters.
+//! To count the hashes of a scheme, instantiate it with the wrapped functions
+//! and use `measure`, or `cost_report` and `reset_counters`.
+//!
+//! Note: the counters are shared by all threads and all instantiations, so
+//! operations that run concurrently are counted together.
+
+use std::{
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use rand_core::{CryptoRng, RngCore};
+
+use crate::MESSAGE_LENGTH;
+
+use super::{message_hash::MessageHash, tweak_hash::TweakableHash};
+
+static CHAIN_HASHES: AtomicU64 = AtomicU64::new(0);
+static TREE_HASHES: AtomicU64 = AtomicU64::new(0);
+static MESSAGE_HASHES: AtomicU64 = AtomicU64::new(0);
+
+/// Number of hash invocations, by kind
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CostReport {
+    /// applications of the tweakable hash with a chain tweak
+    pub chain_hashes: u64,
+    /// applications of the tweakable hash with a tree tweak. This includes
+    /// the hashes of the chain ends, which are the leafs of the Merkle tree.
+    pub tree_hashes: u64,
+    /// applications of the message hash
+    pub message_hashes: u64,
+}
+
+/// Returns the number of hash invocations since the last reset
+pub fn cost_report() -> CostReport {
+    CostReport {
+        chain_hashes: CHAIN_HASHES.load(Ordering::Relaxed),
+        tree_hashes: TREE_HASHES.load(Ordering::Relaxed),
+        message_hashes: MESSAGE_HASHES.load(Ordering::Relaxed),
+    }
+}
+
+/// Sets all counters to zero
+pub fn reset_counters() {
+    CHAIN_HASHES.store(0, Ordering::Relaxed);
+    TREE_HASHES.store(0, Ordering::Relaxed);
+    MESSAGE_HASHES.store(0, Ordering::Relaxed);
+}
+
+/// Runs `op`, and returns its result together with the number
+/// of hash invocations during `op`.
+pub fn measure<T>(op: impl FnOnce() -> T) -> (T, CostReport) {
+    let before = cost_report();
+    let result = op();
+    let after = cost_report();
+    let report = CostReport {
+        chain_hashes: after.chain_hashes - before.chain_hashes,
+        tree_hashes: after.tree_hashes - before.tree_hashes,
+        message_hashes: after.message_hashes - before.message_hashes,
+    };
+    (result, report)
+}
+
+/// Tweak of `CountingTweakHash`, which remembers whether
+/// it is a chain tweak or a tree tweak
+pub enum CountingTweak<T> {
+    Chain(T),
+    Tree(T),
+}
+
+impl<T> CountingTweak<T> {
+    /// Returns the tweak of the wrapped tweakable hash
+    fn inner(&self) -> &T {
+        match self {
+            CountingTweak::Chain(tweak) | CountingTweak::Tree(tweak) => tweak,
+        }
+    }
+
+    /// Counts one application with this tweak
+    fn count(&self) {
+        let counter = match self {
+            CountingTweak::Chain(_) => &CHAIN_HASHES,
+            CountingTweak::Tree(_) => &TREE_HASHES,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// A tweakable hash that computes the same hashes as `TH`,
+/// and counts how often it is applied with each kind of tweak
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountingTweakHash<TH: TweakableHash> {
+    _marker: PhantomData<TH>,
+}
+
+impl<TH: TweakableHash> TweakableHash for CountingTweakHash<TH> {
+    type Parameter = TH::Parameter;
+
+    type Tweak = CountingTweak<TH::Tweak>;
+
+    type Domain = TH::Domain;
+
+    fn rand_parameter<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Parameter {
+        TH::rand_parameter(rng)
+    }
+
+    fn rand_domain<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Domain {
+        TH::rand_domain(rng)
+    }
+
+    fn tree_tweak(level: u8, pos_in_level: u32) -> Self::Tweak {
+        CountingTweak::Tree(TH::tree_tweak(level, pos_in_level))
+    }
+
+    fn chain_tweak(epoch: u32, chain_index: u16, pos_in_chain: u16) -> Self::Tweak {
+        CountingTweak::Chain(TH::chain_tweak(epoch, chain_index, pos_in_chain))
+    }
+
+    fn identifier() -> Vec<u8> {
+        TH::identifier()
+    }
+
+    fn encode_tweak(tweak: &Self::Tweak) -> Vec<u8> {
+        TH::encode_tweak(tweak.inner())
+    }
+
+    fn apply(
+        parameter: &Self::Parameter,
+        tweak: &Self::Tweak,
+        message: &[Self::Domain],
+    ) -> Self::Domain {
+        tweak.count();
+        TH::apply(parameter, tweak.inner(), message)
+    }
+
+    fn apply_iter<I: Iterator<Item = Self::Domain>>(
+        parameter: &Self::Parameter,
+        tweak: &Self::Tweak,
+        message: I,
+    ) -> Self::Domain {
+        tweak.count();
+        TH::apply_iter(parameter, tweak.inner(), message)
+    }
+
+    // Note: `apply_batch` is not forwarded, as the tweaks of `TH` cannot be
+    // taken out of the wrapped tweaks. It applies the hash one by one.
+
+    fn is_canonical(element: &Self::Domain) -> bool {
+        TH::is_canonical(element)
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        TH::internal_consistency_check();
+    }
+}
+
+/// A message hash that computes the same hashes as `MH`,
+/// and counts how often it is applied
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CountingMessageHash<MH: MessageHash> {
+    _marker: PhantomData<MH>,
+}
+
+impl<MH: MessageHash> MessageHash for CountingMessageHash<MH> {
+    type Parameter = MH::Parameter;
+
+    type Randomness = MH::Randomness;
+
+    const NUM_CHUNKS: usize = MH::NUM_CHUNKS;
+
+    const CHUNK_SIZE: usize = MH::CHUNK_SIZE;
+
+    fn identifier() -> Vec<u8> {
+        MH::identifier()
+    }
+
+    fn rand<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Randomness {
+        MH::rand(rng)
+    }
+
+    fn apply(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        MESSAGE_HASHES.fetch_add(1, Ordering::Relaxed);
+        MH::apply(parameter, epoch, randomness, message)
+    }
+
+    fn apply_into(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+        out: &mut [u8],
+    ) {
+        MESSAGE_HASHES.fetch_add(1, Ordering::Relaxed);
+        MH::apply_into(parameter, epoch, randomness, message, out)
+    }
+
+    fn apply_packed(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        MESSAGE_HASHES.fetch_add(1, Ordering::Relaxed);
+        MH::apply_packed(parameter, epoch, randomness, message)
+    }
+
+    fn unpack(packed: &[u8]) -> Vec<u8> {
+        MH::unpack(packed)
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        MH::internal_consistency_check();
+    }
+}
+
+#[cfg(all(test, feature = "sha"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        inc_encoding::{basic_winternitz::WinternitzEncoding, IncomparableEncoding},
+        signature::{generalized_xmss::GeneralizedXMSSSignatureScheme, SignatureScheme},
+        symmetric::{
+            message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, seed_expander::SEED_LENGTH,
+            tweak_hash::sha::ShaTweakHash,
+        },
+    };
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type MH = ShaMessageHash<16, 16, 32, 4>;
+    type TH = ShaTweakHash<16, 16>;
+    type IE<MH> = WinternitzEncoding<MH, 3>;
+    const LOG_LIFETIME: usize = 5;
+    type Sig = GeneralizedXMSSSignatureScheme<ShaPRF<16>, IE<MH>, TH, LOG_LIFETIME>;
+    type CountedSig = GeneralizedXMSSSignatureScheme<
+        ShaPRF<16>,
+        IE<CountingMessageHash<MH>>,
+        CountingTweakHash<TH>,
+        LOG_LIFETIME,
+    >;
+
+    /// The counters are global, so tests that use them must not run concurrently
+    static COUNTERS_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    pub fn test_counts_match_formulas() {
+        let _lock = COUNTERS_LOCK.lock().unwrap();
+        let mut rng = thread_rng();
+        let num_chains = IE::<MH>::NUM_CHUNKS as u64;
+        let chain_length = IE::<MH>::chain_length() as u64;
+        let lifetime = CountedSig::LIFETIME;
+
+        // key generation walks all chains of all epochs, and hashes
+        // the chain ends of each epoch and the inner nodes of the tree
+        let ((pk, sk), keygen) = measure(|| CountedSig::gen(&mut rng));
+        assert_eq!(
+            keygen,
+            CostReport {
+                chain_hashes: lifetime * num_chains * chain_length,
+                tree_hashes: lifetime + (lifetime - 1),
+                message_hashes: 0,
+            }
+        );
+
+        // signing and verification together walk each chain once. Signing
+        // hashes the message once, as the Winternitz encoding never fails.
+        let epoch = 13;
+        let message = rng.gen();
+        let (signature, sign) = measure(|| CountedSig::sign(&mut rng, &sk, epoch, &message));
+        let signature = signature.unwrap();
+        let (is_valid, verify) = measure(|| CountedSig::verify(&pk, epoch, &message, &signature));
+        assert!(is_valid);
+
+        assert_eq!(
+            sign.chain_hashes + verify.chain_hashes,
+            num_chains * chain_length
+        );
+        assert_eq!(sign.tree_hashes, 0);
+        assert_eq!(sign.message_hashes, 1);
+        assert_eq!(verify.tree_hashes, 1 + LOG_LIFETIME as u64);
+        assert_eq!(verify.message_hashes, 1);
+    }
+
+    #[test]
+    pub fn test_sign_count_matches_codeword() {
+        let _lock = COUNTERS_LOCK.lock().unwrap();
+        let mut rng = thread_rng();
+        let (pk, sk) = CountedSig::gen(&mut rng);
+
+        // signing walks each chain up to the position given by the codeword
+        // of the signature, and hashes nothing else but the message
+        for epoch in [0, 13, 31] {
+            let message = rng.gen();
+            let (signature, sign) = measure(|| CountedSig::sign(&mut rng, &sk, epoch, &message));
+            let signature = signature.unwrap();
+            let x = IE::<MH>::encode(&pk.parameter, &message, signature.rho(), epoch).unwrap();
+            assert_eq!(
+                sign,
+                CostReport {
+                    chain_hashes: x.iter().map(|&xi| xi as u64).sum(),
+                    tree_hashes: 0,
+                    message_hashes: 1,
+                }
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_counting_does_not_change_keys() {
+        let _lock = COUNTERS_LOCK.lock().unwrap();
+        let seed = [0x31; SEED_LENGTH];
+        let (pk, _) = Sig::key_gen_from_seed(seed);
+        let (pk_counted, _) = CountedSig::key_gen_from_seed(seed);
+        assert_eq!(
+            bincode::serialize(&pk).unwrap(),
+            bincode::serialize(&pk_counted).unwrap()
+        );
+    }
+}
@@ -0,0 +1,106 @@
+/// Error when converting a slice into a fixed-length byte array
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthMismatch {
+    /// length of the byte array
+    pub expected: usize,
+    /// length of the given slice
+    pub actual: usize,
+}
+
+/// Fixed-length byte array, e.g., a parameter, a randomness, or a domain
+/// element of the SHA based building blocks. It allows to convert byte
+/// slices into such types, reporting the lengths if they do not match:
+///
+/// ```
+/// use hashsig::symmetric::fixed_bytes::FixedBytes;
+///
+/// let buffer = [0x42u8; 32];
+/// let parameter: [u8; 18] = FixedBytes::try_from(&buffer[..18]).unwrap().into();
+/// assert!(FixedBytes::<18>::try_from(&buffer[..]).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedBytes<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> TryFrom<&[u8]> for FixedBytes<N> {
+    type Error = LengthMismatch;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        bytes
+            .try_into()
+            .map(FixedBytes)
+            .map_err(|_| LengthMismatch {
+                expected: N,
+                actual: bytes.len(),
+            })
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for FixedBytes<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        FixedBytes(bytes)
+    }
+}
+
+impl<const N: usize> From<FixedBytes<N>> for [u8; N] {
+    fn from(bytes: FixedBytes<N>) -> Self {
+        bytes.0
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for FixedBytes<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::symmetric::tweak_hash::{sha::ShaTweak128192, TweakableHash};
+
+    use super::*;
+
+    #[test]
+    fn test_correct_length() {
+        let bytes: Vec<u8> = (0..24).collect();
+
+        // domain element of a tweak hash
+        let domain: <ShaTweak128192 as TweakableHash>::Domain =
+            FixedBytes::try_from(&bytes[..]).unwrap().into();
+        assert_eq!(&domain[..], &bytes[..]);
+
+        // parameter of a tweak hash
+        let parameter: <ShaTweak128192 as TweakableHash>::Parameter =
+            FixedBytes::try_from(&bytes[..16]).unwrap().into();
+        assert_eq!(&parameter[..], &bytes[..16]);
+
+        // round trip
+        assert_eq!(FixedBytes::from(domain).as_ref(), &bytes[..]);
+    }
+
+    #[test]
+    fn test_incorrect_length() {
+        let bytes = [0u8; 32];
+
+        assert_eq!(
+            FixedBytes::<24>::try_from(&bytes[..23]),
+            Err(LengthMismatch {
+                expected: 24,
+                actual: 23
+            })
+        );
+        assert_eq!(
+            FixedBytes::<24>::try_from(&bytes[..]),
+            Err(LengthMismatch {
+                expected: 24,
+                actual: 32
+            })
+        );
+        assert_eq!(
+            FixedBytes::<16>::try_from(&bytes[..0]),
+            Err(LengthMismatch {
+                expected: 16,
+                actual: 0
+            })
+        );
+    }
+}
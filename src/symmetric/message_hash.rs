@@ -1,4 +1,6 @@
-use rand::Rng;
+use std::{hash::Hash, ops::Deref};
+
+use rand_core::{CryptoRng, RngCore};
 
 use crate::MESSAGE_LENGTH;
 
@@ -10,15 +12,21 @@ use crate::MESSAGE_LENGTH;
 /// are implicitly derived from the epoch.
 pub trait MessageHash {
     type Parameter: Clone + Sized;
-    type Randomness;
+    type Randomness: Clone + Eq + Hash;
 
     const NUM_CHUNKS: usize;
 
     /// Must be 1, 2, 4, or 8
     const CHUNK_SIZE: usize;
 
+    /// Identifies the hash function, and everything else that determines
+    /// the output besides the constants and lengths, e.g., an application
+    /// identifier. Different message hashes must have different
+    /// identifiers, as they are part of the identifier of an instantiation.
+    fn identifier() -> Vec<u8>;
+
     /// Generates a random domain element.
-    fn rand<R: Rng>(rng: &mut R) -> Self::Randomness;
+    fn rand<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Randomness;
 
     /// Applies the message hash to a parameter, an epoch,
     /// a randomness, and a message. It outputs a list of chunks.
@@ -31,6 +39,62 @@ pub trait MessageHash {
         message: &[u8; MESSAGE_LENGTH],
     ) -> Vec<u8>;
 
+    /// Same as `apply`, but writes the chunks into `out` instead of allocating
+    /// a vector. The output buffer must have length NUM_CHUNKS.
+    ///
+    /// Note: the default implementation uses `apply`, and therefore allocates.
+    fn apply_into(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+        out: &mut [u8],
+    ) {
+        out.copy_from_slice(&Self::apply(parameter, epoch, randomness, message));
+    }
+
+    /// Same as `apply`, but the chunks are bit-packed into bytes, i.e.,
+    /// 8 / CHUNK_SIZE many chunks per byte. This saves memory for chunk
+    /// sizes below 8. Use `unpack` to get back the chunks.
+    fn apply_packed(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        let chunks = Self::apply(parameter, epoch, randomness, message);
+        chunks_to_bytes(&chunks, Self::CHUNK_SIZE)
+    }
+
+    /// Same as `apply`, but returns the chunks as `Chunks`, which guarantees
+    /// that there are NUM_CHUNKS many chunks, each below 2^CHUNK_SIZE. The
+    /// const generics must be equal to `Self::NUM_CHUNKS` and `Self::CHUNK_SIZE`,
+    /// otherwise this fails to build (but not `cargo check`, which does not
+    /// instantiate it).
+    fn apply_typed<const NUM_CHUNKS: usize, const CHUNK_SIZE: usize>(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Chunks<NUM_CHUNKS, CHUNK_SIZE> {
+        const {
+            assert!(
+                NUM_CHUNKS == Self::NUM_CHUNKS && CHUNK_SIZE == Self::CHUNK_SIZE,
+                "Message hash: Chunks must have NUM_CHUNKS many chunks of size CHUNK_SIZE"
+            )
+        };
+        let mut chunks = [0; NUM_CHUNKS];
+        Self::apply_into(parameter, epoch, randomness, message, &mut chunks);
+        Chunks::new(chunks).expect("Message hash: chunks must be below 2^CHUNK_SIZE")
+    }
+
+    /// Restores the chunks from the output of `apply_packed`.
+    fn unpack(packed: &[u8]) -> Vec<u8> {
+        let mut chunks = bytes_to_chunks(packed, Self::CHUNK_SIZE);
+        chunks.truncate(Self::NUM_CHUNKS);
+        chunks
+    }
+
     /// Function to check internal consistency of any given parameters
     /// For testing only, and expected to panic if something is wrong.
     #[cfg(test)]
@@ -38,7 +102,14 @@ pub trait MessageHash {
 }
 
 // pub mod poseidon;
+#[cfg(feature = "sha")]
+pub mod digest;
+#[cfg(feature = "sha")]
+pub mod keccak;
+#[cfg(feature = "sha")]
 pub mod sha;
+#[cfg(feature = "sha")]
+pub mod sha256;
 
 /// Isolates a chunk of bits from a byte based on the specified chunk index and chunk size.
 ///
@@ -67,34 +138,423 @@ const fn isolate_chunk_from_byte(byte: u8, chunk_index: usize, chunk_size: usize
     (byte >> start_bit_pos) & mask
 }
 
+/// Error enum for converting between bytes and chunks
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChunkError {
+    /// The chunk size is not 1, 2, 4, or 8
+    UnsupportedChunkSize(usize),
+    /// There are not enough bytes for the requested number of chunks
+    NotEnoughBytes,
+    /// The chunk at the given index is not below 2^chunk_size
+    ChunkOutOfRange(usize),
+    /// The number of chunks is not the expected one, which is given
+    WrongNumberOfChunks(usize),
+}
+
+/// Checks that `chunk_size` divides 8 and is between 1 and 8
+fn check_chunk_size(chunk_size: usize) -> Result<(), ChunkError> {
+    match chunk_size {
+        1 | 2 | 4 | 8 => Ok(()),
+        _ => Err(ChunkError::UnsupportedChunkSize(chunk_size)),
+    }
+}
+
+/// Checks that all chunks are below 2^chunk_size
+fn check_chunks_in_range(chunks: &[u8], chunk_size: usize) -> Result<(), ChunkError> {
+    match chunks
+        .iter()
+        .position(|&chunk| (chunk as u16) >> chunk_size != 0)
+    {
+        Some(index) => Err(ChunkError::ChunkOutOfRange(index)),
+        None => Ok(()),
+    }
+}
+
 /// Function to turn a list of bytes into a list of chunks.
 /// That is, each byte is split up into chunks containing `chunk_size`
 /// many bits. For example, if `bytes` contains 6 elements, and
 /// `chunk_size` is 2, then the result contains 6 * (8/2) = 24 elements.
 ///  It is assumed that `window_size` divides 8 and is between 1 and 8.
+/// Use `try_bytes_to_chunks` if the chunk size is not known to be valid.
 pub fn bytes_to_chunks(bytes: &[u8], chunk_size: usize) -> Vec<u8> {
-    // Ensure chunk size divides 8 and is between 1 and 8
-    assert!(chunk_size > 0 && chunk_size <= 8 && 8 % chunk_size == 0);
+    try_bytes_to_chunks(bytes, chunk_size).expect("bytes_to_chunks: Invalid chunk size")
+}
+
+/// Same as `bytes_to_chunks`, but returns an error
+/// instead of panicking if the chunk size is not supported.
+pub fn try_bytes_to_chunks(bytes: &[u8], chunk_size: usize) -> Result<Vec<u8>, ChunkError> {
+    check_chunk_size(chunk_size)?;
+    let mut chunks = vec![0; bytes.len() * 8 / chunk_size];
+    try_bytes_to_chunks_into(bytes, chunk_size, &mut chunks)?;
+    Ok(chunks)
+}
+
+/// Same as `bytes_to_chunks`, but writes the chunks into `out`
+/// instead of allocating a vector. Only the first `out.len()`
+/// chunks are computed, so `out` may be shorter than the list of
+/// all chunks, but it must not be longer.
+pub fn bytes_to_chunks_into(bytes: &[u8], chunk_size: usize, out: &mut [u8]) {
+    if let Err(error) = try_bytes_to_chunks_into(bytes, chunk_size, out) {
+        match error {
+            ChunkError::NotEnoughBytes => {
+                panic!("bytes_to_chunks: Not enough bytes for the requested number of chunks")
+            }
+            _ => panic!("bytes_to_chunks: Invalid chunk size"),
+        }
+    }
+}
+
+/// Same as `bytes_to_chunks_into`, but returns an error instead of panicking
+/// if the chunk size is not supported, or if `out` is longer than the list
+/// of all chunks. In case of an error, `out` is left unchanged.
+pub fn try_bytes_to_chunks_into(
+    bytes: &[u8],
+    chunk_size: usize,
+    out: &mut [u8],
+) -> Result<(), ChunkError> {
+    check_chunk_size(chunk_size)?;
 
     // iterate over all chunks and isolate them
     let chunks_per_byte = 8 / chunk_size;
-    let num_chunks = bytes.len() * chunks_per_byte;
-    let mut chunks = Vec::with_capacity(num_chunks);
-    for chunk_index in 0..num_chunks {
+    if out.len() > bytes.len() * chunks_per_byte {
+        return Err(ChunkError::NotEnoughBytes);
+    }
+    for (chunk_index, chunk) in out.iter_mut().enumerate() {
         // first find the right byte
         let byte_index = chunk_index / chunks_per_byte;
         let byte = bytes[byte_index];
         // now isolate the chunk and store it
         let chunk_index_in_byte = chunk_index % chunks_per_byte;
-        let chunk = isolate_chunk_from_byte(byte, chunk_index_in_byte, chunk_size);
-        chunks.push(chunk);
+        *chunk = isolate_chunk_from_byte(byte, chunk_index_in_byte, chunk_size);
+    }
+    Ok(())
+}
+
+/// Function to turn a list of chunks into a list of bytes, i.e., the
+/// inverse of `bytes_to_chunks`. Each byte contains 8 / `chunk_size`
+/// many chunks, starting from the least significant bits. If the number
+/// of chunks is not a multiple of that, the last byte is padded with zeros.
+/// It is assumed that `chunk_size` divides 8 and is between 1 and 8, and
+/// that all chunks are below 2^chunk_size. Use `try_chunks_to_bytes` if
+/// the chunks or the chunk size are not known to be valid.
+pub fn chunks_to_bytes(chunks: &[u8], chunk_size: usize) -> Vec<u8> {
+    try_chunks_to_bytes(chunks, chunk_size).expect("chunks_to_bytes: Invalid chunks")
+}
+
+/// Same as `chunks_to_bytes`, but returns an error instead of panicking if
+/// the chunk size is not supported, and instead of mixing bits of adjacent
+/// chunks if a chunk is not below 2^chunk_size.
+pub fn try_chunks_to_bytes(chunks: &[u8], chunk_size: usize) -> Result<Vec<u8>, ChunkError> {
+    check_chunk_size(chunk_size)?;
+    check_chunks_in_range(chunks, chunk_size)?;
+
+    let chunks_per_byte = 8 / chunk_size;
+    Ok(chunks
+        .chunks(chunks_per_byte)
+        .map(|chunks_in_byte| {
+            chunks_in_byte
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (chunk_index_in_byte, &chunk)| {
+                    byte | (chunk << (chunk_index_in_byte * chunk_size))
+                })
+        })
+        .collect())
+}
+
+/// Function to merge the outputs of two message hashes into the output of a
+/// message hash with more chunks, e.g., to sign with a Winternitz encoding
+/// whose codewords depend on two different hash functions. Both outputs must
+/// consist of chunks of the same size `chunk_size`.
+///
+/// The layout is a concatenation: the merged output starts with the chunks of
+/// `first`, followed by the chunks of `second`, i.e., it has `first.len() +
+/// second.len()` many chunks. Use `split_merged_chunks` to get back the two
+/// outputs. Returns an error if the chunk size is not supported, or if a chunk
+/// is not below 2^chunk_size, e.g., if an output has a larger chunk size.
+/// The index of such a chunk is its index in the merged output.
+pub fn merge_chunks(first: &[u8], second: &[u8], chunk_size: usize) -> Result<Vec<u8>, ChunkError> {
+    check_chunk_size(chunk_size)?;
+    let merged = [first, second].concat();
+    check_chunks_in_range(&merged, chunk_size)?;
+    Ok(merged)
+}
+
+/// Function to split the output of `merge_chunks` into the two outputs
+/// that have been merged, where the first one had `first_len` many chunks.
+/// Returns `None` if there are less than `first_len` many chunks.
+pub fn split_merged_chunks(merged: &[u8], first_len: usize) -> Option<(&[u8], &[u8])> {
+    merged.split_at_checked(first_len)
+}
+
+/// Output of a message hash, i.e., NUM_CHUNKS many chunks, each between
+/// 0 and 2^CHUNK_SIZE - 1 (inclusive). This is checked on construction,
+/// so that code using the chunks can rely on it. See `MessageHash::apply_typed`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Chunks<const NUM_CHUNKS: usize, const CHUNK_SIZE: usize>([u8; NUM_CHUNKS]);
+
+impl<const NUM_CHUNKS: usize, const CHUNK_SIZE: usize> Chunks<NUM_CHUNKS, CHUNK_SIZE> {
+    /// Returns an error if the chunk size is not supported,
+    /// or if a chunk is not below 2^CHUNK_SIZE.
+    #[must_use = "chunks that are out of range must be rejected"]
+    pub fn new(chunks: [u8; NUM_CHUNKS]) -> Result<Self, ChunkError> {
+        check_chunk_size(CHUNK_SIZE)?;
+        check_chunks_in_range(&chunks, CHUNK_SIZE)?;
+        Ok(Self(chunks))
+    }
+
+    /// Returns the chunks as an array
+    pub fn into_array(self) -> [u8; NUM_CHUNKS] {
+        self.0
+    }
+}
+
+impl<const NUM_CHUNKS: usize, const CHUNK_SIZE: usize> TryFrom<&[u8]>
+    for Chunks<NUM_CHUNKS, CHUNK_SIZE>
+{
+    type Error = ChunkError;
+
+    /// Same as `Chunks::new`, but also returns an error if
+    /// there are not exactly NUM_CHUNKS many chunks.
+    fn try_from(chunks: &[u8]) -> Result<Self, ChunkError> {
+        let chunks = chunks
+            .try_into()
+            .map_err(|_| ChunkError::WrongNumberOfChunks(NUM_CHUNKS))?;
+        Self::new(chunks)
+    }
+}
+
+impl<const NUM_CHUNKS: usize, const CHUNK_SIZE: usize> Deref for Chunks<NUM_CHUNKS, CHUNK_SIZE> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_templates {
+    use std::collections::HashSet;
+
+    use rand::{thread_rng, Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+
+    /// Number of random messages hashed by `_test_no_trivial_collisions`
+    const NUM_RANDOM_MESSAGES: usize = 1000;
+
+    /// Smoke test for any implementation of the `MessageHash` trait. Hashes many
+    /// distinct messages under the same parameter, epoch, and randomness, and
+    /// checks that all outputs are distinct. This is not a proof of collision
+    /// resistance, but catches bugs where parts of the message are ignored.
+    ///
+    /// The messages are: all messages differing from a random message in exactly
+    /// one bit, and a number of random messages.
+    pub fn _test_no_trivial_collisions<MH: MessageHash>(parameter: &MH::Parameter) {
+        let mut rng = thread_rng();
+
+        let epoch = 13;
+        let randomness = MH::rand(&mut rng);
+
+        let mut base = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut base);
+
+        let mut messages = HashSet::new();
+        messages.insert(base);
+        for byte in 0..MESSAGE_LENGTH {
+            for bit in 0..8 {
+                let mut message = base;
+                message[byte] ^= 1 << bit;
+                messages.insert(message);
+            }
+        }
+        while messages.len() < 1 + 8 * MESSAGE_LENGTH + NUM_RANDOM_MESSAGES {
+            messages.insert(rng.gen());
+        }
+
+        let mut outputs = HashSet::new();
+        for message in &messages {
+            let chunks = MH::apply(parameter, epoch, &randomness, message);
+            assert!(
+                outputs.insert(chunks),
+                "Message hash: distinct messages have the same chunks"
+            );
+        }
+    }
+
+    /// Number of random messages hashed by `_test_chunks_are_uniform`
+    const NUM_DISTRIBUTION_MESSAGES: usize = 100_000;
+
+    /// Returns the chi-squared statistic of the given histogram,
+    /// testing whether it is a sample of the uniform distribution.
+    fn chi_squared_uniform(observed: &[u64]) -> f64 {
+        let expected = observed.iter().sum::<u64>() as f64 / observed.len() as f64;
+        observed
+            .iter()
+            .map(|&o| (o as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    /// Returns the chi-squared statistic of two histograms with the same
+    /// number of samples, testing whether they are samples of the same
+    /// distribution. Values that occur in neither histogram are skipped.
+    fn chi_squared_equal(a: &[u64], b: &[u64]) -> f64 {
+        assert_eq!(a.iter().sum::<u64>(), b.iter().sum::<u64>());
+        a.iter()
+            .zip(b)
+            .filter(|(&x, &y)| x + y > 0)
+            .map(|(&x, &y)| (x as f64 - y as f64).powi(2) / (x + y) as f64)
+            .sum()
+    }
+
+    /// Threshold for a chi-squared statistic with the given degrees of freedom.
+    /// It is generous, i.e., far in the tail of the distribution, so that only
+    /// gross deviations from the expected distribution exceed it.
+    fn chi_squared_threshold(degrees_of_freedom: usize) -> f64 {
+        let df = degrees_of_freedom as f64;
+        df + 10.0 * (2.0 * df).sqrt() + 20.0
+    }
+
+    /// Statistical test for any implementation of the `MessageHash` trait. Hashes
+    /// random messages with random randomness, and checks with a chi-squared test
+    /// that the values of the chunks at each position are close to uniform. The
+    /// security analysis assumes this, and it fails, e.g., if the chunks are
+    /// extracted from the wrong bits or reduced with the wrong modulus.
+    ///
+    /// As bugs in truncation typically affect the ends, it also checks that the
+    /// distributions of the first and the last chunk match the one in the middle.
+    ///
+    /// Note: the RNG is seeded, so the outcome is the same in every run.
+    pub fn _test_chunks_are_uniform<MH: MessageHash>(parameter: &MH::Parameter) {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let epoch = 13;
+        let num_values = 1 << MH::CHUNK_SIZE;
+
+        // histogram of the chunk values, per position
+        let mut histograms = vec![vec![0u64; num_values]; MH::NUM_CHUNKS];
+        for _ in 0..NUM_DISTRIBUTION_MESSAGES {
+            let randomness = MH::rand(&mut rng);
+            let message = rng.gen();
+            let chunks = MH::apply(parameter, epoch, &randomness, &message);
+            for (histogram, &chunk) in histograms.iter_mut().zip(&chunks) {
+                histogram[chunk as usize] += 1;
+            }
+        }
+
+        let threshold = chi_squared_threshold(num_values - 1);
+        for (position, histogram) in histograms.iter().enumerate() {
+            let statistic = chi_squared_uniform(histogram);
+            assert!(
+                statistic < threshold,
+                "Message hash: chunks at position {} are not uniform (chi-squared {:.1})",
+                position,
+                statistic
+            );
+        }
+
+        let middle = &histograms[MH::NUM_CHUNKS / 2];
+        for position in [0, MH::NUM_CHUNKS - 1] {
+            let statistic = chi_squared_equal(&histograms[position], middle);
+            assert!(
+                statistic < threshold,
+                "Message hash: chunks at position {} differ from the middle (chi-squared {:.1})",
+                position,
+                statistic
+            );
+        }
     }
-    chunks
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{bytes_to_chunks, isolate_chunk_from_byte};
+    use proptest::{prelude::*, test_runner::RngSeed};
+
+    use super::{
+        bytes_to_chunks, bytes_to_chunks_into, chunks_to_bytes, isolate_chunk_from_byte,
+        merge_chunks, split_merged_chunks, try_bytes_to_chunks, try_bytes_to_chunks_into,
+        try_chunks_to_bytes, ChunkError, Chunks,
+    };
+
+    /// Configuration of the property tests. The seed is random, unless it is
+    /// set with `PROPTEST_RNG_SEED`, or the tests run in CI, where it is fixed
+    /// so that runs are reproducible.
+    fn proptest_config() -> ProptestConfig {
+        let mut config = ProptestConfig::default();
+        if std::env::var_os("CI").is_some() && std::env::var_os("PROPTEST_RNG_SEED").is_none() {
+            config.rng_seed = RngSeed::Fixed(0);
+        }
+        config
+    }
+
+    /// Strategy for the supported chunk sizes
+    fn chunk_size() -> impl Strategy<Value = usize> {
+        prop::sample::select(vec![1, 2, 4, 8])
+    }
+
+    proptest! {
+        #![proptest_config(proptest_config())]
+
+        #[test]
+        fn prop_chunks_are_in_range(
+            bytes in prop::collection::vec(any::<u8>(), 0..64),
+            chunk_size in chunk_size(),
+        ) {
+            let chunks = bytes_to_chunks(&bytes, chunk_size);
+            prop_assert_eq!(chunks.len(), bytes.len() * 8 / chunk_size);
+            for chunk in chunks {
+                prop_assert!((chunk as u16) < 1 << chunk_size);
+            }
+        }
+
+        #[test]
+        fn prop_chunks_recompose(
+            bytes in prop::collection::vec(any::<u8>(), 0..64),
+            chunk_size in chunk_size(),
+        ) {
+            // the chunks of a byte are its digits in base 2^chunk_size,
+            // starting from the least significant digit
+            let chunks = bytes_to_chunks(&bytes, chunk_size);
+            let chunks_per_byte = 8 / chunk_size;
+            for (byte, digits) in bytes.iter().zip(chunks.chunks(chunks_per_byte)) {
+                let value = digits
+                    .iter()
+                    .rev()
+                    .fold(0u16, |acc, &digit| (acc << chunk_size) + digit as u16);
+                prop_assert_eq!(value, *byte as u16);
+            }
+            prop_assert_eq!(chunks_to_bytes(&chunks, chunk_size), bytes);
+        }
+
+        #[test]
+        fn prop_merged_chunks_split(
+            first in prop::collection::vec(any::<u8>(), 0..64),
+            second in prop::collection::vec(any::<u8>(), 0..64),
+            chunk_size in chunk_size(),
+        ) {
+            // the two outputs can be extracted from the merged output
+            let first = bytes_to_chunks(&first, chunk_size);
+            let second = bytes_to_chunks(&second, chunk_size);
+            let merged = merge_chunks(&first, &second, chunk_size).unwrap();
+            prop_assert_eq!(merged.len(), first.len() + second.len());
+            let (first_split, second_split) = split_merged_chunks(&merged, first.len()).unwrap();
+            prop_assert_eq!(first_split, &first[..]);
+            prop_assert_eq!(second_split, &second[..]);
+        }
+
+        #[test]
+        fn prop_chunks_into_is_prefix(
+            bytes in prop::collection::vec(any::<u8>(), 1..64),
+            chunk_size in chunk_size(),
+            len in any::<prop::sample::Index>(),
+        ) {
+            let chunks = bytes_to_chunks(&bytes, chunk_size);
+            let len = len.index(chunks.len() + 1);
+            let mut out = vec![0xff; len];
+            bytes_to_chunks_into(&bytes, chunk_size, &mut out);
+            prop_assert_eq!(&out[..], &chunks[..len]);
+        }
+    }
 
     #[test]
     fn test_isolate_chunk_from_byte() {
@@ -136,4 +596,253 @@ mod tests {
         assert_eq!(chunks[0], byte_a);
         assert_eq!(chunks[1], byte_b);
     }
+
+    #[test]
+    fn test_bytes_to_chunks_vectors() {
+        // the chunks of a byte start from its least significant bits,
+        // and the bytes are processed in order. The expected chunks
+        // are given for chunk sizes 1, 2, 4, and 8.
+        let vectors: [(&[u8], [&[u8]; 4]); 5] = [
+            // empty input
+            (&[], [&[], &[], &[], &[]]),
+            // single byte 0b10100101
+            (
+                &[0xa5],
+                [
+                    &[1, 0, 1, 0, 0, 1, 0, 1],
+                    &[1, 1, 2, 2],
+                    &[0x5, 0xa],
+                    &[0xa5],
+                ],
+            ),
+            // single byte 0b00011011, with distinct chunks of size 2
+            (
+                &[0x1b],
+                [
+                    &[1, 1, 0, 1, 1, 0, 0, 0],
+                    &[3, 2, 1, 0],
+                    &[0xb, 0x1],
+                    &[0x1b],
+                ],
+            ),
+            // alternating bit patterns
+            (
+                &[0x55, 0xaa],
+                [
+                    &[1, 0, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 0, 1, 0, 1],
+                    &[1, 1, 1, 1, 2, 2, 2, 2],
+                    &[0x5, 0x5, 0xa, 0xa],
+                    &[0x55, 0xaa],
+                ],
+            ),
+            // lowest bit, highest bit, and the two halves
+            (
+                &[0x01, 0x80, 0xf0, 0x0f],
+                [
+                    &[
+                        1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 1, 1, 1, 1,
+                        1, 1, 1, 0, 0, 0, 0,
+                    ],
+                    &[1, 0, 0, 0, 0, 0, 0, 2, 0, 0, 3, 3, 3, 3, 0, 0],
+                    &[0x1, 0x0, 0x0, 0x8, 0x0, 0xf, 0xf, 0x0],
+                    &[0x01, 0x80, 0xf0, 0x0f],
+                ],
+            ),
+        ];
+
+        for (bytes, expected) in vectors {
+            for (chunk_size, expected_chunks) in [1, 2, 4, 8].into_iter().zip(expected) {
+                assert_eq!(
+                    bytes_to_chunks(bytes, chunk_size),
+                    expected_chunks,
+                    "bytes {:02x?}, chunk size {}",
+                    bytes,
+                    chunk_size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytes_to_chunks_into() {
+        let bytes = [0b01101100, 0b10100110];
+
+        // writing all chunks gives the same as allocating them
+        let mut chunks = [0u8; 8];
+        bytes_to_chunks_into(&bytes, 2, &mut chunks);
+        assert_eq!(&chunks[..], &bytes_to_chunks(&bytes, 2)[..]);
+
+        // a shorter buffer gets a prefix of the chunks
+        let mut chunks = [0u8; 3];
+        bytes_to_chunks_into(&bytes, 4, &mut chunks);
+        assert_eq!(chunks, [0b1100, 0b0110, 0b0110]);
+    }
+
+    #[test]
+    fn test_chunks_to_bytes() {
+        let bytes = [0b01101100, 0b10100110, 0xff, 0x00];
+        for chunk_size in [1, 2, 4, 8] {
+            let chunks = bytes_to_chunks(&bytes, chunk_size);
+            assert_eq!(chunks_to_bytes(&chunks, chunk_size), bytes);
+        }
+
+        // an incomplete last byte is padded with zeros
+        assert_eq!(chunks_to_bytes(&[0b11, 0b01, 0b10], 2), [0b100111]);
+    }
+
+    #[test]
+    fn test_conversion_exhaustive() {
+        // all inputs of up to two bytes, with all chunk sizes up to 9
+        let inputs = std::iter::once(vec![])
+            .chain((0..=255).map(|a| vec![a]))
+            .chain((0..=0xffff_u16).map(|ab| ab.to_le_bytes().to_vec()));
+        for bytes in inputs {
+            for chunk_size in 0..=9 {
+                let Ok(chunks) = try_bytes_to_chunks(&bytes, chunk_size) else {
+                    assert!(![1, 2, 4, 8].contains(&chunk_size));
+                    continue;
+                };
+                assert_eq!(chunks.len(), bytes.len() * 8 / chunk_size);
+                assert!(chunks.iter().all(|&chunk| (chunk as u16) < 1 << chunk_size));
+                assert_eq!(try_chunks_to_bytes(&chunks, chunk_size).unwrap(), bytes);
+
+                // every prefix of the chunks round-trips to a prefix of the bytes,
+                // with the bits of missing chunks set to zero
+                for len in 0..chunks.len() {
+                    let packed = try_chunks_to_bytes(&chunks[..len], chunk_size).unwrap();
+                    assert_eq!(packed.len(), (len * chunk_size).div_ceil(8));
+                    assert_eq!(
+                        try_bytes_to_chunks(&packed, chunk_size).unwrap()[..len],
+                        chunks[..len]
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_conversion_errors() {
+        for chunk_size in [0, 3, 5, 6, 7, 9, usize::MAX] {
+            assert_eq!(
+                try_bytes_to_chunks(&[0xa5], chunk_size),
+                Err(ChunkError::UnsupportedChunkSize(chunk_size))
+            );
+            assert_eq!(
+                try_chunks_to_bytes(&[1], chunk_size),
+                Err(ChunkError::UnsupportedChunkSize(chunk_size))
+            );
+        }
+
+        // a buffer longer than the list of all chunks is rejected, and left unchanged
+        let mut out = [0xff; 5];
+        assert_eq!(
+            try_bytes_to_chunks_into(&[0xa5], 2, &mut out),
+            Err(ChunkError::NotEnoughBytes)
+        );
+        assert_eq!(out, [0xff; 5]);
+
+        // chunks that do not fit into chunk_size bits are rejected
+        for chunk_size in [1, 2, 4] {
+            let too_large = 1 << chunk_size;
+            assert_eq!(
+                try_chunks_to_bytes(&[0, 1, too_large], chunk_size),
+                Err(ChunkError::ChunkOutOfRange(2))
+            );
+        }
+    }
+
+    #[test]
+    fn test_typed_chunks() {
+        let chunks = Chunks::<4, 2>::new([0, 1, 2, 3]).unwrap();
+        assert_eq!(&chunks[..], &[0, 1, 2, 3]);
+        assert_eq!(chunks.into_array(), [0, 1, 2, 3]);
+        assert_eq!(Chunks::<4, 2>::try_from(&[3, 2, 1, 0][..]).unwrap()[0], 3);
+
+        // chunks that do not fit into CHUNK_SIZE bits are rejected
+        assert_eq!(
+            Chunks::<4, 2>::new([0, 1, 4, 3]),
+            Err(ChunkError::ChunkOutOfRange(2))
+        );
+        assert_eq!(
+            Chunks::<2, 1>::try_from(&[1, 2][..]),
+            Err(ChunkError::ChunkOutOfRange(1))
+        );
+        assert!(Chunks::<3, 8>::new([0, 128, 255]).is_ok());
+
+        // so are unsupported chunk sizes and the wrong number of chunks
+        assert_eq!(
+            Chunks::<2, 3>::new([0, 1]),
+            Err(ChunkError::UnsupportedChunkSize(3))
+        );
+        assert_eq!(
+            Chunks::<4, 2>::try_from(&[0, 1, 2][..]),
+            Err(ChunkError::WrongNumberOfChunks(4))
+        );
+    }
+
+    #[test]
+    fn test_merge_chunks() {
+        assert_eq!(
+            merge_chunks(&[0, 1, 2], &[3, 2], 2).unwrap(),
+            vec![0, 1, 2, 3, 2]
+        );
+        assert_eq!(
+            split_merged_chunks(&[0, 1, 2, 3, 2], 3),
+            Some((&[0, 1, 2][..], &[3, 2][..]))
+        );
+        assert_eq!(split_merged_chunks(&[0, 1, 2], 4), None);
+
+        // the outputs must have the same chunk size, which must be supported,
+        // and chunks out of range are reported with their merged index
+        assert_eq!(
+            merge_chunks(&[0, 1], &[3, 4], 2),
+            Err(ChunkError::ChunkOutOfRange(3))
+        );
+        assert_eq!(
+            merge_chunks(&[0, 1], &[1, 0], 3),
+            Err(ChunkError::UnsupportedChunkSize(3))
+        );
+    }
+
+    #[cfg(feature = "sha")]
+    #[test]
+    fn test_merge_message_hashes() {
+        use rand::{thread_rng, Rng};
+
+        use super::{sha::ShaMessageHash192x3, sha256::Sha256MessageHash192x3, MessageHash};
+        use crate::MESSAGE_LENGTH;
+
+        type First = ShaMessageHash192x3;
+        type Second = Sha256MessageHash192x3;
+
+        let mut rng = thread_rng();
+        let parameter = rng.gen();
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let epoch = 13;
+        let first_rho = First::rand(&mut rng);
+        let second_rho = Second::rand(&mut rng);
+
+        let merge = || {
+            let first = First::apply(&parameter, epoch, &first_rho, &message);
+            let second = Second::apply(&parameter, epoch, &second_rho, &message);
+            merge_chunks(&first, &second, First::CHUNK_SIZE).unwrap()
+        };
+
+        // merging is deterministic, and the outputs of both hashes can be extracted
+        let merged = merge();
+        assert_eq!(merged, merge());
+        assert_eq!(merged.len(), First::NUM_CHUNKS + Second::NUM_CHUNKS);
+        let (first, second) = split_merged_chunks(&merged, First::NUM_CHUNKS).unwrap();
+        assert_eq!(first, First::apply(&parameter, epoch, &first_rho, &message));
+        assert_eq!(
+            second,
+            Second::apply(&parameter, epoch, &second_rho, &message)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid chunk size")]
+    fn test_bytes_to_chunks_chunk_size_zero() {
+        bytes_to_chunks(&[0xa5], 0);
+    }
 }
@@ -0,0 +1,333 @@
+use std::marker::PhantomData;
+
+use ::digest::{typenum::Unsigned, Digest};
+
+use crate::{
+    symmetric::message_hash::bytes_to_chunks_into, MESSAGE_LENGTH, TWEAK_SEPARATOR_FOR_MESSAGE_HASH,
+};
+
+use super::MessageHash;
+
+/// A message hash implemented using any hash function with the `Digest` trait
+/// of RustCrypto, e.g., SHA-512 or BLAKE2s.
+/// All lengths must be given in Bytes.
+/// Randomness length must be non-zero.
+/// CHUNK_SIZE has to be 1,2,4, or 8.
+///
+/// It hashes the same inputs in the same order as `Sha256MessageHash`, i.e.,
+/// parameter, domain separator, (non-zero) application identifier, epoch,
+/// randomness, and message, and takes the chunks from the first
+/// `NUM_CHUNKS * CHUNK_SIZE / 8` bytes of the digest, see `bytes_to_chunks`.
+/// Hence, `DigestMessageHash<Sha256, ...>` computes the same hashes as
+/// `Sha256MessageHash<...>`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DigestMessageHash<
+    D: Digest,
+    const PARAMETER_LEN: usize,
+    const RAND_LEN: usize,
+    const NUM_CHUNKS: usize,
+    const CHUNK_SIZE: usize,
+    const APPLICATION_ID: u32 = 0,
+> {
+    _marker: PhantomData<D>,
+}
+
+impl<
+        D: Digest,
+        const PARAMETER_LEN: usize,
+        const RAND_LEN: usize,
+        const NUM_CHUNKS: usize,
+        const CHUNK_SIZE: usize,
+        const APPLICATION_ID: u32,
+    > DigestMessageHash<D, PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
+{
+    /// Number of bytes of the digest that are turned into chunks
+    const DIGEST_LEN: usize = NUM_CHUNKS * CHUNK_SIZE / 8;
+
+    /// Fails to compile if the chunks do not fit into the output of the digest,
+    /// if they do not fill whole bytes, if the chunk size is not supported, or if
+    /// the parameter is empty, which would silently remove the separation of keys.
+    /// It is evaluated whenever the hash is applied or randomness is sampled, so
+    /// that invalid lengths cannot lead to a panic or to a hash that does not
+    /// depend on the key.
+    pub const LENGTHS_VALID: () = {
+        assert!(
+            PARAMETER_LEN > 0,
+            "Digest Message Hash: Parameter Length must be non-zero"
+        );
+        assert!(
+            CHUNK_SIZE == 1 || CHUNK_SIZE == 2 || CHUNK_SIZE == 4 || CHUNK_SIZE == 8,
+            "Digest Message Hash: Chunk Size must be 1, 2, 4, or 8"
+        );
+        assert!(
+            (NUM_CHUNKS * CHUNK_SIZE).is_multiple_of(8),
+            "Digest Message Hash: Hash Length (= NUM_CHUNKS * CHUNK_SIZE) must be a multiple of 8 bit"
+        );
+        assert!(
+            NUM_CHUNKS * CHUNK_SIZE <= 8 * D::OutputSize::USIZE,
+            "Digest Message Hash: Hash Length (= NUM_CHUNKS * CHUNK_SIZE) must be at most the output length of the digest"
+        );
+    };
+
+    /// Returns the digest before it is turned into chunks, i.e., the bytes
+    /// from which `apply` takes its chunks, as `bytes_to_chunks` would.
+    pub fn apply_raw(
+        parameter: &[u8; PARAMETER_LEN],
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        Self::digest(parameter, epoch, randomness, message)[0..Self::DIGEST_LEN].to_vec()
+    }
+
+    /// Computes the full digest of parameter, epoch, randomness, and message
+    fn digest(
+        parameter: &[u8; PARAMETER_LEN],
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> ::digest::Output<D> {
+        let () = Self::LENGTHS_VALID;
+        let mut hasher = D::new();
+
+        // first add the parameter
+        hasher.update(parameter);
+
+        // now add tweak (= domain separator + epoch)
+        // domain separator: this is a message hash tweak.
+        hasher.update([TWEAK_SEPARATOR_FOR_MESSAGE_HASH]);
+        if APPLICATION_ID != 0 {
+            hasher.update(APPLICATION_ID.to_le_bytes());
+        }
+        hasher.update(epoch.to_le_bytes());
+
+        // now add randomness
+        hasher.update(randomness);
+
+        // now add the actual message to be hashed
+        hasher.update(message);
+
+        hasher.finalize()
+    }
+}
+
+impl<
+        D: Digest,
+        const PARAMETER_LEN: usize,
+        const RAND_LEN: usize,
+        const NUM_CHUNKS: usize,
+        const CHUNK_SIZE: usize,
+        const APPLICATION_ID: u32,
+    > MessageHash
+    for DigestMessageHash<D, PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
+{
+    type Parameter = [u8; PARAMETER_LEN];
+
+    type Randomness = [u8; RAND_LEN];
+
+    const NUM_CHUNKS: usize = NUM_CHUNKS;
+
+    const CHUNK_SIZE: usize = CHUNK_SIZE;
+
+    fn identifier() -> Vec<u8> {
+        // digests have no name, so they are identified by the digest
+        // of a fixed input
+        let mut identifier = b"Digest".to_vec();
+        identifier.extend(D::digest(b"hashsig message hash identifier"));
+        // the identifier 0 adds nothing, as it does not change the hashes
+        if APPLICATION_ID != 0 {
+            identifier.extend(&(APPLICATION_ID as u64).to_be_bytes());
+        }
+        identifier
+    }
+
+    fn rand<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R) -> Self::Randomness {
+        let () = Self::LENGTHS_VALID;
+        let mut rand = [0u8; RAND_LEN];
+        rng.fill_bytes(&mut rand);
+        rand
+    }
+
+    fn apply(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        let mut chunks = vec![0; NUM_CHUNKS];
+        Self::apply_into(parameter, epoch, randomness, message, &mut chunks);
+        chunks
+    }
+
+    fn apply_into(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+        out: &mut [u8],
+    ) {
+        let hash = Self::digest(parameter, epoch, randomness, message);
+        // turn the bytes in the hash into chunks
+        assert!(
+            out.len() == NUM_CHUNKS,
+            "Digest Message Hash: Output buffer must have length NUM_CHUNKS"
+        );
+        bytes_to_chunks_into(&hash[0..Self::DIGEST_LEN], Self::CHUNK_SIZE, out);
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        assert!(
+            PARAMETER_LEN > 0,
+            "Digest Message Hash: Parameter Length must be non-zero"
+        );
+        assert!(
+            RAND_LEN > 0,
+            "Digest Message Hash: Randomness Length must be non-zero"
+        );
+        assert!(
+            NUM_CHUNKS * CHUNK_SIZE <= 8 * D::OutputSize::USIZE,
+            "Digest Message Hash: Hash Length (= NUM_CHUNKS * CHUNK_SIZE) must be at most the output length of the digest"
+        );
+    }
+}
+
+// Example instantiations
+pub type Sha512MessageHash128x3 = DigestMessageHash<sha2::Sha512, 16, 16, 16, 8>;
+pub type Sha512MessageHash192x3 = DigestMessageHash<sha2::Sha512, 24, 24, 48, 4>;
+#[cfg(feature = "blake2")]
+pub type Blake2sMessageHash128x3 = DigestMessageHash<blake2::Blake2s256, 16, 16, 16, 8>;
+#[cfg(feature = "blake2")]
+pub type Blake2sMessageHash192x3 = DigestMessageHash<blake2::Blake2s256, 24, 24, 48, 4>;
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::symmetric::message_hash::{
+        bytes_to_chunks,
+        sha256::{Sha256MessageHash, Sha256MessageHash128x3, Sha256MessageHash192x3},
+        test_templates::{_test_chunks_are_uniform, _test_no_trivial_collisions},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_matches_sha256_message_hash() {
+        let mut rng = thread_rng();
+
+        // bit-for-bit the same as the hand-written SHA-256 message hash
+        for _ in 0..10 {
+            let parameter: [u8; 24] = rng.gen();
+            let randomness: [u8; 24] = rng.gen();
+            let message: [u8; MESSAGE_LENGTH] = rng.gen();
+            let epoch = rng.gen();
+
+            assert_eq!(
+                DigestMessageHash::<sha2::Sha256, 24, 24, 48, 4>::apply(
+                    &parameter,
+                    epoch,
+                    &randomness,
+                    &message
+                ),
+                Sha256MessageHash192x3::apply(&parameter, epoch, &randomness, &message)
+            );
+            assert_eq!(
+                DigestMessageHash::<sha2::Sha256, 24, 24, 48, 4, 7>::apply(
+                    &parameter,
+                    epoch,
+                    &randomness,
+                    &message
+                ),
+                Sha256MessageHash::<24, 24, 48, 4, 7>::apply(
+                    &parameter,
+                    epoch,
+                    &randomness,
+                    &message
+                )
+            );
+
+            let parameter: [u8; 16] = rng.gen();
+            let randomness: [u8; 16] = rng.gen();
+            assert_eq!(
+                DigestMessageHash::<sha2::Sha256, 16, 16, 16, 8>::apply(
+                    &parameter,
+                    epoch,
+                    &randomness,
+                    &message
+                ),
+                Sha256MessageHash128x3::apply(&parameter, epoch, &randomness, &message)
+            );
+        }
+    }
+
+    #[test]
+    fn test_known_answer_sha512_128x3() {
+        // expected values computed with an independent implementation of SHA-512
+        let parameter: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 16] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch = 13;
+
+        Sha512MessageHash128x3::internal_consistency_check();
+        let chunks = Sha512MessageHash128x3::apply(&parameter, epoch, &randomness, &message);
+        let expected = [
+            0x4e, 0x00, 0x9b, 0x49, 0xad, 0x49, 0x6b, 0xc8, 0x80, 0x07, 0x5e, 0x0c, 0xd9, 0x07,
+            0x1d, 0xab,
+        ];
+        assert_eq!(chunks, expected);
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn test_known_answer_blake2s_128x3() {
+        // expected values computed with an independent implementation of BLAKE2s-256
+        let parameter: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 16] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch = 13;
+
+        Blake2sMessageHash128x3::internal_consistency_check();
+        let chunks = Blake2sMessageHash128x3::apply(&parameter, epoch, &randomness, &message);
+        let expected = [
+            0x24, 0x5f, 0xd6, 0x1a, 0x36, 0x06, 0x52, 0xc2, 0xf3, 0x11, 0x32, 0x9e, 0x12, 0x41,
+            0x14, 0x8a,
+        ];
+        assert_eq!(chunks, expected);
+    }
+
+    #[test]
+    fn test_apply_raw() {
+        let mut rng = thread_rng();
+
+        let parameter: [u8; 24] = rng.gen();
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let epoch = 13;
+        let randomness = Sha512MessageHash192x3::rand(&mut rng);
+
+        Sha512MessageHash192x3::internal_consistency_check();
+        let raw = Sha512MessageHash192x3::apply_raw(&parameter, epoch, &randomness, &message);
+        assert_eq!(raw.len(), 48 * 4 / 8);
+        assert_eq!(
+            bytes_to_chunks(&raw, 4),
+            Sha512MessageHash192x3::apply(&parameter, epoch, &randomness, &message)
+        );
+    }
+
+    #[test]
+    fn test_no_trivial_collisions() {
+        let mut rng = thread_rng();
+
+        _test_no_trivial_collisions::<Sha512MessageHash128x3>(&rng.gen());
+        _test_no_trivial_collisions::<Sha512MessageHash192x3>(&rng.gen());
+        #[cfg(feature = "blake2")]
+        _test_no_trivial_collisions::<Blake2sMessageHash192x3>(&rng.gen());
+    }
+
+    #[test]
+    fn test_chunks_are_uniform() {
+        // the full output of SHA-512 in chunks of 8 bits
+        let parameter = [0x42; 18];
+        _test_chunks_are_uniform::<DigestMessageHash<sha2::Sha512, 18, 23, 64, 8>>(&parameter);
+    }
+}
@@ -0,0 +1,263 @@
+use crate::{
+    symmetric::{
+        message_hash::bytes_to_chunks_into,
+        sha3_backend::{Sha3, Sha3Backend},
+    },
+    MESSAGE_LENGTH, TWEAK_SEPARATOR_FOR_MESSAGE_HASH,
+};
+
+use super::MessageHash;
+
+/// A message hash implemented using Keccak-256, i.e., the hash of Ethereum.
+/// All lengths must be given in Bytes.
+/// All lengths must be less than 255 bits.
+/// Randomness length must be non-zero.
+/// CHUNK_SIZE has to be 1,2,4, or 8.
+///
+/// It hashes the same inputs in the same order as `ShaMessageHash`, and only
+/// differs in the padding of Keccak. Prefer this variant if signatures are
+/// verified by an EVM contract, which can recompute the digest with the native
+/// `keccak256`:
+///
+/// ```solidity
+/// keccak256(abi.encodePacked(randomness, parameter, bytes1(0x02), epochLE, message))
+/// ```
+///
+/// where `randomness` and `parameter` are of type `bytesN` for their lengths,
+/// and `epochLE` is the `bytes4` of the epoch in little-endian. The chunks are
+/// taken from the first `NUM_CHUNKS * CHUNK_SIZE / 8` bytes of the digest, see
+/// `bytes_to_chunks`. See also `signature::evm`.
+///
+/// A non-zero APPLICATION_ID is absorbed after the domain separator, as in
+/// `ShaMessageHash`, i.e., as a `bytes4` in little-endian before `epochLE`.
+/// The default 0 gives the formula above.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeccakMessageHash<
+    const PARAMETER_LEN: usize,
+    const RAND_LEN: usize,
+    const NUM_CHUNKS: usize,
+    const CHUNK_SIZE: usize,
+    const APPLICATION_ID: u32 = 0,
+>;
+
+impl<
+        const PARAMETER_LEN: usize,
+        const RAND_LEN: usize,
+        const NUM_CHUNKS: usize,
+        const CHUNK_SIZE: usize,
+        const APPLICATION_ID: u32,
+    > KeccakMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
+{
+    /// Number of bytes of the digest that are turned into chunks
+    const DIGEST_LEN: usize = NUM_CHUNKS * CHUNK_SIZE / 8;
+
+    /// Fails to compile if the chunks do not fit into the output of the hash,
+    /// if the chunk size is not supported, or if the parameter is empty, which
+    /// would silently remove the separation of keys. It is evaluated whenever
+    /// the hash is applied or randomness is sampled, so that invalid lengths
+    /// cannot lead to a panic or to a hash that does not depend on the key.
+    pub const LENGTHS_VALID: () = {
+        assert!(
+            PARAMETER_LEN > 0,
+            "Keccak Message Hash: Parameter Length must be non-zero"
+        );
+        assert!(
+            CHUNK_SIZE == 1 || CHUNK_SIZE == 2 || CHUNK_SIZE == 4 || CHUNK_SIZE == 8,
+            "Keccak Message Hash: Chunk Size must be 1, 2, 4, or 8"
+        );
+        assert!(
+            NUM_CHUNKS * CHUNK_SIZE <= 256,
+            "Keccak Message Hash: Hash Length (= NUM_CHUNKS * CHUNK_SIZE) must be at most 256 bit"
+        );
+    };
+
+    /// Returns the digest before it is turned into chunks, i.e., the bytes
+    /// from which `apply` takes its chunks, as `bytes_to_chunks` would.
+    pub fn apply_raw(
+        parameter: &[u8; PARAMETER_LEN],
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        Self::digest(parameter, epoch, randomness, message)[0..Self::DIGEST_LEN].to_vec()
+    }
+
+    /// Computes the full hash of parameter, epoch, randomness, and message
+    fn digest(
+        parameter: &[u8; PARAMETER_LEN],
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> [u8; 32] {
+        let () = Self::LENGTHS_VALID;
+        let mut hasher = Sha3::keccak256();
+
+        // the same order as in the SHA3 variant: randomness, parameter,
+        // tweak (= domain separator + epoch), and message
+        hasher.update(randomness);
+        hasher.update(parameter);
+        hasher.update(&[TWEAK_SEPARATOR_FOR_MESSAGE_HASH]);
+        if APPLICATION_ID != 0 {
+            hasher.update(&APPLICATION_ID.to_le_bytes());
+        }
+        hasher.update(&epoch.to_le_bytes());
+        hasher.update(message);
+
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        hash
+    }
+}
+
+impl<
+        const PARAMETER_LEN: usize,
+        const RAND_LEN: usize,
+        const NUM_CHUNKS: usize,
+        const CHUNK_SIZE: usize,
+        const APPLICATION_ID: u32,
+    > MessageHash
+    for KeccakMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
+{
+    type Parameter = [u8; PARAMETER_LEN];
+
+    type Randomness = [u8; RAND_LEN];
+
+    const NUM_CHUNKS: usize = NUM_CHUNKS;
+
+    const CHUNK_SIZE: usize = CHUNK_SIZE;
+
+    fn identifier() -> Vec<u8> {
+        let mut identifier = b"Keccak-256".to_vec();
+        // the identifier 0 adds nothing, as it does not change the hashes
+        if APPLICATION_ID != 0 {
+            identifier.extend(&(APPLICATION_ID as u64).to_be_bytes());
+        }
+        identifier
+    }
+
+    fn rand<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R) -> Self::Randomness {
+        let () = Self::LENGTHS_VALID;
+        let mut rand = [0u8; RAND_LEN];
+        rng.fill_bytes(&mut rand);
+        rand
+    }
+
+    fn apply(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        let mut chunks = vec![0; NUM_CHUNKS];
+        Self::apply_into(parameter, epoch, randomness, message, &mut chunks);
+        chunks
+    }
+
+    fn apply_into(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+        out: &mut [u8],
+    ) {
+        let hash = Self::digest(parameter, epoch, randomness, message);
+        assert!(
+            out.len() == NUM_CHUNKS,
+            "Keccak Message Hash: Output buffer must have length NUM_CHUNKS"
+        );
+        bytes_to_chunks_into(&hash[0..Self::DIGEST_LEN], Self::CHUNK_SIZE, out);
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        assert!(
+            PARAMETER_LEN > 0 && PARAMETER_LEN < 256 / 8,
+            "Keccak Message Hash: Parameter Length must be non-zero and less than 256 bit"
+        );
+        assert!(
+            RAND_LEN < 256 / 8,
+            "Keccak Message Hash: Randomness Length must be less than 256 bit"
+        );
+        assert!(
+            RAND_LEN > 0,
+            "Keccak Message Hash: Randomness Length must be non-zero"
+        );
+        assert!(
+            NUM_CHUNKS * CHUNK_SIZE < 256,
+            "Keccak Message Hash: Hash Length (= NUM_CHUNKS * CHUNK_SIZE) must be less than 256 bit"
+        );
+    }
+}
+
+// Example instantiation
+pub type KeccakMessageHash192x3 = KeccakMessageHash<24, 24, 48, 4>;
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        symmetric::message_hash::{
+            bytes_to_chunks,
+            sha::ShaMessageHash192x3,
+            test_templates::{_test_chunks_are_uniform, _test_no_trivial_collisions},
+        },
+        MESSAGE_LENGTH,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_apply_192x3() {
+        let mut rng = thread_rng();
+
+        let parameter: [u8; 24] = rng.gen();
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+        let epoch = 13;
+        let randomness = KeccakMessageHash192x3::rand(&mut rng);
+
+        KeccakMessageHash192x3::internal_consistency_check();
+        let chunks = KeccakMessageHash192x3::apply(&parameter, epoch, &randomness, &message);
+
+        // the padding of Keccak separates it from the SHA3 variant
+        assert_ne!(
+            chunks,
+            ShaMessageHash192x3::apply(&parameter, epoch, &randomness, &message)
+        );
+    }
+
+    #[test]
+    fn test_known_answer_192x3() {
+        // expected values computed with an independent implementation of Keccak-256
+        let parameter: [u8; 24] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 24] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch = 13;
+
+        let raw = KeccakMessageHash192x3::apply_raw(&parameter, epoch, &randomness, &message);
+        let expected: [u8; 24] = [
+            0x4a, 0xc5, 0xaa, 0x27, 0x16, 0xe6, 0x11, 0x01, 0x13, 0x85, 0x46, 0x56, 0x6e, 0xd8,
+            0x59, 0x7c, 0x24, 0xe5, 0x2c, 0x47, 0x10, 0xfb, 0xcb, 0xad,
+        ];
+        assert_eq!(raw, expected);
+        assert_eq!(
+            KeccakMessageHash192x3::apply(&parameter, epoch, &randomness, &message),
+            bytes_to_chunks(&raw, 4)
+        );
+    }
+
+    #[test]
+    fn test_no_trivial_collisions() {
+        let mut rng = thread_rng();
+
+        _test_no_trivial_collisions::<KeccakMessageHash192x3>(&rng.gen());
+    }
+
+    #[test]
+    fn test_chunks_are_uniform() {
+        // the message hash of the EVM instantiation
+        let parameter = [0x42; 18];
+        _test_chunks_are_uniform::<KeccakMessageHash<18, 20, 36, 4>>(&parameter);
+    }
+}
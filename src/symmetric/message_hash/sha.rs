@@ -1,23 +1,33 @@
 use crate::{
-    symmetric::message_hash::bytes_to_chunks, MESSAGE_LENGTH, TWEAK_SEPARATOR_FOR_MESSAGE_HASH,
+    symmetric::{
+        message_hash::bytes_to_chunks_into,
+        sha3_backend::{Sha3, Sha3Backend},
+    },
+    MESSAGE_LENGTH, TWEAK_SEPARATOR_FOR_MESSAGE_HASH,
 };
 
 use super::MessageHash;
 
-// use sha3::{Digest, Sha3_256};
-use tiny_keccak::{Hasher, Sha3};
-
-
 /// A message hash implemented using SHA3
 /// All lengths must be given in Bytes.
 /// All lengths must be less than 255 bits.
 /// Randomness length must be non-zero.
 /// CHUNK_SIZE has to be 1,2,4, or 8.
+///
+/// Prefer this variant to use a single primitive family for message hash
+/// and tweak hash, which is also based on SHA3. The SHA-256 based variant
+/// `Sha256MessageHash` is an alternative if SHA-256 is required.
+///
+/// A non-zero APPLICATION_ID is absorbed after the domain separator, in
+/// little-endian as the epoch, see `ShaTweakHash` for its purpose. The
+/// default 0 gives the same hashes as without it.
+#[derive(Clone, Copy, Debug, Default)]
 pub struct ShaMessageHash<
     const PARAMETER_LEN: usize,
     const RAND_LEN: usize,
     const NUM_CHUNKS: usize,
     const CHUNK_SIZE: usize,
+    const APPLICATION_ID: u32 = 0,
 >;
 
 impl<
@@ -25,29 +35,52 @@ impl<
         const RAND_LEN: usize,
         const NUM_CHUNKS: usize,
         const CHUNK_SIZE: usize,
-    > MessageHash for ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE>
+        const APPLICATION_ID: u32,
+    > ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
 {
-    type Parameter = [u8; PARAMETER_LEN];
-
-    type Randomness = [u8; RAND_LEN];
-
-    const NUM_CHUNKS: usize = NUM_CHUNKS;
+    /// Number of bytes of the digest that are turned into chunks
+    const DIGEST_LEN: usize = NUM_CHUNKS * CHUNK_SIZE / 8;
 
-    const CHUNK_SIZE: usize = CHUNK_SIZE;
+    /// Fails to compile if the chunks do not fit into the output of the hash,
+    /// if the chunk size is not supported, or if the parameter is empty, which
+    /// would silently remove the separation of keys. It is evaluated whenever
+    /// the hash is applied or randomness is sampled, so that invalid lengths
+    /// cannot lead to a panic or to a hash that does not depend on the key.
+    pub const LENGTHS_VALID: () = {
+        assert!(
+            PARAMETER_LEN > 0,
+            "SHA Message Hash: Parameter Length must be non-zero"
+        );
+        assert!(
+            CHUNK_SIZE == 1 || CHUNK_SIZE == 2 || CHUNK_SIZE == 4 || CHUNK_SIZE == 8,
+            "SHA Message Hash: Chunk Size must be 1, 2, 4, or 8"
+        );
+        assert!(
+            NUM_CHUNKS * CHUNK_SIZE <= 256,
+            "SHA Message Hash: Hash Length (= NUM_CHUNKS * CHUNK_SIZE) must be at most 256 bit"
+        );
+    };
 
-    fn rand<R: rand::Rng>(rng: &mut R) -> Self::Randomness {
-        let mut rand = [0u8; RAND_LEN];
-        rng.fill_bytes(&mut rand);
-        rand
+    /// Returns the digest before it is turned into chunks, i.e., the bytes
+    /// from which `apply` takes its chunks, as `bytes_to_chunks` would. This
+    /// allows to use other chunk encoders, and helps to debug encodings.
+    pub fn apply_raw(
+        parameter: &[u8; PARAMETER_LEN],
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        Self::digest(parameter, epoch, randomness, message)[0..Self::DIGEST_LEN].to_vec()
     }
 
-    fn apply(
-        parameter: &Self::Parameter,
+    /// Computes the full hash of parameter, epoch, randomness, and message
+    fn digest(
+        parameter: &[u8; PARAMETER_LEN],
         epoch: u32,
-        randomness: &Self::Randomness,
+        randomness: &[u8; RAND_LEN],
         message: &[u8; MESSAGE_LENGTH],
-    ) -> Vec<u8> {
-        // let mut hasher = Sha3_256::new();
+    ) -> [u8; 32] {
+        let () = Self::LENGTHS_VALID;
         let mut hasher = Sha3::v256();
 
         // first add randomness
@@ -59,23 +92,86 @@ impl<
         // now add tweak (= domain separator + epoch)
         // domain separator: this is a message hash tweak.
         hasher.update(&[TWEAK_SEPARATOR_FOR_MESSAGE_HASH]);
+        if APPLICATION_ID != 0 {
+            hasher.update(&APPLICATION_ID.to_le_bytes());
+        }
         hasher.update(&epoch.to_le_bytes());
 
         // now add the actual message to be hashed
         hasher.update(message);
 
-        // finalize the hash, and take as many bytes as we need
+        // finalize the hash
         let mut hash = [0u8; 32];
         hasher.finalize(&mut hash);
+        hash
+    }
+}
+
+impl<
+        const PARAMETER_LEN: usize,
+        const RAND_LEN: usize,
+        const NUM_CHUNKS: usize,
+        const CHUNK_SIZE: usize,
+        const APPLICATION_ID: u32,
+    > MessageHash
+    for ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
+{
+    type Parameter = [u8; PARAMETER_LEN];
+
+    type Randomness = [u8; RAND_LEN];
+
+    const NUM_CHUNKS: usize = NUM_CHUNKS;
+
+    const CHUNK_SIZE: usize = CHUNK_SIZE;
+
+    fn identifier() -> Vec<u8> {
+        let mut identifier = b"SHA3-256".to_vec();
+        // the identifier 0 adds nothing, as it does not change the hashes
+        if APPLICATION_ID != 0 {
+            identifier.extend(&(APPLICATION_ID as u64).to_be_bytes());
+        }
+        identifier
+    }
+
+    fn rand<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R) -> Self::Randomness {
+        let () = Self::LENGTHS_VALID;
+        let mut rand = [0u8; RAND_LEN];
+        rng.fill_bytes(&mut rand);
+        rand
+    }
+
+    fn apply(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        let mut chunks = vec![0; NUM_CHUNKS];
+        Self::apply_into(parameter, epoch, randomness, message, &mut chunks);
+        chunks
+    }
+
+    fn apply_into(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+        out: &mut [u8],
+    ) {
+        let hash = Self::digest(parameter, epoch, randomness, message);
         // turn the bytes in the hash into chunks
-        bytes_to_chunks(&hash[0..NUM_CHUNKS * CHUNK_SIZE / 8], Self::CHUNK_SIZE)
+        assert!(
+            out.len() == NUM_CHUNKS,
+            "SHA Message Hash: Output buffer must have length NUM_CHUNKS"
+        );
+        bytes_to_chunks_into(&hash[0..Self::DIGEST_LEN], Self::CHUNK_SIZE, out);
     }
 
     #[cfg(test)]
     fn internal_consistency_check() {
         assert!(
-            PARAMETER_LEN < 256 / 8,
-            "SHA Message Hash: Parameter Length must be less than 256 bit"
+            PARAMETER_LEN > 0 && PARAMETER_LEN < 256 / 8,
+            "SHA Message Hash: Parameter Length must be non-zero and less than 256 bit"
         );
         assert!(
             RAND_LEN < 256 / 8,
@@ -92,6 +188,15 @@ impl<
     }
 }
 
+/// SHA3-256 based message hash, named explicitly to distinguish it from the SHA-256 variant
+pub type Sha3_256MessageHash<
+    const PARAMETER_LEN: usize,
+    const RAND_LEN: usize,
+    const NUM_CHUNKS: usize,
+    const CHUNK_SIZE: usize,
+    const APPLICATION_ID: u32 = 0,
+> = ShaMessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>;
+
 // Example instantiations
 pub type ShaMessageHash128x3 = ShaMessageHash<16, 16, 16, 8>;
 pub type ShaMessageHash192x3 = ShaMessageHash<24, 24, 48, 4>;
@@ -100,7 +205,13 @@ pub type ShaMessageHash192x3 = ShaMessageHash<24, 24, 48, 4>;
 mod tests {
     use rand::{thread_rng, Rng};
 
-    use crate::MESSAGE_LENGTH;
+    use crate::{
+        symmetric::message_hash::{
+            bytes_to_chunks,
+            test_templates::{_test_chunks_are_uniform, _test_no_trivial_collisions},
+        },
+        MESSAGE_LENGTH,
+    };
 
     use super::*;
 
@@ -137,4 +248,122 @@ mod tests {
         ShaMessageHash192x3::internal_consistency_check();
         ShaMessageHash192x3::apply(&parameter, epoch, &randomness, &message);
     }
+
+    #[test]
+    fn test_known_answer_128x3() {
+        // expected values computed with an independent implementation of SHA3-256
+        let parameter: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 16] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch = 13;
+
+        let chunks =
+            Sha3_256MessageHash::<16, 16, 16, 8>::apply(&parameter, epoch, &randomness, &message);
+        assert_eq!(
+            chunks,
+            [51, 248, 132, 4, 52, 130, 115, 255, 184, 235, 9, 26, 152, 24, 236, 175]
+        );
+    }
+
+    #[test]
+    fn test_known_answer_192x3() {
+        // expected values computed with an independent implementation of SHA3-256
+        let parameter: [u8; 24] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 24] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch = 13;
+
+        let chunks = ShaMessageHash192x3::apply(&parameter, epoch, &randomness, &message);
+        assert_eq!(
+            chunks,
+            [
+                12, 2, 1, 8, 4, 8, 11, 9, 14, 12, 2, 7, 0, 3, 8, 2, 14, 5, 1, 8, 3, 6, 12, 3, 0,
+                15, 8, 3, 6, 14, 13, 1, 8, 1, 10, 11, 12, 10, 7, 9, 10, 15, 11, 8, 1, 11, 8, 15
+            ]
+        );
+    }
+
+    /// Checks that packed chunks are unpacked to the chunks of `apply`.
+    fn _test_packed_round_trip<MH: MessageHash<Parameter = [u8; 16], Randomness = [u8; 16]>>() {
+        let mut rng = thread_rng();
+
+        let parameter: [u8; 16] = rng.gen();
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+        let epoch = 13;
+        let randomness = MH::rand(&mut rng);
+
+        let chunks = MH::apply(&parameter, epoch, &randomness, &message);
+        let packed = MH::apply_packed(&parameter, epoch, &randomness, &message);
+        assert_eq!(packed.len(), MH::NUM_CHUNKS * MH::CHUNK_SIZE / 8);
+        assert_eq!(MH::unpack(&packed), chunks);
+    }
+
+    #[test]
+    fn test_packed_round_trip() {
+        _test_packed_round_trip::<ShaMessageHash<16, 16, 128, 1>>();
+        _test_packed_round_trip::<ShaMessageHash<16, 16, 64, 2>>();
+        _test_packed_round_trip::<ShaMessageHash<16, 16, 32, 4>>();
+    }
+
+    #[test]
+    fn test_no_trivial_collisions() {
+        let mut rng = thread_rng();
+
+        _test_no_trivial_collisions::<ShaMessageHash128x3>(&rng.gen());
+        _test_no_trivial_collisions::<ShaMessageHash192x3>(&rng.gen());
+    }
+
+    #[test]
+    fn test_chunks_are_uniform() {
+        // the message hashes of the exported instantiations
+        let parameter = [0x42; 18];
+        _test_chunks_are_uniform::<ShaMessageHash<18, 20, 144, 1>>(&parameter);
+        _test_chunks_are_uniform::<ShaMessageHash<18, 20, 72, 2>>(&parameter);
+        _test_chunks_are_uniform::<ShaMessageHash<18, 20, 36, 4>>(&parameter);
+        _test_chunks_are_uniform::<ShaMessageHash<18, 20, 18, 8>>(&parameter);
+        _test_chunks_are_uniform::<ShaMessageHash<18, 23, 144, 1>>(&parameter);
+        _test_chunks_are_uniform::<ShaMessageHash<18, 23, 72, 2>>(&parameter);
+        _test_chunks_are_uniform::<ShaMessageHash<18, 23, 36, 4>>(&parameter);
+        _test_chunks_are_uniform::<ShaMessageHash<18, 23, 18, 8>>(&parameter);
+    }
+
+    #[test]
+    fn test_apply_raw() {
+        let mut rng = thread_rng();
+
+        let parameter: [u8; 24] = rng.gen();
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+        let epoch = 13;
+        let randomness = ShaMessageHash192x3::rand(&mut rng);
+
+        // chunking the raw digest gives the chunks of `apply`
+        let raw = ShaMessageHash192x3::apply_raw(&parameter, epoch, &randomness, &message);
+        assert_eq!(raw.len(), 48 * 4 / 8);
+        assert_eq!(
+            bytes_to_chunks(&raw, 4),
+            ShaMessageHash192x3::apply(&parameter, epoch, &randomness, &message)
+        );
+    }
+
+    #[test]
+    fn test_application_id() {
+        let mut rng = thread_rng();
+
+        let parameter: [u8; 24] = rng.gen();
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+        let epoch = 13;
+        let randomness = ShaMessageHash192x3::rand(&mut rng);
+
+        // the identifier 0 is the default, and others give unrelated hashes
+        let chunks = ShaMessageHash192x3::apply(&parameter, epoch, &randomness, &message);
+        let chunks_0 =
+            ShaMessageHash::<24, 24, 48, 4, 0>::apply(&parameter, epoch, &randomness, &message);
+        let chunks_1 =
+            ShaMessageHash::<24, 24, 48, 4, 1>::apply(&parameter, epoch, &randomness, &message);
+        assert_eq!(chunks, chunks_0);
+        assert_ne!(chunks, chunks_1);
+    }
 }
@@ -0,0 +1,586 @@
+use crate::{
+    symmetric::message_hash::bytes_to_chunks_into, MESSAGE_LENGTH, TWEAK_SEPARATOR_FOR_MESSAGE_HASH,
+};
+
+use super::MessageHash;
+
+use sha2::{Digest, Sha256};
+
+/// Error enum for the strict mode of `Sha256MessageHash`
+#[derive(Debug, PartialEq, Eq)]
+pub enum StrictMessageHashError {
+    /// The randomness consists of zero bytes only
+    ZeroRandomness,
+}
+
+/// A message hash implemented using SHA-256.
+/// All lengths must be given in Bytes.
+/// All lengths must be less than 255 bits.
+/// Randomness length must be non-zero.
+/// CHUNK_SIZE has to be 1,2,4, or 8.
+///
+/// Prefer this variant if SHA-256 is required or hardware-accelerated on the
+/// target platform. If the same primitive family should be used for message
+/// hash and tweak hash, use the SHA3-based `Sha3_256MessageHash` instead.
+///
+/// Note: in contrast to the SHA3 variant, the parameter is hashed first.
+/// This way, the hash state after absorbing the parameter is the same for
+/// all messages signed with one key.
+///
+/// A non-zero APPLICATION_ID is absorbed after the domain separator, as
+/// in `ShaMessageHash`. The default 0 gives the same hashes as without it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha256MessageHash<
+    const PARAMETER_LEN: usize,
+    const RAND_LEN: usize,
+    const NUM_CHUNKS: usize,
+    const CHUNK_SIZE: usize,
+    const APPLICATION_ID: u32 = 0,
+>;
+
+impl<
+        const PARAMETER_LEN: usize,
+        const RAND_LEN: usize,
+        const NUM_CHUNKS: usize,
+        const CHUNK_SIZE: usize,
+        const APPLICATION_ID: u32,
+    > Sha256MessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
+{
+    /// Number of bytes of the digest that are turned into chunks
+    const DIGEST_LEN: usize = NUM_CHUNKS * CHUNK_SIZE / 8;
+
+    /// Fails to compile if the chunks do not fit into the output of the hash,
+    /// if the chunk size is not supported, or if the parameter is empty, which
+    /// would silently remove the separation of keys. It is evaluated whenever
+    /// the hash is applied or randomness is sampled, so that invalid lengths
+    /// cannot lead to a panic or to a hash that does not depend on the key.
+    pub const LENGTHS_VALID: () = {
+        assert!(
+            PARAMETER_LEN > 0,
+            "SHA-256 Message Hash: Parameter Length must be non-zero"
+        );
+        assert!(
+            CHUNK_SIZE == 1 || CHUNK_SIZE == 2 || CHUNK_SIZE == 4 || CHUNK_SIZE == 8,
+            "SHA-256 Message Hash: Chunk Size must be 1, 2, 4, or 8"
+        );
+        assert!(
+            NUM_CHUNKS * CHUNK_SIZE <= 256,
+            "SHA-256 Message Hash: Hash Length (= NUM_CHUNKS * CHUNK_SIZE) must be at most 256 bit"
+        );
+    };
+
+    /// Returns the digest before it is turned into chunks, i.e., the bytes
+    /// from which `apply` takes its chunks, as `bytes_to_chunks` would. This
+    /// allows to use other chunk encoders, and helps to debug encodings.
+    pub fn apply_raw(
+        parameter: &[u8; PARAMETER_LEN],
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        Self::digest(parameter, epoch, randomness, message)[0..Self::DIGEST_LEN].to_vec()
+    }
+
+    /// Strict mode of `apply`: same as `apply`, but rejects randomness that
+    /// consists of zero bytes only. Randomness sampled with `rand` is all-zero
+    /// with negligible probability, so this catches randomness buffers that
+    /// were never filled, e.g., by deterministic callers.
+    pub fn apply_strict(
+        parameter: &[u8; PARAMETER_LEN],
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Result<Vec<u8>, StrictMessageHashError> {
+        if randomness.iter().all(|&byte| byte == 0) {
+            return Err(StrictMessageHashError::ZeroRandomness);
+        }
+        Ok(<Self as MessageHash>::apply(
+            parameter, epoch, randomness, message,
+        ))
+    }
+
+    /// Returns a variant of the message hash for a fixed parameter, which
+    /// absorbs the parameter only once, see `PreparedSha256MessageHash`.
+    pub fn prepare(
+        parameter: &[u8; PARAMETER_LEN],
+    ) -> PreparedSha256MessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
+    {
+        PreparedSha256MessageHash {
+            hasher: Self::hasher(parameter),
+        }
+    }
+
+    /// Returns a hasher that has absorbed the parameter
+    fn hasher(parameter: &[u8; PARAMETER_LEN]) -> Sha256 {
+        let mut hasher = Sha256::new();
+
+        // first add the parameter
+        hasher.update(parameter);
+
+        hasher
+    }
+
+    /// Computes the full hash of parameter, epoch, randomness, and message
+    fn digest(
+        parameter: &[u8; PARAMETER_LEN],
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> [u8; 32] {
+        Self::digest_with(Self::hasher(parameter), epoch, randomness, message)
+    }
+
+    /// Same as `digest`, but given a hasher that has absorbed the parameter
+    fn digest_with(
+        mut hasher: Sha256,
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> [u8; 32] {
+        let () = Self::LENGTHS_VALID;
+        // now add tweak (= domain separator + epoch)
+        // domain separator: this is a message hash tweak.
+        hasher.update([TWEAK_SEPARATOR_FOR_MESSAGE_HASH]);
+        if APPLICATION_ID != 0 {
+            hasher.update(APPLICATION_ID.to_le_bytes());
+        }
+        hasher.update(epoch.to_le_bytes());
+
+        // now add randomness
+        hasher.update(randomness);
+
+        // now add the actual message to be hashed
+        hasher.update(message);
+
+        // finalize the hash
+        hasher.finalize().into()
+    }
+}
+
+/// A `Sha256MessageHash` for a fixed parameter, obtained with
+/// `Sha256MessageHash::prepare`. It stores the hash state after absorbing
+/// the parameter, and computes the same outputs as `Sha256MessageHash`.
+/// This is useful if many messages are hashed with the same parameter.
+///
+/// Note: the state is only advanced by a compression of SHA-256 once a full
+/// block of 64 bytes is absorbed, so for short parameters this saves copying
+/// the parameter, but not a compression.
+#[derive(Clone)]
+pub struct PreparedSha256MessageHash<
+    const PARAMETER_LEN: usize,
+    const RAND_LEN: usize,
+    const NUM_CHUNKS: usize,
+    const CHUNK_SIZE: usize,
+    const APPLICATION_ID: u32 = 0,
+> {
+    hasher: Sha256,
+}
+
+impl<
+        const PARAMETER_LEN: usize,
+        const RAND_LEN: usize,
+        const NUM_CHUNKS: usize,
+        const CHUNK_SIZE: usize,
+        const APPLICATION_ID: u32,
+    > PreparedSha256MessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
+{
+    /// Number of bytes of the digest that are turned into chunks
+    const DIGEST_LEN: usize = NUM_CHUNKS * CHUNK_SIZE / 8;
+
+    /// Same as `MessageHash::apply` for the prepared parameter
+    pub fn apply(
+        &self,
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        let mut chunks = vec![0; NUM_CHUNKS];
+        self.apply_into(epoch, randomness, message, &mut chunks);
+        chunks
+    }
+
+    /// Same as `MessageHash::apply_into` for the prepared parameter
+    pub fn apply_into(
+        &self,
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+        out: &mut [u8],
+    ) {
+        let hash = self.digest(epoch, randomness, message);
+        // turn the bytes in the hash into chunks
+        assert!(
+            out.len() == NUM_CHUNKS,
+            "SHA-256 Message Hash: Output buffer must have length NUM_CHUNKS"
+        );
+        bytes_to_chunks_into(&hash[0..Self::DIGEST_LEN], CHUNK_SIZE, out);
+    }
+
+    /// Same as `Sha256MessageHash::apply_raw` for the prepared parameter
+    pub fn apply_raw(
+        &self,
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        self.digest(epoch, randomness, message)[0..Self::DIGEST_LEN].to_vec()
+    }
+
+    /// Computes the full hash of the prepared parameter, epoch, randomness, and message
+    fn digest(
+        &self,
+        epoch: u32,
+        randomness: &[u8; RAND_LEN],
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> [u8; 32] {
+        Sha256MessageHash::<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>::digest_with(
+            self.hasher.clone(),
+            epoch,
+            randomness,
+            message,
+        )
+    }
+}
+
+impl<
+        const PARAMETER_LEN: usize,
+        const RAND_LEN: usize,
+        const NUM_CHUNKS: usize,
+        const CHUNK_SIZE: usize,
+        const APPLICATION_ID: u32,
+    > MessageHash
+    for Sha256MessageHash<PARAMETER_LEN, RAND_LEN, NUM_CHUNKS, CHUNK_SIZE, APPLICATION_ID>
+{
+    type Parameter = [u8; PARAMETER_LEN];
+
+    type Randomness = [u8; RAND_LEN];
+
+    const NUM_CHUNKS: usize = NUM_CHUNKS;
+
+    const CHUNK_SIZE: usize = CHUNK_SIZE;
+
+    fn identifier() -> Vec<u8> {
+        let mut identifier = b"SHA-256".to_vec();
+        // the identifier 0 adds nothing, as it does not change the hashes
+        if APPLICATION_ID != 0 {
+            identifier.extend(&(APPLICATION_ID as u64).to_be_bytes());
+        }
+        identifier
+    }
+
+    fn rand<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R) -> Self::Randomness {
+        let () = Self::LENGTHS_VALID;
+        let mut rand = [0u8; RAND_LEN];
+        rng.fill_bytes(&mut rand);
+        rand
+    }
+
+    fn apply(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        let mut chunks = vec![0; NUM_CHUNKS];
+        Self::apply_into(parameter, epoch, randomness, message, &mut chunks);
+        chunks
+    }
+
+    fn apply_into(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+        out: &mut [u8],
+    ) {
+        let hash = Self::digest(parameter, epoch, randomness, message);
+        // turn the bytes in the hash into chunks
+        assert!(
+            out.len() == NUM_CHUNKS,
+            "SHA-256 Message Hash: Output buffer must have length NUM_CHUNKS"
+        );
+        bytes_to_chunks_into(&hash[0..Self::DIGEST_LEN], Self::CHUNK_SIZE, out);
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        assert!(
+            PARAMETER_LEN > 0 && PARAMETER_LEN < 256 / 8,
+            "SHA-256 Message Hash: Parameter Length must be non-zero and less than 256 bit"
+        );
+        assert!(
+            RAND_LEN < 256 / 8,
+            "SHA-256 Message Hash: Randomness Length must be less than 256 bit"
+        );
+        assert!(
+            RAND_LEN > 0,
+            "SHA-256 Message Hash: Randomness Length must be non-zero"
+        );
+        assert!(
+            NUM_CHUNKS * CHUNK_SIZE < 256,
+            "SHA-256 Message Hash: Hash Length (= NUM_CHUNKS * CHUNK_SIZE) must be less than 256 bit"
+        );
+    }
+}
+
+// Example instantiations
+pub type Sha256MessageHash128x3 = Sha256MessageHash<16, 16, 16, 8>;
+pub type Sha256MessageHash192x3 = Sha256MessageHash<24, 24, 48, 4>;
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::{
+        symmetric::message_hash::{
+            bytes_to_chunks,
+            test_templates::{_test_chunks_are_uniform, _test_no_trivial_collisions},
+            Chunks,
+        },
+        MESSAGE_LENGTH,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_apply_128x3() {
+        let mut rng = thread_rng();
+
+        let mut parameter = [0u8; 16];
+        rng.fill(&mut parameter);
+
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+
+        let epoch = 13;
+        let randomness = Sha256MessageHash128x3::rand(&mut rng);
+
+        Sha256MessageHash128x3::internal_consistency_check();
+        Sha256MessageHash128x3::apply(&parameter, epoch, &randomness, &message);
+    }
+
+    #[test]
+    fn test_apply_192x3() {
+        let mut rng = thread_rng();
+
+        let mut parameter = [0u8; 24];
+        rng.fill(&mut parameter);
+
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+
+        let epoch = 13;
+        let randomness = Sha256MessageHash192x3::rand(&mut rng);
+
+        Sha256MessageHash192x3::internal_consistency_check();
+        Sha256MessageHash192x3::apply(&parameter, epoch, &randomness, &message);
+    }
+
+    #[test]
+    fn test_apply_typed() {
+        let mut rng = thread_rng();
+
+        let parameter: [u8; 24] = rng.gen();
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let epoch = 13;
+        let randomness = Sha256MessageHash192x3::rand(&mut rng);
+
+        let chunks: Chunks<48, 4> =
+            Sha256MessageHash192x3::apply_typed(&parameter, epoch, &randomness, &message);
+        assert_eq!(
+            &chunks[..],
+            &Sha256MessageHash192x3::apply(&parameter, epoch, &randomness, &message)[..]
+        );
+    }
+
+    #[test]
+    fn test_known_answer_128x3() {
+        // expected values computed with an independent implementation of SHA-256
+        let parameter: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 16] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch = 13;
+
+        let chunks = Sha256MessageHash128x3::apply(&parameter, epoch, &randomness, &message);
+        assert_eq!(
+            chunks,
+            [209, 211, 118, 154, 54, 187, 184, 79, 153, 241, 102, 139, 159, 64, 189, 117]
+        );
+    }
+
+    #[test]
+    fn test_known_answer_192x3() {
+        // expected values computed with an independent implementation of SHA-256
+        let parameter: [u8; 24] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 24] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch = 13;
+
+        let chunks = Sha256MessageHash192x3::apply(&parameter, epoch, &randomness, &message);
+        assert_eq!(
+            chunks,
+            [
+                7, 3, 14, 0, 12, 2, 8, 14, 15, 9, 9, 13, 2, 2, 0, 0, 13, 1, 5, 12, 8, 6, 6, 15, 4,
+                2, 1, 14, 1, 11, 5, 15, 14, 4, 2, 14, 0, 6, 4, 14, 1, 11, 13, 14, 11, 11, 5, 8
+            ]
+        );
+    }
+
+    #[test]
+    fn test_epoch_is_little_endian() {
+        // The epoch is absorbed in little-endian byte order. This is part of the
+        // wire format: changing it, e.g., to big-endian, changes all message hashes
+        // and therefore breaks all existing signatures. So we pin the absorbed
+        // bytes by hashing the expected input explicitly.
+        let parameter: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 16] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch = 0x01020304;
+
+        let hash_of = |epoch_bytes: [u8; 4]| {
+            let mut hasher = Sha256::new();
+            hasher.update(parameter);
+            hasher.update([TWEAK_SEPARATOR_FOR_MESSAGE_HASH]);
+            hasher.update(epoch_bytes);
+            hasher.update(randomness);
+            hasher.update(message);
+            hasher.finalize()[0..16].to_vec()
+        };
+
+        let chunks = Sha256MessageHash128x3::apply(&parameter, epoch, &randomness, &message);
+        assert_eq!(chunks, hash_of([0x04, 0x03, 0x02, 0x01]));
+        assert_ne!(chunks, hash_of([0x01, 0x02, 0x03, 0x04]));
+    }
+
+    #[test]
+    fn test_application_id() {
+        // a non-zero application identifier is absorbed after the separator,
+        // and the identifier 0 is absorbed as nothing, as before identifiers
+        let parameter: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 16] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch: u32 = 0x01020304;
+
+        let hash_of = |application_id_bytes: &[u8]| {
+            let mut hasher = Sha256::new();
+            hasher.update(parameter);
+            hasher.update([TWEAK_SEPARATOR_FOR_MESSAGE_HASH]);
+            hasher.update(application_id_bytes);
+            hasher.update(epoch.to_le_bytes());
+            hasher.update(randomness);
+            hasher.update(message);
+            hasher.finalize()[0..16].to_vec()
+        };
+
+        assert_eq!(
+            Sha256MessageHash::<16, 16, 16, 8, 0>::apply(&parameter, epoch, &randomness, &message),
+            hash_of(&[])
+        );
+        assert_eq!(
+            Sha256MessageHash::<16, 16, 16, 8, 0x11223344>::apply(
+                &parameter,
+                epoch,
+                &randomness,
+                &message
+            ),
+            hash_of(&[0x44, 0x33, 0x22, 0x11])
+        );
+    }
+
+    #[test]
+    fn test_no_trivial_collisions() {
+        let mut rng = thread_rng();
+
+        _test_no_trivial_collisions::<Sha256MessageHash128x3>(&rng.gen());
+        _test_no_trivial_collisions::<Sha256MessageHash192x3>(&rng.gen());
+    }
+
+    #[test]
+    fn test_chunks_are_uniform() {
+        _test_chunks_are_uniform::<Sha256MessageHash128x3>(&[0x42; 16]);
+        _test_chunks_are_uniform::<Sha256MessageHash192x3>(&[0x42; 24]);
+    }
+
+    #[test]
+    fn test_apply_raw() {
+        let mut rng = thread_rng();
+
+        let parameter: [u8; 24] = rng.gen();
+        let mut message = [0u8; MESSAGE_LENGTH];
+        rng.fill(&mut message);
+        let epoch = 13;
+        let randomness = Sha256MessageHash192x3::rand(&mut rng);
+
+        // chunking the raw digest gives the chunks of `apply`
+        let raw = Sha256MessageHash192x3::apply_raw(&parameter, epoch, &randomness, &message);
+        assert_eq!(raw.len(), 48 * 4 / 8);
+        assert_eq!(
+            bytes_to_chunks(&raw, 4),
+            Sha256MessageHash192x3::apply(&parameter, epoch, &randomness, &message)
+        );
+    }
+
+    #[test]
+    fn test_apply_strict_rejects_zero_randomness() {
+        let mut rng = thread_rng();
+        let parameter: [u8; 16] = rng.gen();
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        let epoch = 13;
+        let zero = [0u8; 16];
+
+        // all-zero randomness is accepted by `apply`, but not in strict mode
+        let chunks = Sha256MessageHash128x3::apply(&parameter, epoch, &zero, &message);
+        assert_eq!(chunks.len(), 16);
+        assert_eq!(
+            Sha256MessageHash128x3::apply_strict(&parameter, epoch, &zero, &message),
+            Err(StrictMessageHashError::ZeroRandomness)
+        );
+
+        // a single non-zero byte suffices, and gives the same chunks as `apply`
+        let mut randomness = zero;
+        randomness[15] = 1;
+        assert_eq!(
+            Sha256MessageHash128x3::apply_strict(&parameter, epoch, &randomness, &message),
+            Ok(Sha256MessageHash128x3::apply(
+                &parameter,
+                epoch,
+                &randomness,
+                &message
+            ))
+        );
+    }
+
+    #[test]
+    fn test_prepared_matches_unprepared() {
+        // known answer of `test_known_answer_128x3`
+        let parameter: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let randomness: [u8; 16] = std::array::from_fn(|i| 100 + i as u8);
+        let message: [u8; MESSAGE_LENGTH] = std::array::from_fn(|i| 200 + i as u8);
+        let epoch = 13;
+
+        let prepared = Sha256MessageHash128x3::prepare(&parameter);
+        assert_eq!(
+            prepared.apply(epoch, &randomness, &message),
+            [209, 211, 118, 154, 54, 187, 184, 79, 153, 241, 102, 139, 159, 64, 189, 117]
+        );
+
+        // the prepared hash can be used for many messages
+        let mut rng = thread_rng();
+        let parameter: [u8; 24] = rng.gen();
+        let prepared = Sha256MessageHash192x3::prepare(&parameter);
+        for epoch in 0..10 {
+            let mut message = [0u8; MESSAGE_LENGTH];
+            rng.fill(&mut message);
+            let randomness = Sha256MessageHash192x3::rand(&mut rng);
+
+            assert_eq!(
+                prepared.apply(epoch, &randomness, &message),
+                Sha256MessageHash192x3::apply(&parameter, epoch, &randomness, &message)
+            );
+            assert_eq!(
+                prepared.apply_raw(epoch, &randomness, &message),
+                Sha256MessageHash192x3::apply_raw(&parameter, epoch, &randomness, &message)
+            );
+        }
+    }
+}
@@ -1,12 +1,14 @@
-use rand::Rng;
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroize;
 
 /// Trait to model a pseudorandom function
 pub trait Pseudorandom {
-    type Key: Send + Sync;
+    /// The key, which is zeroized if key generation is cancelled
+    type Key: Clone + Send + Sync + Zeroize;
     type Output;
 
     /// Sample a random domain element
-    fn gen<R: Rng>(rng: &mut R) -> Self::Key;
+    fn gen<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Key;
 
     /// Apply the one-way function to an epoch and an index
     fn apply(key: &Self::Key, epoch: u32, index: u64) -> Self::Output;
@@ -17,5 +19,7 @@ pub trait Pseudorandom {
     fn internal_consistency_check();
 }
 
+#[cfg(feature = "sha")]
 pub mod sha;
+#[cfg(feature = "poseidon")]
 pub mod shake_to_field;
@@ -1,6 +1,6 @@
+use crate::symmetric::sha3_backend::{Sha3, Sha3Backend};
+
 use super::Pseudorandom;
-// use sha3::{Digest, Sha3_256};
-use tiny_keccak::{Hasher, Sha3};
 
 const KEY_LENGTH: usize = 32; // 32 bytes
 const PRF_DOMAIN_SEP: [u8; 16] = [
@@ -9,20 +9,31 @@ const PRF_DOMAIN_SEP: [u8; 16] = [
 
 // Implement a SHA3-based PRF
 // Output Length must be at most 32 bytes
+#[derive(Clone, Copy, Debug, Default)]
 pub struct ShaPRF<const OUTPUT_LENGTH: usize>;
 
+impl<const OUTPUT_LENGTH: usize> ShaPRF<OUTPUT_LENGTH> {
+    /// Fails to compile if the output is longer than the output of SHA3-256.
+    /// It is evaluated whenever the PRF is applied, so that an invalid
+    /// length cannot lead to a panic.
+    pub const LENGTHS_VALID: () = assert!(
+        OUTPUT_LENGTH <= 256 / 8,
+        "SHA PRF: Output length must be at most 256 bit"
+    );
+}
+
 impl<const OUTPUT_LENGTH: usize> Pseudorandom for ShaPRF<OUTPUT_LENGTH> {
     type Key = [u8; KEY_LENGTH];
     type Output = [u8; OUTPUT_LENGTH];
 
-    fn gen<R: rand::Rng>(rng: &mut R) -> Self::Key {
+    fn gen<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R) -> Self::Key {
         let mut key = [0u8; KEY_LENGTH];
-        rng.fill(&mut key);
+        rng.fill_bytes(&mut key);
         key
     }
 
     fn apply(key: &Self::Key, epoch: u32, index: u64) -> Self::Output {
-        // let mut hasher = Sha3_256::new();
+        let () = Self::LENGTHS_VALID;
         let mut hasher = Sha3::v256();
 
         // Hash the domain separator
@@ -40,7 +51,9 @@ impl<const OUTPUT_LENGTH: usize> Pseudorandom for ShaPRF<OUTPUT_LENGTH> {
         // Finalize and convert to output
         let mut result = [0u8; 32];
         hasher.finalize(&mut result);
-        result[0..OUTPUT_LENGTH].try_into().unwrap()
+        let mut output = [0u8; OUTPUT_LENGTH];
+        output.copy_from_slice(&result[0..OUTPUT_LENGTH]);
+        output
     }
 
     #[cfg(test)]
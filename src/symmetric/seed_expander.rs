@@ -0,0 +1,110 @@
+use rand_core::{CryptoRng, RngCore};
+use tiny_keccak::{Hasher, Shake, Xof};
+
+/// Seed length in bytes for deterministic key generation.
+pub const SEED_LENGTH: usize = 32;
+
+const SEED_EXPANDER_DOMAIN_SEP: [u8; 16] = [
+    0x5e, 0xed, 0x00, 0xff, 0x01, 0xfa, 0x12, 0x00, 0xff, 0x5e, 0xed, 0x01, 0xaf, 0x00, 0x12, 0xff,
+];
+
+/// A deterministic random number generator that expands a seed
+/// using Shake128. It is used for key generation from a seed.
+///
+/// The output is determined by the seed and an identifier. Different
+/// identifiers yield unrelated outputs for the same seed, so schemes
+/// should use an identifier that is unique for their instantiation.
+pub struct SeedExpander {
+    xof: Shake,
+}
+
+impl SeedExpander {
+    /// Creates a new expander for the given identifier and seed.
+    pub fn new(identifier: &[u8], seed: &[u8; SEED_LENGTH]) -> Self {
+        let mut xof = Shake::v128();
+
+        // Hash the domain separator
+        xof.update(&SEED_EXPANDER_DOMAIN_SEP);
+
+        // Hash the identifier, prefixed with its length so
+        // that identifier and seed cannot be confused
+        xof.update(&(identifier.len() as u64).to_be_bytes());
+        xof.update(identifier);
+
+        // Hash the seed
+        xof.update(seed);
+
+        Self { xof }
+    }
+}
+
+impl RngCore for SeedExpander {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.xof.squeeze(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// The output of Shake128 is indistinguishable from random
+/// as long as the seed is, so this is a cryptographic RNG.
+impl CryptoRng for SeedExpander {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic() {
+        let seed = [0x42; SEED_LENGTH];
+
+        let mut a = SeedExpander::new(b"test", &seed);
+        let mut b = SeedExpander::new(b"test", &seed);
+        let mut out_a = [0u8; 100];
+        let mut out_b = [0u8; 100];
+        a.fill_bytes(&mut out_a);
+        b.fill_bytes(&mut out_b);
+        assert_eq!(out_a, out_b);
+
+        // squeezing in several steps gives the same stream
+        let mut c = SeedExpander::new(b"test", &seed);
+        let mut out_c = [0u8; 100];
+        c.fill_bytes(&mut out_c[..37]);
+        c.fill_bytes(&mut out_c[37..]);
+        assert_eq!(out_a, out_c);
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let seed = [0x42; SEED_LENGTH];
+        let mut other_seed = seed;
+        other_seed[31] ^= 0x01;
+
+        let mut out = [0u8; 32];
+        SeedExpander::new(b"test", &seed).fill_bytes(&mut out);
+
+        // different identifiers or seeds give different outputs
+        let mut out_identifier = [0u8; 32];
+        SeedExpander::new(b"test2", &seed).fill_bytes(&mut out_identifier);
+        assert_ne!(out, out_identifier);
+
+        let mut out_seed = [0u8; 32];
+        SeedExpander::new(b"test", &other_seed).fill_bytes(&mut out_seed);
+        assert_ne!(out, out_seed);
+    }
+}
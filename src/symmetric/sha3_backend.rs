@@ -0,0 +1,205 @@
+//! Abstraction over the implementation of SHA3 that is used by the SHA based
+//! building blocks. The implementation is selected using features:
+//! - `backend-tiny-keccak` (default): the `tiny-keccak` crate
+//! - `backend-rustcrypto`: the `sha3` crate of RustCrypto
+//!
+//! If both are enabled, the RustCrypto implementation is used. Both compute
+//! standard SHA3, so keys and signatures do not depend on the selected backend.
+//! Both also provide Keccak-256, the variant with the original Keccak padding
+//! that is used by Ethereum, see `KeccakMessageHash`.
+
+#[cfg(not(any(feature = "backend-tiny-keccak", feature = "backend-rustcrypto")))]
+compile_error!(
+    "No SHA3 backend selected: enable one of the features `backend-tiny-keccak` or `backend-rustcrypto`."
+);
+
+/// Incremental SHA3 hasher, with the variant selected at construction.
+pub(crate) trait Sha3Backend: Sized {
+    /// Returns a hasher for SHA3-256
+    fn v256() -> Self;
+
+    /// Returns a hasher for SHA3-384
+    fn v384() -> Self;
+
+    /// Returns a hasher for SHA3-512
+    fn v512() -> Self;
+
+    /// Returns a hasher for Keccak-256, which only differs
+    /// from SHA3-256 in the padding
+    fn keccak256() -> Self;
+
+    /// Absorbs the given input
+    fn update(&mut self, input: &[u8]);
+
+    /// Finalizes the hash and writes it to `output`, which
+    /// must have the output length of the selected variant.
+    fn finalize(self, output: &mut [u8]);
+}
+
+/// SHA3 hasher of the selected backend
+#[cfg(feature = "backend-rustcrypto")]
+pub(crate) type Sha3 = RustCryptoSha3;
+/// SHA3 hasher of the selected backend
+#[cfg(all(feature = "backend-tiny-keccak", not(feature = "backend-rustcrypto")))]
+pub(crate) type Sha3 = TinyKeccakSha3;
+
+/// SHA3 based on the `tiny-keccak` crate
+///
+/// Note: if both backends are enabled, this is only used to test that they agree,
+/// and by the self-test, see `hashsig::self_test`.
+#[cfg(feature = "backend-tiny-keccak")]
+#[cfg_attr(feature = "backend-rustcrypto", allow(dead_code))]
+pub(crate) enum TinyKeccakSha3 {
+    Sha3(tiny_keccak::Sha3),
+    Keccak(tiny_keccak::Keccak),
+}
+
+#[cfg(feature = "backend-tiny-keccak")]
+impl Sha3Backend for TinyKeccakSha3 {
+    fn v256() -> Self {
+        Self::Sha3(tiny_keccak::Sha3::v256())
+    }
+
+    fn v384() -> Self {
+        Self::Sha3(tiny_keccak::Sha3::v384())
+    }
+
+    fn v512() -> Self {
+        Self::Sha3(tiny_keccak::Sha3::v512())
+    }
+
+    fn keccak256() -> Self {
+        Self::Keccak(tiny_keccak::Keccak::v256())
+    }
+
+    fn update(&mut self, input: &[u8]) {
+        match self {
+            Self::Sha3(hasher) => tiny_keccak::Hasher::update(hasher, input),
+            Self::Keccak(hasher) => tiny_keccak::Hasher::update(hasher, input),
+        }
+    }
+
+    fn finalize(self, output: &mut [u8]) {
+        match self {
+            Self::Sha3(hasher) => tiny_keccak::Hasher::finalize(hasher, output),
+            Self::Keccak(hasher) => tiny_keccak::Hasher::finalize(hasher, output),
+        }
+    }
+}
+
+/// SHA3 based on the `sha3` crate of RustCrypto
+#[cfg(feature = "backend-rustcrypto")]
+pub(crate) enum RustCryptoSha3 {
+    V256(sha3::Sha3_256),
+    V384(sha3::Sha3_384),
+    V512(sha3::Sha3_512),
+    Keccak256(sha3::Keccak256),
+}
+
+#[cfg(feature = "backend-rustcrypto")]
+impl Sha3Backend for RustCryptoSha3 {
+    fn v256() -> Self {
+        Self::V256(sha3::Digest::new())
+    }
+
+    fn v384() -> Self {
+        Self::V384(sha3::Digest::new())
+    }
+
+    fn v512() -> Self {
+        Self::V512(sha3::Digest::new())
+    }
+
+    fn keccak256() -> Self {
+        Self::Keccak256(sha3::Digest::new())
+    }
+
+    fn update(&mut self, input: &[u8]) {
+        match self {
+            Self::V256(hasher) => sha3::Digest::update(hasher, input),
+            Self::V384(hasher) => sha3::Digest::update(hasher, input),
+            Self::V512(hasher) => sha3::Digest::update(hasher, input),
+            Self::Keccak256(hasher) => sha3::Digest::update(hasher, input),
+        }
+    }
+
+    fn finalize(self, output: &mut [u8]) {
+        match self {
+            Self::V256(hasher) => output.copy_from_slice(&sha3::Digest::finalize(hasher)),
+            Self::V384(hasher) => output.copy_from_slice(&sha3::Digest::finalize(hasher)),
+            Self::V512(hasher) => output.copy_from_slice(&sha3::Digest::finalize(hasher)),
+            Self::Keccak256(hasher) => output.copy_from_slice(&sha3::Digest::finalize(hasher)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hashes the given inputs with the given hasher, returning `LEN` bytes.
+    fn hash<H: Sha3Backend, const LEN: usize>(mut hasher: H, inputs: &[&[u8]]) -> [u8; LEN] {
+        for input in inputs {
+            hasher.update(input);
+        }
+        let mut output = [0u8; LEN];
+        hasher.finalize(&mut output);
+        output
+    }
+
+    #[test]
+    fn test_known_answer() {
+        // SHA3-256 of the empty string
+        let expected: [u8; 32] = [
+            0xa7, 0xff, 0xc6, 0xf8, 0xbf, 0x1e, 0xd7, 0x66, 0x51, 0xc1, 0x47, 0x56, 0xa0, 0x61,
+            0xd6, 0x62, 0xf5, 0x80, 0xff, 0x4d, 0xe4, 0x3b, 0x49, 0xfa, 0x82, 0xd8, 0x0a, 0x4b,
+            0x80, 0xf8, 0x43, 0x4a,
+        ];
+        assert_eq!(hash::<_, 32>(Sha3::v256(), &[]), expected);
+
+        // Keccak-256 of the empty string, as computed by `keccak256("")` in Solidity
+        let expected: [u8; 32] = [
+            0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+            0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+            0x5d, 0x85, 0xa4, 0x70,
+        ];
+        assert_eq!(hash::<_, 32>(Sha3::keccak256(), &[]), expected);
+
+        // inputs can be absorbed in pieces
+        assert_eq!(
+            hash::<_, 48>(Sha3::v384(), &[b"ab", b"c"]),
+            hash::<_, 48>(Sha3::v384(), &[b"abc"])
+        );
+    }
+
+    #[cfg(all(feature = "backend-tiny-keccak", feature = "backend-rustcrypto"))]
+    #[test]
+    fn test_backends_agree() {
+        use rand::{thread_rng, Rng};
+
+        let mut rng = thread_rng();
+
+        for _ in 0..100 {
+            let len = rng.gen_range(0..300);
+            let input: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let (a, b) = input.split_at(rng.gen_range(0..=len));
+
+            assert_eq!(
+                hash::<_, 32>(TinyKeccakSha3::v256(), &[a, b]),
+                hash::<_, 32>(RustCryptoSha3::v256(), &[a, b])
+            );
+            assert_eq!(
+                hash::<_, 48>(TinyKeccakSha3::v384(), &[a, b]),
+                hash::<_, 48>(RustCryptoSha3::v384(), &[a, b])
+            );
+            assert_eq!(
+                hash::<_, 64>(TinyKeccakSha3::v512(), &[a, b]),
+                hash::<_, 64>(RustCryptoSha3::v512(), &[a, b])
+            );
+            assert_eq!(
+                hash::<_, 32>(TinyKeccakSha3::keccak256(), &[a, b]),
+                hash::<_, 32>(RustCryptoSha3::keccak256(), &[a, b])
+            );
+        }
+    }
+}
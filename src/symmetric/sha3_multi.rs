@@ -0,0 +1,235 @@
+//! Multi-lane SHA3, computing the hashes of several short inputs at once.
+//!
+//! The states of all lanes are interleaved in AVX2 registers, so that each step
+//! of the Keccak permutation operates on all lanes with the same instruction.
+//! AVX2 is detected at runtime, see `is_available`, and callers fall back to
+//! the scalar implementation if it is not available.
+//!
+//! Only inputs that fit into a single block are supported, which is the case
+//! for the inputs of the SHA tweakable hash in hash chains.
+
+use std::arch::x86_64::*;
+
+/// Number of inputs that are hashed at once
+pub(crate) const LANES: usize = 4;
+
+/// Number of 64-bit words of the Keccak state
+const STATE_WORDS: usize = 25;
+
+/// A Keccak state per lane, where word `i` of lane `l` is `state[i][l]`
+type State = [[u64; LANES]; STATE_WORDS];
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of the rho step, in the order of the pi step
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// Order in which the pi step moves the words
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// SHA3 domain separation and first padding bit
+const SHA3_PAD: u8 = 0x06;
+
+#[inline(always)]
+unsafe fn xor(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_xor_si256(a, b)
+}
+
+#[inline(always)]
+unsafe fn rotate_left(a: __m256i, n: u32) -> __m256i {
+    let left = _mm256_sll_epi64(a, _mm_cvtsi32_si128(n as i32));
+    let right = _mm256_srl_epi64(a, _mm_cvtsi32_si128(64 - n as i32));
+    _mm256_or_si256(left, right)
+}
+
+/// Computes `!a & b` lane-wise
+#[inline(always)]
+unsafe fn and_not(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_andnot_si256(a, b)
+}
+
+/// Repeats the body with the given variable set to each of the values,
+/// so that all indices in the body are constants
+macro_rules! unroll {
+    ($var:ident in [$($value:expr),*] $body:block) => {
+        $({
+            #[allow(non_upper_case_globals)]
+            const $var: usize = $value;
+            $body
+        })*
+    };
+}
+
+/// The Keccak-f[1600] permutation, applied to all lanes.
+///
+/// Safety: the CPU must support AVX2.
+// the last iteration of the unrolled rho and pi step assigns `last` without reading it
+#[allow(unused_assignments)]
+#[target_feature(enable = "avx2")]
+unsafe fn keccak_f1600(state: &mut State) {
+    let mut a: [__m256i; STATE_WORDS] =
+        std::array::from_fn(|i| _mm256_loadu_si256(state[i].as_ptr() as *const __m256i));
+
+    for round_constant in ROUND_CONSTANTS {
+        // theta
+        let mut columns = [_mm256_setzero_si256(); 5];
+        unroll!(x in [0, 1, 2, 3, 4] {
+            columns[x] = xor(
+                xor(xor(a[x], a[x + 5]), xor(a[x + 10], a[x + 15])),
+                a[x + 20],
+            );
+        });
+        unroll!(x in [0, 1, 2, 3, 4] {
+            let d = xor(columns[(x + 4) % 5], rotate_left(columns[(x + 1) % 5], 1));
+            unroll!(y in [0, 1, 2, 3, 4] {
+                a[x + 5 * y] = xor(a[x + 5 * y], d);
+            });
+        });
+
+        // rho and pi
+        let mut last = a[1];
+        unroll!(i in [
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23
+        ] {
+            let tmp = a[PI[i]];
+            a[PI[i]] = rotate_left(last, RHO[i]);
+            last = tmp;
+        });
+
+        // chi
+        unroll!(y in [0, 1, 2, 3, 4] {
+            let row = [a[5 * y], a[5 * y + 1], a[5 * y + 2], a[5 * y + 3], a[5 * y + 4]];
+            unroll!(x in [0, 1, 2, 3, 4] {
+                a[x + 5 * y] = xor(row[x], and_not(row[(x + 1) % 5], row[(x + 2) % 5]));
+            });
+        });
+
+        // iota
+        a[0] = xor(a[0], _mm256_set1_epi64x(round_constant as i64));
+    }
+
+    for (word, w) in state.iter_mut().zip(a) {
+        _mm256_storeu_si256(word.as_mut_ptr() as *mut __m256i, w);
+    }
+}
+
+/// Returns whether multi-lane SHA3 is available, i.e., whether the CPU supports AVX2
+pub(crate) fn is_available() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+/// Returns the rate in bytes of SHA3 with an output length of `bits`
+pub(crate) const fn rate(bits: usize) -> usize {
+    200 - 2 * bits / 8
+}
+
+/// Computes SHA3 with an output length of `bits`, i.e., SHA3-256, SHA3-384,
+/// or SHA3-512, of `LANES` inputs at once. The hash of input `l` is written
+/// to the first `bits / 8` bytes of `outputs[l]`.
+///
+/// Each input must be shorter than `rate(bits)`, so that it fits into one block,
+/// and multi-lane SHA3 must be available, see `is_available`.
+pub(crate) fn sha3(bits: usize, inputs: [&[u8]; LANES], outputs: &mut [[u8; 64]; LANES]) {
+    assert!(is_available(), "Multi-lane SHA3: AVX2 is not available");
+    let rate = rate(bits);
+    let mut state: State = [[0u64; LANES]; STATE_WORDS];
+
+    // absorb the padded input of each lane
+    for (l, input) in inputs.iter().enumerate() {
+        assert!(
+            input.len() < rate,
+            "Multi-lane SHA3: Input must fit into a single block"
+        );
+        let mut block = [0u8; 200];
+        block[..input.len()].copy_from_slice(input);
+        block[input.len()] ^= SHA3_PAD;
+        block[rate - 1] ^= 0x80;
+        for (word, bytes) in state.iter_mut().zip(block[..rate].chunks_exact(8)) {
+            word[l] = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+    }
+
+    // SAFETY: we checked that the CPU supports AVX2
+    unsafe { keccak_f1600(&mut state) };
+
+    // squeeze, the output is shorter than the rate
+    for (l, output) in outputs.iter_mut().enumerate() {
+        for (bytes, word) in output[..bits / 8].chunks_exact_mut(8).zip(state.iter()) {
+            bytes.copy_from_slice(&word[l].to_le_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::symmetric::sha3_backend::{Sha3, Sha3Backend};
+
+    use super::*;
+
+    #[test]
+    fn test_matches_scalar_sha3() {
+        if !is_available() {
+            return;
+        }
+
+        let mut rng = thread_rng();
+
+        for bits in [256, 384, 512] {
+            let hasher = || match bits {
+                256 => Sha3::v256(),
+                384 => Sha3::v384(),
+                _ => Sha3::v512(),
+            };
+
+            for _ in 0..100 {
+                // inputs of different lengths, up to the maximum length
+                let inputs: [Vec<u8>; LANES] = std::array::from_fn(|_| {
+                    let len = rng.gen_range(0..rate(bits));
+                    (0..len).map(|_| rng.gen()).collect()
+                });
+
+                let mut outputs = [[0u8; 64]; LANES];
+                sha3(bits, std::array::from_fn(|l| &inputs[l][..]), &mut outputs);
+
+                for (input, output) in inputs.iter().zip(outputs.iter()) {
+                    let mut expected = [0u8; 64];
+                    let mut hasher = hasher();
+                    hasher.update(input);
+                    hasher.finalize(&mut expected[..bits / 8]);
+                    assert_eq!(output[..bits / 8], expected[..bits / 8]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,646 @@
+//! Traces of hash invocations, e.g., to generate the witness of a circuit
+//! that constrains each invocation of a verification one by one.
+//!
+//! `TracingTweakHash` and `TracingMessageHash` wrap a tweakable hash and a
+//! message hash, respectively. They compute exactly the same hashes as the
+//! wrapped functions, and report each invocation, with its inputs and output,
+//! to the recorder of the current thread, if any. A recorder is installed
+//! for the duration of `record`. See also `verify_traced` of
+//! `GeneralizedXMSSSignatureScheme`.
+//!
+//! Traces are deterministic, i.e., they only depend on the inputs of the
+//! traced operation, and can be checked against the hashes with `replay_trace`.
+
+use std::{any::Any, cell::RefCell, marker::PhantomData};
+
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::MESSAGE_LENGTH;
+
+use super::{message_hash::MessageHash, tweak_hash::TweakableHash};
+
+thread_local! {
+    /// Invocations of the traced hashes on this thread, while `record` runs
+    static CALLS: RefCell<Option<Vec<Box<dyn Any>>>> = const { RefCell::new(None) };
+}
+
+/// Reports an invocation to the recording of this thread, if any.
+/// The invocation is only constructed during a recording.
+fn report<T: Any>(call: impl FnOnce() -> T) {
+    CALLS.with_borrow_mut(|calls| {
+        if let Some(calls) = calls {
+            calls.push(Box::new(call()));
+        }
+    });
+}
+
+/// Tweak of a traced invocation, given by the arguments of
+/// `TweakableHash::tree_tweak` or `TweakableHash::chain_tweak`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TraceTweak {
+    Tree {
+        level: u8,
+        pos_in_level: u32,
+    },
+    Chain {
+        epoch: u32,
+        chain_index: u16,
+        pos_in_chain: u16,
+    },
+}
+
+impl TraceTweak {
+    /// Returns the tweak of `TH` with the same arguments
+    fn to_tweak<TH: TweakableHash>(self) -> TH::Tweak {
+        match self {
+            TraceTweak::Tree {
+                level,
+                pos_in_level,
+            } => TH::tree_tweak(level, pos_in_level),
+            TraceTweak::Chain {
+                epoch,
+                chain_index,
+                pos_in_chain,
+            } => TH::chain_tweak(epoch, chain_index, pos_in_chain),
+        }
+    }
+}
+
+/// One invocation of a tweakable hash
+#[derive(Serialize, Deserialize)]
+pub struct TweakHashCall<TH: TweakableHash> {
+    #[serde(bound(
+        serialize = "TH::Parameter: Serialize",
+        deserialize = "TH::Parameter: Deserialize<'de>"
+    ))]
+    pub parameter: TH::Parameter,
+    pub tweak: TraceTweak,
+    /// the tweak as it enters the hash, see `TweakableHash::encode_tweak`
+    pub encoded_tweak: Vec<u8>,
+    #[serde(bound(
+        serialize = "TH::Domain: Serialize",
+        deserialize = "TH::Domain: Deserialize<'de>"
+    ))]
+    pub inputs: Vec<TH::Domain>,
+    #[serde(bound(
+        serialize = "TH::Domain: Serialize",
+        deserialize = "TH::Domain: Deserialize<'de>"
+    ))]
+    pub output: TH::Domain,
+}
+
+impl<TH: TweakableHash> Clone for TweakHashCall<TH> {
+    fn clone(&self) -> Self {
+        Self {
+            parameter: self.parameter,
+            tweak: self.tweak,
+            encoded_tweak: self.encoded_tweak.clone(),
+            inputs: self.inputs.clone(),
+            output: self.output,
+        }
+    }
+}
+
+impl<TH: TweakableHash> PartialEq for TweakHashCall<TH> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parameter == other.parameter
+            && self.tweak == other.tweak
+            && self.encoded_tweak == other.encoded_tweak
+            && self.inputs == other.inputs
+            && self.output == other.output
+    }
+}
+
+impl<TH: TweakableHash> Eq for TweakHashCall<TH> {}
+
+/// One invocation of a message hash
+#[derive(Serialize, Deserialize)]
+pub struct MessageHashCall<MH: MessageHash> {
+    #[serde(bound(
+        serialize = "MH::Parameter: Serialize",
+        deserialize = "MH::Parameter: Deserialize<'de>"
+    ))]
+    pub parameter: MH::Parameter,
+    pub epoch: u32,
+    #[serde(bound(
+        serialize = "MH::Randomness: Serialize",
+        deserialize = "MH::Randomness: Deserialize<'de>"
+    ))]
+    pub randomness: MH::Randomness,
+    pub message: [u8; MESSAGE_LENGTH],
+    /// the chunks, as returned by `MessageHash::apply`
+    pub output: Vec<u8>,
+}
+
+impl<MH: MessageHash> Clone for MessageHashCall<MH> {
+    fn clone(&self) -> Self {
+        Self {
+            parameter: self.parameter.clone(),
+            epoch: self.epoch,
+            randomness: self.randomness.clone(),
+            message: self.message,
+            output: self.output.clone(),
+        }
+    }
+}
+
+impl<MH: MessageHash> PartialEq for MessageHashCall<MH>
+where
+    MH::Parameter: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.parameter == other.parameter
+            && self.epoch == other.epoch
+            && self.randomness == other.randomness
+            && self.message == other.message
+            && self.output == other.output
+    }
+}
+
+impl<MH: MessageHash> Eq for MessageHashCall<MH> where MH::Parameter: Eq {}
+
+/// An invocation of a tweakable hash or of a message hash
+#[derive(Serialize, Deserialize)]
+pub enum HashCall<TH: TweakableHash, MH: MessageHash> {
+    #[serde(bound(
+        serialize = "TweakHashCall<TH>: Serialize",
+        deserialize = "TweakHashCall<TH>: Deserialize<'de>"
+    ))]
+    TweakHash(TweakHashCall<TH>),
+    #[serde(bound(
+        serialize = "MessageHashCall<MH>: Serialize",
+        deserialize = "MessageHashCall<MH>: Deserialize<'de>"
+    ))]
+    MessageHash(MessageHashCall<MH>),
+}
+
+impl<TH: TweakableHash, MH: MessageHash> Clone for HashCall<TH, MH> {
+    fn clone(&self) -> Self {
+        match self {
+            HashCall::TweakHash(call) => HashCall::TweakHash(call.clone()),
+            HashCall::MessageHash(call) => HashCall::MessageHash(call.clone()),
+        }
+    }
+}
+
+impl<TH: TweakableHash, MH: MessageHash> PartialEq for HashCall<TH, MH>
+where
+    MH::Parameter: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (HashCall::TweakHash(a), HashCall::TweakHash(b)) => a == b,
+            (HashCall::MessageHash(a), HashCall::MessageHash(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<TH: TweakableHash, MH: MessageHash> Eq for HashCall<TH, MH> where MH::Parameter: Eq {}
+
+/// Receives the invocations of `TracingTweakHash<TH>` and
+/// `TracingMessageHash<MH>` during `record`, in order
+pub trait HashTraceRecorder<TH: TweakableHash, MH: MessageHash> {
+    fn record_tweak_hash(&mut self, call: TweakHashCall<TH>);
+
+    fn record_message_hash(&mut self, call: MessageHashCall<MH>);
+}
+
+/// All invocations of an operation, in the order in which they happened
+#[derive(Serialize, Deserialize)]
+pub struct HashTrace<TH: TweakableHash, MH: MessageHash> {
+    #[serde(bound(
+        serialize = "HashCall<TH, MH>: Serialize",
+        deserialize = "HashCall<TH, MH>: Deserialize<'de>"
+    ))]
+    pub calls: Vec<HashCall<TH, MH>>,
+}
+
+impl<TH: TweakableHash, MH: MessageHash> Default for HashTrace<TH, MH> {
+    fn default() -> Self {
+        Self { calls: Vec::new() }
+    }
+}
+
+impl<TH: TweakableHash, MH: MessageHash> Clone for HashTrace<TH, MH> {
+    fn clone(&self) -> Self {
+        Self {
+            calls: self.calls.clone(),
+        }
+    }
+}
+
+impl<TH: TweakableHash, MH: MessageHash> PartialEq for HashTrace<TH, MH>
+where
+    MH::Parameter: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.calls == other.calls
+    }
+}
+
+impl<TH: TweakableHash, MH: MessageHash> Eq for HashTrace<TH, MH> where MH::Parameter: Eq {}
+
+impl<TH: TweakableHash, MH: MessageHash> HashTraceRecorder<TH, MH> for HashTrace<TH, MH> {
+    fn record_tweak_hash(&mut self, call: TweakHashCall<TH>) {
+        self.calls.push(HashCall::TweakHash(call));
+    }
+
+    fn record_message_hash(&mut self, call: MessageHashCall<MH>) {
+        self.calls.push(HashCall::MessageHash(call));
+    }
+}
+
+/// Removes the recording of this thread when dropped, also if the recorded operation panics
+struct RecordingGuard;
+
+impl Drop for RecordingGuard {
+    fn drop(&mut self) {
+        CALLS.with_borrow_mut(|calls| *calls = None);
+    }
+}
+
+/// Runs `op`, and passes all invocations of `TracingTweakHash<TH>` and
+/// `TracingMessageHash<MH>` on this thread during `op` to `recorder`, in order.
+///
+/// Panics if a recording is already running on this thread, or if `op`
+/// invokes traced hashes other than `TH` and `MH`.
+pub fn record<TH, MH, R, T>(recorder: &mut R, op: impl FnOnce() -> T) -> T
+where
+    TH: TweakableHash + 'static,
+    MH: MessageHash + 'static,
+    R: HashTraceRecorder<TH, MH>,
+{
+    CALLS.with_borrow_mut(|calls| {
+        assert!(calls.is_none(), "Hash Trace: Recordings cannot be nested");
+        *calls = Some(Vec::new());
+    });
+    let guard = RecordingGuard;
+    let result = op();
+    let calls = CALLS
+        .with_borrow_mut(|calls| calls.take())
+        .unwrap_or_default();
+    drop(guard);
+
+    for call in calls {
+        let call = match call.downcast::<TweakHashCall<TH>>() {
+            Ok(call) => {
+                recorder.record_tweak_hash(*call);
+                continue;
+            }
+            Err(call) => call,
+        };
+        match call.downcast::<MessageHashCall<MH>>() {
+            Ok(call) => recorder.record_message_hash(*call),
+            Err(_) => panic!("Hash Trace: Invocation of a hash that is not recorded"),
+        }
+    }
+    result
+}
+
+/// Recomputes each invocation of `trace`, and panics if the output or the
+/// encoded tweak differs from the recorded one. This checks that a trace is
+/// consistent with the hashes, e.g., before generating a witness from it.
+pub fn replay_trace<TH: TweakableHash, MH: MessageHash>(trace: &HashTrace<TH, MH>) {
+    for (index, call) in trace.calls.iter().enumerate() {
+        match call {
+            HashCall::TweakHash(call) => {
+                let tweak = call.tweak.to_tweak::<TH>();
+                assert!(
+                    TH::encode_tweak(&tweak) == call.encoded_tweak,
+                    "Hash Trace: Encoded tweak of invocation {} differs",
+                    index
+                );
+                assert!(
+                    TH::apply(&call.parameter, &tweak, &call.inputs) == call.output,
+                    "Hash Trace: Output of invocation {} differs",
+                    index
+                );
+            }
+            HashCall::MessageHash(call) => {
+                assert!(
+                    MH::apply(&call.parameter, call.epoch, &call.randomness, &call.message)
+                        == call.output,
+                    "Hash Trace: Output of invocation {} differs",
+                    index
+                );
+            }
+        }
+    }
+}
+
+/// Tweak of `TracingTweakHash`, which remembers the arguments it was created with
+pub struct TracingTweak<T> {
+    tweak: TraceTweak,
+    inner: T,
+}
+
+/// A tweakable hash that computes the same hashes as `TH`, and
+/// reports each invocation to the recording of the current thread
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TracingTweakHash<TH: TweakableHash> {
+    _marker: PhantomData<TH>,
+}
+
+impl<TH: TweakableHash + 'static> TracingTweakHash<TH> {
+    /// Applies `TH` to the given inputs, and reports the invocation
+    fn apply_and_report(
+        parameter: &TH::Parameter,
+        tweak: &TracingTweak<TH::Tweak>,
+        inputs: &[TH::Domain],
+    ) -> TH::Domain {
+        let output = TH::apply(parameter, &tweak.inner, inputs);
+        report(|| TweakHashCall::<TH> {
+            parameter: *parameter,
+            tweak: tweak.tweak,
+            encoded_tweak: TH::encode_tweak(&tweak.inner),
+            inputs: inputs.to_vec(),
+            output,
+        });
+        output
+    }
+}
+
+impl<TH: TweakableHash + 'static> TweakableHash for TracingTweakHash<TH> {
+    type Parameter = TH::Parameter;
+
+    type Tweak = TracingTweak<TH::Tweak>;
+
+    type Domain = TH::Domain;
+
+    fn rand_parameter<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Parameter {
+        TH::rand_parameter(rng)
+    }
+
+    fn rand_domain<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Domain {
+        TH::rand_domain(rng)
+    }
+
+    fn tree_tweak(level: u8, pos_in_level: u32) -> Self::Tweak {
+        TracingTweak {
+            tweak: TraceTweak::Tree {
+                level,
+                pos_in_level,
+            },
+            inner: TH::tree_tweak(level, pos_in_level),
+        }
+    }
+
+    fn chain_tweak(epoch: u32, chain_index: u16, pos_in_chain: u16) -> Self::Tweak {
+        TracingTweak {
+            tweak: TraceTweak::Chain {
+                epoch,
+                chain_index,
+                pos_in_chain,
+            },
+            inner: TH::chain_tweak(epoch, chain_index, pos_in_chain),
+        }
+    }
+
+    fn identifier() -> Vec<u8> {
+        TH::identifier()
+    }
+
+    fn encode_tweak(tweak: &Self::Tweak) -> Vec<u8> {
+        TH::encode_tweak(&tweak.inner)
+    }
+
+    fn apply(
+        parameter: &Self::Parameter,
+        tweak: &Self::Tweak,
+        message: &[Self::Domain],
+    ) -> Self::Domain {
+        Self::apply_and_report(parameter, tweak, message)
+    }
+
+    fn apply_iter<I: Iterator<Item = Self::Domain>>(
+        parameter: &Self::Parameter,
+        tweak: &Self::Tweak,
+        message: I,
+    ) -> Self::Domain {
+        // the inputs are part of the trace, so they are collected
+        let message: Vec<_> = message.collect();
+        Self::apply_and_report(parameter, tweak, &message)
+    }
+
+    // Note: `apply_batch` is not forwarded, so that each step
+    // of a chain is reported on its own.
+
+    fn is_canonical(element: &Self::Domain) -> bool {
+        TH::is_canonical(element)
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        TH::internal_consistency_check();
+    }
+}
+
+/// A message hash that computes the same hashes as `MH`, and
+/// reports each invocation to the recording of the current thread
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TracingMessageHash<MH: MessageHash> {
+    _marker: PhantomData<MH>,
+}
+
+impl<MH: MessageHash + 'static> MessageHash for TracingMessageHash<MH> {
+    type Parameter = MH::Parameter;
+
+    type Randomness = MH::Randomness;
+
+    const NUM_CHUNKS: usize = MH::NUM_CHUNKS;
+
+    const CHUNK_SIZE: usize = MH::CHUNK_SIZE;
+
+    fn identifier() -> Vec<u8> {
+        MH::identifier()
+    }
+
+    fn rand<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Randomness {
+        MH::rand(rng)
+    }
+
+    fn apply(
+        parameter: &Self::Parameter,
+        epoch: u32,
+        randomness: &Self::Randomness,
+        message: &[u8; MESSAGE_LENGTH],
+    ) -> Vec<u8> {
+        let output = MH::apply(parameter, epoch, randomness, message);
+        report(|| MessageHashCall::<MH> {
+            parameter: parameter.clone(),
+            epoch,
+            randomness: randomness.clone(),
+            message: *message,
+            output: output.clone(),
+        });
+        output
+    }
+
+    // Note: `apply_into` and `apply_packed` are not forwarded,
+    // so that they are reported via `apply`.
+
+    fn unpack(packed: &[u8]) -> Vec<u8> {
+        MH::unpack(packed)
+    }
+
+    #[cfg(test)]
+    fn internal_consistency_check() {
+        MH::internal_consistency_check();
+    }
+}
+
+#[cfg(all(test, feature = "sha"))]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::symmetric::{message_hash::sha::ShaMessageHash, tweak_hash::sha::ShaTweakHash};
+
+    use super::*;
+
+    // Note: do not use these parameters, they are just for testing
+    type MH = ShaMessageHash<16, 16, 32, 4>;
+    type TH = ShaTweakHash<16, 16>;
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut rng = thread_rng();
+        let parameter = TH::rand_parameter(&mut rng);
+        let inputs = [TH::rand_domain(&mut rng), TH::rand_domain(&mut rng)];
+        let randomness = MH::rand(&mut rng);
+        let message = rng.gen();
+
+        let mut trace = HashTrace::<TH, MH>::default();
+        let outputs = record(&mut trace, || {
+            type Traced = TracingTweakHash<TH>;
+            let tree_tweak = Traced::tree_tweak(3, 7);
+            let chain_tweak = Traced::chain_tweak(13, 2, 5);
+            (
+                Traced::apply(&parameter, &tree_tweak, &inputs),
+                Traced::apply_iter(&parameter, &chain_tweak, inputs[..1].iter().copied()),
+                TracingMessageHash::<MH>::apply(&parameter, 13, &randomness, &message),
+            )
+        });
+
+        // the wrappers compute the same hashes, and the trace
+        // contains each invocation with its inputs, in order
+        let tree_tweak = TH::tree_tweak(3, 7);
+        let chain_tweak = TH::chain_tweak(13, 2, 5);
+        assert_eq!(outputs.0, TH::apply(&parameter, &tree_tweak, &inputs));
+        assert_eq!(outputs.1, TH::apply(&parameter, &chain_tweak, &inputs[..1]));
+        assert_eq!(outputs.2, MH::apply(&parameter, 13, &randomness, &message));
+        let expected = HashTrace::<TH, MH> {
+            calls: vec![
+                HashCall::TweakHash(TweakHashCall {
+                    parameter,
+                    tweak: TraceTweak::Tree {
+                        level: 3,
+                        pos_in_level: 7,
+                    },
+                    encoded_tweak: TH::encode_tweak(&tree_tweak),
+                    inputs: inputs.to_vec(),
+                    output: outputs.0,
+                }),
+                HashCall::TweakHash(TweakHashCall {
+                    parameter,
+                    tweak: TraceTweak::Chain {
+                        epoch: 13,
+                        chain_index: 2,
+                        pos_in_chain: 5,
+                    },
+                    encoded_tweak: TH::encode_tweak(&chain_tweak),
+                    inputs: inputs[..1].to_vec(),
+                    output: outputs.1,
+                }),
+                HashCall::MessageHash(MessageHashCall {
+                    parameter,
+                    epoch: 13,
+                    randomness,
+                    message,
+                    output: outputs.2,
+                }),
+            ],
+        };
+        assert!(trace == expected);
+        replay_trace(&trace);
+
+        // the encoded tweaks are those of the SHA tweak hash
+        assert_eq!(
+            trace_encoded_tweaks(&trace),
+            [
+                vec![0x01, 3, 0, 0, 0, 7],
+                vec![0x00, 0, 0, 0, 13, 0, 2, 0, 5]
+            ]
+        );
+
+        // outside of a recording, nothing is reported
+        let mut other = HashTrace::<TH, MH>::default();
+        TracingTweakHash::<TH>::apply(
+            &parameter,
+            &TracingTweakHash::<TH>::tree_tweak(0, 0),
+            &inputs,
+        );
+        record(&mut other, || ());
+        assert!(other.calls.is_empty());
+    }
+
+    fn trace_encoded_tweaks(trace: &HashTrace<TH, MH>) -> Vec<Vec<u8>> {
+        trace
+            .calls
+            .iter()
+            .filter_map(|call| match call {
+                HashCall::TweakHash(call) => Some(call.encoded_tweak.clone()),
+                HashCall::MessageHash(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut rng = thread_rng();
+        let parameter = TH::rand_parameter(&mut rng);
+        let randomness = MH::rand(&mut rng);
+        let message = rng.gen();
+
+        let mut trace = HashTrace::<TH, MH>::default();
+        record(&mut trace, || {
+            let tweak = TracingTweakHash::<TH>::chain_tweak(1, 2, 3);
+            TracingTweakHash::<TH>::apply(&parameter, &tweak, &[TH::rand_domain(&mut rng)]);
+            TracingMessageHash::<MH>::apply(&parameter, 1, &randomness, &message);
+        });
+
+        let json = serde_json::to_string(&trace).unwrap();
+        let decoded: HashTrace<TH, MH> = serde_json::from_str(&json).unwrap();
+        assert!(decoded == trace);
+        replay_trace(&decoded);
+    }
+
+    #[test]
+    #[should_panic(expected = "Output of invocation 0 differs")]
+    fn test_replay_rejects_modified_output() {
+        let mut rng = thread_rng();
+        let parameter = TH::rand_parameter(&mut rng);
+
+        let mut trace = HashTrace::<TH, MH>::default();
+        record(&mut trace, || {
+            let tweak = TracingTweakHash::<TH>::tree_tweak(1, 0);
+            TracingTweakHash::<TH>::apply(&parameter, &tweak, &[TH::rand_domain(&mut rng)]);
+        });
+        if let HashCall::TweakHash(call) = &mut trace.calls[0] {
+            call.output[0] ^= 1;
+        }
+        replay_trace(&trace);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invocation of a hash that is not recorded")]
+    fn test_record_rejects_other_hashes() {
+        // the recorder is for other parameters than the traced hash
+        let mut trace = HashTrace::<ShaTweakHash<16, 24>, MH>::default();
+        record(&mut trace, || {
+            let tweak = TracingTweakHash::<TH>::tree_tweak(1, 0);
+            TracingTweakHash::<TH>::apply(&[0; 16], &tweak, &[[0; 16]]);
+        });
+    }
+}
@@ -1,4 +1,8 @@
-use rand::Rng;
+use std::hash::Hash;
+
+use rand_core::{CryptoRng, RngCore};
+
+use super::constant_time::ConstantTimeEq;
 
 /// Trait to model a tweakable hash function.
 /// Such a function takes a public parameter, a tweak, and a
@@ -12,16 +16,21 @@ use rand::Rng;
 /// We also require that the tweak hash already specifies how
 /// to obtain distinct tweaks for applications in chains and
 /// applications in Merkle trees.
+///
+/// Domain elements can be compared in constant time, which verification
+/// uses to compare a recomputed Merkle root with the given one.
 pub trait TweakableHash {
-    type Parameter: Copy + Sized + Send + Sync;
+    type Parameter: Copy + Eq + Hash + Sized + Send + Sync;
     type Tweak;
-    type Domain: Copy + PartialEq + Sized + Send + Sync;
+    type Domain: Copy + Eq + ConstantTimeEq + Hash + Sized + Send + Sync;
 
-    /// Generates a random public parameter.
-    fn rand_parameter<R: Rng>(rng: &mut R) -> Self::Parameter;
+    /// Generates a random public parameter. The RNG must be cryptographically
+    /// secure, as the parameter is part of the keys.
+    fn rand_parameter<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Parameter;
 
-    /// Generates a random domain element.
-    fn rand_domain<R: Rng>(rng: &mut R) -> Self::Domain;
+    /// Generates a random domain element. The RNG must be cryptographically
+    /// secure, as domain elements may be secret, e.g., the starts of chains.
+    fn rand_domain<R: RngCore + CryptoRng>(rng: &mut R) -> Self::Domain;
 
     /// Returns a tweak to be used in the Merkle tree.
     /// Note: this is assumed to be distinct from the outputs of chain_tweak
@@ -31,6 +40,18 @@ pub trait TweakableHash {
     /// Note: this is assumed to be distinct from the outputs of tree_tweak
     fn chain_tweak(epoch: u32, chain_index: u16, pos_in_chain: u16) -> Self::Tweak;
 
+    /// Identifies the hash function, and everything else that determines
+    /// the output besides the lengths of parameter and domain elements,
+    /// e.g., an application identifier. Different tweakable hashes must
+    /// have different identifiers, as they are part of the identifier of
+    /// an instantiation.
+    fn identifier() -> Vec<u8>;
+
+    /// Returns the encoding of a tweak, as it enters the hash. This is not
+    /// used for hashing, but exposes the input of the hash, e.g., for traces
+    /// of hash invocations, see `symmetric::trace`.
+    fn encode_tweak(tweak: &Self::Tweak) -> Vec<u8>;
+
     /// Applies the tweakable hash to parameter, tweak, and message.
     fn apply(
         parameter: &Self::Parameter,
@@ -38,6 +59,43 @@ pub trait TweakableHash {
         message: &[Self::Domain],
     ) -> Self::Domain;
 
+    /// Same as `apply`, but the message is given as an iterator. This allows
+    /// to hash messages that are computed on the fly, without collecting them.
+    ///
+    /// Note: the default implementation collects the message, and therefore allocates.
+    fn apply_iter<I: Iterator<Item = Self::Domain>>(
+        parameter: &Self::Parameter,
+        tweak: &Self::Tweak,
+        message: I,
+    ) -> Self::Domain {
+        let message: Vec<_> = message.collect();
+        Self::apply(parameter, tweak, &message)
+    }
+
+    /// Applies the tweakable hash to several messages that consist of a single
+    /// domain element, using the i-th tweak for the i-th message. The results
+    /// are written back to `messages`.
+    ///
+    /// Note: the default implementation applies the hash to one message after
+    /// another. Implementations may process several messages at once, e.g., using SIMD.
+    fn apply_batch(
+        parameter: &Self::Parameter,
+        tweaks: &[Self::Tweak],
+        messages: &mut [Self::Domain],
+    ) {
+        assert_eq!(tweaks.len(), messages.len());
+        for (tweak, message) in tweaks.iter().zip(messages.iter_mut()) {
+            *message = Self::apply(parameter, tweak, &[*message]);
+        }
+    }
+
+    /// Checks that a domain element is in canonical form, e.g., that
+    /// field elements are reduced. By default, all elements are canonical,
+    /// which is the case for hashes whose domain is just bytes.
+    fn is_canonical(_element: &Self::Domain) -> bool {
+        true
+    }
+
     /// Function to check internal consistency of any given parameters
     /// For testing only, and expected to panic if something is wrong.
     #[cfg(test)]
@@ -72,7 +130,32 @@ pub(crate) fn chain<TH: TweakableHash>(
     current
 }
 
+/// Same as `chain`, but walks several chains in lockstep, so that the hashes
+/// of all chains in one step can be computed at once, see `apply_batch`.
+/// The chains have consecutive indices, starting at `first_chain_index`, and
+/// `values` contains the start of each chain, which is replaced by its end.
+pub(crate) fn chain_batch<TH: TweakableHash>(
+    parameter: &TH::Parameter,
+    epoch: u32,
+    first_chain_index: u16,
+    start_pos_in_chain: u16,
+    steps: usize,
+    values: &mut [TH::Domain],
+) {
+    let mut tweaks = Vec::with_capacity(values.len());
+    for j in 0..steps {
+        let pos_in_chain = start_pos_in_chain + (j as u16) + 1;
+        tweaks.clear();
+        tweaks.extend(
+            (0..values.len())
+                .map(|i| TH::chain_tweak(epoch, first_chain_index + i as u16, pos_in_chain)),
+        );
+        TH::apply_batch(parameter, &tweaks, values);
+    }
+}
+
 // pub mod poseidon;
+#[cfg(feature = "sha")]
 pub mod sha;
 
 #[cfg(test)]
@@ -119,4 +202,49 @@ mod tests {
             assert_eq!(end_direct, end_indirect);
         }
     }
+
+    /// Checks that walking chains in lockstep gives the same chain ends as
+    /// walking each chain on its own, for all numbers of chains up to `max_chains`.
+    fn _test_chain_batch_matches_chain<TH: TweakableHash>(max_chains: usize) {
+        let mut rng = thread_rng();
+
+        let epoch = 9;
+        let first_chain_index = 3;
+        let start_pos_in_chain = 2;
+        let steps = 5;
+        let parameter = TH::rand_parameter(&mut rng);
+
+        for num_chains in 0..=max_chains {
+            let starts: Vec<_> = (0..num_chains).map(|_| TH::rand_domain(&mut rng)).collect();
+
+            let mut ends = starts.clone();
+            chain_batch::<TH>(
+                &parameter,
+                epoch,
+                first_chain_index,
+                start_pos_in_chain,
+                steps,
+                &mut ends,
+            );
+
+            for (i, start) in starts.iter().enumerate() {
+                let end = chain::<TH>(
+                    &parameter,
+                    epoch,
+                    first_chain_index + i as u16,
+                    start_pos_in_chain,
+                    steps,
+                    start,
+                );
+                assert!(ends[i] == end);
+            }
+        }
+    }
+
+    #[test]
+    fn test_chain_batch_matches_chain() {
+        _test_chain_batch_matches_chain::<sha::ShaTweak128128>(17);
+        _test_chain_batch_matches_chain::<sha::ShaTweak192384>(17);
+        _test_chain_batch_matches_chain::<sha::ShaTweak192512>(17);
+    }
 }
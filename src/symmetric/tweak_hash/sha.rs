@@ -1,8 +1,12 @@
-// use sha3::{Digest, Sha3_256};
-use tiny_keccak::{Hasher, Sha3};
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
 
-use crate::{TWEAK_SEPARATOR_FOR_CHAIN_HASH, TWEAK_SEPARATOR_FOR_TREE_HASH};
+use crate::{
+    symmetric::sha3_backend::{Sha3, Sha3Backend},
+    TWEAK_SEPARATOR_FOR_CHAIN_HASH, TWEAK_SEPARATOR_FOR_TREE_HASH,
+};
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+use crate::symmetric::sha3_multi::{self, LANES};
 
 use super::TweakableHash;
 
@@ -19,38 +23,58 @@ pub enum ShaTweak {
     },
 }
 
+/// Maximum length of an encoded tweak, attained by chain tweaks
+/// with a non-zero application identifier
+const MAX_TWEAK_LEN: usize = 13;
+
 impl ShaTweak {
-    fn to_bytes(&self) -> Vec<u8> {
+    /// Encodes the tweak into a stack array, to avoid allocations in the
+    /// hot path. Returns the array and the length of the encoding in it.
+    ///
+    /// A non-zero application identifier is inserted after the domain separator,
+    /// in big-endian, and the identifier 0 gives the encoding without it. Inputs
+    /// with and without identifier cannot coincide: the inputs of each kind of
+    /// hash have a fixed number of hashes, and the identifier changes their
+    /// length by 4 bytes, which is less than the length of a hash.
+    fn to_bytes(&self, application_id: u32) -> ([u8; MAX_TWEAK_LEN], usize) {
+        let mut bytes = [0u8; MAX_TWEAK_LEN];
+
+        // a non-zero application identifier follows the domain separator
+        let offset = if application_id != 0 {
+            bytes[1..5].copy_from_slice(&application_id.to_be_bytes());
+            5
+        } else {
+            1
+        };
+
         match self {
             Self::TreeTweak {
                 level,
                 pos_in_level,
             } => {
-                let mut bytes = Vec::new();
-                // this is a tree tweak, so we start with a 0x00 byte
-                bytes.push(TWEAK_SEPARATOR_FOR_TREE_HASH);
+                // this is a tree tweak, so we start with a 0x01 byte
+                bytes[0] = TWEAK_SEPARATOR_FOR_TREE_HASH;
                 // then we extend with the actual data
-                bytes.extend(&level.to_be_bytes());
-                bytes.extend(&pos_in_level.to_be_bytes());
+                bytes[offset] = *level;
+                bytes[offset + 1..offset + 5].copy_from_slice(&pos_in_level.to_be_bytes());
                 // Note: it is fine that both tweaks have different
                 // lengths as the domain separator (0x00 or 0x01)
                 // ensures that the length is known and we know when
                 // the tweak ends.
-                bytes
+                (bytes, offset + 5)
             }
             Self::ChainTweak {
                 epoch,
                 chain_index,
                 pos_in_chain,
             } => {
-                let mut bytes = Vec::new();
-                // this is a chain tweak, so we start with a 0x01 byte
-                bytes.push(TWEAK_SEPARATOR_FOR_CHAIN_HASH);
+                // this is a chain tweak, so we start with a 0x00 byte
+                bytes[0] = TWEAK_SEPARATOR_FOR_CHAIN_HASH;
                 // then we extend with the actual data
-                bytes.extend(&epoch.to_be_bytes());
-                bytes.extend(&chain_index.to_be_bytes());
-                bytes.extend(&pos_in_chain.to_be_bytes());
-                bytes
+                bytes[offset..offset + 4].copy_from_slice(&epoch.to_be_bytes());
+                bytes[offset + 4..offset + 6].copy_from_slice(&chain_index.to_be_bytes());
+                bytes[offset + 6..offset + 8].copy_from_slice(&pos_in_chain.to_be_bytes());
+                (bytes, offset + 8)
             }
         }
     }
@@ -59,12 +83,95 @@ impl ShaTweak {
 /// A tweakable hash function implemented using SHA3,
 /// given a parameter length and the hash output length.
 /// Both lengths must be given in Bytes.
-/// Both lengths must be less than 255 bits.
-#[derive(Serialize,Deserialize,Clone, Copy)]
-pub struct ShaTweakHash<const PARAMETER_LEN: usize, const HASH_LEN: usize>;
+/// The parameter length must be less than 255 bits.
+///
+/// SHA3_BITS selects the SHA3 variant, i.e., SHA3-256, SHA3-384, or SHA3-512,
+/// and the hash output length must be at most its output length. The default
+/// is SHA3-256, and larger variants allow for hash output lengths up to 64 bytes.
+///
+/// APPLICATION_ID is mixed into every tweak, so that hashes, and hence keys and
+/// signatures, of one application are never valid in another, even for the same
+/// parameter and seed. The default 0 gives the same hashes as without it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct ShaTweakHash<
+    const PARAMETER_LEN: usize,
+    const HASH_LEN: usize,
+    const SHA3_BITS: usize = 256,
+    const APPLICATION_ID: u32 = 0,
+>;
+
+impl<
+        const PARAMETER_LEN: usize,
+        const HASH_LEN: usize,
+        const SHA3_BITS: usize,
+        const APPLICATION_ID: u32,
+    > ShaTweakHash<PARAMETER_LEN, HASH_LEN, SHA3_BITS, APPLICATION_ID>
+{
+    /// Fails to compile if the SHA3 variant is not supported, if the hash is
+    /// longer than the output of SHA3, or if the parameter is empty, which would
+    /// silently remove the separation of keys. It is evaluated whenever the hash
+    /// is applied or a parameter is sampled, so that invalid lengths cannot lead
+    /// to a panic, to truncated hashes, or to keys without a parameter.
+    pub const LENGTHS_VALID: () = {
+        assert!(
+            PARAMETER_LEN > 0,
+            "SHA Tweak Hash: Parameter Length must be non-zero"
+        );
+        assert!(
+            SHA3_BITS == 256 || SHA3_BITS == 384 || SHA3_BITS == 512,
+            "SHA Tweak Hash: SHA3 variant must be 256, 384, or 512"
+        );
+        assert!(
+            HASH_LEN <= SHA3_BITS / 8,
+            "SHA Tweak Hash: Hash Length must be at most the output length of SHA3"
+        );
+    };
+
+    /// Returns a hasher for the selected SHA3 variant, using the given backend
+    fn hasher<H: Sha3Backend>() -> H {
+        let () = Self::LENGTHS_VALID;
+        match SHA3_BITS {
+            256 => H::v256(),
+            384 => H::v384(),
+            512 => H::v512(),
+            _ => unreachable!("SHA Tweak Hash: SHA3 variant is checked by LENGTHS_VALID"),
+        }
+    }
+
+    /// Implementation of `apply_iter` with the given SHA3 backend. The hash
+    /// does not depend on the backend, which is tested if both are enabled.
+    fn apply_iter_with<H: Sha3Backend, I: Iterator<Item = [u8; HASH_LEN]>>(
+        parameter: &[u8; PARAMETER_LEN],
+        tweak: &ShaTweak,
+        message: I,
+    ) -> [u8; HASH_LEN] {
+        let mut hasher = Self::hasher::<H>();
 
-impl<const PARAMETER_LEN: usize, const HASH_LEN: usize> TweakableHash
-    for ShaTweakHash<PARAMETER_LEN, HASH_LEN>
+        // add the parameter and tweak
+        hasher.update(parameter);
+        let (tweak_bytes, tweak_len) = tweak.to_bytes(APPLICATION_ID);
+        hasher.update(&tweak_bytes[..tweak_len]);
+
+        // now add the actual message to be hashed
+        for m in message {
+            hasher.update(&m);
+        }
+
+        // finalize the hash, and take as many bytes as we need
+        let mut result = [0u8; 512 / 8];
+        hasher.finalize(&mut result[0..SHA3_BITS / 8]);
+        let mut hash = [0u8; HASH_LEN];
+        hash.copy_from_slice(&result[0..HASH_LEN]);
+        hash
+    }
+}
+
+impl<
+        const PARAMETER_LEN: usize,
+        const HASH_LEN: usize,
+        const SHA3_BITS: usize,
+        const APPLICATION_ID: u32,
+    > TweakableHash for ShaTweakHash<PARAMETER_LEN, HASH_LEN, SHA3_BITS, APPLICATION_ID>
 {
     type Parameter = [u8; PARAMETER_LEN];
 
@@ -72,13 +179,16 @@ impl<const PARAMETER_LEN: usize, const HASH_LEN: usize> TweakableHash
 
     type Domain = [u8; HASH_LEN];
 
-    fn rand_parameter<R: rand::Rng>(rng: &mut R) -> Self::Parameter {
+    fn rand_parameter<R: rand_core::RngCore + rand_core::CryptoRng>(
+        rng: &mut R,
+    ) -> Self::Parameter {
+        let () = Self::LENGTHS_VALID;
         let mut par = [0u8; PARAMETER_LEN];
         rng.fill_bytes(&mut par);
         par
     }
 
-    fn rand_domain<R: rand::Rng>(rng: &mut R) -> Self::Domain {
+    fn rand_domain<R: rand_core::RngCore + rand_core::CryptoRng>(rng: &mut R) -> Self::Domain {
         let mut dom = [0u8; HASH_LEN];
         rng.fill_bytes(&mut dom);
         dom
@@ -99,38 +209,106 @@ impl<const PARAMETER_LEN: usize, const HASH_LEN: usize> TweakableHash
         }
     }
 
+    fn identifier() -> Vec<u8> {
+        let mut identifier = b"SHA3".to_vec();
+        identifier.extend(&(SHA3_BITS as u64).to_be_bytes());
+        // the identifier 0 adds nothing, as it does not change the hashes
+        if APPLICATION_ID != 0 {
+            identifier.extend(&(APPLICATION_ID as u64).to_be_bytes());
+        }
+        identifier
+    }
+
+    fn encode_tweak(tweak: &Self::Tweak) -> Vec<u8> {
+        let (bytes, len) = tweak.to_bytes(APPLICATION_ID);
+        bytes[..len].to_vec()
+    }
+
     fn apply(
         parameter: &Self::Parameter,
         tweak: &Self::Tweak,
         message: &[Self::Domain],
     ) -> Self::Domain {
-        // let mut hasher = Sha3_256::new();
-        let mut hasher = Sha3::v256();
+        Self::apply_iter(parameter, tweak, message.iter().copied())
+    }
 
-        // add the parameter and tweak
-        hasher.update(parameter);
-        hasher.update(&tweak.to_bytes());
+    fn apply_iter<I: Iterator<Item = Self::Domain>>(
+        parameter: &Self::Parameter,
+        tweak: &Self::Tweak,
+        message: I,
+    ) -> Self::Domain {
+        Self::apply_iter_with::<Sha3, I>(parameter, tweak, message)
+    }
 
-        // now add the actual message to be hashed
-        for m in message {
-            hasher.update(m);
+    /// With the feature `simd`, the messages are hashed `LANES` at a time using
+    /// multi-lane SHA3, if the CPU supports it. Remaining messages, and inputs that
+    /// do not fit into a single block of SHA3, are hashed one after another.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    fn apply_batch(
+        parameter: &Self::Parameter,
+        tweaks: &[Self::Tweak],
+        messages: &mut [Self::Domain],
+    ) {
+        assert_eq!(tweaks.len(), messages.len());
+        let () = Self::LENGTHS_VALID;
+
+        let input_len = PARAMETER_LEN + MAX_TWEAK_LEN + HASH_LEN;
+        let num_batched = if sha3_multi::is_available() && input_len < sha3_multi::rate(SHA3_BITS) {
+            messages.len() - messages.len() % LANES
+        } else {
+            0
+        };
+
+        let (batched_tweaks, remaining_tweaks) = tweaks.split_at(num_batched);
+        let (batched_messages, remaining_messages) = messages.split_at_mut(num_batched);
+
+        for (tweaks, messages) in batched_tweaks
+            .chunks_exact(LANES)
+            .zip(batched_messages.chunks_exact_mut(LANES))
+        {
+            // inputs are parameter, tweak, and message, as in `apply_iter`
+            let mut inputs = [[0u8; 200]; LANES];
+            let mut lengths = [0; LANES];
+            for l in 0..LANES {
+                let (tweak_bytes, tweak_len) = tweaks[l].to_bytes(APPLICATION_ID);
+                let input = &mut inputs[l];
+                input[..PARAMETER_LEN].copy_from_slice(parameter);
+                input[PARAMETER_LEN..PARAMETER_LEN + tweak_len]
+                    .copy_from_slice(&tweak_bytes[..tweak_len]);
+                input[PARAMETER_LEN + tweak_len..PARAMETER_LEN + tweak_len + HASH_LEN]
+                    .copy_from_slice(&messages[l]);
+                lengths[l] = PARAMETER_LEN + tweak_len + HASH_LEN;
+            }
+
+            let mut outputs = [[0u8; 64]; LANES];
+            sha3_multi::sha3(
+                SHA3_BITS,
+                std::array::from_fn(|l| &inputs[l][..lengths[l]]),
+                &mut outputs,
+            );
+            for (message, output) in messages.iter_mut().zip(outputs.iter()) {
+                message.copy_from_slice(&output[0..HASH_LEN]);
+            }
         }
 
-        // finalize the hash, and take as many bytes as we need
-        let mut result = [0u8;32];
-        hasher.finalize(&mut result);
-        result[0..HASH_LEN].try_into().unwrap()
+        for (tweak, message) in remaining_tweaks.iter().zip(remaining_messages.iter_mut()) {
+            *message = Self::apply(parameter, tweak, &[*message]);
+        }
     }
 
     #[cfg(test)]
     fn internal_consistency_check() {
         assert!(
-            PARAMETER_LEN < 256 / 8,
-            "SHA Tweak Hash: Parameter Length must be less than 256 bit"
+            PARAMETER_LEN > 0 && PARAMETER_LEN < 256 / 8,
+            "SHA Tweak Hash: Parameter Length must be non-zero and less than 256 bit"
+        );
+        assert!(
+            SHA3_BITS == 256 || SHA3_BITS == 384 || SHA3_BITS == 512,
+            "SHA Tweak Hash: SHA3 variant must be 256, 384, or 512"
         );
         assert!(
-            HASH_LEN < 256 / 8,
-            "SHA Tweak Hash: Hash Length must be less than 256 bit"
+            HASH_LEN <= SHA3_BITS / 8,
+            "SHA Tweak Hash: Hash Length must be at most the output length of SHA3"
         );
     }
 }
@@ -139,6 +317,9 @@ impl<const PARAMETER_LEN: usize, const HASH_LEN: usize> TweakableHash
 pub type ShaTweak128128 = ShaTweakHash<16, 16>;
 pub type ShaTweak128192 = ShaTweakHash<16, 24>;
 pub type ShaTweak192192 = ShaTweakHash<24, 24>;
+pub type ShaTweak128320 = ShaTweakHash<16, 40, 384>;
+pub type ShaTweak192384 = ShaTweakHash<24, 48, 384>;
+pub type ShaTweak192512 = ShaTweakHash<24, 64, 512>;
 
 #[cfg(test)]
 mod tests {
@@ -211,4 +392,256 @@ mod tests {
         let tweak_chain = ShaTweak192192::chain_tweak(2, 3, 4);
         ShaTweak192192::apply(&parameter, &tweak_chain, &[message_one, message_two]);
     }
+
+    #[test]
+    fn test_apply_larger_hash_lengths() {
+        let mut rng = thread_rng();
+
+        // these hash lengths are only possible with SHA3-384 and SHA3-512
+        ShaTweak128320::internal_consistency_check();
+        ShaTweak192384::internal_consistency_check();
+        ShaTweak192512::internal_consistency_check();
+
+        let parameter = ShaTweak128320::rand_parameter(&mut rng);
+        let message = ShaTweak128320::rand_domain(&mut rng);
+        let tweak = ShaTweak128320::chain_tweak(2, 3, 4);
+        let hash: [u8; 40] = ShaTweak128320::apply(&parameter, &tweak, &[message]);
+        assert!(hash[32..].iter().any(|&b| b != 0));
+
+        let parameter = ShaTweak192384::rand_parameter(&mut rng);
+        let message = ShaTweak192384::rand_domain(&mut rng);
+        let tweak = ShaTweak192384::tree_tweak(0, 3);
+        let hash: [u8; 48] = ShaTweak192384::apply(&parameter, &tweak, &[message, message]);
+        assert!(hash[32..].iter().any(|&b| b != 0));
+
+        let parameter = ShaTweak192512::rand_parameter(&mut rng);
+        let message = ShaTweak192512::rand_domain(&mut rng);
+        let tweak = ShaTweak192512::tree_tweak(1, 5);
+        let hash: [u8; 64] = ShaTweak192512::apply(&parameter, &tweak, &[message, message]);
+        assert!(hash[48..].iter().any(|&b| b != 0));
+    }
+
+    #[test]
+    fn test_sha3_variants_differ() {
+        let mut rng = thread_rng();
+
+        // the same inputs give unrelated outputs for different variants
+        let parameter = ShaTweak192192::rand_parameter(&mut rng);
+        let message = ShaTweak192192::rand_domain(&mut rng);
+        let tweak = || ShaTweak192192::chain_tweak(1, 2, 3);
+        let hash_256 = ShaTweakHash::<24, 24, 256>::apply(&parameter, &tweak(), &[message]);
+        let hash_384 = ShaTweakHash::<24, 24, 384>::apply(&parameter, &tweak(), &[message]);
+        let hash_512 = ShaTweakHash::<24, 24, 512>::apply(&parameter, &tweak(), &[message]);
+        assert_eq!(
+            hash_256,
+            ShaTweak192192::apply(&parameter, &tweak(), &[message])
+        );
+        assert_ne!(hash_256, hash_384);
+        assert_ne!(hash_256, hash_512);
+        assert_ne!(hash_384, hash_512);
+
+        // and shorter hash lengths are prefixes of longer ones
+        let short = ShaTweakHash::<24, 24, 384>::apply(&parameter, &tweak(), &[]);
+        let long = ShaTweakHash::<24, 48, 384>::apply(&parameter, &tweak(), &[]);
+        assert_eq!(&long[..24], &short[..]);
+    }
+
+    #[test]
+    fn test_tweak_encoding() {
+        // the encoding must not change, as it determines all hashes
+        let (bytes, len) = ShaTweak::TreeTweak {
+            level: 0x0a,
+            pos_in_level: 0x01020304,
+        }
+        .to_bytes(0);
+        assert_eq!(
+            &bytes[..len],
+            &[TWEAK_SEPARATOR_FOR_TREE_HASH, 0x0a, 0x01, 0x02, 0x03, 0x04]
+        );
+
+        let (bytes, len) = ShaTweak::ChainTweak {
+            epoch: 0x01020304,
+            chain_index: 0x0506,
+            pos_in_chain: 0x0708,
+        }
+        .to_bytes(0);
+        assert_eq!(
+            &bytes[..len],
+            &[
+                TWEAK_SEPARATOR_FOR_CHAIN_HASH,
+                0x01,
+                0x02,
+                0x03,
+                0x04,
+                0x05,
+                0x06,
+                0x07,
+                0x08
+            ]
+        );
+
+        // a non-zero application identifier follows the separator
+        let (bytes, len) = ShaTweak::TreeTweak {
+            level: 0x0a,
+            pos_in_level: 0x01020304,
+        }
+        .to_bytes(0x11223344);
+        assert_eq!(
+            &bytes[..len],
+            &[
+                TWEAK_SEPARATOR_FOR_TREE_HASH,
+                0x11,
+                0x22,
+                0x33,
+                0x44,
+                0x0a,
+                0x01,
+                0x02,
+                0x03,
+                0x04
+            ]
+        );
+    }
+
+    #[test]
+    fn test_application_id() {
+        let mut rng = thread_rng();
+
+        let parameter = ShaTweak192192::rand_parameter(&mut rng);
+        let message = ShaTweak192192::rand_domain(&mut rng);
+        let tweak = || ShaTweak192192::chain_tweak(1, 2, 3);
+
+        // the identifier 0 is the default
+        let hash = ShaTweak192192::apply(&parameter, &tweak(), &[message]);
+        assert_eq!(
+            hash,
+            ShaTweakHash::<24, 24, 256, 0>::apply(&parameter, &tweak(), &[message])
+        );
+
+        // different identifiers give unrelated hashes
+        let hash_1 = ShaTweakHash::<24, 24, 256, 1>::apply(&parameter, &tweak(), &[message]);
+        let hash_2 = ShaTweakHash::<24, 24, 256, 2>::apply(&parameter, &tweak(), &[message]);
+        assert_ne!(hash, hash_1);
+        assert_ne!(hash, hash_2);
+        assert_ne!(hash_1, hash_2);
+        assert_eq!(
+            ShaTweakHash::<24, 24, 256, 1>::encode_tweak(&tweak()).len(),
+            MAX_TWEAK_LEN
+        );
+    }
+}
+
+/// Differential tests of the two SHA3 backends, on the inputs of the tweak hash
+#[cfg(all(test, feature = "backend-tiny-keccak", feature = "backend-rustcrypto"))]
+mod backend_tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    use crate::symmetric::sha3_backend::{RustCryptoSha3, TinyKeccakSha3};
+
+    use super::*;
+
+    /// Tweaks of both variants, with all fields at their extreme values
+    fn corpus_tweaks() -> Vec<ShaTweak> {
+        let mut tweaks = Vec::new();
+        for level in [0, 1, u8::MAX] {
+            for pos_in_level in [0, 1, u32::MAX] {
+                tweaks.push(ShaTweak::TreeTweak {
+                    level,
+                    pos_in_level,
+                });
+            }
+        }
+        for epoch in [0, 1, u32::MAX] {
+            for chain_index in [0, u16::MAX] {
+                for pos_in_chain in [0, u16::MAX] {
+                    tweaks.push(ShaTweak::ChainTweak {
+                        epoch,
+                        chain_index,
+                        pos_in_chain,
+                    });
+                }
+            }
+        }
+        tweaks
+    }
+
+    /// Asserts that both backends, and the selected one, give the same hash
+    fn assert_backends_agree<
+        const PARAMETER_LEN: usize,
+        const HASH_LEN: usize,
+        const BITS: usize,
+    >(
+        parameter: &[u8; PARAMETER_LEN],
+        tweak: &ShaTweak,
+        message: &[[u8; HASH_LEN]],
+    ) {
+        let tiny_keccak = ShaTweakHash::<PARAMETER_LEN, HASH_LEN, BITS>::apply_iter_with::<
+            TinyKeccakSha3,
+            _,
+        >(parameter, tweak, message.iter().copied());
+        let rustcrypto = ShaTweakHash::<PARAMETER_LEN, HASH_LEN, BITS>::apply_iter_with::<
+            RustCryptoSha3,
+            _,
+        >(parameter, tweak, message.iter().copied());
+        assert_eq!(tiny_keccak, rustcrypto);
+        assert_eq!(
+            ShaTweakHash::<PARAMETER_LEN, HASH_LEN, BITS>::apply(parameter, tweak, message),
+            tiny_keccak
+        );
+    }
+
+    /// Runs the corpus of tweaks and message counts 0 to 4 for one instantiation
+    fn check_corpus<const PARAMETER_LEN: usize, const HASH_LEN: usize, const BITS: usize>(
+        rng: &mut ChaCha20Rng,
+    ) {
+        let parameter: [u8; PARAMETER_LEN] = std::array::from_fn(|_| rng.gen());
+        for tweak in corpus_tweaks() {
+            for num_messages in 0..=4 {
+                let message: Vec<[u8; HASH_LEN]> = (0..num_messages)
+                    .map(|_| std::array::from_fn(|_| rng.gen()))
+                    .collect();
+                assert_backends_agree::<PARAMETER_LEN, HASH_LEN, BITS>(
+                    &parameter, &tweak, &message,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_backends_agree_on_corpus() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        check_corpus::<16, 16, 256>(&mut rng);
+        check_corpus::<16, 24, 256>(&mut rng);
+        check_corpus::<24, 24, 256>(&mut rng);
+        check_corpus::<24, 32, 256>(&mut rng);
+        check_corpus::<32, 32, 256>(&mut rng);
+        check_corpus::<32, 48, 384>(&mut rng);
+        check_corpus::<32, 64, 512>(&mut rng);
+    }
+
+    #[test]
+    fn test_backends_agree_on_random_inputs() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let tweak = if rng.gen() {
+                ShaTweak::TreeTweak {
+                    level: rng.gen(),
+                    pos_in_level: rng.gen(),
+                }
+            } else {
+                ShaTweak::ChainTweak {
+                    epoch: rng.gen(),
+                    chain_index: rng.gen(),
+                    pos_in_chain: rng.gen(),
+                }
+            };
+            let message: Vec<[u8; 24]> = (0..rng.gen_range(0..=8)).map(|_| rng.gen()).collect();
+            assert_backends_agree::<24, 24, 256>(&rng.gen(), &tweak, &message);
+
+            // messages of more than one block of SHA3
+            let message: Vec<[u8; 32]> = (0..rng.gen_range(0..=16)).map(|_| rng.gen()).collect();
+            assert_backends_agree::<32, 32, 512>(&rng.gen(), &tweak, &message);
+        }
+    }
 }
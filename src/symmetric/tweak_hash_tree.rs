@@ -1,29 +1,158 @@
-use crate::symmetric::tweak_hash::TweakableHash;
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    ops::ControlFlow,
+};
+
+use crate::{
+    parallelism::map_range,
+    symmetric::{constant_time::ConstantTimeEq, tweak_hash::TweakableHash},
+};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
 
 /// Hash-Tree based on a tweakable hash function
 /// We consider hash trees in which each leaf is first
 /// hashed individually.
+#[derive(Serialize, Deserialize)]
 pub struct HashTree<TH: TweakableHash> {
     /// Layers of the hash tree, starting with the
     /// bottom layer. The leafs are not included: the
     /// bottom layer is the list of hashes of all leafs
+    #[serde(
+        bound(
+            serialize = "Vec<Vec<TH::Domain>>: Serialize",
+            deserialize = "Vec<Vec<TH::Domain>>: Deserialize<'de>"
+        ),
+        deserialize_with = "deserialize_layers"
+    )]
     layers: Vec<Vec<TH::Domain>>,
 }
 
+/// Deserializes the layers of a hash tree, and rejects them unless the tree
+/// is well-formed, i.e., the bottom layer has a power of two many nodes, each
+/// layer is half the size of the previous layer, and the final layer has size
+/// 1. This ensures that paths and roots of deserialized trees do not panic.
+fn deserialize_layers<'de, D, Domain>(deserializer: D) -> Result<Vec<Vec<Domain>>, D::Error>
+where
+    D: Deserializer<'de>,
+    Vec<Vec<Domain>>: Deserialize<'de>,
+{
+    let layers = Vec::<Vec<Domain>>::deserialize(deserializer)?;
+    let well_formed = layers.len() <= 33
+        && layers.last().is_some_and(|layer| layer.len() == 1)
+        && layers
+            .windows(2)
+            .all(|pair| pair[0].len() == 2 * pair[1].len());
+    if !well_formed {
+        return Err(D::Error::custom("Hash-Tree: malformed layers"));
+    }
+    Ok(layers)
+}
+
+impl<TH: TweakableHash> HashTree<TH> {
+    /// Returns the depth of the tree, i.e., the base 2 log of the number of leafs
+    pub fn depth(&self) -> usize {
+        self.layers.len() - 1
+    }
+}
+
+impl<TH: TweakableHash> Clone for HashTree<TH> {
+    fn clone(&self) -> Self {
+        Self {
+            layers: self.layers.clone(),
+        }
+    }
+}
+
 /// Function to compute a hash-tree given the leafs hashes as input.
 /// The number of leafs hashes must be a power of two.
 pub fn build_tree<TH: TweakableHash>(
     parameter: &TH::Parameter,
     leafs_hashes: Vec<TH::Domain>,
 ) -> HashTree<TH> {
+    build_tree_with(
+        leafs_hashes,
+        |level, layer, layer_size| {
+            (0..layer_size)
+                .map(|i| hash_children::<TH>(parameter, level, i, layer))
+                .collect()
+        },
+        |_| ControlFlow::Continue(()),
+    )
+    .expect("Hash-Tree build_tree: Building the tree is never cancelled")
+}
+
+/// Function to compute a hash-tree given the leafs hashes as input.
+/// The number of leafs hashes must be a power of two.
+///
+/// This computes the same tree as `build_tree`, but the nodes
+/// within each layer are computed in parallel.
+#[cfg(feature = "parallel")]
+pub fn build_tree_parallel<TH: TweakableHash>(
+    parameter: &TH::Parameter,
+    leafs_hashes: Vec<TH::Domain>,
+) -> HashTree<TH> {
+    build_tree_with(
+        leafs_hashes,
+        |level, layer, layer_size| {
+            (0..layer_size)
+                .into_par_iter()
+                .map(|i| hash_children::<TH>(parameter, level, i, layer))
+                .collect()
+        },
+        |_| ControlFlow::Continue(()),
+    )
+    .expect("Hash-Tree build_tree_parallel: Building the tree is never cancelled")
+}
+
+/// Function to compute a hash-tree given the leafs hashes as input.
+/// The number of leafs hashes must be a power of two.
+///
+/// This computes the same tree as `build_tree`, in parallel unless `sequential`
+/// is set or the feature `parallel` is disabled. Before each layer is computed,
+/// `on_layer` is called with its level. If it returns `Break`, this stops
+/// and returns `None`.
+pub(crate) fn build_tree_cancellable<TH: TweakableHash>(
+    parameter: &TH::Parameter,
+    leafs_hashes: Vec<TH::Domain>,
+    sequential: bool,
+    on_layer: impl FnMut(u8) -> ControlFlow<()>,
+) -> Option<HashTree<TH>> {
+    build_tree_with(
+        leafs_hashes,
+        |level, layer, layer_size| {
+            map_range(sequential, 0..layer_size as u64, |i| {
+                hash_children::<TH>(parameter, level, i as usize, layer)
+            })
+        },
+        on_layer,
+    )
+}
+
+/// Helper to build a hash-tree layer by layer. The closure `compute_layer` gets
+/// the level of the new layer, the previous layer, and the size of the new layer,
+/// and must return the new layer. Before each new layer, `on_layer` is called
+/// with its level, and building stops with `None` if it returns `Break`.
+fn build_tree_with<TH: TweakableHash, F, C>(
+    leafs_hashes: Vec<TH::Domain>,
+    compute_layer: F,
+    mut on_layer: C,
+) -> Option<HashTree<TH>>
+where
+    F: Fn(u8, &[TH::Domain], usize) -> Vec<TH::Domain>,
+    C: FnMut(u8) -> ControlFlow<()>,
+{
     // check that number of leafs is a power of two
     assert!(
         leafs_hashes.len().is_power_of_two(),
         "Hash-Tree build_tree: Number of leafs should be power of two"
     );
 
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("build_tree", leafs = leafs_hashes.len()).entered();
+
     let mut layer_size = leafs_hashes.len();
     let mut layers: Vec<Vec<TH::Domain>> = Vec::with_capacity(layer_size.ilog2() as usize + 1);
 
@@ -35,23 +164,39 @@ pub fn build_tree<TH: TweakableHash>(
     while layer_size >= 2 {
         // this new layer will have half the size
         layer_size /= 2;
-        // parallelize the two to one compressions
-        layers.push(
-            (0..layer_size)
-                .into_par_iter()
-                .map(|i| {
-                    let left_idx = 2 * i;
-                    let right_idx = 2 * i + 1;
-                    let tweak = TH::tree_tweak(level, i as u32);
-                    let children = &layers[(level - 1) as usize][left_idx..=right_idx];
-                    TH::apply(parameter, &tweak, children)
-                })
-                .collect(),
+        if on_layer(level).is_break() {
+            return None;
+        }
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+        let layer = compute_layer(level, &layers[(level - 1) as usize], layer_size);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            level,
+            nodes = layer_size,
+            elapsed_us = start.elapsed().as_micros() as u64,
+            "built tree layer"
         );
+        layers.push(layer);
         level += 1;
     }
 
-    HashTree { layers }
+    Some(HashTree { layers })
+}
+
+/// Helper to compute the node at position `i` in the given level,
+/// by hashing its two children in the previous layer.
+fn hash_children<TH: TweakableHash>(
+    parameter: &TH::Parameter,
+    level: u8,
+    i: usize,
+    previous_layer: &[TH::Domain],
+) -> TH::Domain {
+    let left_idx = 2 * i;
+    let right_idx = 2 * i + 1;
+    let tweak = TH::tree_tweak(level, i as u32);
+    let children = &previous_layer[left_idx..=right_idx];
+    TH::apply(parameter, &tweak, children)
 }
 
 /// Function to get a root from a tree. The tree must have at least one layer.
@@ -63,18 +208,58 @@ pub fn hash_tree_root<TH: TweakableHash>(tree: &HashTree<TH>) -> TH::Domain {
 }
 
 /// Opening in a hash-tree: a co-path, without the leaf
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize)]
 pub struct HashTreeOpening<TH: TweakableHash> {
     /// The co-path needed to verify
     /// If the tree has depth h, i.e, 2^h leafs
     /// the co-path should have size D
     #[serde(bound(
-    serialize = "Vec<TH::Domain>: Serialize",
-    deserialize = "Vec<TH::Domain>: Deserialize<'de>"
-))]
+        serialize = "Vec<TH::Domain>: Serialize",
+        deserialize = "Vec<TH::Domain>: Deserialize<'de>"
+    ))]
     co_path: Vec<TH::Domain>,
 }
 
+impl<TH: TweakableHash> HashTreeOpening<TH> {
+    /// Creates an opening from a co-path, from the leaf level upwards
+    pub(crate) fn from_co_path(co_path: Vec<TH::Domain>) -> Self {
+        Self { co_path }
+    }
+
+    /// Returns the depth of the tree that this opening is for,
+    /// i.e., the length of the co-path.
+    pub fn depth(&self) -> usize {
+        self.co_path.len()
+    }
+
+    /// Returns the co-path of this opening, from the leaf level upwards.
+    pub fn co_path(&self) -> &[TH::Domain] {
+        &self.co_path
+    }
+}
+
+impl<TH: TweakableHash> Clone for HashTreeOpening<TH> {
+    fn clone(&self) -> Self {
+        Self {
+            co_path: self.co_path.clone(),
+        }
+    }
+}
+
+impl<TH: TweakableHash> PartialEq for HashTreeOpening<TH> {
+    fn eq(&self, other: &Self) -> bool {
+        self.co_path == other.co_path
+    }
+}
+
+impl<TH: TweakableHash> Eq for HashTreeOpening<TH> {}
+
+impl<TH: TweakableHash> Hash for HashTreeOpening<TH> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.co_path.hash(state);
+    }
+}
+
 /// Function to compute the Merkle authentication path
 /// from a tree and the position of the leaf. It is assumed
 /// that the tree is well-formed, i.e., each layer is half
@@ -117,14 +302,83 @@ pub fn hash_tree_path<TH: TweakableHash>(
     HashTreeOpening { co_path }
 }
 
+/// Function to hash a leaf, i.e., to compute the node in the bottom layer at
+/// position `pos_in_level`. For XMSS, a leaf is a one-time public key, given
+/// as the list of its chain ends, which may be computed on the fly.
+///
+/// Note: the hash uses the tree tweak for level 0. Internal nodes use levels 1
+/// and above, so leafs are domain-separated from them. Building and verifying
+/// trees must both use this function, so that they agree on the leaf hashes.
+pub fn ots_public_key_to_leaf<TH: TweakableHash, I: Iterator<Item = TH::Domain>>(
+    parameter: &TH::Parameter,
+    pos_in_level: u32,
+    pk: I,
+) -> TH::Domain {
+    TH::apply_iter(parameter, &TH::tree_tweak(0, pos_in_level), pk)
+}
+
 /// Function to verify an Merkle authentication path
 /// with respect to a root, a position, and a leaf.
+#[must_use = "a path must be rejected if verification fails"]
 pub fn hash_tree_verify<TH: TweakableHash>(
     parameter: &TH::Parameter,
     root: &TH::Domain,
     position: u32,
     leaf: &[TH::Domain],
     opening: &HashTreeOpening<TH>,
+) -> bool {
+    let leaf_hash = ots_public_key_to_leaf::<TH, _>(parameter, position, leaf.iter().copied());
+    hash_tree_verify_leaf_hash(parameter, root, position, &leaf_hash, opening)
+}
+
+/// Function to verify an Merkle authentication path with respect to a root,
+/// a position, and the hash of a leaf, i.e., the node in the bottom layer.
+/// This allows to hash the leaf without collecting it, see `apply_iter`.
+#[must_use = "a path must be rejected if verification fails"]
+pub fn hash_tree_verify_leaf_hash<TH: TweakableHash>(
+    parameter: &TH::Parameter,
+    root: &TH::Domain,
+    position: u32,
+    leaf_hash: &TH::Domain,
+    opening: &HashTreeOpening<TH>,
+) -> bool {
+    verify_leaf_hash_with::<TH>(
+        root,
+        position,
+        leaf_hash,
+        opening,
+        |level, pos, children| TH::apply(parameter, &TH::tree_tweak(level, pos), &children),
+    )
+}
+
+/// Same as `hash_tree_verify_leaf_hash`, but the nodes on the path are looked
+/// up in `cache`, and computed only if they are not cached. The parameter is
+/// that of the cache.
+#[must_use = "a path must be rejected if verification fails"]
+pub fn hash_tree_verify_leaf_hash_cached<TH: TweakableHash>(
+    cache: &mut NodeCache<TH>,
+    root: &TH::Domain,
+    position: u32,
+    leaf_hash: &TH::Domain,
+    opening: &HashTreeOpening<TH>,
+) -> bool {
+    verify_leaf_hash_with::<TH>(
+        root,
+        position,
+        leaf_hash,
+        opening,
+        |level, pos, children| cache.node(level, pos, children),
+    )
+}
+
+/// Verifies a Merkle authentication path for the hash of a leaf, where
+/// `hash_node` computes a node from its level, position, and children.
+fn verify_leaf_hash_with<TH: TweakableHash>(
+    root: &TH::Domain,
+    position: u32,
+    leaf_hash: &TH::Domain,
+    opening: &HashTreeOpening<TH>,
+    mut hash_node: impl FnMut(u8, u32, [TH::Domain; 2]) -> TH::Domain,
 ) -> bool {
     // given the length of the path, we know how
     // large the tree was. So we can check if the
@@ -142,16 +396,14 @@ pub fn hash_tree_verify<TH: TweakableHash>(
         "Hash-Tree hash tree verify: Position and Path Length not compatible"
     );
 
-    // first hash the leaf to get the node in the bottom layer
-    let tweak = TH::tree_tweak(0, position);
-    let mut current_node = TH::apply(parameter, &tweak, leaf);
-
-    // now reconstruct the root using the co-path
+    // start with the node in the bottom layer, and
+    // reconstruct the root using the co-path
+    let mut current_node = *leaf_hash;
     let mut current_position = position;
     for l in 0..depth {
         // Need to distinguish two cases, depending on
         // if current is a left child or a right child
-        let children = if current_position % 2 == 0 {
+        let children = if current_position.is_multiple_of(2) {
             // left child, so co-path contains the right sibling
             [current_node, opening.co_path[l]]
         } else {
@@ -163,12 +415,102 @@ pub fn hash_tree_verify<TH: TweakableHash>(
         current_position >>= 1;
 
         // now hash to get the parent
-        let tweak = TH::tree_tweak((l + 1) as u8, current_position);
-        current_node = TH::apply(parameter, &tweak, &children);
+        current_node = hash_node((l + 1) as u8, current_position, children);
     }
 
     // Finally, check that recomputed root matches given root
-    current_node == *root
+    current_node.ct_eq(root)
+}
+
+/// Key of a node in `NodeCache`: level, position in the level, and children
+type NodeKey<TH> = (u8, u32, [<TH as TweakableHash>::Domain; 2]);
+
+/// Cache of inner nodes of hash trees, i.e., of applications of the tweakable
+/// hash with a tree tweak to two children. A node is keyed by its level, its
+/// position, and its children, so a cached node is exactly what hashing would
+/// return. This helps to verify many signatures under the same public key, as
+/// the upper nodes of their authentication paths repeat.
+///
+/// A cache is for a single parameter, i.e., a single public key, and it holds
+/// at most `capacity` nodes. They are kept in two generations: a node that is
+/// used is moved to the recent generation, and once it is full, the old one is
+/// dropped. This approximates evicting the least recently used nodes.
+pub struct NodeCache<TH: TweakableHash> {
+    parameter: TH::Parameter,
+    generation_size: usize,
+    recent: HashMap<NodeKey<TH>, TH::Domain>,
+    old: HashMap<NodeKey<TH>, TH::Domain>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<TH: TweakableHash> NodeCache<TH> {
+    /// Creates an empty cache for the given parameter, holding at most
+    /// `capacity` nodes. A capacity below 2 disables caching.
+    pub fn new(parameter: TH::Parameter, capacity: usize) -> Self {
+        Self {
+            parameter,
+            generation_size: capacity / 2,
+            recent: HashMap::new(),
+            old: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the parameter of the tweakable hash for which nodes are cached
+    pub fn parameter(&self) -> &TH::Parameter {
+        &self.parameter
+    }
+
+    /// Returns the node at the given level and position with the given
+    /// children, from the cache if possible. Otherwise, it is computed
+    /// and added to the cache.
+    pub fn node(&mut self, level: u8, pos_in_level: u32, children: [TH::Domain; 2]) -> TH::Domain {
+        let key = (level, pos_in_level, children);
+        if let Some(&node) = self.recent.get(&key) {
+            self.hits += 1;
+            return node;
+        }
+        let node = match self.old.remove(&key) {
+            Some(node) => {
+                self.hits += 1;
+                node
+            }
+            None => {
+                self.misses += 1;
+                let tweak = TH::tree_tweak(level, pos_in_level);
+                TH::apply(&self.parameter, &tweak, &children)
+            }
+        };
+        if self.generation_size > 0 {
+            if self.recent.len() == self.generation_size {
+                self.old = std::mem::take(&mut self.recent);
+            }
+            self.recent.insert(key, node);
+        }
+        node
+    }
+
+    /// Returns the number of cached nodes
+    pub fn len(&self) -> usize {
+        self.recent.len() + self.old.len()
+    }
+
+    /// Returns whether no nodes are cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns how often a node was found in the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns how often a node was not found in the cache, and was computed
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
 }
 
 #[cfg(test)]
@@ -225,5 +567,220 @@ mod tests {
             let leaf = leafs[position as usize].as_slice();
             assert!(hash_tree_verify(&parameter, &root, position, leaf, &path));
         }
+
+        // roots that differ in a single bit do not verify
+        let path = hash_tree_path(&tree, 0);
+        for i in 0..root.len() {
+            let mut other_root = root;
+            other_root[i] ^= 1;
+            assert!(!hash_tree_verify(
+                &parameter,
+                &other_root,
+                0,
+                leafs[0].as_slice(),
+                &path
+            ));
+        }
+    }
+
+    #[test]
+    fn test_deserialize_malformed_tree() {
+        let mut rng = thread_rng();
+        let parameter = TestTH::rand_parameter(&mut rng);
+        let leafs_hashes: Vec<_> = (0..8).map(|_| TestTH::rand_domain(&mut rng)).collect();
+        let tree = build_tree::<TestTH>(&parameter, leafs_hashes);
+
+        // a tree is encoded as its list of layers
+        let bytes = bincode::serialize(&tree).unwrap();
+        let decoded: HashTree<TestTH> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.depth(), 3);
+        assert_eq!(bytes, bincode::serialize(&tree.layers).unwrap());
+
+        let node = TestTH::rand_domain(&mut rng);
+        let malformed = [
+            // no layers at all
+            vec![],
+            // the root is missing
+            vec![vec![node; 4], vec![node; 2]],
+            // a layer that is not half the size of the previous layer
+            vec![vec![node; 4], vec![node; 3], vec![node; 1]],
+            vec![vec![node; 3], vec![node; 1]],
+        ];
+        for layers in malformed {
+            let bytes = bincode::serialize(&layers).unwrap();
+            assert!(bincode::deserialize::<HashTree<TestTH>>(&bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn test_ots_public_key_to_leaf() {
+        let mut rng = thread_rng();
+        let parameter = TestTH::rand_parameter(&mut rng);
+        let pk: Vec<_> = (0..5).map(|_| TestTH::rand_domain(&mut rng)).collect();
+        let position = 6;
+
+        // the leaf is stable, and it is the hash under the tree tweak for level 0
+        let leaf = ots_public_key_to_leaf::<TestTH, _>(&parameter, position, pk.iter().copied());
+        assert_eq!(
+            leaf,
+            ots_public_key_to_leaf::<TestTH, _>(&parameter, position, pk.iter().copied())
+        );
+        assert_eq!(
+            leaf,
+            TestTH::apply(&parameter, &TestTH::tree_tweak(0, position), &pk)
+        );
+
+        // it depends on the position, and it is domain-separated
+        // from internal nodes with the same children
+        assert_ne!(
+            leaf,
+            ots_public_key_to_leaf::<TestTH, _>(&parameter, position + 1, pk.iter().copied())
+        );
+        let children = [pk[0], pk[1]];
+        let leaf = ots_public_key_to_leaf::<TestTH, _>(&parameter, position, children.into_iter());
+        for level in 1..=32 {
+            let node = TestTH::apply(&parameter, &TestTH::tree_tweak(level, position), &children);
+            assert_ne!(leaf, node);
+        }
+
+        // the verifier recomputes the leaf that the tree is built from
+        let leafs: Vec<Vec<_>> = (0..8)
+            .map(|_| (0..5).map(|_| TestTH::rand_domain(&mut rng)).collect())
+            .collect();
+        let leafs_hashes = leafs
+            .iter()
+            .enumerate()
+            .map(|(i, pk)| {
+                ots_public_key_to_leaf::<TestTH, _>(&parameter, i as u32, pk.iter().copied())
+            })
+            .collect();
+        let tree = build_tree::<TestTH>(&parameter, leafs_hashes);
+        let root = hash_tree_root(&tree);
+        let path = hash_tree_path(&tree, position);
+        assert!(hash_tree_verify(
+            &parameter,
+            &root,
+            position,
+            &leafs[position as usize],
+            &path
+        ));
+    }
+
+    #[test]
+    fn test_node_cache() {
+        let mut rng = thread_rng();
+        let parameter = TestTH::rand_parameter(&mut rng);
+        let num_leafs = 64;
+        let leafs_hashes: Vec<_> = (0..num_leafs)
+            .map(|_| TestTH::rand_domain(&mut rng))
+            .collect();
+        let tree = build_tree::<TestTH>(&parameter, leafs_hashes.clone());
+        let root = hash_tree_root(&tree);
+
+        // cached and uncached verification agree, for valid and invalid paths
+        let mut cache = NodeCache::<TestTH>::new(parameter, 1000);
+        for round in 0..2 {
+            for position in 0..num_leafs {
+                let leaf_hash = &leafs_hashes[position as usize];
+                let path = hash_tree_path(&tree, position);
+                assert!(hash_tree_verify_leaf_hash_cached(
+                    &mut cache, &root, position, leaf_hash, &path
+                ));
+
+                let mut co_path = path.co_path().to_vec();
+                co_path[round] = TestTH::rand_domain(&mut rng);
+                let wrong_path = HashTreeOpening::from_co_path(co_path);
+                let wrong_position = (position + 1) % num_leafs;
+                for (position, path) in [(position, &wrong_path), (wrong_position, &path)] {
+                    assert_eq!(
+                        hash_tree_verify_leaf_hash_cached(
+                            &mut cache, &root, position, leaf_hash, path
+                        ),
+                        hash_tree_verify_leaf_hash(&parameter, &root, position, leaf_hash, path)
+                    );
+                }
+            }
+        }
+        // the inner nodes of the tree are computed once for the valid paths
+        assert!(cache.hits() > 0);
+        assert!(cache.len() <= 1000);
+
+        // the cache holds at most its capacity, and a small capacity disables it
+        for capacity in [0, 1, 2, 5, 16] {
+            let mut cache = NodeCache::<TestTH>::new(parameter, capacity);
+            for position in 0..num_leafs {
+                let path = hash_tree_path(&tree, position);
+                let leaf_hash = &leafs_hashes[position as usize];
+                assert!(hash_tree_verify_leaf_hash_cached(
+                    &mut cache, &root, position, leaf_hash, &path
+                ));
+                assert!(cache.len() <= capacity);
+            }
+            assert_eq!(cache.is_empty(), capacity < 2);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_build_matches_sequential() {
+        let mut rng = thread_rng();
+
+        // we test that the parallel and the sequential builder
+        // result in exactly the same tree, for various sizes
+        let parameter = TestTH::rand_parameter(&mut rng);
+        for log_num_leafs in 0..=10 {
+            let num_leafs = 1 << log_num_leafs;
+            let leafs_hashes: Vec<_> = (0..num_leafs)
+                .map(|_| TestTH::rand_domain(&mut rng))
+                .collect();
+
+            let tree_sequential = build_tree::<TestTH>(&parameter, leafs_hashes.clone());
+            let tree_parallel = build_tree_parallel::<TestTH>(&parameter, leafs_hashes);
+
+            assert!(tree_sequential.layers == tree_parallel.layers);
+            assert_eq!(
+                hash_tree_root(&tree_sequential),
+                hash_tree_root(&tree_parallel)
+            );
+        }
+    }
+
+    #[test]
+    fn test_cancellable_build() {
+        let mut rng = thread_rng();
+        let parameter = TestTH::rand_parameter(&mut rng);
+        let leafs_hashes: Vec<_> = (0..16).map(|_| TestTH::rand_domain(&mut rng)).collect();
+        let tree = build_tree::<TestTH>(&parameter, leafs_hashes.clone());
+
+        // without cancellation, we get the same tree, and are
+        // called once for each layer above the leafs hashes
+        for sequential in [true, false] {
+            let mut levels = Vec::new();
+            let tree_cancellable = build_tree_cancellable::<TestTH>(
+                &parameter,
+                leafs_hashes.clone(),
+                sequential,
+                |level| {
+                    levels.push(level);
+                    ControlFlow::Continue(())
+                },
+            )
+            .unwrap();
+            assert!(tree.layers == tree_cancellable.layers);
+            assert_eq!(levels, vec![1, 2, 3, 4]);
+        }
+
+        // cancelling before the third layer stops building
+        let mut levels = Vec::new();
+        let result = build_tree_cancellable::<TestTH>(&parameter, leafs_hashes, true, |level| {
+            levels.push(level);
+            if level == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert!(result.is_none());
+        assert_eq!(levels, vec![1, 2, 3]);
     }
 }
@@ -0,0 +1,106 @@
+// Exports the vectors in this directory from upstream hash-sig, at the revision
+// from which this crate was forked. This file is not compiled with the crate.
+// To regenerate the vectors, copy it to `tests/export.rs` of an upstream
+// checkout, add `rand_chacha = "0.3"`, `bincode = "1.3"`, and
+// `serde_json = "1.0"` to its dev-dependencies, and run
+// `cargo test --release --test export`. The files are written to the
+// directory given by the environment variable `EXPORT_DIR`.
+
+use std::path::PathBuf;
+
+use hashsig::{
+    inc_encoding::{
+        basic_winternitz::WinternitzEncoding, target_sum::TargetSumEncoding, IncomparableEncoding,
+    },
+    signature::{generalized_xmss::GeneralizedXMSSSignatureScheme, SignatureScheme},
+    symmetric::{
+        message_hash::sha::ShaMessageHash,
+        prf::{sha::ShaPRF, Pseudorandom},
+        tweak_hash::{sha::ShaTweakHash, TweakableHash},
+    },
+    MESSAGE_LENGTH,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{de::DeserializeOwned, Serialize};
+
+const LOG_LIFETIME: usize = 6;
+
+type Sig<PRF, IE, TH> = GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+fn to_hex<T: Serialize>(value: &T) -> String {
+    bincode::serialize(value)
+        .unwrap()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn export<PRF: Pseudorandom, IE: IncomparableEncoding, TH: TweakableHash>(name: &str, rng_seed: u64)
+where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter> + Serialize + DeserializeOwned,
+    <Sig<PRF, IE, TH> as SignatureScheme>::PublicKey: Serialize,
+    <Sig<PRF, IE, TH> as SignatureScheme>::Signature: Serialize,
+    IE::Randomness: Serialize,
+{
+    let mut rng = ChaCha20Rng::seed_from_u64(rng_seed);
+    let (pk, sk) = Sig::<PRF, IE, TH>::gen(&mut rng);
+
+    // the parameter is the last field of the public key
+    let pk_bytes = bincode::serialize(&pk).unwrap();
+    let parameter_bytes = &pk_bytes[pk_bytes.len() - std::mem::size_of::<TH::Parameter>()..];
+    let parameter: TH::Parameter = bincode::deserialize(parameter_bytes).unwrap();
+
+    let mut signatures = vec![];
+    for epoch in [0, 13, (1 << LOG_LIFETIME) - 1] {
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+
+        // replay the sampling of the randomness in `sign`
+        let mut replay = rng.clone();
+        let (rho, codeword) = loop {
+            let rho = IE::rand(&mut replay);
+            if let Ok(codeword) = IE::encode(&parameter.into(), &message, &rho, epoch) {
+                break (rho, codeword);
+            }
+        };
+
+        let sig = Sig::<PRF, IE, TH>::sign(&mut rng, &sk, epoch, &message).unwrap();
+        assert!(Sig::<PRF, IE, TH>::verify(&pk, epoch, &message, &sig));
+        signatures.push(serde_json::json!({
+            "epoch": epoch,
+            "message": message.iter().map(|byte| format!("{:02x}", byte)).collect::<String>(),
+            "randomness": to_hex(&rho),
+            "codeword": codeword,
+            "signature": to_hex(&sig),
+        }));
+    }
+
+    let vector = serde_json::json!({
+        "instantiation": name,
+        "rng_seed": rng_seed,
+        "public_key": to_hex(&pk),
+        "signatures": signatures,
+    });
+    let path = PathBuf::from(std::env::var("EXPORT_DIR").unwrap()).join(format!("{}.json", name));
+    std::fs::write(path, serde_json::to_string_pretty(&vector).unwrap() + "\n").unwrap();
+}
+
+// the parameters of `instantiations_sha::lifetime_2_to_the_18`
+type MHw1 = ShaMessageHash<18, 20, 144, 1>;
+type MHw2 = ShaMessageHash<18, 20, 72, 2>;
+type MHw4 = ShaMessageHash<18, 20, 36, 4>;
+type MHw8 = ShaMessageHash<18, 20, 18, 8>;
+type MHw4TargetSum = ShaMessageHash<18, 23, 36, 4>;
+
+#[test]
+fn export_upstream_vectors() {
+    export::<ShaPRF<25>, WinternitzEncoding<MHw1, 8>, ShaTweakHash<18, 25>>("winternitz_w1", 1);
+    export::<ShaPRF<25>, WinternitzEncoding<MHw2, 4>, ShaTweakHash<18, 25>>("winternitz_w2", 2);
+    export::<ShaPRF<26>, WinternitzEncoding<MHw4, 3>, ShaTweakHash<18, 26>>("winternitz_w4", 4);
+    export::<ShaPRF<28>, WinternitzEncoding<MHw8, 2>, ShaTweakHash<18, 28>>("winternitz_w8", 8);
+    export::<ShaPRF<26>, TargetSumEncoding<MHw4TargetSum, 297>, ShaTweakHash<18, 26>>(
+        "target_sum_w4",
+        40,
+    );
+}
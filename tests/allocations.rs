@@ -0,0 +1,130 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use hashsig::{
+    inc_encoding::{basic_winternitz::WinternitzEncoding, target_sum::TargetSumEncoding},
+    signature::{
+        codec::{decode_signature, encode_signature, UNREGISTERED_PARAM_SET_ID},
+        generalized_xmss::GeneralizedXMSSSignatureScheme,
+        SignatureScheme,
+    },
+    symmetric::{
+        message_hash::sha::ShaMessageHash192x3,
+        prf::sha::ShaPRF,
+        tweak_hash::{
+            sha::{ShaTweak128192, ShaTweak192192, ShaTweak192384},
+            TweakableHash,
+        },
+    },
+    MESSAGE_LENGTH,
+};
+use rand::Rng;
+
+/// Allocator that counts the allocations of the current thread,
+/// so that tests running in parallel do not interfere.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations made by the current thread while running `f`.
+fn count_allocations<F: FnMut()>(mut f: F) -> usize {
+    let before = ALLOCATIONS.with(|count| count.get());
+    f();
+    ALLOCATIONS.with(|count| count.get()) - before
+}
+
+/// Checks that applying the tweak hash does not allocate, for both kinds of tweaks.
+fn _test_apply_does_not_allocate<TH: TweakableHash>() {
+    let mut rng = rand::thread_rng();
+    let parameter = TH::rand_parameter(&mut rng);
+    let left = TH::rand_domain(&mut rng);
+    let right = TH::rand_domain(&mut rng);
+
+    let allocations = count_allocations(|| {
+        for i in 0..100 {
+            let tweak = TH::chain_tweak(i, 3, 4);
+            std::hint::black_box(TH::apply(&parameter, &tweak, &[left]));
+            let tweak = TH::tree_tweak(1, i);
+            std::hint::black_box(TH::apply(&parameter, &tweak, &[left, right]));
+        }
+    });
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn test_sha_tweak_hash_does_not_allocate() {
+    // sanity check that allocations are counted at all
+    assert!(count_allocations(|| drop(std::hint::black_box(vec![0u8; 16]))) > 0);
+
+    _test_apply_does_not_allocate::<ShaTweak128192>();
+    _test_apply_does_not_allocate::<ShaTweak192384>();
+}
+
+/// Checks that verifying a deserialized signature does not allocate.
+fn _test_verify_does_not_allocate<S: SignatureScheme>()
+where
+    S::Signature: serde::Serialize + serde::de::DeserializeOwned,
+{
+    let mut rng = rand::thread_rng();
+    let (pk, sk) = S::gen(&mut rng);
+    let mut message = [0u8; MESSAGE_LENGTH];
+    rng.fill(&mut message);
+    let epoch = 13;
+
+    let signature = S::sign(&mut rng, &sk, epoch, &message).unwrap();
+    let bytes = encode_signature::<S>(UNREGISTERED_PARAM_SET_ID, &signature);
+    let signature = decode_signature::<S>(UNREGISTERED_PARAM_SET_ID, &bytes).unwrap();
+
+    let mut valid = false;
+    let allocations = count_allocations(|| {
+        valid = S::verify(&pk, epoch, &message, &signature);
+    });
+    assert!(valid);
+    assert_eq!(allocations, 0);
+
+    // rejecting an invalid signature does not allocate either
+    message[0] ^= 0x01;
+    let allocations = count_allocations(|| {
+        valid = S::verify(&pk, epoch, &message, &signature);
+    });
+    assert!(!valid);
+    assert_eq!(allocations, 0);
+}
+
+#[test]
+fn test_sha_verify_does_not_allocate() {
+    // Note: do not use these parameters, they are just for testing
+    type SigWinternitz = GeneralizedXMSSSignatureScheme<
+        ShaPRF<24>,
+        WinternitzEncoding<ShaMessageHash192x3, 3>,
+        ShaTweak192192,
+        6,
+    >;
+    type SigTargetSum = GeneralizedXMSSSignatureScheme<
+        ShaPRF<24>,
+        TargetSumEncoding<ShaMessageHash192x3, 360>,
+        ShaTweak192192,
+        5,
+    >;
+
+    _test_verify_does_not_allocate::<SigWinternitz>();
+    _test_verify_does_not_allocate::<SigTargetSum>();
+}
@@ -0,0 +1,224 @@
+// Drives the command line tool `hashsig-cli` through key generation,
+// signing, and verification, using the parameter set with lifetime 2^10.
+#![cfg(all(feature = "sha", feature = "getrandom"))]
+
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+
+/// Returns a fresh directory for the files of a test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn cli() -> Command {
+    Command::cargo_bin("hashsig-cli").unwrap()
+}
+
+/// Generates a key in the given directory, and writes a message to sign
+fn setup(dir: &PathBuf) {
+    let output = cli()
+        .current_dir(dir)
+        .args(["keygen", "--scheme", "sha-128", "--lifetime", "1024"])
+        .args(["--out", "key.bin", "--pub", "pub.bin"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("scheme: ShaWinternitzLifetime10W4"));
+    assert!(stdout.contains("lifetime: 1024"));
+    assert!(stdout.contains("fingerprint: "));
+
+    std::fs::write(dir.join("msg.txt"), b"hello hash-based signatures").unwrap();
+}
+
+#[test]
+fn test_keygen_sign_verify() {
+    let dir = test_dir("cli_keygen_sign_verify");
+    setup(&dir);
+
+    cli()
+        .current_dir(&dir)
+        .args([
+            "sign", "--key", "key.bin", "--epoch", "7", "--msg", "msg.txt",
+        ])
+        .assert()
+        .success();
+    assert!(dir.join("msg.txt.sig").exists());
+
+    cli()
+        .current_dir(&dir)
+        .args(["verify", "--pub", "pub.bin", "--epoch", "7"])
+        .args(["--msg", "msg.txt", "--sig", "msg.txt.sig"])
+        .assert()
+        .success();
+
+    // a different epoch or message is rejected with exit code 1
+    cli()
+        .current_dir(&dir)
+        .args(["verify", "--pub", "pub.bin", "--epoch", "8"])
+        .args(["--msg", "msg.txt", "--sig", "msg.txt.sig"])
+        .assert()
+        .code(1);
+    std::fs::write(dir.join("other.txt"), b"hello hash-based signatures!").unwrap();
+    cli()
+        .current_dir(&dir)
+        .args(["verify", "--pub", "pub.bin", "--epoch", "7"])
+        .args(["--msg", "other.txt", "--sig", "msg.txt.sig"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn test_sign_refuses_reused_epoch() {
+    let dir = test_dir("cli_sign_refuses_reused_epoch");
+    setup(&dir);
+
+    let sign = |epoch: &str, sig: &str| {
+        cli()
+            .current_dir(&dir)
+            .args(["sign", "--key", "key.bin", "--epoch", epoch])
+            .args(["--msg", "msg.txt", "--sig", sig])
+            .assert()
+    };
+
+    sign("7", "first.sig").success();
+
+    // the same epoch, and earlier epochs, are refused, and no signature is written
+    sign("7", "second.sig")
+        .code(2)
+        .stderr(predicates::str::contains("refusing to reuse an epoch"));
+    sign("3", "third.sig").code(2);
+    assert!(!dir.join("second.sig").exists());
+    assert!(!dir.join("third.sig").exists());
+
+    // later epochs can be used, and epochs beyond the lifetime are rejected
+    sign("8", "fourth.sig").success();
+    sign("1024", "fifth.sig").code(2);
+    assert!(!dir.join("fifth.sig").exists());
+}
+
+#[test]
+fn test_invalid_arguments() {
+    let dir = test_dir("cli_invalid_arguments");
+
+    cli().assert().code(2);
+    cli().arg("frobnicate").assert().code(2);
+    cli()
+        .current_dir(&dir)
+        .args(["keygen", "--scheme", "sha-128", "--lifetime", "1000"])
+        .args(["--out", "key.bin"])
+        .assert()
+        .code(2)
+        .stderr(predicates::str::contains("unsupported lifetime"));
+    cli()
+        .current_dir(&dir)
+        .args(["keygen", "--scheme", "NotAParameterSet", "--out", "key.bin"])
+        .assert()
+        .code(2);
+    assert!(!dir.join("key.bin").exists());
+}
+
+#[test]
+fn test_keygen_derived() {
+    let dir = test_dir("cli_keygen_derived");
+    std::fs::write(dir.join("master.bin"), [0x42; 32]).unwrap();
+
+    let keygen = |path: &str, out: &str| {
+        cli()
+            .current_dir(&dir)
+            .args(["keygen", "--scheme", "sha-128", "--lifetime", "1024"])
+            .args(["--master", "master.bin", "--path", path, "--out", out])
+            .assert()
+    };
+
+    // the same path gives the same key, and another path another key
+    keygen("m/1'/7'", "first.bin")
+        .success()
+        .stdout(predicates::str::contains("path: m/1'/7'"));
+    keygen("m/1h/7h", "second.bin").success();
+    keygen("m/1'/8'", "third.bin").success();
+    let read = |name: &str| std::fs::read(dir.join(name)).unwrap();
+    assert_eq!(read("first.pub"), read("second.pub"));
+    assert_ne!(read("first.pub"), read("third.pub"));
+
+    // a derived key can sign
+    std::fs::write(dir.join("msg.txt"), b"derived").unwrap();
+    cli()
+        .current_dir(&dir)
+        .args([
+            "sign",
+            "--key",
+            "third.bin",
+            "--epoch",
+            "0",
+            "--msg",
+            "msg.txt",
+        ])
+        .assert()
+        .success();
+    cli()
+        .current_dir(&dir)
+        .args(["verify", "--pub", "third.pub", "--epoch", "0"])
+        .args(["--msg", "msg.txt", "--sig", "msg.txt.sig"])
+        .assert()
+        .success();
+
+    // only hardened indices below 2^31 are supported
+    for path in ["m/1/7'", "1'/7'", "m/2147483648'", "m/x'"] {
+        keygen(path, "invalid.bin")
+            .code(2)
+            .stderr(predicates::str::contains("invalid value of --path"));
+    }
+    assert!(!dir.join("invalid.bin").exists());
+
+    // a path needs a master seed of the right length
+    cli()
+        .current_dir(&dir)
+        .args(["keygen", "--scheme", "sha-128", "--lifetime", "1024"])
+        .args(["--path", "m/1'", "--out", "invalid.bin"])
+        .assert()
+        .code(2)
+        .stderr(predicates::str::contains("--path needs --master"));
+    std::fs::write(dir.join("short.bin"), [0x42; 31]).unwrap();
+    cli()
+        .current_dir(&dir)
+        .args(["keygen", "--scheme", "sha-128", "--lifetime", "1024"])
+        .args(["--master", "short.bin", "--out", "invalid.bin"])
+        .assert()
+        .code(2)
+        .stderr(predicates::str::contains(
+            "master seed must consist of 32 bytes",
+        ));
+    assert!(!dir.join("invalid.bin").exists());
+}
+
+#[cfg(feature = "slow-tests")]
+#[test]
+fn test_keygen_derived_schemes() {
+    use hashsig::signature::codec::HEADER_LENGTH;
+
+    let dir = test_dir("cli_keygen_derived_schemes");
+    std::fs::write(dir.join("master.bin"), [0x42; 32]).unwrap();
+
+    // two schemes that only differ in the target sum, with the same
+    // master seed and path, give different keys. The encoded public
+    // keys differ in the header anyway, so we compare what follows it
+    let keygen = |scheme: &str, out: &str| {
+        cli()
+            .current_dir(&dir)
+            .args(["keygen", "--scheme", scheme])
+            .args(["--master", "master.bin", "--path", "m/1'/7'", "--out", out])
+            .assert()
+            .success();
+        std::fs::read(dir.join(out).with_extension("pub")).unwrap()[HEADER_LENGTH..].to_vec()
+    };
+    let pk = keygen("ShaTargetSumLifetime18W1NoOff", "no_offset.bin");
+    let pk_offset = keygen("ShaTargetSumLifetime18W1Off10", "offset.bin");
+    assert_ne!(pk, pk_offset);
+}
@@ -0,0 +1,8 @@
+/// Checks at compile time that APIs generating secret material reject RNGs
+/// that are not cryptographically secure, that impossible instantiations
+/// are rejected, and that results of verification cannot be ignored.
+#[test]
+fn test_compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
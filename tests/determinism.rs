@@ -0,0 +1,152 @@
+// Determinism across platforms. Keys are generated on one architecture, and
+// signatures verified on others, e.g., x86_64, aarch64, and wasm32. So every
+// output must be independent of endianness and of the size of `usize`.
+//
+// All inputs are fixed or drawn from seeded `ChaCha20Rng`s, which are portable,
+// and all outputs are pinned as constants. Running `cargo test` on any target
+// checks against the same constants. Large outputs are pinned by their SHA3-256
+// digest. The building blocks are those of `SIGWinternitzLifetime18W4`.
+#![cfg(feature = "sha")]
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use tiny_keccak::{Hasher, Sha3};
+
+use hashsig::{
+    declare_instantiation,
+    signature::{
+        codec::{self, UNREGISTERED_PARAM_SET_ID},
+        SignatureScheme,
+    },
+    symmetric::{
+        message_hash::{
+            bytes_to_chunks, sha::ShaMessageHash, sha256::Sha256MessageHash, MessageHash,
+        },
+        seed_expander::SEED_LENGTH,
+        tweak_hash::{sha::ShaTweakHash, TweakableHash},
+    },
+    MESSAGE_LENGTH,
+};
+
+// Note: the parameters are those of `SIGWinternitzLifetime18W4`,
+// with a shorter lifetime so that key generation is fast enough.
+declare_instantiation! {
+    type SIGWinternitzW4 = Sha3 {
+        log_lifetime: 6,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+}
+
+// Pinned outputs, see the tests below for the inputs
+const PINNED_RANDOMNESS: &str = "b93606144264009e561c82d0d0f2ad461884748d";
+const PINNED_CHUNKS_SHA3: &str =
+    "05070b0e010703070b0e010500050300070c0f00050f020e0004010a0d0e0007080d0c0a";
+const PINNED_CHUNKS_SHA256: &str =
+    "030f04040f02080f0c0f01010c04020d0a090405010b0f05060e060208070e0c0905000b";
+const PINNED_TREE_HASH: &str = "41c5cb56c6ab0816cb4e35d4f77430a2fcac01cd523319e54a0b";
+const PINNED_CHAIN_HASH: &str = "ac1a86d0f5e221900be7a945905b24868efb3c05d4489e53b0f4";
+const PINNED_PUBLIC_KEY: &str = "02010000215cda1621514257316d0f60eddd36a0ffc1cf85fc345373117e8ef8d4a5103183b1d56be4d883b29631755d";
+const PINNED_SECRET_KEY_DIGEST: &str =
+    "5ed7af48bf90ddb7778a75b628f690c83894b9b5440515b01c4cbfd38c6e6639";
+const PINNED_SIGNATURE_DIGEST: &str =
+    "d614512cb3707b9a9dc15adf1fc2cb3f1b88b6fb281cae3898f50c60f7599f63";
+const PINNED_MINIMAL_SIGNATURE_DIGEST: &str =
+    "d326a0f442005726acad6ad7d87b8498273558622854b5ddfc66d7ab44e9a1e5";
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn sha3_256(bytes: &[u8]) -> String {
+    let mut hasher = Sha3::v256();
+    hasher.update(bytes);
+    let mut digest = [0u8; 32];
+    hasher.finalize(&mut digest);
+    hex(&digest)
+}
+
+/// A message that does not depend on any RNG
+fn message() -> [u8; MESSAGE_LENGTH] {
+    std::array::from_fn(|i| (i as u8).wrapping_mul(7))
+}
+
+#[test]
+fn test_bytes_to_chunks() {
+    let bytes = [0x12, 0x34, 0xfe];
+    assert_eq!(
+        bytes_to_chunks(&bytes, 1),
+        [0, 1, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1]
+    );
+    assert_eq!(
+        bytes_to_chunks(&bytes, 2),
+        [2, 0, 1, 0, 0, 1, 3, 0, 2, 3, 3, 3]
+    );
+    assert_eq!(bytes_to_chunks(&bytes, 4), [2, 1, 4, 3, 14, 15]);
+    assert_eq!(bytes_to_chunks(&bytes, 8), [0x12, 0x34, 0xfe]);
+}
+
+#[test]
+fn test_message_hash() {
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+    let epoch = 0x0102_0304;
+
+    type MH = ShaMessageHash<18, 20, 36, 4>;
+    let parameter: [u8; 18] = rng.gen();
+    let randomness = MH::rand(&mut rng);
+    assert_eq!(hex(&randomness), PINNED_RANDOMNESS);
+    let chunks = MH::apply(&parameter, epoch, &randomness, &message());
+    assert_eq!(hex(&chunks), PINNED_CHUNKS_SHA3);
+
+    type MH256 = Sha256MessageHash<18, 20, 36, 4>;
+    let chunks = MH256::apply(&parameter, epoch, &randomness, &message());
+    assert_eq!(hex(&chunks), PINNED_CHUNKS_SHA256);
+}
+
+#[test]
+fn test_tweak_hash() {
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+
+    type TH = ShaTweakHash<18, 26>;
+    let parameter = TH::rand_parameter(&mut rng);
+    let children = [TH::rand_domain(&mut rng), TH::rand_domain(&mut rng)];
+
+    let tree_hash = TH::apply(&parameter, &TH::tree_tweak(3, 0x0102_0304), &children);
+    assert_eq!(hex(&tree_hash), PINNED_TREE_HASH);
+    let chain_hash = TH::apply(
+        &parameter,
+        &TH::chain_tweak(0x0102_0304, 0x0506, 0x0708),
+        &children[..1],
+    );
+    assert_eq!(hex(&chain_hash), PINNED_CHAIN_HASH);
+}
+
+#[test]
+fn test_key_gen_sign_and_serialization() {
+    type Sig = SIGWinternitzW4;
+    let id = UNREGISTERED_PARAM_SET_ID;
+    let seed: [u8; SEED_LENGTH] = std::array::from_fn(|i| i as u8);
+
+    let (pk, sk) = Sig::key_gen_from_seed(seed);
+    let pk_bytes = codec::encode_public_key::<Sig>(id, &pk);
+    assert_eq!(hex(&pk_bytes), PINNED_PUBLIC_KEY);
+    let sk_bytes = codec::encode_secret_key::<Sig>(id, &sk);
+    assert_eq!(sha3_256(&sk_bytes), PINNED_SECRET_KEY_DIGEST);
+
+    let epoch = 13;
+    let mut rng = ChaCha20Rng::seed_from_u64(3);
+    let sig = Sig::sign(&mut rng, &sk, epoch, &message()).unwrap();
+    assert!(Sig::verify(&pk, epoch, &message(), &sig));
+    let sig_bytes = codec::encode_signature::<Sig>(id, &sig);
+    assert_eq!(sha3_256(&sig_bytes), PINNED_SIGNATURE_DIGEST);
+    let minimal_bytes = Sig::encode_signature_minimal(id, &sig);
+    assert_eq!(sha3_256(&minimal_bytes), PINNED_MINIMAL_SIGNATURE_DIGEST);
+
+    // decoding the pinned encodings gives back the same values
+    assert!(codec::decode_public_key::<Sig>(id, &pk_bytes).unwrap() == pk);
+    assert!(codec::decode_signature::<Sig>(id, &sig_bytes).unwrap() == sig);
+}
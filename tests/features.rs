@@ -0,0 +1,96 @@
+// Checks that the crate compiles for the supported feature combinations,
+// and that it fails with a clear error for unsupported ones.
+// This runs cargo itself, and is therefore only run with `slow-tests`.
+#![cfg(feature = "slow-tests")]
+
+use std::process::Command;
+
+/// Runs `cargo check` on the library with the given features and
+/// without default features. Returns whether it succeeded, and stderr.
+fn check_with_features(features: &str) -> (bool, String) {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let output = Command::new(env!("CARGO"))
+        .current_dir(manifest_dir)
+        .args([
+            "check",
+            "--lib",
+            "--no-default-features",
+            "--features",
+            features,
+        ])
+        // use a separate target directory, to not block the outer cargo
+        .env(
+            "CARGO_TARGET_DIR",
+            format!("{}/target/feature-matrix", manifest_dir),
+        )
+        .output()
+        .expect("Failed to run cargo");
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    (output.status.success(), stderr)
+}
+
+#[test]
+fn test_feature_matrix() {
+    // supported combinations must compile
+    for features in [
+        "sha,backend-tiny-keccak",
+        "sha,backend-rustcrypto",
+        "sha,backend-tiny-keccak,backend-rustcrypto",
+        "sha,backend-tiny-keccak,getrandom",
+        "sha,backend-tiny-keccak,getrandom,rand_core_09",
+        "sha,backend-tiny-keccak,parallel",
+        "sha,backend-tiny-keccak,tracing",
+        "sha,backend-tiny-keccak,signature-traits",
+    ] {
+        let (success, stderr) = check_with_features(features);
+        assert!(
+            success,
+            "Features `{}` should compile:\n{}",
+            features, stderr
+        );
+    }
+
+    // without any backend, we expect a clear error
+    let (success, stderr) = check_with_features("");
+    assert!(!success);
+    assert!(stderr.contains("No hash backend selected"));
+
+    // without a SHA3 backend, we expect a clear error
+    let (success, stderr) = check_with_features("sha");
+    assert!(!success);
+    assert!(stderr.contains("No SHA3 backend selected"));
+
+    // the Poseidon backend is not available yet
+    let (success, stderr) = check_with_features("poseidon");
+    assert!(!success);
+    assert!(stderr.contains("The Poseidon backend is currently not available"));
+}
+
+/// Returns the names of the normal dependencies of the library with
+/// the given features, including transitive ones
+fn dependencies_with_features(features: &str) -> String {
+    let output = Command::new(env!("CARGO"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args([
+            "tree",
+            "--edges",
+            "normal",
+            "--prefix",
+            "none",
+            "--format",
+            "{p}",
+            "--features",
+            features,
+        ])
+        .output()
+        .expect("Failed to run cargo");
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_optional_dependencies() {
+    // tracing is only a dependency with the feature `tracing`
+    assert!(!dependencies_with_features("").contains("tracing"));
+    assert!(dependencies_with_features("tracing").contains("tracing"));
+}
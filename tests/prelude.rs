@@ -0,0 +1,37 @@
+use hashsig::prelude::*;
+use rand::{thread_rng, Rng};
+
+// Note: do not use this instantiation, it has a very short lifetime and is just for testing
+type MH = ShaMessageHash<24, 24, 48, 4>;
+type TH = ShaTweakHash<24, 24>;
+type Prf = ShaPRF<24>;
+type IE = WinternitzEncoding<MH, 3>;
+type Sig = GeneralizedXMSSSignatureScheme<Prf, IE, TH, 6>;
+
+/// Generates a key pair, signs a message, and verifies the signature
+/// for the given scheme, using only items from the prelude.
+fn generate_sign_verify<S: SignatureScheme>() {
+    let mut rng = thread_rng();
+
+    let (pk, sk) = S::gen(&mut rng);
+
+    let mut message = [0u8; MESSAGE_LENGTH];
+    rng.fill(&mut message);
+    let epoch = 5;
+
+    let signature: Result<_, SigningError> = S::sign(&mut rng, &sk, epoch, &message);
+    let signature = signature.expect("Signing should succeed");
+
+    assert!(S::verify(&pk, epoch, &message, &signature));
+}
+
+#[test]
+fn test_prelude_generate_sign_verify() {
+    generate_sign_verify::<Sig>();
+}
+
+#[cfg(feature = "slow-tests")]
+#[test]
+fn test_prelude_recommended_instantiation() {
+    generate_sign_verify::<SIGWinternitzLifetime18W4>();
+}
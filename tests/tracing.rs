@@ -0,0 +1,102 @@
+// Checks that key generation, signing, and verification emit the expected
+// spans and events with the feature `tracing`.
+#![cfg(all(feature = "tracing", feature = "sha"))]
+
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use hashsig::{
+    inc_encoding::basic_winternitz::{num_chunks_checksum, WinternitzEncoding},
+    signature::{generalized_xmss::GeneralizedXMSSSignatureScheme, SignatureScheme},
+    symmetric::{
+        message_hash::sha::ShaMessageHash, prf::sha::ShaPRF, tweak_hash::sha::ShaTweakHash,
+    },
+    MESSAGE_LENGTH,
+};
+use rand::thread_rng;
+use tracing_subscriber::fmt::{format::FmtSpan, MakeWriter};
+
+// Note: do not use these parameters, they are just for testing
+type MH = ShaMessageHash<16, 16, 8, 4>;
+type Sig = GeneralizedXMSSSignatureScheme<
+    ShaPRF<16>,
+    WinternitzEncoding<MH, { num_chunks_checksum(8, 4) }>,
+    ShaTweakHash<16, 16>,
+    10,
+>;
+
+/// Collects the output of the subscriber
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Buffer {
+    type Writer = Buffer;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[test]
+fn test_spans_and_events() {
+    // key generation may run on other threads, so the subscriber must be global
+    let buffer = Buffer::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .with_writer(buffer.clone())
+        .finish();
+    tracing::subscriber::set_global_default(subscriber).unwrap();
+
+    let mut rng = thread_rng();
+    let (pk, sk) = Sig::gen(&mut rng);
+    let message = [0x2a; MESSAGE_LENGTH];
+    let signature = Sig::sign(&mut rng, &sk, 5, &message).unwrap();
+    assert!(Sig::verify(&pk, 5, &message, &signature));
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    let has_line = |parts: &[&str]| {
+        lines
+            .iter()
+            .any(|line| parts.iter().all(|part| line.contains(part)))
+    };
+
+    // key generation, with one progress event for 2^10 leafs
+    assert!(has_line(&["key_gen", "log_lifetime=10", "close"]));
+    assert!(has_line(&[
+        "key gen progress",
+        "leafs_done=1024",
+        "leafs=1024"
+    ]));
+
+    // one event per layer of the Merkle tree
+    assert!(has_line(&["build_tree", "leafs=1024", "close"]));
+    for level in 1..=10 {
+        assert!(has_line(&["built tree layer", &format!("level={}", level)]));
+    }
+
+    // signing and verification
+    assert!(has_line(&[
+        "sign",
+        "epoch=5",
+        "encoded message",
+        "success=true"
+    ]));
+    assert!(has_line(&["sign", "epoch=5", "close"]));
+    assert!(has_line(&["verify", "epoch=5", "close"]));
+}
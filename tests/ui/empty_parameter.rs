@@ -0,0 +1,24 @@
+use hashsig::symmetric::{
+    message_hash::{keccak::KeccakMessageHash, sha::ShaMessageHash, sha256::Sha256MessageHash},
+    tweak_hash::sha::ShaTweakHash,
+};
+
+// An empty parameter would remove the separation of keys, so all of these are rejected
+const _: () = ShaTweakHash::<0, 24>::LENGTHS_VALID;
+const _: () = ShaMessageHash::<0, 16, 32, 4>::LENGTHS_VALID;
+const _: () = Sha256MessageHash::<0, 16, 32, 4>::LENGTHS_VALID;
+const _: () = KeccakMessageHash::<0, 16, 32, 4>::LENGTHS_VALID;
+
+hashsig::declare_instantiation! {
+    type EmptyParameter = Sha3 {
+        log_lifetime: 10,
+        parameter_len: 0,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+}
+
+fn main() {}
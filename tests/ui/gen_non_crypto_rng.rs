@@ -0,0 +1,11 @@
+use hashsig::signature::{
+    generalized_xmss::instantiations_sha::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W1,
+    SignatureScheme,
+};
+use rand::rngs::mock::StepRng;
+
+fn main() {
+    // StepRng is deterministic and not a CryptoRng, so key generation must reject it
+    let mut rng = StepRng::new(0, 1);
+    let _ = SIGWinternitzLifetime18W1::gen(&mut rng);
+}
@@ -0,0 +1,10 @@
+use hashsig::prelude::*;
+use hashsig::signature::hash_sig::HashSigScheme;
+
+// the message hash outputs 72 chunks of 2 bits, i.e., 144 bits,
+// but the tweakable hash only has 128 bit domain elements
+type Sig = HashSigScheme<ShaPRF<16>, ShaMessageHash<18, 20, 72, 2>, ShaTweakHash<18, 16>, 4, 10>;
+
+const _: () = Sig::CHECK;
+
+fn main() {}
@@ -0,0 +1,26 @@
+#![deny(unused_must_use)]
+
+use hashsig::{
+    signature::{
+        generalized_xmss::instantiations_sha::lifetime_2_to_the_10::winternitz::SIGWinternitzLifetime10W4,
+        SignatureScheme,
+    },
+    symmetric::message_hash::Chunks,
+    MESSAGE_LENGTH,
+};
+
+type Sig = SIGWinternitzLifetime10W4;
+
+// Ignoring the result of a verification or of a checked construction
+// would silently accept invalid inputs, so all of these are rejected
+fn ignore(
+    pk: &<Sig as SignatureScheme>::PublicKey,
+    message: &[u8; MESSAGE_LENGTH],
+    sig: &<Sig as SignatureScheme>::Signature,
+) {
+    Sig::verify(pk, 0, message, sig);
+    Sig::is_structurally_valid(sig);
+    Chunks::<4, 2>::new([0; 4]);
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+hashsig::declare_instantiation! {
+    // chunk size 3 does not divide 8, so this instantiation is impossible
+    pub type ImpossibleInstantiation = Sha3 {
+        log_lifetime: 10,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 3,
+        encoding: Winternitz,
+    };
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use hashsig::symmetric::{
+    message_hash::{digest::DigestMessageHash, sha::ShaMessageHash, sha256::Sha256MessageHash},
+    prf::sha::ShaPRF,
+    tweak_hash::sha::ShaTweakHash,
+};
+
+// The outputs of SHA3-256 and SHA-256 have 32 bytes, so all of these are impossible
+const _: () = ShaTweakHash::<16, 40>::LENGTHS_VALID;
+const _: () = ShaTweakHash::<16, 16, 128>::LENGTHS_VALID;
+const _: () = ShaPRF::<40>::LENGTHS_VALID;
+const _: () = ShaMessageHash::<16, 16, 72, 4>::LENGTHS_VALID;
+const _: () = Sha256MessageHash::<16, 16, 32, 3>::LENGTHS_VALID;
+
+// The output of SHA-512 has 64 bytes
+const _: () = DigestMessageHash::<sha2::Sha512, 16, 16, 65, 8>::LENGTHS_VALID;
+
+fn main() {}
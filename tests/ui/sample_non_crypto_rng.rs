@@ -0,0 +1,14 @@
+use hashsig::symmetric::{
+    message_hash::{sha::ShaMessageHash128x3, MessageHash},
+    tweak_hash::{sha::ShaTweak128192, TweakableHash},
+};
+use rand::rngs::mock::StepRng;
+
+fn main() {
+    // StepRng is deterministic and not a CryptoRng, so it must not be usable
+    // to sample parameters, domain elements, or message hash randomness
+    let mut rng = StepRng::new(0, 1);
+    let _ = ShaTweak128192::rand_parameter(&mut rng);
+    let _ = ShaTweak128192::rand_domain(&mut rng);
+    let _ = ShaMessageHash128x3::rand(&mut rng);
+}
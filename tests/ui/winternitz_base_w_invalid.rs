@@ -0,0 +1,12 @@
+use hashsig::{
+    inc_encoding::base_w_winternitz::WinternitzBaseWEncoding,
+    symmetric::message_hash::sha::ShaMessageHash,
+};
+
+type MH = ShaMessageHash<18, 20, 36, 4>;
+
+// chains need at least one step, and chain positions must fit into u16
+const _: () = WinternitzBaseWEncoding::<MH, 1>::PARAMETERS_VALID;
+const _: () = WinternitzBaseWEncoding::<MH, { (1 << 16) + 1 }>::PARAMETERS_VALID;
+
+fn main() {}
@@ -0,0 +1,261 @@
+// Compatibility with the upstream hash-sig implementation, at the revision
+// from which this crate was forked. The vectors in `testdata/upstream` were
+// produced by upstream, see `testdata/upstream/export.rs`, for instantiations
+// that exist in both: the SHA3 instantiations with lifetime 2^18, with the
+// lifetime reduced to 2^6, so that key generation is fast enough.
+//
+// Public keys, codewords, and signatures are identical, and their plain
+// bincode encodings are interchangeable. The encodings that this crate adds
+// diverge as pinned by `check_added_encodings`. Upstream cannot serialize
+// secret keys, so there is nothing to compare them against.
+#![cfg(feature = "sha")]
+
+use std::{marker::PhantomData, path::PathBuf};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use hashsig::{
+    declare_instantiation,
+    inc_encoding::IncomparableEncoding,
+    signature::{
+        codec::{self, ArtifactKind, UNREGISTERED_PARAM_SET_ID},
+        generalized_xmss::{
+            GeneralizedXMSSPublicKey, GeneralizedXMSSSignature, GeneralizedXMSSSignatureScheme,
+        },
+        SignatureScheme,
+    },
+    symmetric::{prf::Pseudorandom, tweak_hash::TweakableHash},
+    MESSAGE_LENGTH,
+};
+
+/// Contents of a file in `testdata/upstream`. The key pair is generated with a
+/// `ChaCha20Rng` seeded with `rng_seed`, and then, with the same RNG, for each
+/// signature a message is sampled and signed. All bytes are in lowercase hex,
+/// in the encoding of `bincode`.
+#[derive(Deserialize)]
+struct UpstreamVector {
+    instantiation: String,
+    rng_seed: u64,
+    public_key: String,
+    signatures: Vec<UpstreamSignature>,
+}
+
+#[derive(Deserialize)]
+struct UpstreamSignature {
+    epoch: u32,
+    message: String,
+    randomness: String,
+    codeword: Vec<u16>,
+    signature: String,
+}
+
+// Note: the parameters are those of the instantiations with lifetime 2^18,
+// see `instantiations_sha::lifetime_2_to_the_18`, with a shorter lifetime.
+declare_instantiation! {
+    type SIGWinternitzW1 = Sha3 {
+        log_lifetime: 6,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 25,
+        chunk_size: 1,
+        encoding: Winternitz,
+    };
+
+    type SIGWinternitzW2 = Sha3 {
+        log_lifetime: 6,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 25,
+        chunk_size: 2,
+        encoding: Winternitz,
+    };
+
+    type SIGWinternitzW4 = Sha3 {
+        log_lifetime: 6,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: Winternitz,
+    };
+
+    type SIGWinternitzW8 = Sha3 {
+        log_lifetime: 6,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 20,
+        hash_len: 28,
+        chunk_size: 8,
+        encoding: Winternitz,
+    };
+
+    type SIGTargetSumW4Off10 = Sha3 {
+        log_lifetime: 6,
+        parameter_len: 18,
+        message_hash_len: 18,
+        rand_len: 23,
+        hash_len: 26,
+        chunk_size: 4,
+        encoding: TargetSum(297),
+    };
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Checks that the encodings of `codec` are the upstream encodings with a header,
+/// and that the minimal encoding of a signature omits the two lengths of upstream.
+fn check_added_encodings<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+>(
+    upstream_pk: &[u8],
+    upstream_sig: &[u8],
+    pk: &GeneralizedXMSSPublicKey<TH>,
+    sig: &GeneralizedXMSSSignature<IE, TH>,
+) where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+    GeneralizedXMSSPublicKey<TH>: Serialize,
+    GeneralizedXMSSSignature<IE, TH>: Serialize,
+    TH::Domain: Serialize,
+    IE::Randomness: Serialize,
+{
+    let id = UNREGISTERED_PARAM_SET_ID;
+    let header = |kind: ArtifactKind| codec::encode(kind, id, &());
+
+    let encoded = codec::encode(ArtifactKind::PublicKey, id, pk);
+    assert_eq!(
+        encoded,
+        [header(ArtifactKind::PublicKey), upstream_pk.to_vec()].concat()
+    );
+    let encoded = codec::encode(ArtifactKind::Signature, id, sig);
+    assert_eq!(
+        encoded,
+        [header(ArtifactKind::Signature), upstream_sig.to_vec()].concat()
+    );
+
+    // upstream: length of the path (u64), path, randomness,
+    // length of the chain values (u64), chain values
+    let path_len = LOG_LIFETIME * std::mem::size_of::<TH::Domain>();
+    let rho_len = std::mem::size_of::<IE::Randomness>();
+    let (path, rest) = upstream_sig[8..].split_at(path_len);
+    let (rho, rest) = rest.split_at(rho_len);
+    assert_eq!(rest[..8], (IE::NUM_CHUNKS as u64).to_le_bytes());
+    let minimal = [
+        &header(ArtifactKind::MinimalSignature)[..],
+        path,
+        rho,
+        &rest[8..],
+    ]
+    .concat();
+    let encoded =
+        GeneralizedXMSSSignatureScheme::<PRF, IE, TH, LOG_LIFETIME>::encode_signature_minimal(
+            id, sig,
+        );
+    assert_eq!(encoded, minimal);
+}
+
+/// Checks an instantiation against the upstream vectors in the given file
+fn check_upstream<
+    PRF: Pseudorandom,
+    IE: IncomparableEncoding,
+    TH: TweakableHash,
+    const LOG_LIFETIME: usize,
+>(
+    _scheme: PhantomData<GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>>,
+    name: &str,
+) where
+    PRF::Output: Into<TH::Domain>,
+    TH::Parameter: Into<IE::Parameter>,
+    GeneralizedXMSSPublicKey<TH>: Serialize + DeserializeOwned,
+    GeneralizedXMSSSignature<IE, TH>: Serialize + DeserializeOwned,
+    TH::Domain: Serialize,
+    IE::Randomness: Serialize + DeserializeOwned,
+{
+    type Sig<PRF, IE, TH, const LOG_LIFETIME: usize> =
+        GeneralizedXMSSSignatureScheme<PRF, IE, TH, LOG_LIFETIME>;
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata/upstream")
+        .join(format!("{}.json", name));
+    let vector: UpstreamVector =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(vector.instantiation, name);
+
+    // the same RNG gives the same key pair
+    let mut rng = ChaCha20Rng::seed_from_u64(vector.rng_seed);
+    let (pk, sk) = Sig::<PRF, IE, TH, LOG_LIFETIME>::gen(&mut rng);
+    let upstream_pk_bytes = from_hex(&vector.public_key);
+    assert_eq!(
+        bincode::serialize(&pk).unwrap(),
+        upstream_pk_bytes,
+        "{}",
+        name
+    );
+    let upstream_pk: GeneralizedXMSSPublicKey<TH> =
+        bincode::deserialize(&upstream_pk_bytes).unwrap();
+
+    for upstream in &vector.signatures {
+        let message: [u8; MESSAGE_LENGTH] = rng.gen();
+        assert_eq!(to_hex(&message), upstream.message);
+        let epoch = upstream.epoch;
+
+        // the same randomness gives the same codeword
+        let rho: IE::Randomness = bincode::deserialize(&from_hex(&upstream.randomness)).unwrap();
+        let codeword = IE::encode(&pk.parameter.into(), &message, &rho, epoch).unwrap();
+        assert_eq!(codeword, upstream.codeword, "{}, epoch {}", name, epoch);
+
+        // the same RNG gives the same signature
+        let sig = Sig::<PRF, IE, TH, LOG_LIFETIME>::sign(&mut rng, &sk, epoch, &message).unwrap();
+        let upstream_sig_bytes = from_hex(&upstream.signature);
+        assert_eq!(
+            bincode::serialize(&sig).unwrap(),
+            upstream_sig_bytes,
+            "{}, epoch {}",
+            name,
+            epoch
+        );
+
+        // signatures of upstream verify here
+        let upstream_sig: GeneralizedXMSSSignature<IE, TH> =
+            bincode::deserialize(&upstream_sig_bytes).unwrap();
+        assert!(Sig::<PRF, IE, TH, LOG_LIFETIME>::verify(
+            &upstream_pk,
+            epoch,
+            &message,
+            &upstream_sig
+        ));
+
+        check_added_encodings::<PRF, IE, TH, LOG_LIFETIME>(
+            &upstream_pk_bytes,
+            &upstream_sig_bytes,
+            &pk,
+            &sig,
+        );
+    }
+}
+
+#[test]
+fn test_upstream_compat() {
+    check_upstream(PhantomData::<SIGWinternitzW1>, "winternitz_w1");
+    check_upstream(PhantomData::<SIGWinternitzW2>, "winternitz_w2");
+    check_upstream(PhantomData::<SIGWinternitzW4>, "winternitz_w4");
+    check_upstream(PhantomData::<SIGWinternitzW8>, "winternitz_w8");
+    check_upstream(PhantomData::<SIGTargetSumW4Off10>, "target_sum_w4");
+}